@@ -0,0 +1,221 @@
+use crate::error::{CoavError, CoavResult};
+use serde::Serialize;
+use std::fs::File;
+use std::path::{Component, Path, PathBuf};
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+/// zipファイルの先頭のバイト列
+pub const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// zip内のアノテーションファイルの情報（フロントエンドに送信される）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveEntry {
+    /// zip内のパス
+    pub name: String,
+    /// `load_annotations` にそのまま渡せるパス（`<zipのパス>/<zip内のパス>`）
+    pub file_path: String,
+    pub size: u64,
+    pub compressed_size: u64,
+}
+
+/// zip内のファイルを指すパス
+pub struct ArchivePath {
+    pub archive: PathBuf,
+    pub entry: String,
+}
+
+impl ArchivePath {
+    /// `archive.zip/dir/file.json` のようなパスをzipファイルとzip内のパスに分ける
+    ///
+    /// パスがそのまま存在する場合や、途中に通常のファイルが無い場合は `None` を返す。
+    pub fn split(path: &Path) -> Option<Self> {
+        if path.exists() {
+            return None;
+        }
+        let archive = path.ancestors().skip(1).find(|p| p.is_file())?;
+        let entry = path
+            .strip_prefix(archive)
+            .ok()?
+            .components()
+            .map(|c| match c {
+                Component::Normal(name) => name.to_str(),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?
+            .join("/");
+        Some(ArchivePath {
+            archive: archive.to_path_buf(),
+            entry,
+        })
+    }
+}
+
+pub fn open_archive(path: &Path) -> CoavResult<ZipArchive<File>> {
+    let file = File::open(path).map_err(|e| CoavError::io(path, "Failed to read file", e))?;
+    ZipArchive::new(file).map_err(|e| zip_error(path, e))
+}
+
+/// zip内のアノテーションファイル（JSON）の一覧を返す
+pub fn list_entries(path: &Path) -> CoavResult<Vec<ArchiveEntry>> {
+    let mut archive = open_archive(path)?;
+    let mut entries = Vec::new();
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i).map_err(|e| zip_error(path, e))?;
+        if !file.is_file() || !is_annotation_name(file.name()) {
+            continue;
+        }
+        entries.push(ArchiveEntry {
+            name: file.name().to_string(),
+            file_path: path.join(file.name()).display().to_string(),
+            size: file.size(),
+            compressed_size: file.compressed_size(),
+        });
+    }
+    Ok(entries)
+}
+
+/// zipが直接指定された場合に読み込むファイルを選ぶ
+///
+/// アノテーションファイルが1つだけの場合はそのファイルを、複数ある場合は
+/// `instances_*.json` が1つだけであればそのファイルを選ぶ。
+pub fn default_entry(entries: &[ArchiveEntry]) -> Option<&ArchiveEntry> {
+    if let [entry] = entries {
+        return Some(entry);
+    }
+    let mut instances = entries.iter().filter(|entry| {
+        let file_name = entry.name.rsplit('/').next().unwrap_or(&entry.name);
+        file_name.starts_with("instances_")
+    });
+    match (instances.next(), instances.next()) {
+        (Some(entry), None) => Some(entry),
+        _ => None,
+    }
+}
+
+/// アノテーションファイルとして扱う拡張子（圧縮されたJSONを含む）
+fn is_annotation_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    // macOSで作成したzipに含まれるメタデータは除く
+    if name.starts_with("__macosx/") {
+        return false;
+    }
+    [".json", ".json.gz", ".json.zst"]
+        .iter()
+        .any(|ext| name.ends_with(ext))
+}
+
+/// zipの読み込みエラーを変換する（zip内のJSONの構文エラーとは区別する）
+pub fn zip_error(path: &Path, error: ZipError) -> CoavError {
+    if let ZipError::Io(error) = error {
+        return CoavError::io(path, "Failed to read zip archive", error);
+    }
+    CoavError::Archive {
+        path: path.display().to_string(),
+        message: format!("Failed to read zip archive: {error}"),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    /// テスト用のzipファイルを一時フォルダに作成する
+    pub(crate) fn write_zip(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("coav-archive-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let mut writer = ZipWriter::new(File::create(&path).unwrap());
+        for (file_name, contents) in files {
+            writer
+                .start_file(*file_name, FileOptions::default())
+                .unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        writer.finish().unwrap();
+        path
+    }
+
+    fn entry(name: &str) -> ArchiveEntry {
+        ArchiveEntry {
+            name: name.to_string(),
+            file_path: format!("data.zip/{name}"),
+            size: 0,
+            compressed_size: 0,
+        }
+    }
+
+    #[test]
+    fn lists_only_annotation_files() {
+        let path = write_zip(
+            "list.zip",
+            &[
+                ("annotations/instances_val.json", b"{}"),
+                ("annotations/captions_val.JSON.gz", b""),
+                ("__MACOSX/annotations/._instances_val.json", b""),
+                ("images/a.jpg", b""),
+            ],
+        );
+        let entries = list_entries(&path).unwrap();
+        let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "annotations/instances_val.json",
+                "annotations/captions_val.JSON.gz"
+            ]
+        );
+        assert_eq!(entries[0].size, 2);
+        assert_eq!(
+            PathBuf::from(&entries[0].file_path),
+            path.join("annotations/instances_val.json")
+        );
+    }
+
+    #[test]
+    fn splits_paths_inside_archive() {
+        let path = write_zip("split.zip", &[("dir/a.json", b"{}")]);
+
+        let split = ArchivePath::split(&path.join("dir").join("a.json")).unwrap();
+        assert_eq!(split.archive, path);
+        assert_eq!(split.entry, "dir/a.json");
+
+        // 存在するパスや、zipを含まないパスは分けない
+        assert!(ArchivePath::split(&path).is_none());
+        assert!(ArchivePath::split(&path.with_file_name("missing").join("a.json")).is_none());
+    }
+
+    #[test]
+    fn chooses_default_entry() {
+        let single = [entry("val.json")];
+        assert_eq!(default_entry(&single).unwrap().name, "val.json");
+
+        let coco = [
+            entry("annotations/captions_val2017.json"),
+            entry("annotations/instances_val2017.json"),
+            entry("annotations/person_keypoints_val2017.json"),
+        ];
+        assert_eq!(
+            default_entry(&coco).unwrap().name,
+            "annotations/instances_val2017.json"
+        );
+
+        // instances_ が複数ある、または無い場合は選ばない
+        let ambiguous = [entry("instances_train.json"), entry("instances_val.json")];
+        assert!(default_entry(&ambiguous).is_none());
+        assert!(default_entry(&[entry("a.json"), entry("b.json")]).is_none());
+        assert!(default_entry(&[]).is_none());
+    }
+
+    #[test]
+    fn reports_invalid_archives() {
+        let path = std::env::temp_dir().join(format!("coav-not-a-zip-{}.zip", std::process::id()));
+        std::fs::write(&path, b"PK\x03\x04 broken").unwrap();
+        let error = list_entries(&path).err().unwrap();
+        assert_eq!(error.code(), "archive_error");
+    }
+}
@@ -0,0 +1,806 @@
+use crate::error::{CoavError, CoavResult};
+use crate::models::segmentation::{CompressedRle, Rle};
+use crate::models::{
+    COCOAnnotation, COCOCategory, COCOData, COCOImage, COCOInfo, COCOLicense, COCOTrack, COCOVideo,
+    CocoId, Coercion, NumberStyle, Segmentation,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// キャッシュファイルの先頭に置く識別子
+const MAGIC: &[u8; 8] = b"COAVCCH\0";
+/// キャッシュの形式バージョン（キャッシュ用の構造体を変えたら上げる）
+const FORMAT_VERSION: u32 = 8;
+const CACHE_EXTENSION: &str = "coavcache";
+/// 画像・アノテーション1件あたりの最小バイト数（件数の妥当性の確認に使う）
+const MIN_RECORD_SIZE: u64 = 8;
+
+/// キャッシュの元になったファイルの情報（一致しなければキャッシュは無効）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SourceKey {
+    path: String,
+    size: u64,
+    modified_ns: u64,
+}
+
+impl SourceKey {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified_ns = metadata
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_nanos() as u64;
+        Some(SourceKey {
+            path: canonical_string(path),
+            size: metadata.len(),
+            modified_ns,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheHeader {
+    app_version: String,
+    source: SourceKey,
+    images: usize,
+    annotations: usize,
+}
+
+/// キャッシュファイルの情報（フロントエンドに送信される）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheEntry {
+    pub source_path: String,
+    pub cache_path: String,
+    pub cache_size: u64,
+    pub images: usize,
+    pub annotations: usize,
+    /// 元ファイルが変更・削除されている、または形式が古い
+    pub stale: bool,
+}
+
+/// 解析済みアノテーションのディスクキャッシュ
+///
+/// 元ファイルのパス・サイズ・更新日時が一致する場合のみ使用され、
+/// JSONを解析し直すことなく `COCOData` を復元できる。
+pub struct AnnotationCache {
+    dir: PathBuf,
+}
+
+impl AnnotationCache {
+    pub fn new(dir: PathBuf) -> Self {
+        AnnotationCache { dir }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 有効なキャッシュがあれば読み込む（無効・破損したキャッシュは削除する）
+    pub fn load(&self, source: &Path) -> Option<COCOData> {
+        let key = SourceKey::of(source)?;
+        let cache_path = self.cache_path(&key.path);
+        let file = File::open(&cache_path).ok()?;
+        let file_len = file.metadata().ok()?.len();
+        let mut reader = BufReader::new(file);
+
+        let data = read_header(&mut reader)
+            .filter(|header| is_current(header) && header.source == key)
+            .and_then(|header| read_body(&mut reader, &header, file_len).ok());
+        if data.is_none() {
+            let _ = fs::remove_file(&cache_path);
+        }
+        data
+    }
+
+    /// キャッシュを書き込む（一時ファイルに書き込んでから置き換える）
+    pub fn store(&self, source: &Path, data: &COCOData) -> CoavResult<()> {
+        let Some(key) = SourceKey::of(source) else {
+            return Ok(());
+        };
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| CoavError::io(&self.dir, "Failed to create cache directory", e))?;
+
+        let cache_path = self.cache_path(&key.path);
+        let temp_path = cache_path.with_extension("tmp");
+        let header = CacheHeader {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            source: key,
+            images: data.images.len(),
+            annotations: data.annotations.len(),
+        };
+
+        let result = write_cache(&temp_path, &header, data).and_then(|_| {
+            fs::rename(&temp_path, &cache_path)
+                .map_err(|e| CoavError::io(&cache_path, "Failed to write cache", e))
+        });
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result
+    }
+
+    /// キャッシュの一覧を返す
+    pub fn entries(&self) -> CoavResult<Vec<CacheEntry>> {
+        let mut entries = Vec::new();
+        for path in self.cache_files()? {
+            let cache_size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            let header = File::open(&path)
+                .ok()
+                .and_then(|file| read_header(&mut BufReader::new(file)));
+            let entry = match header {
+                Some(header) => CacheEntry {
+                    stale: !is_current(&header)
+                        || SourceKey::of(Path::new(&header.source.path)).as_ref()
+                            != Some(&header.source),
+                    source_path: header.source.path,
+                    cache_path: path.display().to_string(),
+                    cache_size,
+                    images: header.images,
+                    annotations: header.annotations,
+                },
+                None => CacheEntry {
+                    source_path: String::new(),
+                    cache_path: path.display().to_string(),
+                    cache_size,
+                    images: 0,
+                    annotations: 0,
+                    stale: true,
+                },
+            };
+            entries.push(entry);
+        }
+        entries.sort_by(|a, b| a.source_path.cmp(&b.source_path));
+        Ok(entries)
+    }
+
+    /// キャッシュを削除する（`source` を指定した場合はそのファイルのキャッシュのみ）
+    ///
+    /// 削除したキャッシュファイルの数を返す。
+    pub fn clear(&self, source: Option<&Path>) -> CoavResult<usize> {
+        let targets = match source {
+            Some(source) => vec![self.cache_path(&canonical_string(source))],
+            None => self.cache_files()?,
+        };
+
+        let mut removed = 0;
+        for path in targets {
+            match fs::remove_file(&path) {
+                Ok(()) => removed += 1,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(CoavError::io(&path, "Failed to remove cache", e)),
+            }
+        }
+        Ok(removed)
+    }
+
+    fn cache_files(&self) -> CoavResult<Vec<PathBuf>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(CoavError::io(
+                    &self.dir,
+                    "Failed to read cache directory",
+                    e,
+                ))
+            }
+        };
+        Ok(entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|e| e.to_str()) == Some(CACHE_EXTENSION))
+            .collect())
+    }
+
+    fn cache_path(&self, source_path: &str) -> PathBuf {
+        self.dir.join(format!(
+            "{:016x}.{CACHE_EXTENSION}",
+            fnv1a(source_path.as_bytes())
+        ))
+    }
+}
+
+/// TFRecordなどのレコードから書き出した画像の保存先
+///
+/// 読み込み元のファイルごとにフォルダを分け、同じファイルを読み込み直した場合は置き換える。
+pub struct ExtractedImages {
+    dir: PathBuf,
+}
+
+impl ExtractedImages {
+    pub fn new(dir: PathBuf) -> Self {
+        ExtractedImages { dir }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// `source` から書き出す画像のフォルダ
+    pub fn dir_for(&self, source: &Path) -> PathBuf {
+        self.dir.join(format!(
+            "{:016x}",
+            fnv1a(canonical_string(source).as_bytes())
+        ))
+    }
+
+    /// 書き出した画像の合計サイズ
+    pub fn total_bytes(&self) -> u64 {
+        let mut total = 0;
+        let mut dirs = vec![self.dir.clone()];
+        while let Some(dir) = dirs.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                match entry.metadata() {
+                    Ok(metadata) if metadata.is_dir() => dirs.push(entry.path()),
+                    Ok(metadata) => total += metadata.len(),
+                    Err(_) => {}
+                }
+            }
+        }
+        total
+    }
+
+    /// 画像を削除し、削除したフォルダの数を返す
+    ///
+    /// `source` を指定した場合はそのファイルから書き出した画像のみ削除する。
+    pub fn clear(&self, source: Option<&Path>) -> CoavResult<usize> {
+        let targets = match source {
+            Some(source) => vec![self.dir_for(source)],
+            None => match fs::read_dir(&self.dir) {
+                Ok(entries) => entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_dir())
+                    .collect(),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => {
+                    return Err(CoavError::io(
+                        &self.dir,
+                        "Failed to read cache directory",
+                        e,
+                    ))
+                }
+            },
+        };
+
+        let mut removed = 0;
+        for path in targets {
+            match fs::remove_dir_all(&path) {
+                Ok(()) => removed += 1,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(CoavError::io(&path, "Failed to remove cache", e)),
+            }
+        }
+        Ok(removed)
+    }
+}
+
+fn write_cache(path: &Path, header: &CacheHeader, data: &COCOData) -> CoavResult<()> {
+    let file = File::create(path).map_err(|e| CoavError::io(path, "Failed to write cache", e))?;
+    let mut writer = BufWriter::new(file);
+    writer
+        .write_all(MAGIC)
+        .and_then(|_| writer.write_all(&FORMAT_VERSION.to_le_bytes()))
+        .map_err(|e| CoavError::io(path, "Failed to write cache", e))?;
+    write_body(&mut writer, header, data).map_err(|e| CoavError::Serialization {
+        message: format!("Failed to encode cache: {e}"),
+    })?;
+    writer
+        .flush()
+        .map_err(|e| CoavError::io(path, "Failed to write cache", e))
+}
+
+/// 件数の多い画像・アノテーションは1件ずつ書き込み、変換用のコピーを作らない
+fn write_body<W: Write>(
+    writer: &mut W,
+    header: &CacheHeader,
+    data: &COCOData,
+) -> Result<(), Box<dyn Error>> {
+    bincode::serialize_into(&mut *writer, header)?;
+    let head = DatasetHead {
+        info: data.info.clone(),
+        categories: data.categories.clone(),
+        licenses: data.licenses.clone(),
+        videos: data.videos.clone(),
+        tracks: data.tracks.clone(),
+        extra: data.extra.clone(),
+        coercions: data.coercions.clone(),
+    };
+    bincode::serialize_into(&mut *writer, &serde_json::to_string(&head)?)?;
+
+    bincode::serialize_into(&mut *writer, &(data.images.len() as u64))?;
+    for image in &data.images {
+        bincode::serialize_into(&mut *writer, &CachedImage::from_image(image)?)?;
+    }
+    bincode::serialize_into(&mut *writer, &(data.annotations.len() as u64))?;
+    for annotation in &data.annotations {
+        bincode::serialize_into(
+            &mut *writer,
+            &CachedAnnotation::from_annotation(annotation)?,
+        )?;
+    }
+    Ok(())
+}
+
+/// 本体を読み込む（件数がヘッダーと一致しない、またはファイルサイズに収まらない場合はエラー）
+fn read_body<R: Read>(
+    reader: &mut R,
+    header: &CacheHeader,
+    file_len: u64,
+) -> Result<COCOData, Box<dyn Error>> {
+    let head: String = bincode::deserialize_from(&mut *reader)?;
+    let head: DatasetHead = serde_json::from_str(&head)?;
+
+    let count = read_count(reader, header.images, file_len)?;
+    let mut images = Vec::with_capacity(count);
+    for _ in 0..count {
+        let image: CachedImage = bincode::deserialize_from(&mut *reader)?;
+        images.push(image.into_image()?);
+    }
+    let count = read_count(reader, header.annotations, file_len)?;
+    let mut annotations = Vec::with_capacity(count);
+    for _ in 0..count {
+        let annotation: CachedAnnotation = bincode::deserialize_from(&mut *reader)?;
+        annotations.push(annotation.into_annotation()?);
+    }
+
+    Ok(COCOData {
+        info: head.info,
+        images,
+        annotations,
+        categories: head.categories,
+        licenses: head.licenses,
+        videos: head.videos,
+        tracks: head.tracks,
+        extra: head.extra,
+        coercions: head.coercions,
+    })
+}
+
+/// 件数を読み込む（破損したファイルの件数で巨大な領域を確保しないよう検証する）
+fn read_count<R: Read>(
+    reader: &mut R,
+    expected: usize,
+    file_len: u64,
+) -> Result<usize, Box<dyn Error>> {
+    let count: u64 = bincode::deserialize_from(reader)?;
+    if count != expected as u64 || count > file_len / MIN_RECORD_SIZE {
+        return Err(format!("Invalid record count in cache: {count}").into());
+    }
+    Ok(count as usize)
+}
+
+fn read_header<R: Read>(reader: &mut R) -> Option<CacheHeader> {
+    let mut prefix = [0u8; 12];
+    reader.read_exact(&mut prefix).ok()?;
+    if &prefix[..8] != MAGIC || prefix[8..] != FORMAT_VERSION.to_le_bytes() {
+        return None;
+    }
+    bincode::deserialize_from(reader).ok()
+}
+
+/// 件数の少ないトップレベルの項目（JSONのまま保存する）
+#[derive(Serialize, Deserialize)]
+struct DatasetHead {
+    info: Option<COCOInfo>,
+    categories: Vec<COCOCategory>,
+    licenses: Option<Vec<COCOLicense>>,
+    videos: Option<Vec<COCOVideo>>,
+    tracks: Option<Vec<COCOTrack>>,
+    extra: HashMap<String, Value>,
+    coercions: Vec<Coercion>,
+}
+
+/// キャッシュ用の `COCOImage`
+///
+/// bincode は `flatten` や任意のJSON値を扱えないため、追加の項目はJSON文字列で保存する。
+#[derive(Serialize, Deserialize)]
+struct CachedImage {
+    id: CachedId,
+    width: i32,
+    height: i32,
+    file_name: String,
+    license: Option<i32>,
+    flickr_url: Option<String>,
+    coco_url: Option<String>,
+    date_captured: Option<String>,
+    video_id: Option<CachedId>,
+    frame_id: Option<i64>,
+    neg_category_ids: Option<Vec<CachedId>>,
+    not_exhaustive_category_ids: Option<Vec<CachedId>>,
+    extra: Option<String>,
+}
+
+impl CachedImage {
+    fn from_image(image: &COCOImage) -> serde_json::Result<Self> {
+        // 項目の追加漏れを防ぐため、すべてのフィールドを列挙する
+        let COCOImage {
+            id,
+            width,
+            height,
+            file_name,
+            license,
+            flickr_url,
+            coco_url,
+            date_captured,
+            video_id,
+            frame_id,
+            neg_category_ids,
+            not_exhaustive_category_ids,
+            extra,
+        } = image;
+        Ok(CachedImage {
+            id: id.into(),
+            width: *width,
+            height: *height,
+            file_name: file_name.clone(),
+            license: *license,
+            flickr_url: flickr_url.clone(),
+            coco_url: coco_url.clone(),
+            date_captured: date_captured.clone(),
+            video_id: video_id.as_ref().map(CachedId::from),
+            frame_id: *frame_id,
+            neg_category_ids: CachedId::list(neg_category_ids),
+            not_exhaustive_category_ids: CachedId::list(not_exhaustive_category_ids),
+            extra: encode_extra(extra)?,
+        })
+    }
+
+    fn into_image(self) -> serde_json::Result<COCOImage> {
+        Ok(COCOImage {
+            id: self.id.into(),
+            width: self.width,
+            height: self.height,
+            file_name: self.file_name,
+            license: self.license,
+            flickr_url: self.flickr_url,
+            coco_url: self.coco_url,
+            date_captured: self.date_captured,
+            video_id: self.video_id.map(CocoId::from),
+            frame_id: self.frame_id,
+            neg_category_ids: CachedId::into_list(self.neg_category_ids),
+            not_exhaustive_category_ids: CachedId::into_list(self.not_exhaustive_category_ids),
+            extra: decode_extra(self.extra)?,
+        })
+    }
+}
+
+/// キャッシュ用の `CocoId`（bincode は型を問わない読み込みを扱えないため、タグ付き列挙型にする）
+#[derive(Serialize, Deserialize)]
+enum CachedId {
+    Int(i64),
+    Str(String),
+    Uint(u64),
+}
+
+impl CachedId {
+    fn list(ids: &Option<Vec<CocoId>>) -> Option<Vec<CachedId>> {
+        ids.as_ref()
+            .map(|ids| ids.iter().map(CachedId::from).collect())
+    }
+
+    fn into_list(ids: Option<Vec<CachedId>>) -> Option<Vec<CocoId>> {
+        ids.map(|ids| ids.into_iter().map(CocoId::from).collect())
+    }
+}
+
+impl From<&CocoId> for CachedId {
+    fn from(id: &CocoId) -> Self {
+        match id {
+            CocoId::Int(id) => CachedId::Int(*id),
+            CocoId::Uint(id) => CachedId::Uint(*id),
+            CocoId::Str(id) => CachedId::Str(id.clone()),
+        }
+    }
+}
+
+impl From<CachedId> for CocoId {
+    fn from(id: CachedId) -> Self {
+        match id {
+            CachedId::Int(id) => CocoId::Int(id),
+            CachedId::Uint(id) => CocoId::Uint(id),
+            CachedId::Str(id) => CocoId::Str(id),
+        }
+    }
+}
+
+/// キャッシュ用の `Segmentation`（形式を明示したタグ付き列挙型）
+#[derive(Serialize, Deserialize)]
+enum CachedSegmentation {
+    Polygon(Vec<Vec<f64>>),
+    Rle(Rle),
+    CompressedRle(CompressedRle),
+}
+
+/// キャッシュ用の `COCOAnnotation`
+#[derive(Serialize, Deserialize)]
+struct CachedAnnotation {
+    id: CachedId,
+    image_id: CachedId,
+    category_id: CachedId,
+    segmentation: Option<CachedSegmentation>,
+    area: f64,
+    bbox: Vec<f64>,
+    iscrowd: i32,
+    option: Option<String>,
+    keypoints: Option<Vec<f64>>,
+    num_keypoints: Option<u32>,
+    score: Option<f64>,
+    rbox: Option<[f64; 5]>,
+    track_id: Option<CachedId>,
+    extra: Option<String>,
+    number_style: u8,
+}
+
+impl CachedAnnotation {
+    fn from_annotation(annotation: &COCOAnnotation) -> serde_json::Result<Self> {
+        // 項目の追加漏れを防ぐため、すべてのフィールドを列挙する
+        let COCOAnnotation {
+            id,
+            image_id,
+            category_id,
+            segmentation,
+            area,
+            bbox,
+            iscrowd,
+            option,
+            keypoints,
+            num_keypoints,
+            score,
+            rbox,
+            track_id,
+            extra,
+            number_style,
+        } = annotation;
+        let segmentation = segmentation.as_ref().map(|s| match s {
+            Segmentation::Polygon(polygons) => CachedSegmentation::Polygon(polygons.clone()),
+            Segmentation::Rle(rle) => CachedSegmentation::Rle(rle.clone()),
+            Segmentation::CompressedRle(rle) => CachedSegmentation::CompressedRle(rle.clone()),
+        });
+        Ok(CachedAnnotation {
+            id: id.into(),
+            image_id: image_id.into(),
+            category_id: category_id.into(),
+            segmentation,
+            area: *area,
+            bbox: bbox.clone(),
+            iscrowd: *iscrowd,
+            option: option.as_ref().map(serde_json::to_string).transpose()?,
+            keypoints: keypoints.clone(),
+            num_keypoints: *num_keypoints,
+            score: *score,
+            rbox: rbox.map(Into::into),
+            track_id: track_id.as_ref().map(CachedId::from),
+            extra: encode_extra(extra)?,
+            number_style: number_style.bits(),
+        })
+    }
+
+    fn into_annotation(self) -> serde_json::Result<COCOAnnotation> {
+        let segmentation = self.segmentation.map(|s| match s {
+            CachedSegmentation::Polygon(polygons) => Segmentation::Polygon(polygons),
+            CachedSegmentation::Rle(rle) => Segmentation::Rle(rle),
+            CachedSegmentation::CompressedRle(rle) => Segmentation::CompressedRle(rle),
+        });
+        Ok(COCOAnnotation {
+            id: self.id.into(),
+            image_id: self.image_id.into(),
+            category_id: self.category_id.into(),
+            segmentation,
+            area: self.area,
+            bbox: self.bbox,
+            iscrowd: self.iscrowd,
+            option: self
+                .option
+                .as_deref()
+                .map(serde_json::from_str)
+                .transpose()?,
+            keypoints: self.keypoints,
+            num_keypoints: self.num_keypoints,
+            score: self.score,
+            rbox: self.rbox.map(Into::into),
+            track_id: self.track_id.map(CocoId::from),
+            extra: decode_extra(self.extra)?,
+            number_style: NumberStyle::from_bits(self.number_style),
+        })
+    }
+}
+
+fn encode_extra(extra: &HashMap<String, Value>) -> serde_json::Result<Option<String>> {
+    if extra.is_empty() {
+        return Ok(None);
+    }
+    serde_json::to_string(extra).map(Some)
+}
+
+fn decode_extra(extra: Option<String>) -> serde_json::Result<HashMap<String, Value>> {
+    match extra {
+        Some(extra) => serde_json::from_str(&extra),
+        None => Ok(HashMap::new()),
+    }
+}
+
+fn is_current(header: &CacheHeader) -> bool {
+    header.app_version == env!("CARGO_PKG_VERSION")
+}
+
+pub(crate) fn canonical_string(path: &Path) -> String {
+    path.canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+/// キャッシュファイル名用のハッシュ（実行環境によらず同じ値になる）
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"{
+        "info": {"description": "cache test"},
+        "images": [{"id": 1, "width": 10, "height": 10, "file_name": "a.jpg", "camera": "x"}],
+        "annotations": [
+            {"id": "a-1", "image_id": 1, "category_id": 1, "bbox": [1, 2.0, 3, 4], "area": 12,
+             "iscrowd": 1, "segmentation": {"size": [10, 10], "counts": "2114"},
+             "option": {"k": [1, 2]}, "track_id": 3}
+        ],
+        "categories": [{"id": 1, "name": "x"}],
+        "custom": true
+    }"#;
+
+    fn setup(name: &str) -> (AnnotationCache, PathBuf) {
+        let dir =
+            std::env::temp_dir().join(format!("coav-cache-test-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("annotations.json");
+        fs::write(&source, SOURCE).unwrap();
+        (AnnotationCache::new(dir.join("cache")), source)
+    }
+
+    fn data() -> COCOData {
+        serde_json::from_str(SOURCE).unwrap()
+    }
+
+    #[test]
+    fn round_trips_all_fields() {
+        let (cache, source) = setup("round-trip");
+        assert!(cache.load(&source).is_none());
+
+        let original = data();
+        cache.store(&source, &original).unwrap();
+        let loaded = cache.load(&source).unwrap();
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&original).unwrap()
+        );
+        // 数値の表記も引き継がれる
+        let value = serde_json::to_value(&loaded).unwrap();
+        assert_eq!(value["annotations"][0]["bbox"][1].to_string(), "2.0");
+        assert_eq!(value["annotations"][0]["area"].to_string(), "12");
+
+        let entries = cache.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].source_path, canonical_string(&source));
+        assert_eq!((entries[0].images, entries[0].annotations), (1, 1));
+        assert!(!entries[0].stale);
+    }
+
+    #[test]
+    fn invalidates_when_source_changes() {
+        let (cache, source) = setup("invalidate");
+        cache.store(&source, &data()).unwrap();
+
+        fs::write(&source, format!("{SOURCE} ")).unwrap();
+        assert!(cache.entries().unwrap()[0].stale);
+        assert!(cache.load(&source).is_none());
+        // 無効なキャッシュは読み込み時に削除される
+        assert!(cache.entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn clears_single_source_or_everything() {
+        let (cache, source) = setup("clear");
+        let other = source.with_file_name("other.json");
+        fs::write(&other, SOURCE).unwrap();
+        cache.store(&source, &data()).unwrap();
+        cache.store(&other, &data()).unwrap();
+
+        assert_eq!(cache.clear(Some(&source)).unwrap(), 1);
+        assert_eq!(cache.clear(Some(&source)).unwrap(), 0);
+        assert_eq!(cache.entries().unwrap().len(), 1);
+        assert_eq!(cache.clear(None).unwrap(), 1);
+    }
+
+    #[test]
+    fn treats_bad_record_counts_as_miss() {
+        let (cache, source) = setup("bad-count");
+        let key = SourceKey::of(&source).unwrap();
+        let cache_path = cache.cache_path(&key.path);
+        fs::create_dir_all(cache.dir()).unwrap();
+
+        // ヘッダーと本体の件数が一致しない
+        let mut header = CacheHeader {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            source: key,
+            images: 2,
+            annotations: 1,
+        };
+        write_cache(&cache_path, &header, &data()).unwrap();
+        assert!(cache.load(&source).is_none());
+        assert!(!cache_path.exists());
+
+        // ファイルに収まらない件数（領域を確保せずに失敗する）
+        header.images = 1 << 40;
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend(bincode::serialize(&header).unwrap());
+        bytes.extend(
+            bincode::serialize(
+                &serde_json::to_string(&DatasetHead {
+                    info: None,
+                    categories: Vec::new(),
+                    licenses: None,
+                    videos: None,
+                    tracks: None,
+                    extra: HashMap::new(),
+                    coercions: Vec::new(),
+                })
+                .unwrap(),
+            )
+            .unwrap(),
+        );
+        bytes.extend(bincode::serialize(&(1u64 << 40)).unwrap());
+        fs::write(&cache_path, bytes).unwrap();
+        assert!(cache.load(&source).is_none());
+        assert!(!cache_path.exists());
+    }
+
+    #[test]
+    fn clears_extracted_images() {
+        let (_, source) = setup("extracted-images");
+        let other = source.with_file_name("other.tfrecord");
+        fs::write(&other, b"").unwrap();
+        let images = ExtractedImages::new(source.with_file_name("tfrecord_images"));
+        assert_eq!(images.total_bytes(), 0);
+        assert_eq!(images.clear(None).unwrap(), 0);
+
+        for (path, size) in [(&source, 3), (&other, 5)] {
+            let dir = images.dir_for(path).join("sub");
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("a.jpg"), vec![0u8; size]).unwrap();
+        }
+        assert_ne!(images.dir_for(&source), images.dir_for(&other));
+        assert_eq!(images.total_bytes(), 8);
+
+        assert_eq!(images.clear(Some(&source)).unwrap(), 1);
+        assert!(!images.dir_for(&source).exists());
+        assert_eq!(images.total_bytes(), 5);
+        assert_eq!(images.clear(None).unwrap(), 1);
+        assert_eq!(images.total_bytes(), 0);
+    }
+
+    #[test]
+    fn fnv1a_is_stable() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+    }
+}
@@ -0,0 +1,53 @@
+use super::{annotation_cache, extracted_images, run_blocking};
+use crate::cache::CacheEntry;
+use crate::error::CoavResult;
+use serde::Serialize;
+use std::path::Path;
+use tauri::AppHandle;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheInfo {
+    pub directory: String,
+    /// アノテーションキャッシュとTFRecordから書き出した画像の合計
+    pub total_bytes: u64,
+    pub entries: Vec<CacheEntry>,
+    /// TFRecordから書き出した画像のフォルダ
+    pub image_directory: String,
+    pub image_bytes: u64,
+}
+
+/// アノテーションキャッシュの一覧と使用量を返す
+#[tauri::command]
+pub async fn get_annotation_cache_info(app: AppHandle) -> CoavResult<CacheInfo> {
+    run_blocking(move || {
+        let cache = annotation_cache(&app)?;
+        let images = extracted_images(&app)?;
+        let entries = cache.entries()?;
+        let image_bytes = images.total_bytes();
+        Ok(CacheInfo {
+            directory: cache.dir().display().to_string(),
+            total_bytes: entries.iter().map(|e| e.cache_size).sum::<u64>() + image_bytes,
+            entries,
+            image_directory: images.dir().display().to_string(),
+            image_bytes,
+        })
+    })
+    .await
+}
+
+/// アノテーションキャッシュとTFRecordから書き出した画像を削除し、削除した件数を返す
+///
+/// `file_path` を指定した場合はそのファイルのキャッシュ・画像のみ削除する。
+#[tauri::command]
+pub async fn clear_annotation_cache(
+    app: AppHandle,
+    file_path: Option<String>,
+) -> CoavResult<usize> {
+    run_blocking(move || {
+        let source = file_path.as_deref().map(Path::new);
+        let removed = annotation_cache(&app)?.clear(source)?;
+        Ok(removed + extracted_images(&app)?.clear(source)?)
+    })
+    .await
+}
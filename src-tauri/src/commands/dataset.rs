@@ -0,0 +1,658 @@
+use super::{check_validation, read_annotation_file, run_blocking};
+use crate::error::{CoavError, CoavResult};
+use crate::json_repair::ParseDiagnostics;
+use crate::models::index::DatasetIndex;
+use crate::models::{
+    COCOAnnotation, COCOCategory, COCOData, COCOImage, COCOInfo, COCOVideo, CategoryFrequency,
+    CocoId, Ipc,
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Manager, State};
+
+/// バックエンドで保持している読み込み済みデータセット
+pub struct LoadedDataset {
+    pub data: COCOData,
+    pub index: DatasetIndex,
+}
+
+impl LoadedDataset {
+    pub fn new(data: COCOData) -> Self {
+        let index = DatasetIndex::build(&data);
+        LoadedDataset { data, index }
+    }
+
+    fn annotations_at<'a>(
+        &'a self,
+        positions: &'a [usize],
+    ) -> impl ExactSizeIterator<Item = &'a COCOAnnotation> + 'a {
+        positions.iter().map(|&i| &self.data.annotations[i])
+    }
+
+    /// 指定画像のアノテーション（画像が無い場合はエラー）
+    pub(super) fn image_annotations(
+        &self,
+        image_id: &CocoId,
+    ) -> CoavResult<impl ExactSizeIterator<Item = &COCOAnnotation> + '_> {
+        if self.index.image_position(image_id).is_none() {
+            return Err(CoavError::InvalidInput {
+                message: format!("Image not found: {image_id}"),
+            });
+        }
+        Ok(self.annotations_at(self.index.image_annotations(image_id)))
+    }
+
+    /// カテゴリを出現頻度の区分ごとにまとめる（区分の無いものは最後）
+    fn frequency_groups(&self) -> Vec<FrequencyGroup> {
+        let mut groups: Vec<FrequencyGroup> = Vec::new();
+        for category in &self.data.categories {
+            let count = self.index.category_annotations(&category.id).len();
+            match groups
+                .iter_mut()
+                .find(|group| group.frequency == category.frequency)
+            {
+                Some(group) => {
+                    group.categories.push(category.clone());
+                    group.annotation_count += count;
+                }
+                None => groups.push(FrequencyGroup {
+                    frequency: category.frequency,
+                    categories: vec![category.clone()],
+                    annotation_count: count,
+                }),
+            }
+        }
+        groups.sort_by_key(|group| (group.frequency.is_none(), group.frequency));
+        groups
+    }
+}
+
+/// 読み込み済みデータセットの識別子
+///
+/// 検出結果ファイルは結合する正解データによって内容が変わるため、`reference_path` も含めて区別する。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatasetKey {
+    pub file_path: String,
+    #[serde(default)]
+    pub reference_path: Option<String>,
+}
+
+impl fmt::Display for DatasetKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.reference_path {
+            Some(reference_path) => write!(f, "{} (reference: {reference_path})", self.file_path),
+            None => f.write_str(&self.file_path),
+        }
+    }
+}
+
+/// 読み込み済みデータセット（`DatasetKey` ごと、Tauriのmanaged stateとして共有）
+#[derive(Default)]
+pub struct DatasetState {
+    datasets: RwLock<HashMap<DatasetKey, Arc<LoadedDataset>>>,
+}
+
+impl DatasetState {
+    pub fn insert(&self, key: DatasetKey, dataset: LoadedDataset) -> Arc<LoadedDataset> {
+        let dataset = Arc::new(dataset);
+        self.datasets
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, Arc::clone(&dataset));
+        dataset
+    }
+
+    pub fn remove(&self, key: &DatasetKey) -> bool {
+        self.datasets
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(key)
+            .is_some()
+    }
+
+    pub fn get(&self, key: &DatasetKey) -> CoavResult<Arc<LoadedDataset>> {
+        self.datasets
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .cloned()
+            .ok_or_else(|| CoavError::InvalidInput {
+                message: format!("Dataset is not open: {key}"),
+            })
+    }
+}
+
+/// データセットの概要（アノテーション本体は含まない）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatasetSummary {
+    #[serde(flatten)]
+    pub dataset: DatasetKey,
+    pub info: Option<COCOInfo>,
+    pub categories: Vec<COCOCategory>,
+    pub image_count: usize,
+    pub annotation_count: usize,
+    pub annotation_counts_by_category: HashMap<CocoId, usize>,
+    /// 動画の一覧（`videos` が無い場合は画像の `video_id` から作成する）
+    pub videos: Vec<COCOVideo>,
+    /// 寛容モードで開いた場合の修復・除外した箇所
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<ParseDiagnostics>,
+}
+
+impl DatasetSummary {
+    fn new(key: DatasetKey, dataset: &LoadedDataset) -> Self {
+        let annotation_counts_by_category = dataset
+            .data
+            .categories
+            .iter()
+            .map(|c| {
+                (
+                    c.id.clone(),
+                    dataset.index.category_annotations(&c.id).len(),
+                )
+            })
+            .collect();
+        DatasetSummary {
+            dataset: key,
+            info: dataset.data.info.clone(),
+            categories: dataset.data.categories.clone(),
+            image_count: dataset.data.images.len(),
+            annotation_count: dataset.data.annotations.len(),
+            annotation_counts_by_category,
+            videos: video_list(dataset),
+            diagnostics: None,
+        }
+    }
+}
+
+fn video_list(dataset: &LoadedDataset) -> Vec<COCOVideo> {
+    if let Some(videos) = &dataset.data.videos {
+        return videos.clone();
+    }
+    let mut video_ids: Vec<&CocoId> = dataset.index.video_ids().collect();
+    video_ids.sort_unstable();
+    video_ids
+        .into_iter()
+        .map(|id| COCOVideo {
+            id: id.clone(),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// 出現頻度の区分ごとのカテゴリ（LVIS）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrequencyGroup {
+    /// 区分（`frequency` を持たないカテゴリは `None`）
+    pub frequency: Option<CategoryFrequency>,
+    pub categories: Vec<COCOCategory>,
+    pub annotation_count: usize,
+}
+
+/// ページ単位の取得結果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub offset: usize,
+    /// フィルタ適用後の総件数
+    pub total: usize,
+}
+
+impl<T> Page<T> {
+    fn slice(items: impl ExactSizeIterator<Item = T>, offset: usize, limit: usize) -> Self {
+        let total = items.len();
+        Page {
+            items: items.skip(offset).take(limit).collect(),
+            offset,
+            total,
+        }
+    }
+}
+
+/// 一覧表示用の画像情報
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageListItem {
+    #[serde(flatten)]
+    pub image: COCOImage,
+    pub annotation_count: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImageSortKey {
+    #[default]
+    Id,
+    FileName,
+    AnnotationCount,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageSort {
+    #[serde(default)]
+    pub key: ImageSortKey,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// 画像一覧の絞り込み条件（指定された条件をすべて満たす画像を返す）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageFilter {
+    /// ファイル名の部分一致（大文字・小文字を区別しない）
+    pub file_name: Option<String>,
+    /// いずれかのカテゴリのアノテーションを含む
+    pub category_ids: Option<Vec<CocoId>>,
+    /// アノテーションの有無
+    pub has_annotations: Option<bool>,
+}
+
+/// アノテーションファイルを読み込み、バックエンドに保持する
+///
+/// 以降は `list_images`・`get_image_annotations` などで必要な分だけ取得する。
+/// 検出結果ファイルは `reference_path` の正解データに結合する。
+/// `lenient` を指定した場合は `load_annotations` と同じ修復を行う寛容モードで読み込み、
+/// 修復・除外した箇所を `diagnostics` に返す（寛容モードではキャッシュを使用しない）。
+/// 読み込み中は `annotation-load-progress` イベントで進捗を通知する。
+#[tauri::command]
+pub async fn open_dataset(
+    app: AppHandle,
+    file_path: String,
+    reference_path: Option<String>,
+    lenient: Option<bool>,
+) -> CoavResult<Ipc<DatasetSummary>> {
+    run_blocking(move || {
+        let mut diagnostics = lenient.unwrap_or(false).then(ParseDiagnostics::default);
+        let coco_data = read_annotation_file(
+            &app,
+            &file_path,
+            reference_path.as_deref(),
+            diagnostics.as_mut(),
+        )?;
+        check_validation(&coco_data, &file_path)?;
+
+        let key = DatasetKey {
+            file_path,
+            reference_path,
+        };
+        let dataset = LoadedDataset::new(coco_data);
+        let mut summary = DatasetSummary::new(key.clone(), &dataset);
+        summary.diagnostics = diagnostics;
+        app.state::<DatasetState>().insert(key, dataset);
+        Ok(Ipc(summary))
+    })
+    .await
+}
+
+/// 保持しているデータセットを破棄する
+#[tauri::command]
+pub fn close_dataset(state: State<'_, DatasetState>, dataset: DatasetKey) -> bool {
+    state.remove(&dataset)
+}
+
+#[tauri::command]
+pub fn get_dataset_summary(
+    state: State<'_, DatasetState>,
+    dataset: DatasetKey,
+) -> CoavResult<Ipc<DatasetSummary>> {
+    let loaded = state.get(&dataset)?;
+    Ok(Ipc(DatasetSummary::new(dataset, &loaded)))
+}
+
+/// 保持しているデータセット全体を返す
+///
+/// 画面ですべてのアノテーションを扱える規模のデータセット用。大きなデータセットは
+/// `list_images`・`get_image_annotations` で必要な分だけ取得する。
+#[tauri::command]
+pub async fn get_dataset(
+    state: State<'_, DatasetState>,
+    dataset: DatasetKey,
+) -> CoavResult<Ipc<COCOData>> {
+    let dataset = state.get(&dataset)?;
+    run_blocking(move || Ok(Ipc(dataset.data.clone()))).await
+}
+
+/// 指定画像のアノテーションを返す
+#[tauri::command]
+pub fn get_image_annotations(
+    state: State<'_, DatasetState>,
+    dataset: DatasetKey,
+    image_id: CocoId,
+) -> CoavResult<Ipc<Vec<COCOAnnotation>>> {
+    let dataset = state.get(&dataset)?;
+    let annotations = dataset.image_annotations(&image_id)?.cloned().collect();
+    Ok(Ipc(annotations))
+}
+
+/// 指定カテゴリのアノテーションをページ単位で返す
+#[tauri::command]
+pub fn get_category_annotations(
+    state: State<'_, DatasetState>,
+    dataset: DatasetKey,
+    category_id: CocoId,
+    offset: usize,
+    limit: usize,
+) -> CoavResult<Ipc<Page<COCOAnnotation>>> {
+    let dataset = state.get(&dataset)?;
+    let annotations = dataset.annotations_at(dataset.index.category_annotations(&category_id));
+    Ok(Ipc(Page::slice(annotations.cloned(), offset, limit)))
+}
+
+/// カテゴリを出現頻度の区分（rare・common・frequent、区分なしの順）ごとにまとめて返す
+#[tauri::command]
+pub fn get_categories_by_frequency(
+    state: State<'_, DatasetState>,
+    dataset: DatasetKey,
+) -> CoavResult<Ipc<Vec<FrequencyGroup>>> {
+    Ok(Ipc(state.get(&dataset)?.frequency_groups()))
+}
+
+/// 動画の画像をフレーム番号順に返す
+#[tauri::command]
+pub fn get_video_frames(
+    state: State<'_, DatasetState>,
+    dataset: DatasetKey,
+    video_id: Ipc<CocoId>,
+) -> CoavResult<Ipc<Vec<ImageListItem>>> {
+    let video_id = video_id.0;
+    let dataset = state.get(&dataset)?;
+    let positions = dataset.index.video_frames(&video_id);
+    if positions.is_empty() {
+        return Err(CoavError::InvalidInput {
+            message: format!("Video not found: {video_id}"),
+        });
+    }
+    Ok(Ipc(positions
+        .iter()
+        .map(|&i| {
+            let image = &dataset.data.images[i];
+            ImageListItem {
+                image: image.clone(),
+                annotation_count: dataset.index.image_annotations(&image.id).len(),
+            }
+        })
+        .collect()))
+}
+
+/// 指定トラックのアノテーションをフレーム番号順に返す
+#[tauri::command]
+pub fn get_track_annotations(
+    state: State<'_, DatasetState>,
+    dataset: DatasetKey,
+    track_id: Ipc<CocoId>,
+) -> CoavResult<Ipc<Vec<COCOAnnotation>>> {
+    let track_id = track_id.0;
+    let dataset = state.get(&dataset)?;
+    let positions = dataset.index.track_annotations(&track_id);
+    if positions.is_empty() {
+        return Err(CoavError::InvalidInput {
+            message: format!("Track not found: {track_id}"),
+        });
+    }
+    Ok(Ipc(dataset.annotations_at(positions).cloned().collect()))
+}
+
+/// 画像一覧をページ単位で返す
+#[tauri::command]
+pub async fn list_images(
+    state: State<'_, DatasetState>,
+    dataset: DatasetKey,
+    offset: usize,
+    limit: usize,
+    sort: Option<ImageSort>,
+    filter: Option<ImageFilter>,
+) -> CoavResult<Ipc<Page<ImageListItem>>> {
+    let dataset = state.get(&dataset)?;
+    run_blocking(move || {
+        let sort = sort.unwrap_or_default();
+        let filter = filter.unwrap_or_default();
+        let images = filter_images(&dataset, &filter);
+        let images = sort_images(&dataset, images, sort);
+
+        let items = images.into_iter().map(|image| ImageListItem {
+            image: image.clone(),
+            annotation_count: dataset.index.image_annotations(&image.id).len(),
+        });
+        Ok(Ipc(Page::slice(items, offset, limit)))
+    })
+    .await
+}
+
+fn filter_images<'a>(dataset: &'a LoadedDataset, filter: &ImageFilter) -> Vec<&'a COCOImage> {
+    let file_name = filter.file_name.as_ref().map(|s| s.to_lowercase());
+    let category_ids: Option<HashSet<&CocoId>> =
+        filter.category_ids.as_ref().map(|ids| ids.iter().collect());
+
+    dataset
+        .data
+        .images
+        .iter()
+        .filter(|image| {
+            let positions = dataset.index.image_annotations(&image.id);
+            if let Some(has_annotations) = filter.has_annotations {
+                if positions.is_empty() == has_annotations {
+                    return false;
+                }
+            }
+            if let Some(file_name) = &file_name {
+                if !image.file_name.to_lowercase().contains(file_name) {
+                    return false;
+                }
+            }
+            if let Some(category_ids) = &category_ids {
+                if !dataset
+                    .annotations_at(positions)
+                    .any(|a| category_ids.contains(&a.category_id))
+                {
+                    return false;
+                }
+            }
+            true
+        })
+        .collect()
+}
+
+fn sort_images<'a>(
+    dataset: &LoadedDataset,
+    mut images: Vec<&'a COCOImage>,
+    sort: ImageSort,
+) -> Vec<&'a COCOImage> {
+    let compare = |a: &&COCOImage, b: &&COCOImage| -> Ordering {
+        let ordering = match sort.key {
+            ImageSortKey::Id => a.id.cmp(&b.id),
+            ImageSortKey::FileName => a.file_name.cmp(&b.file_name),
+            ImageSortKey::AnnotationCount => dataset
+                .index
+                .image_annotations(&a.id)
+                .len()
+                .cmp(&dataset.index.image_annotations(&b.id).len()),
+        };
+        // 同順位はIDで並べて結果を安定させる
+        let ordering = ordering.then_with(|| a.id.cmp(&b.id));
+        if sort.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    };
+    images.sort_unstable_by(compare);
+    images
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataset() -> LoadedDataset {
+        let data: COCOData = serde_json::from_str(
+            r#"{
+                "images": [
+                    {"id": 3, "width": 1, "height": 1, "file_name": "Beta.jpg"},
+                    {"id": 1, "width": 1, "height": 1, "file_name": "alpha.jpg"},
+                    {"id": 2, "width": 1, "height": 1, "file_name": "gamma.png"}
+                ],
+                "annotations": [
+                    {"id": 1, "image_id": 3, "category_id": 1, "bbox": [0, 0, 1, 1], "area": 1, "iscrowd": 0},
+                    {"id": 2, "image_id": 3, "category_id": 2, "bbox": [0, 0, 1, 1], "area": 1, "iscrowd": 0},
+                    {"id": 3, "image_id": 1, "category_id": 2, "bbox": [0, 0, 1, 1], "area": 1, "iscrowd": 0}
+                ],
+                "categories": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}]
+            }"#,
+        )
+        .unwrap();
+        LoadedDataset::new(data)
+    }
+
+    fn ids(images: &[&COCOImage]) -> Vec<i64> {
+        images
+            .iter()
+            .filter_map(|image| image.id.as_int())
+            .collect()
+    }
+
+    #[test]
+    fn groups_categories_by_frequency() {
+        let mut dataset = dataset();
+        dataset.data.categories[0].frequency = Some(CategoryFrequency::Frequent);
+        dataset
+            .data
+            .categories
+            .push(serde_json::from_str(r#"{"id": 3, "name": "c", "frequency": "r"}"#).unwrap());
+        dataset
+            .data
+            .categories
+            .push(serde_json::from_str(r#"{"id": 4, "name": "d", "frequency": "f"}"#).unwrap());
+        let groups: Vec<_> = dataset
+            .frequency_groups()
+            .into_iter()
+            .map(|group| {
+                let names: Vec<_> = group.categories.into_iter().map(|c| c.name).collect();
+                (group.frequency, names, group.annotation_count)
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (Some(CategoryFrequency::Rare), vec!["c".to_string()], 0),
+                (
+                    Some(CategoryFrequency::Frequent),
+                    vec!["a".to_string(), "d".to_string()],
+                    1
+                ),
+                (None, vec!["b".to_string()], 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn page_slices_after_counting_total() {
+        let page = Page::slice(0..10, 8, 5);
+        assert_eq!(page.items, vec![8, 9]);
+        assert_eq!((page.offset, page.total), (8, 10));
+
+        let page = Page::slice(0..3, 5, 5);
+        assert!(page.items.is_empty());
+        assert_eq!(page.total, 3);
+    }
+
+    #[test]
+    fn filters_images() {
+        let dataset = dataset();
+        let filter = |filter: ImageFilter| ids(&filter_images(&dataset, &filter));
+
+        assert_eq!(filter(ImageFilter::default()), vec![3, 1, 2]);
+        assert_eq!(
+            filter(ImageFilter {
+                file_name: Some("JPG".to_string()),
+                ..Default::default()
+            }),
+            vec![3, 1]
+        );
+        assert_eq!(
+            filter(ImageFilter {
+                has_annotations: Some(false),
+                ..Default::default()
+            }),
+            vec![2]
+        );
+        assert_eq!(
+            filter(ImageFilter {
+                category_ids: Some(vec![CocoId::Int(1)]),
+                has_annotations: Some(true),
+                ..Default::default()
+            }),
+            vec![3]
+        );
+    }
+
+    #[test]
+    fn sorts_images_with_stable_ties() {
+        let dataset = dataset();
+        let sorted = |key, descending| {
+            let images = filter_images(&dataset, &ImageFilter::default());
+            ids(&sort_images(
+                &dataset,
+                images,
+                ImageSort { key, descending },
+            ))
+        };
+
+        assert_eq!(sorted(ImageSortKey::Id, false), vec![1, 2, 3]);
+        assert_eq!(sorted(ImageSortKey::Id, true), vec![3, 2, 1]);
+        // 大文字・小文字はバイト順で比較する
+        assert_eq!(sorted(ImageSortKey::FileName, false), vec![3, 1, 2]);
+        assert_eq!(sorted(ImageSortKey::AnnotationCount, false), vec![2, 1, 3]);
+        assert_eq!(sorted(ImageSortKey::AnnotationCount, true), vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn state_keeps_datasets_by_path_and_reference() {
+        let key = |file_path: &str, reference_path: Option<&str>| DatasetKey {
+            file_path: file_path.to_string(),
+            reference_path: reference_path.map(str::to_string),
+        };
+        let state = DatasetState::default();
+        assert!(state.get(&key("a.json", None)).is_err());
+
+        state.insert(key("a.json", None), dataset());
+        assert_eq!(
+            state.get(&key("a.json", None)).unwrap().data.images.len(),
+            3
+        );
+
+        // 同じ検出結果ファイルでも、正解データが異なれば別のデータセット
+        let mut other = dataset();
+        other.data.images.pop();
+        state.insert(key("a.json", Some("gt.json")), other);
+        assert_eq!(
+            state.get(&key("a.json", None)).unwrap().data.images.len(),
+            3
+        );
+        let error = state.get(&key("a.json", Some("val.json"))).err().unwrap();
+        assert_eq!(
+            error.message(),
+            "Dataset is not open: a.json (reference: val.json)"
+        );
+
+        assert!(state.remove(&key("a.json", None)));
+        assert!(!state.remove(&key("a.json", None)));
+        assert!(state.get(&key("a.json", None)).is_err());
+        assert_eq!(
+            state
+                .get(&key("a.json", Some("gt.json")))
+                .unwrap()
+                .data
+                .images
+                .len(),
+            2
+        );
+    }
+}
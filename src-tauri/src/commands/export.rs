@@ -0,0 +1,135 @@
+use super::run_blocking;
+use crate::commands::dataset::{DatasetKey, DatasetState, LoadedDataset};
+use crate::error::{CoavError, CoavResult};
+use crate::export::{self, ExportOptions};
+use crate::formats::{tfrecord, voc, yolo, ExportReport};
+use crate::models::{COCOData, Ipc};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+/// エクスポート結果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSummary {
+    pub file_path: String,
+    pub images: usize,
+    pub annotations: usize,
+    pub bytes: u64,
+}
+
+/// エクスポートするデータ（渡されたデータ、または `open_dataset` で開いたデータセット）
+enum ExportSource {
+    Data(Box<COCOData>),
+    Dataset(Arc<LoadedDataset>),
+}
+
+impl ExportSource {
+    fn resolve(
+        app: &AppHandle,
+        coco_data: Option<Ipc<COCOData>>,
+        dataset: Option<DatasetKey>,
+    ) -> CoavResult<Self> {
+        match (coco_data, dataset) {
+            (Some(Ipc(coco_data)), None) => Ok(ExportSource::Data(Box::new(coco_data))),
+            (None, Some(dataset)) => Ok(ExportSource::Dataset(
+                app.state::<DatasetState>().get(&dataset)?,
+            )),
+            _ => Err(CoavError::InvalidInput {
+                message: "Specify either annotation data or an open dataset to export".to_string(),
+            }),
+        }
+    }
+
+    fn data(&self) -> &COCOData {
+        match self {
+            ExportSource::Data(coco_data) => coco_data,
+            ExportSource::Dataset(dataset) => &dataset.data,
+        }
+    }
+}
+
+/// アノテーションをCOCO形式のJSONとして書き出す
+///
+/// `coco_data` を渡すか、`open_dataset` で開いたデータセットを `dataset` に指定する。
+/// 追加の項目（`extra`・`option`）はすべてそのまま出力される。
+#[tauri::command]
+pub async fn export_annotations(
+    app: AppHandle,
+    output_path: String,
+    coco_data: Option<Ipc<COCOData>>,
+    dataset: Option<DatasetKey>,
+    options: Option<ExportOptions>,
+) -> CoavResult<ExportSummary> {
+    run_blocking(move || {
+        let options = options.unwrap_or_default();
+        let source = ExportSource::resolve(&app, coco_data, dataset)?;
+        let source = source.data();
+
+        let filtered = export::filtered_view(source, &options);
+        let coco_data = filtered.as_ref().unwrap_or(source);
+        let bytes = export::write_coco_json(Path::new(&output_path), coco_data, options.pretty)?;
+
+        Ok(ExportSummary {
+            file_path: output_path,
+            images: coco_data.images.len(),
+            annotations: coco_data.annotations.len(),
+            bytes,
+        })
+    })
+    .await
+}
+
+/// YOLO形式のラベルファイルと `data.yaml` を書き出す
+#[tauri::command]
+pub async fn export_yolo(
+    app: AppHandle,
+    output_dir: String,
+    coco_data: Option<Ipc<COCOData>>,
+    dataset: Option<DatasetKey>,
+    mode: yolo::ExportMode,
+) -> CoavResult<ExportReport> {
+    run_blocking(move || {
+        let source = ExportSource::resolve(&app, coco_data, dataset)?;
+        yolo::export(source.data(), Path::new(&output_dir), mode)
+    })
+    .await
+}
+
+/// 画像ごとにPascal VOC形式のXMLを書き出す
+#[tauri::command]
+pub async fn export_voc(
+    app: AppHandle,
+    output_dir: String,
+    coco_data: Option<Ipc<COCOData>>,
+    dataset: Option<DatasetKey>,
+) -> CoavResult<ExportReport> {
+    run_blocking(move || {
+        let source = ExportSource::resolve(&app, coco_data, dataset)?;
+        voc::export(source.data(), Path::new(&output_dir))
+    })
+    .await
+}
+
+/// TFRecord（TensorFlow Object Detection API）として書き出す
+///
+/// `image_root` を指定した場合は画像ファイルをレコードに埋め込む。
+#[tauri::command]
+pub async fn export_tfrecord(
+    app: AppHandle,
+    output_path: String,
+    coco_data: Option<Ipc<COCOData>>,
+    dataset: Option<DatasetKey>,
+    image_root: Option<String>,
+) -> CoavResult<ExportReport> {
+    run_blocking(move || {
+        let source = ExportSource::resolve(&app, coco_data, dataset)?;
+        tfrecord::export(
+            source.data(),
+            Path::new(&output_path),
+            image_root.as_deref().map(Path::new),
+        )
+    })
+    .await
+}
@@ -0,0 +1,100 @@
+use super::{extracted_images, run_blocking};
+use crate::error::CoavResult;
+use crate::formats::open_images::{self, OpenImagesSources};
+use crate::formats::{
+    cvat, dota, kitti, label_studio, labelme, mot, panoptic, tfrecord, voc, yolo, ImportResult,
+};
+use crate::models::Ipc;
+use std::path::Path;
+use tauri::AppHandle;
+
+/// YOLO形式（Ultralytics）のデータセットフォルダ、または `data.yaml` を読み込む
+#[tauri::command]
+pub async fn import_yolo(path: String) -> CoavResult<Ipc<ImportResult>> {
+    run_blocking(move || yolo::import(Path::new(&path)).map(Ipc)).await
+}
+
+/// Pascal VOC形式のデータセットフォルダ（`Annotations/*.xml`）を読み込む
+#[tauri::command]
+pub async fn import_voc(path: String) -> CoavResult<Ipc<ImportResult>> {
+    run_blocking(move || voc::import(Path::new(&path)).map(Ipc)).await
+}
+
+/// LabelMe形式のJSONが保存されたフォルダを読み込む
+#[tauri::command]
+pub async fn import_labelme(path: String) -> CoavResult<Ipc<ImportResult>> {
+    run_blocking(move || labelme::import(Path::new(&path)).map(Ipc)).await
+}
+
+/// CVAT for images 1.1 形式のXML（`annotations.xml`）を読み込む
+#[tauri::command]
+pub async fn import_cvat(path: String) -> CoavResult<Ipc<ImportResult>> {
+    run_blocking(move || cvat::import(Path::new(&path)).map(Ipc)).await
+}
+
+/// Label StudioのJSON・JSONL形式のエクスポートを読み込む
+///
+/// 画像は `image_folder`（省略時はエクスポートファイルのフォルダ）から探す。
+#[tauri::command]
+pub async fn import_label_studio(
+    path: String,
+    image_folder: Option<String>,
+) -> CoavResult<Ipc<ImportResult>> {
+    run_blocking(move || {
+        label_studio::import(Path::new(&path), image_folder.as_deref().map(Path::new)).map(Ipc)
+    })
+    .await
+}
+
+/// KITTI物体検出形式のラベルフォルダ（`label_2/`）を読み込む
+#[tauri::command]
+pub async fn import_kitti(path: String) -> CoavResult<Ipc<ImportResult>> {
+    run_blocking(move || kitti::import(Path::new(&path)).map(Ipc)).await
+}
+
+/// Open ImagesのCSV（矩形・セグメンテーション）を読み込む
+#[tauri::command]
+pub async fn import_open_images(sources: OpenImagesSources) -> CoavResult<Ipc<ImportResult>> {
+    run_blocking(move || open_images::import(&sources).map(Ipc)).await
+}
+
+/// DOTA形式の回転矩形のラベルフォルダ（`labelTxt/`）を読み込む
+#[tauri::command]
+pub async fn import_dota(path: String) -> CoavResult<Ipc<ImportResult>> {
+    run_blocking(move || dota::import(Path::new(&path)).map(Ipc)).await
+}
+
+/// MOTChallenge形式のシーケンスのフォルダ（`gt/gt.txt`）を読み込む
+#[tauri::command]
+pub async fn import_mot(path: String) -> CoavResult<Ipc<ImportResult>> {
+    run_blocking(move || mot::import(Path::new(&path)).map(Ipc)).await
+}
+
+/// COCOパノプティック形式のJSONと、セグメントIDのPNGを読み込む
+///
+/// PNGは `png_folder`（省略時はJSONと同じ名前のフォルダ）から探す。
+#[tauri::command]
+pub async fn import_panoptic(
+    path: String,
+    png_folder: Option<String>,
+) -> CoavResult<Ipc<ImportResult>> {
+    run_blocking(move || {
+        panoptic::import(Path::new(&path), png_folder.as_deref().map(Path::new)).map(Ipc)
+    })
+    .await
+}
+
+/// TFRecord（TensorFlow Object Detection API）のファイル、またはシャードのフォルダを読み込む
+///
+/// レコードに埋め込まれた画像はキャッシュフォルダに書き出し、`image_root` をそのフォルダとする。
+/// 同じファイルを前回読み込んだ際に書き出した画像は削除する（`clear_annotation_cache` でも削除できる）。
+#[tauri::command]
+pub async fn import_tfrecord(app: AppHandle, path: String) -> CoavResult<Ipc<ImportResult>> {
+    let images = extracted_images(&app)?;
+    run_blocking(move || {
+        let path = Path::new(&path);
+        images.clear(Some(path))?;
+        tfrecord::import(path, &images.dir_for(path)).map(Ipc)
+    })
+    .await
+}
@@ -0,0 +1,156 @@
+use super::dataset::{DatasetKey, DatasetState};
+use crate::error::{CoavError, CoavResult, ErrorLocation};
+use crate::models::{COCOAnnotation, COCOCategory, CocoId, Ipc, Keypoint};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedKeypoint {
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub keypoint: Keypoint,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationKeypoints {
+    pub annotation_id: CocoId,
+    pub category_id: CocoId,
+    pub keypoints: Vec<NamedKeypoint>,
+    pub skeleton: Vec<[u32; 2]>, // 1始まりのキーポイント番号の組
+}
+
+/// 保持しているデータセットから、指定画像のキーポイントを描画用にまとめて返す
+#[tauri::command]
+pub fn get_image_keypoints(
+    state: State<'_, DatasetState>,
+    dataset: DatasetKey,
+    image_id: CocoId,
+) -> CoavResult<Ipc<Vec<AnnotationKeypoints>>> {
+    let dataset = state.get(&dataset)?;
+    let keypoints = image_keypoints(
+        dataset.image_annotations(&image_id)?,
+        &dataset.data.categories,
+    )?;
+    Ok(Ipc(keypoints))
+}
+
+fn image_keypoints<'a>(
+    annotations: impl Iterator<Item = &'a COCOAnnotation>,
+    categories: &[COCOCategory],
+) -> CoavResult<Vec<AnnotationKeypoints>> {
+    let categories: HashMap<&CocoId, &COCOCategory> =
+        categories.iter().map(|c| (&c.id, c)).collect();
+
+    let mut result = Vec::new();
+    for annotation in annotations {
+        let Some(keypoints) = annotation.keypoint_list() else {
+            continue;
+        };
+        let keypoints = keypoints.map_err(|e| CoavError::Validation {
+            message: format!("Invalid keypoints for annotation {}: {e}", annotation.id),
+            location: ErrorLocation::default(),
+        })?;
+
+        let category = categories.get(&annotation.category_id);
+        let names = category.and_then(|c| c.keypoints.as_ref());
+        let skeleton = category
+            .and_then(|c| c.skeleton.clone())
+            .unwrap_or_default();
+
+        result.push(AnnotationKeypoints {
+            annotation_id: annotation.id.clone(),
+            category_id: annotation.category_id.clone(),
+            keypoints: keypoints
+                .into_iter()
+                .enumerate()
+                .map(|(i, keypoint)| NamedKeypoint {
+                    name: names.and_then(|n| n.get(i).cloned()),
+                    keypoint,
+                })
+                .collect(),
+            skeleton,
+        });
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::dataset::LoadedDataset;
+    use crate::models::keypoints::Visibility;
+    use crate::models::COCOData;
+
+    fn fixture() -> LoadedDataset {
+        let data: COCOData = serde_json::from_str(
+            r#"{
+                "images": [
+                    {"id": 1, "width": 10, "height": 10, "file_name": "a.jpg"},
+                    {"id": 2, "width": 10, "height": 10, "file_name": "b.jpg"}
+                ],
+                "annotations": [
+                    {"id": 1, "image_id": 1, "category_id": 1, "bbox": [0, 0, 10, 10],
+                     "area": 100, "iscrowd": 0, "keypoints": [1, 2, 2, 3, 4, 1], "num_keypoints": 2},
+                    {"id": 2, "image_id": 1, "category_id": 2, "bbox": [0, 0, 5, 5],
+                     "area": 25, "iscrowd": 0},
+                    {"id": 3, "image_id": 2, "category_id": 1, "bbox": [0, 0, 5, 5],
+                     "area": 25, "iscrowd": 0, "keypoints": [0, 0, 0, 0, 0, 0]}
+                ],
+                "categories": [
+                    {"id": 1, "name": "person", "keypoints": ["nose", "eye"], "skeleton": [[1, 2]]},
+                    {"id": 2, "name": "car"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        LoadedDataset::new(data)
+    }
+
+    fn keypoints_of(
+        dataset: &LoadedDataset,
+        image_id: i64,
+    ) -> CoavResult<Vec<AnnotationKeypoints>> {
+        image_keypoints(
+            dataset.image_annotations(&CocoId::Int(image_id))?,
+            &dataset.data.categories,
+        )
+    }
+
+    #[test]
+    fn names_keypoints_from_category() {
+        let result = keypoints_of(&fixture(), 1).unwrap();
+
+        // キーポイントを持たないアノテーションと他の画像は含まれない
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].annotation_id, CocoId::Int(1));
+        assert_eq!(result[0].skeleton, vec![[1, 2]]);
+        let names: Vec<_> = result[0]
+            .keypoints
+            .iter()
+            .map(|k| k.name.as_deref())
+            .collect();
+        assert_eq!(names, vec![Some("nose"), Some("eye")]);
+        assert_eq!(
+            result[0].keypoints[1].keypoint.visibility,
+            Visibility::Occluded
+        );
+    }
+
+    #[test]
+    fn reports_invalid_keypoints() {
+        let mut data = fixture().data;
+        data.annotations[0].keypoints = Some(vec![1.0, 2.0, 5.0]);
+        let error = keypoints_of(&LoadedDataset::new(data), 1).unwrap_err();
+        assert!(matches!(error, CoavError::Validation { .. }));
+    }
+
+    #[test]
+    fn reports_missing_image() {
+        let error = keypoints_of(&fixture(), 9).unwrap_err();
+        assert!(matches!(error, CoavError::InvalidInput { .. }));
+    }
+}
@@ -1,42 +1,210 @@
-use crate::models::{COCOData, COCOImage};
+use crate::archive::{self, ArchiveEntry};
+use crate::cache::{AnnotationCache, ExtractedImages};
+use crate::error::{CoavError, CoavResult, ErrorLocation};
+use crate::formats::find_image_file;
+use crate::json_repair::ParseDiagnostics;
+use crate::loader::{
+    self, AnnotationFile, LoadControl, LoadProgress, LoadRegistry, LOAD_PROGRESS_EVENT,
+};
+use crate::models::{COCOData, COCOImage, CocoId, Ipc, ValidationReport};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, State};
 
+pub mod cache;
+pub mod dataset;
+pub mod export;
+pub mod import;
+pub mod keypoints;
 pub mod sample_generator;
 
+/// アノテーションファイルを読み込む
+///
+/// 検出結果ファイル（`[{image_id, category_id, bbox, score}, ...]` 形式の配列）の場合は
+/// `reference_path` の正解データから画像とカテゴリを取得して結合する。
+/// 読み込み中は `annotation-load-progress` イベントで進捗を通知する。
 #[tauri::command]
 #[allow(dead_code)]
-pub async fn load_annotations(file_path: String) -> Result<COCOData, String> {
-    // ファイルの存在確認
-    if !Path::new(&file_path).exists() {
-        return Err(format!("File not found: {file_path}"));
+pub async fn load_annotations(
+    app: AppHandle,
+    file_path: String,
+    reference_path: Option<String>,
+) -> CoavResult<Ipc<COCOData>> {
+    run_blocking(move || {
+        let coco_data = read_annotation_file(&app, &file_path, reference_path.as_deref(), None)?;
+        check_validation(&coco_data, &file_path)?;
+        Ok(Ipc(coco_data))
+    })
+    .await
+}
+
+/// バリデーション（`Severity::Error` の問題が無ければ読み込みを続行する）
+fn check_validation(coco_data: &COCOData, file_path: &str) -> CoavResult<()> {
+    match coco_data.validate().error_summary() {
+        Some(message) => Err(CoavError::Validation {
+            message,
+            location: ErrorLocation::file(file_path),
+        }),
+        None => Ok(()),
     }
+}
+
+/// アノテーションファイルを検証し、警告を含むすべての問題を返す
+#[tauri::command]
+pub async fn validate_annotations(
+    app: AppHandle,
+    file_path: String,
+    reference_path: Option<String>,
+) -> CoavResult<Ipc<ValidationReport>> {
+    run_blocking(move || {
+        let coco_data = read_annotation_file(&app, &file_path, reference_path.as_deref(), None)?;
+        Ok(Ipc(coco_data.validate()))
+    })
+    .await
+}
+
+/// zip内のアノテーションファイルの一覧を返す（展開はしない）
+///
+/// 返されたパスは `load_annotations` などにそのまま渡して読み込める。
+#[tauri::command]
+pub async fn list_archive_entries(file_path: String) -> CoavResult<Vec<ArchiveEntry>> {
+    run_blocking(move || archive::list_entries(Path::new(&file_path))).await
+}
 
-    // ファイル読み込み
-    let content =
-        fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {e}"))?;
+/// 実行中のアノテーション読み込みを中断する
+///
+/// `load_id` は進捗イベント（`annotation-load-progress`）の `loadId`。
+/// 読み込みが既に終了していた場合は `false` を返す。
+#[tauri::command]
+pub fn cancel_annotation_load(registry: State<'_, LoadRegistry>, load_id: u64) -> bool {
+    registry.cancel(load_id)
+}
 
-    // JSONパース
-    let coco_data: COCOData =
-        serde_json::from_str(&content).map_err(|e| format!("Failed to parse JSON: {e}"))?;
+/// アノテーションファイルを読み込み、検出結果の場合は正解データに結合する
+///
+/// `diagnostics` を指定した場合は `file_path` を寛容モードで読み込み、修復・除外した箇所を格納する。
+fn read_annotation_file(
+    app: &AppHandle,
+    file_path: &str,
+    reference_path: Option<&str>,
+    diagnostics: Option<&mut ParseDiagnostics>,
+) -> CoavResult<COCOData> {
+    let registry = app.state::<LoadRegistry>();
+    let control = registry.begin();
+    let mut emit_progress = |progress: LoadProgress| {
+        let _ = app.emit(LOAD_PROGRESS_EVENT, progress);
+    };
 
-    // バリデーション
-    coco_data.validate()?;
+    let file = match diagnostics {
+        Some(diagnostics) => {
+            let (file, found) = loader::read_annotation_file_lenient(
+                Path::new(file_path),
+                &control,
+                &mut emit_progress,
+            )?;
+            *diagnostics = found;
+            file
+        }
+        None => load_file(app, Path::new(file_path), &control, &mut emit_progress)?,
+    };
+    let (results, coercions) = match file {
+        AnnotationFile::Dataset(coco_data) => return Ok(*coco_data),
+        AnnotationFile::Results(results, coercions) => (results, coercions),
+    };
 
+    // 検出結果は正解データの画像・カテゴリに結合する
+    let Some(reference_path) = reference_path else {
+        return Err(CoavError::ReferenceRequired {
+            path: file_path.to_string(),
+            message: format!(
+                "{file_path} is a detection results file; a reference annotation file is required"
+            ),
+        });
+    };
+    let reference = match load_file(app, Path::new(reference_path), &control, &mut emit_progress)? {
+        AnnotationFile::Dataset(coco_data) => *coco_data,
+        AnnotationFile::Results(..) => {
+            return Err(CoavError::InvalidInput {
+                message: format!("{reference_path} is not a COCO annotation file"),
+            })
+        }
+    };
+
+    let mut coco_data = COCOData::from_results(&reference, results);
+    coco_data.coercions.extend(coercions);
     Ok(coco_data)
 }
 
+/// キャッシュが有効であればキャッシュから、無ければファイルから読み込む
+///
+/// 検出結果ファイルは正解データとの組み合わせで内容が決まるためキャッシュしない。
+fn load_file(
+    app: &AppHandle,
+    path: &Path,
+    control: &LoadControl,
+    on_progress: &mut dyn FnMut(LoadProgress),
+) -> CoavResult<AnnotationFile> {
+    let cache = annotation_cache(app).ok();
+    if let Some(coco_data) = cache.as_ref().and_then(|cache| cache.load(path)) {
+        on_progress(LoadProgress::cached(path, &coco_data, control));
+        return Ok(AnnotationFile::Dataset(Box::new(coco_data)));
+    }
+
+    let file = loader::read_annotation_file(path, control, on_progress)?;
+    if let (Some(cache), AnnotationFile::Dataset(coco_data)) = (&cache, &file) {
+        // キャッシュの書き込みに失敗しても読み込み自体は成功として扱う
+        let _ = cache.store(path, coco_data);
+    }
+    Ok(file)
+}
+
+fn annotation_cache(app: &AppHandle) -> CoavResult<AnnotationCache> {
+    Ok(AnnotationCache::new(
+        app_cache_dir(app)?.join("annotations"),
+    ))
+}
+
+/// TFRecordから書き出した画像の保存先
+fn extracted_images(app: &AppHandle) -> CoavResult<ExtractedImages> {
+    Ok(ExtractedImages::new(
+        app_cache_dir(app)?.join("tfrecord_images"),
+    ))
+}
+
+fn app_cache_dir(app: &AppHandle) -> CoavResult<PathBuf> {
+    app.path().app_cache_dir().map_err(|e| CoavError::Internal {
+        message: format!("Failed to resolve cache directory: {e}"),
+    })
+}
+
+/// 重い処理をブロッキング用スレッドで実行する
+async fn run_blocking<T, F>(task: F) -> CoavResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> CoavResult<T> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|e| CoavError::Internal {
+            message: format!("Background task failed: {e}"),
+        })?
+}
+
 #[tauri::command]
 #[allow(dead_code)]
-pub async fn load_image(file_path: String) -> Result<Vec<u8>, String> {
+pub async fn load_image(file_path: String) -> CoavResult<Vec<u8>> {
     // ファイルの存在確認
     if !Path::new(&file_path).exists() {
-        return Err(format!("Image file not found: {file_path}"));
+        return Err(CoavError::not_found(
+            &file_path,
+            format!("Image file not found: {file_path}"),
+        ));
     }
 
     // 画像ファイルの読み込み
-    let image_data = fs::read(&file_path).map_err(|e| format!("Failed to read image file: {e}"))?;
+    let image_data = fs::read(&file_path)
+        .map_err(|e| CoavError::io(&file_path, "Failed to read image file", e))?;
 
     Ok(image_data)
 }
@@ -44,7 +212,7 @@ pub async fn load_image(file_path: String) -> Result<Vec<u8>, String> {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ImageMetadata {
-    pub id: i64,
+    pub id: CocoId,
     pub file_name: String,
     pub file_path: String,
     pub width: i32,
@@ -58,19 +226,27 @@ pub struct ImageMetadata {
 #[allow(dead_code)]
 pub async fn scan_folder(
     path: String,
-    coco_images: Vec<COCOImage>,
-) -> Result<Vec<ImageMetadata>, String> {
+    coco_images: Ipc<Vec<COCOImage>>,
+) -> CoavResult<Ipc<Vec<ImageMetadata>>> {
     let folder_path = Path::new(&path);
 
     // Verify folder exists
-    if !folder_path.exists() || !folder_path.is_dir() {
-        return Err(format!("Invalid folder path: {path}"));
+    if !folder_path.exists() {
+        return Err(CoavError::not_found(
+            folder_path,
+            format!("Folder not found: {path}"),
+        ));
+    }
+    if !folder_path.is_dir() {
+        return Err(CoavError::InvalidInput {
+            message: format!("Invalid folder path: {path}"),
+        });
     }
 
     let mut image_metadata_list = Vec::new();
 
     // If no COCO images provided, scan folder for all image files
-    if coco_images.is_empty() {
+    if coco_images.0.is_empty() {
         let supported_extensions = ["jpg", "jpeg", "png", "bmp", "gif", "webp"];
 
         if let Ok(entries) = fs::read_dir(folder_path) {
@@ -87,7 +263,7 @@ pub async fn scan_folder(
                                 .to_string();
 
                             let mut metadata = ImageMetadata {
-                                id: id_counter,
+                                id: id_counter.into(),
                                 file_name: file_name.clone(),
                                 file_path: entry_path.to_string_lossy().to_string(),
                                 width: 0,  // Will be determined when image is loaded
@@ -114,9 +290,9 @@ pub async fn scan_folder(
         image_metadata_list.sort_by(|a, b| a.file_name.cmp(&b.file_name));
     } else {
         // Process each COCO image
-        for coco_image in coco_images {
+        for coco_image in coco_images.0 {
             let mut metadata = ImageMetadata {
-                id: coco_image.id,
+                id: coco_image.id.clone(),
                 file_name: coco_image.file_name.clone(),
                 file_path: String::new(),
                 width: coco_image.width,
@@ -126,44 +302,18 @@ pub async fn scan_folder(
                 load_error: None,
             };
 
-            // Try to find the image file in the folder
-            let image_path = folder_path.join(&coco_image.file_name);
-
-            if image_path.exists() && image_path.is_file() {
-                metadata.file_path = image_path.to_string_lossy().to_string();
-                metadata.exists = true;
+            // Try to find the image file in the folder (or by file name in the folder)
+            match find_image_file(folder_path, &coco_image.file_name) {
+                Some(image_path) => {
+                    metadata.file_path = image_path.to_string_lossy().to_string();
+                    metadata.exists = true;
 
-                // Get file size
-                if let Ok(file_metadata) = fs::metadata(&image_path) {
-                    metadata.file_size = Some(file_metadata.len());
-                }
-            } else {
-                // Try to find in subdirectories
-                let file_name_only = Path::new(&coco_image.file_name)
-                    .file_name()
-                    .and_then(|f| f.to_str())
-                    .unwrap_or(&coco_image.file_name);
-
-                if let Ok(entries) = fs::read_dir(folder_path) {
-                    for entry in entries.flatten() {
-                        if let Ok(entry_path) = entry.path().canonicalize() {
-                            if entry_path.is_file()
-                                && entry_path.file_name().and_then(|f| f.to_str())
-                                    == Some(file_name_only)
-                            {
-                                metadata.file_path = entry_path.to_string_lossy().to_string();
-                                metadata.exists = true;
-
-                                if let Ok(file_metadata) = fs::metadata(&entry_path) {
-                                    metadata.file_size = Some(file_metadata.len());
-                                }
-                                break;
-                            }
-                        }
+                    // Get file size
+                    if let Ok(file_metadata) = fs::metadata(&image_path) {
+                        metadata.file_size = Some(file_metadata.len());
                     }
                 }
-
-                if !metadata.exists {
+                None => {
                     metadata.load_error = Some(format!("File not found: {}", coco_image.file_name));
                 }
             }
@@ -172,5 +322,5 @@ pub async fn scan_folder(
         }
     }
 
-    Ok(image_metadata_list)
+    Ok(Ipc(image_metadata_list))
 }
@@ -1,4 +1,7 @@
-use crate::models::{COCOAnnotation, COCOCategory, COCOData, COCOImage, COCOInfo, COCOLicense};
+use crate::error::{CoavError, CoavResult};
+use crate::models::{
+    COCOAnnotation, COCOCategory, COCOData, COCOImage, COCOInfo, COCOLicense, CocoId, Segmentation,
+};
 use image::{Rgb, RgbImage};
 use imageproc::drawing::{draw_filled_rect_mut, draw_polygon_mut};
 use imageproc::rect::Rect;
@@ -43,7 +46,7 @@ pub struct SampleGeneratorParams {
 
 #[tauri::command]
 #[allow(dead_code)]
-pub async fn generate_sample_data(params: SampleGeneratorParams) -> Result<String, String> {
+pub async fn generate_sample_data(params: SampleGeneratorParams) -> CoavResult<String> {
     let mut rng = rand::thread_rng();
 
     // Extract parameters
@@ -64,7 +67,7 @@ pub async fn generate_sample_data(params: SampleGeneratorParams) -> Result<Strin
 
     // Create output directory if it doesn't exist
     fs::create_dir_all(&output_dir)
-        .map_err(|e| format!("Failed to create output directory: {e}"))?;
+        .map_err(|e| CoavError::io(&output_dir, "Failed to create output directory", e))?;
 
     // Define all possible categories
     let all_categories = vec![
@@ -86,9 +89,13 @@ pub async fn generate_sample_data(params: SampleGeneratorParams) -> Result<Strin
         .take(num_classes as usize)
         .enumerate()
         .map(|(i, (name, supercategory))| COCOCategory {
-            id: (i + 1) as i32,
+            id: CocoId::from((i + 1) as i64),
             name: name.to_string(),
             supercategory: Some(supercategory.to_string()),
+            keypoints: None,
+            skeleton: None,
+            isthing: None,
+            frequency: None,
             extra: HashMap::new(),
         })
         .collect();
@@ -280,11 +287,11 @@ pub async fn generate_sample_data(params: SampleGeneratorParams) -> Result<Strin
         };
         let image_path = Path::new(&output_dir).join(&image_filename);
         img.save(&image_path)
-            .map_err(|e| format!("Failed to save image: {e}"))?;
+            .map_err(|e| CoavError::image(&image_path, format!("Failed to save image: {e}")))?;
 
         // Store image info
         all_images.push(COCOImage {
-            id: (image_idx + 1) as i64,
+            id: CocoId::from((image_idx + 1) as i64),
             width: width as i32,
             height: height as i32,
             file_name: image_filename.to_string(),
@@ -296,6 +303,10 @@ pub async fn generate_sample_data(params: SampleGeneratorParams) -> Result<Strin
             flickr_url: None,
             coco_url: None,
             date_captured: None,
+            video_id: None,
+            frame_id: None,
+            neg_category_ids: None,
+            not_exhaustive_category_ids: None,
             extra: HashMap::new(),
         });
 
@@ -330,15 +341,21 @@ pub async fn generate_sample_data(params: SampleGeneratorParams) -> Result<Strin
                     };
 
                 let annotation = COCOAnnotation {
-                    id: annotation_id_counter,
-                    image_id: (image_idx + 1) as i64,
-                    category_id: shape.category_id,
-                    segmentation: Some(segmentation),
+                    id: annotation_id_counter.into(),
+                    image_id: CocoId::from((image_idx + 1) as i64),
+                    category_id: shape.category_id.into(),
+                    segmentation: Some(Segmentation::Polygon(segmentation)),
                     area: shape.area,
                     bbox,
                     iscrowd: 0,
                     option,
+                    keypoints: None,
+                    num_keypoints: None,
+                    score: None,
+                    rbox: None,
+                    track_id: None,
                     extra: HashMap::new(),
+                    number_style: Default::default(),
                 };
                 annotation_id_counter += 1;
                 annotation
@@ -363,15 +380,21 @@ pub async fn generate_sample_data(params: SampleGeneratorParams) -> Result<Strin
         annotations: all_annotations,
         categories,
         licenses,
+        videos: None,
+        tracks: None,
         extra: HashMap::new(),
+        coercions: Vec::new(),
     };
 
     // Save COCO JSON
     let json_filename = format!("{base_filename}-annotation.json");
     let json_path = Path::new(&output_dir).join(&json_filename);
-    let json_content = serde_json::to_string_pretty(&coco_data)
-        .map_err(|e| format!("Failed to serialize COCO data: {e}"))?;
-    fs::write(&json_path, json_content).map_err(|e| format!("Failed to save JSON file: {e}"))?;
+    let json_content =
+        serde_json::to_string_pretty(&coco_data).map_err(|e| CoavError::Serialization {
+            message: format!("Failed to serialize COCO data: {e}"),
+        })?;
+    fs::write(&json_path, json_content)
+        .map_err(|e| CoavError::io(&json_path, "Failed to save JSON file", e))?;
 
     // Generate pair JSON if requested
     if params.include_pair_json.unwrap_or(false) {
@@ -392,10 +415,13 @@ pub async fn generate_sample_data(params: SampleGeneratorParams) -> Result<Strin
         );
         let pair_json_filename = format!("{base_filename}-pair.json");
         let pair_json_path = Path::new(&output_dir).join(&pair_json_filename);
-        let pair_json_content = serde_json::to_string_pretty(&pair_coco_data)
-            .map_err(|e| format!("Failed to serialize pair COCO data: {e}"))?;
+        let pair_json_content = serde_json::to_string_pretty(&pair_coco_data).map_err(|e| {
+            CoavError::Serialization {
+                message: format!("Failed to serialize pair COCO data: {e}"),
+            }
+        })?;
         fs::write(&pair_json_path, pair_json_content)
-            .map_err(|e| format!("Failed to save pair JSON file: {e}"))?;
+            .map_err(|e| CoavError::io(&pair_json_path, "Failed to save pair JSON file", e))?;
     }
 
     let total_annotations = coco_data.annotations.len();
@@ -451,14 +477,14 @@ fn generate_pair_json(
             "perfect" => {
                 // Perfect match - exact copy with same category and shape
                 let mut pair_annotation = annotation.clone();
-                pair_annotation.id = annotation_id_counter;
+                pair_annotation.id = annotation_id_counter.into();
                 annotation_id_counter += 1;
                 pair_annotations.push(pair_annotation);
             }
             "partial" => {
                 // Partial match - shift to create partial overlap but keep same category
                 let mut pair_annotation = annotation.clone();
-                pair_annotation.id = annotation_id_counter;
+                pair_annotation.id = annotation_id_counter.into();
                 annotation_id_counter += 1;
 
                 // Shift by 30-60% of the object size to create partial overlap
@@ -486,7 +512,8 @@ fn generate_pair_json(
                 let actual_shift_y = pair_annotation.bbox[1] - original_y;
 
                 // Also shift segmentation if present using actual shift amounts
-                if let Some(segmentation) = &mut pair_annotation.segmentation {
+                if let Some(Segmentation::Polygon(segmentation)) = &mut pair_annotation.segmentation
+                {
                     for polygon in segmentation.iter_mut() {
                         for i in (0..polygon.len()).step_by(2) {
                             polygon[i] += actual_shift_x;
@@ -528,14 +555,14 @@ fn generate_pair_json(
                         0 => {
                             // Exact match - copy annotation as-is with same category
                             let mut pair_annotation = annotation.clone();
-                            pair_annotation.id = annotation_id_counter;
+                            pair_annotation.id = annotation_id_counter.into();
                             annotation_id_counter += 1;
                             pair_annotations.push(pair_annotation);
                         }
                         1 => {
                             // Slight shift - move the annotation by a small amount but keep same category
                             let mut pair_annotation = annotation.clone();
-                            pair_annotation.id = annotation_id_counter;
+                            pair_annotation.id = annotation_id_counter.into();
                             annotation_id_counter += 1;
 
                             // Shift bbox by different amounts based on match index
@@ -561,7 +588,9 @@ fn generate_pair_json(
                             let actual_shift_y = pair_annotation.bbox[1] - original_y;
 
                             // Also shift segmentation if present using actual shift amounts
-                            if let Some(segmentation) = &mut pair_annotation.segmentation {
+                            if let Some(Segmentation::Polygon(segmentation)) =
+                                &mut pair_annotation.segmentation
+                            {
                                 for polygon in segmentation.iter_mut() {
                                     for i in (0..polygon.len()).step_by(2) {
                                         polygon[i] += actual_shift_x;
@@ -595,13 +624,13 @@ fn generate_pair_json(
         let height = rng.gen_range(30.0..80.0);
 
         let new_annotation = COCOAnnotation {
-            id: annotation_id_counter,
-            image_id: original_data.annotations[0].image_id,
+            id: annotation_id_counter.into(),
+            image_id: original_data.annotations[0].image_id.clone(),
             // category_idはrectangle固定とする
-            category_id: 1, // Assuming category_id 1 is "rectangle"
+            category_id: CocoId::from(1), // Assuming category_id 1 is "rectangle"
             bbox: vec![x, y, width, height],
             area: width * height,
-            segmentation: Some(vec![vec![
+            segmentation: Some(Segmentation::Polygon(vec![vec![
                 x,
                 y,
                 x + width,
@@ -610,10 +639,16 @@ fn generate_pair_json(
                 y + height,
                 x,
                 y + height,
-            ]]),
+            ]])),
             iscrowd: 0,
             option: None,
+            keypoints: None,
+            num_keypoints: None,
+            score: None,
+            rbox: None,
+            track_id: None,
             extra: HashMap::new(),
+            number_style: Default::default(),
         };
 
         annotation_id_counter += 1;
@@ -676,9 +711,13 @@ fn generate_pair_json(
             .categories
             .iter()
             .map(|cat| COCOCategory {
-                id: cat.id,
+                id: cat.id.clone(),
                 name: format!("{}2", cat.name),
                 supercategory: cat.supercategory.clone(),
+                keypoints: cat.keypoints.clone(),
+                skeleton: cat.skeleton.clone(),
+                isthing: cat.isthing,
+                frequency: cat.frequency,
                 extra: cat.extra.clone(),
             })
             .collect()
@@ -693,7 +732,10 @@ fn generate_pair_json(
         annotations: pair_annotations,
         categories,
         licenses: original_data.licenses.clone(),
+        videos: original_data.videos.clone(),
+        tracks: original_data.tracks.clone(),
         extra: original_data.extra.clone(),
+        coercions: Vec::new(),
     }
 }
 
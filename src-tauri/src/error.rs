@@ -0,0 +1,182 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// エラーの発生位置（分かる範囲のみ設定される）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorLocation {
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub json_path: Option<String>,
+}
+
+impl ErrorLocation {
+    pub fn file(path: impl AsRef<Path>) -> Self {
+        ErrorLocation {
+            file: Some(path.as_ref().display().to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Tauriコマンドが返すエラー
+///
+/// フロントエンドには `{ code, message, location }` の形でシリアライズされる。
+/// `code` は種類ごとに固定の文字列で、表示や分岐に使用できる。
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoavError {
+    /// ファイルやフォルダが存在しない
+    NotFound { path: String, message: String },
+    /// ファイルの読み書きに失敗した
+    Io { path: String, message: String },
+    /// JSONの構文エラー、または型が一致しない
+    Parse {
+        message: String,
+        location: ErrorLocation,
+    },
+    /// 読み込んだデータが検証を通過しなかった
+    Validation {
+        message: String,
+        location: ErrorLocation,
+    },
+    /// 画像の読み込み・保存に失敗した
+    Image { path: String, message: String },
+    /// コマンドの引数が不正
+    InvalidInput { message: String },
+    /// 検出結果ファイルのため、結合する正解データ（`reference_path`）の指定が必要
+    ReferenceRequired { path: String, message: String },
+    /// zip内に複数のアノテーションファイルがあるため、読み込むファイルの指定が必要
+    ArchiveEntryRequired { path: String, message: String },
+    /// zipファイル自体が壊れているなど、zipとして読み込めない
+    Archive { path: String, message: String },
+    /// データのシリアライズに失敗した
+    Serialization { message: String },
+    /// ユーザーの操作により処理が中断された
+    Cancelled { message: String },
+    /// バックグラウンド処理の失敗など、その他の内部エラー
+    Internal { message: String },
+}
+
+pub type CoavResult<T> = Result<T, CoavError>;
+
+impl CoavError {
+    pub fn not_found(path: impl AsRef<Path>, message: String) -> Self {
+        CoavError::NotFound {
+            path: path.as_ref().display().to_string(),
+            message,
+        }
+    }
+
+    /// I/Oエラーを変換する（存在しないファイルは `NotFound` になる）
+    pub fn io(path: impl AsRef<Path>, context: &str, error: io::Error) -> Self {
+        let path = path.as_ref().display().to_string();
+        if error.kind() == io::ErrorKind::NotFound {
+            let message = format!("File not found: {path}");
+            return CoavError::NotFound { path, message };
+        }
+        CoavError::Io {
+            path,
+            message: format!("{context}: {error}"),
+        }
+    }
+
+    /// パス付きのJSONデシリアライズエラーを変換する
+    pub fn json(
+        path: impl AsRef<Path>,
+        error: serde_path_to_error::Error<serde_json::Error>,
+    ) -> Self {
+        let json_path = error.path().to_string();
+        let inner = error.inner();
+        let location = ErrorLocation {
+            file: Some(path.as_ref().display().to_string()),
+            line: Some(inner.line()),
+            column: Some(inner.column()),
+            json_path: (json_path != ".").then_some(json_path),
+        };
+        CoavError::Parse {
+            message: format!("Failed to parse JSON: {inner}"),
+            location,
+        }
+    }
+
+    pub fn image(path: impl AsRef<Path>, message: String) -> Self {
+        CoavError::Image {
+            path: path.as_ref().display().to_string(),
+            message,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            CoavError::NotFound { .. } => "not_found",
+            CoavError::Io { .. } => "io_error",
+            CoavError::Parse { .. } => "parse_error",
+            CoavError::Validation { .. } => "validation_error",
+            CoavError::Image { .. } => "image_error",
+            CoavError::InvalidInput { .. } => "invalid_input",
+            CoavError::ReferenceRequired { .. } => "reference_required",
+            CoavError::ArchiveEntryRequired { .. } => "archive_entry_required",
+            CoavError::Archive { .. } => "archive_error",
+            CoavError::Serialization { .. } => "serialization_error",
+            CoavError::Cancelled { .. } => "cancelled",
+            CoavError::Internal { .. } => "internal_error",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            CoavError::NotFound { message, .. }
+            | CoavError::Io { message, .. }
+            | CoavError::Parse { message, .. }
+            | CoavError::Validation { message, .. }
+            | CoavError::Image { message, .. }
+            | CoavError::InvalidInput { message }
+            | CoavError::ReferenceRequired { message, .. }
+            | CoavError::ArchiveEntryRequired { message, .. }
+            | CoavError::Archive { message, .. }
+            | CoavError::Serialization { message }
+            | CoavError::Cancelled { message }
+            | CoavError::Internal { message } => message,
+        }
+    }
+
+    pub fn location(&self) -> Option<ErrorLocation> {
+        match self {
+            CoavError::NotFound { path, .. }
+            | CoavError::Io { path, .. }
+            | CoavError::Image { path, .. }
+            | CoavError::ReferenceRequired { path, .. }
+            | CoavError::ArchiveEntryRequired { path, .. }
+            | CoavError::Archive { path, .. } => Some(ErrorLocation::file(path)),
+            CoavError::Parse { location, .. } | CoavError::Validation { location, .. } => {
+                Some(location.clone())
+            }
+            CoavError::InvalidInput { .. }
+            | CoavError::Serialization { .. }
+            | CoavError::Cancelled { .. }
+            | CoavError::Internal { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for CoavError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for CoavError {}
+
+impl Serialize for CoavError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CoavError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.serialize_field("location", &self.location())?;
+        state.end()
+    }
+}
@@ -0,0 +1,314 @@
+use crate::error::{CoavError, CoavResult};
+use crate::models::{COCOData, CocoId};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// エクスポートの設定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportOptions {
+    /// インデントを付けて出力する（`false` の場合は改行・空白を含めない）
+    #[serde(default = "default_pretty")]
+    pub pretty: bool,
+    /// 指定した画像のみ出力する
+    #[serde(default)]
+    pub image_ids: Option<Vec<CocoId>>,
+    /// 指定したカテゴリのみ出力する
+    #[serde(default)]
+    pub category_ids: Option<Vec<CocoId>>,
+}
+
+fn default_pretty() -> bool {
+    true
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            pretty: default_pretty(),
+            image_ids: None,
+            category_ids: None,
+        }
+    }
+}
+
+impl ExportOptions {
+    fn is_filtered(&self) -> bool {
+        self.image_ids.is_some() || self.category_ids.is_some()
+    }
+}
+
+/// 一時ファイルに書き込んでから置き換える
+///
+/// 書き込みに失敗した場合は一時ファイルを削除し、既存のファイルには手を付けない。
+pub fn write_atomic<F>(path: &Path, write: F) -> CoavResult<u64>
+where
+    F: FnOnce(&mut BufWriter<File>) -> CoavResult<()>,
+{
+    let temp_path = temp_path_for(path);
+    let result = File::create(&temp_path)
+        .map_err(|e| CoavError::io(&temp_path, "Failed to create file", e))
+        .and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            write(&mut writer)?;
+            let file = writer
+                .into_inner()
+                .map_err(|e| CoavError::io(&temp_path, "Failed to write file", e.into_error()))?;
+            file.sync_all()
+                .map_err(|e| CoavError::io(&temp_path, "Failed to write file", e))?;
+            Ok(file.metadata().map(|m| m.len()).unwrap_or(0))
+        })
+        .and_then(|size| {
+            fs::rename(&temp_path, path)
+                .map_err(|e| CoavError::io(path, "Failed to write file", e))?;
+            Ok(size)
+        });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// 出力先と同じフォルダの一時ファイル（同じファイルシステム内で置き換えるため）
+fn temp_path_for(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()))
+}
+
+/// COCO形式のJSONとして書き込み、書き込んだバイト数を返す
+pub fn write_coco_json(path: &Path, coco_data: &COCOData, pretty: bool) -> CoavResult<u64> {
+    write_atomic(path, |writer| {
+        let result = if pretty {
+            serde_json::to_writer_pretty(&mut *writer, coco_data)
+        } else {
+            serde_json::to_writer(&mut *writer, coco_data)
+        };
+        result.map_err(|e| CoavError::Serialization {
+            message: format!("Failed to serialize annotations: {e}"),
+        })?;
+        writer
+            .write_all(b"\n")
+            .map_err(|e| CoavError::io(path, "Failed to write file", e))
+    })
+}
+
+/// 画像・カテゴリで絞り込んだデータを作成する（絞り込みが無い場合は `None`）
+///
+/// `info`・`licenses`・`videos`・`tracks` やトップレベルの追加項目はそのまま引き継ぐ。
+pub fn filtered_view(coco_data: &COCOData, options: &ExportOptions) -> Option<COCOData> {
+    if !options.is_filtered() {
+        return None;
+    }
+    let image_ids: Option<HashSet<&CocoId>> =
+        options.image_ids.as_ref().map(|ids| ids.iter().collect());
+    let category_ids: Option<HashSet<&CocoId>> = options
+        .category_ids
+        .as_ref()
+        .map(|ids| ids.iter().collect());
+
+    let keep_image = |id: &CocoId| image_ids.as_ref().map_or(true, |ids| ids.contains(id));
+    let keep_category = |id: &CocoId| category_ids.as_ref().map_or(true, |ids| ids.contains(id));
+
+    Some(COCOData {
+        info: coco_data.info.clone(),
+        images: coco_data
+            .images
+            .iter()
+            .filter(|image| keep_image(&image.id))
+            .cloned()
+            .collect(),
+        annotations: coco_data
+            .annotations
+            .iter()
+            .filter(|a| keep_image(&a.image_id) && keep_category(&a.category_id))
+            .cloned()
+            .collect(),
+        categories: coco_data
+            .categories
+            .iter()
+            .filter(|category| keep_category(&category.id))
+            .cloned()
+            .collect(),
+        licenses: coco_data.licenses.clone(),
+        videos: coco_data.videos.clone(),
+        tracks: coco_data.tracks.clone(),
+        extra: coco_data.extra.clone(),
+        coercions: coco_data.coercions.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::{read_annotation_file, AnnotationFile, LoadControl};
+    use serde_json::Value;
+
+    const SOURCE: &str = r##"{
+        "info": {"description": "test", "url": null, "version": "1.0", "year": 2024,
+                 "contributor": null, "date_created": "2024-01-01", "custom_info": [1, 2]},
+        "licenses": [{"url": "http://example.com", "id": 1, "name": "CC", "note": "x"}],
+        "images": [
+            {"id": 1, "width": 640, "height": 480, "file_name": "a.jpg", "license": 1,
+             "camera": {"model": "X", "exposure": 0.1}}
+        ],
+        "annotations": [
+            {"id": 1, "image_id": 1, "category_id": 1,
+             "segmentation": [[10.5, 10.25, 20.1, 10.0, 20.0, 30.3]],
+             "area": 98.765432109876, "bbox": [10.0, 10.0, 10.1, 20.3], "iscrowd": 0,
+             "option": {"occluded": true, "tags": ["a", "b"]}, "attributes": {"color": "red"}},
+            {"id": 2, "image_id": 1, "category_id": 2,
+             "segmentation": {"size": [480, 640], "counts": "PPYo09"},
+             "area": 0.1, "bbox": [0.1, 0.2, 0.30000000000000004, 1e-7], "iscrowd": 1,
+             "score": 0.987654321,
+             "keypoints": [1.0, 2.0, 2.0, 3.5, 4.5, 1.0], "num_keypoints": 2}
+        ],
+        "categories": [
+            {"id": 1, "name": "cat", "supercategory": "animal"},
+            {"id": 2, "name": "person",
+             "keypoints": ["nose", "eye"], "skeleton": [[1, 2]], "color": "#ff0000"}
+        ],
+        "custom_top_level": {"nested": [true, null, 1.5]}
+    }"##;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("coav-export-test-{}-{name}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn load(path: &Path) -> COCOData {
+        match read_annotation_file(path, &LoadControl::default(), &mut |_| {}).unwrap() {
+            AnnotationFile::Dataset(coco_data) => *coco_data,
+            AnnotationFile::Results(..) => panic!("unexpected results file"),
+        }
+    }
+
+    #[test]
+    fn load_export_load_round_trip() {
+        let dir = temp_dir("round-trip");
+        let source_path = dir.join("source.json");
+        fs::write(&source_path, SOURCE).unwrap();
+        let loaded = load(&source_path);
+
+        for pretty in [true, false] {
+            let export_path = dir.join(format!("exported-{pretty}.json"));
+            write_coco_json(&export_path, &loaded, pretty).unwrap();
+            let reloaded = load(&export_path);
+
+            assert_eq!(
+                serde_json::to_value(&loaded).unwrap(),
+                serde_json::to_value(&reloaded).unwrap()
+            );
+            // 元のファイルの項目がすべてそのまま出力されている
+            let source: Value = serde_json::from_str(SOURCE).unwrap();
+            let exported: Value =
+                serde_json::from_str(&fs::read_to_string(&export_path).unwrap()).unwrap();
+            assert_eq!(source, exported);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn filtered_view_keeps_matching_annotations() {
+        let coco_data: COCOData = serde_json::from_str(SOURCE).unwrap();
+        let options = ExportOptions {
+            category_ids: Some(vec![2.into()]),
+            ..Default::default()
+        };
+        let view = filtered_view(&coco_data, &options).unwrap();
+        assert_eq!(view.images.len(), 1);
+        assert_eq!(view.categories.len(), 1);
+        assert_eq!(view.annotations.len(), 1);
+        assert_eq!(view.annotations[0].id, 2);
+        assert_eq!(view.extra, coco_data.extra);
+        assert!(filtered_view(&coco_data, &ExportOptions::default()).is_none());
+    }
+
+    /// COCO 2017（instances・person_keypoints）の形式を縮小したデータ
+    const COCO_2017: &str = r##"{
+        "info": {"description": "COCO 2017 Dataset", "url": "http://cocodataset.org",
+                 "version": "1.0", "year": 2017, "contributor": "COCO Consortium",
+                 "date_created": "2017/09/01"},
+        "licenses": [{"url": "http://creativecommons.org/licenses/by-nc-sa/2.0/", "id": 1,
+                      "name": "Attribution-NonCommercial-ShareAlike License"}],
+        "images": [
+            {"license": 4, "file_name": "000000397133.jpg",
+             "coco_url": "http://images.cocodataset.org/val2017/000000397133.jpg",
+             "height": 427, "width": 640, "date_captured": "2013-11-14 17:02:52",
+             "flickr_url": "http://farm7.staticflickr.com/6116/6255196340_da26cf2c9e_z.jpg",
+             "id": 397133}
+        ],
+        "annotations": [
+            {"segmentation": [[510.66, 423.01, 511.72, 420.03, 510.45, 416.0, 510.34, 413.02]],
+             "area": 702.1057499999998, "iscrowd": 0, "image_id": 397133,
+             "bbox": [473.07, 395.93, 38.65, 28.67], "category_id": 18, "id": 1768},
+            {"segmentation": {"counts": [272, 2, 4, 4, 4, 4, 2, 9], "size": [427, 640]},
+             "area": 220834, "iscrowd": 1, "image_id": 397133,
+             "bbox": [0, 34, 639, 388], "category_id": 1, "id": 900100397133},
+            {"segmentation": [[125.12, 539.69, 140.94, 522.43, 100.67, 496.54]],
+             "num_keypoints": 10, "area": 47803.27955, "iscrowd": 0,
+             "keypoints": [0, 0, 0, 0, 0, 0, 0, 0, 0, 142, 309, 1, 177, 320, 2],
+             "image_id": 397133, "bbox": [73.35, 206.02, 300.58, 372.5], "category_id": 1,
+             "id": 183126}
+        ],
+        "categories": [
+            {"supercategory": "person", "id": 1, "name": "person",
+             "keypoints": ["nose", "left_eye", "right_eye", "left_ear", "right_ear"],
+             "skeleton": [[1, 2], [1, 3], [2, 4], [3, 5]]},
+            {"supercategory": "animal", "id": 18, "name": "dog"}
+        ]
+    }"##;
+
+    #[test]
+    fn coco_file_round_trips_unchanged() {
+        let dir = temp_dir("coco-2017");
+        let source_path = dir.join("instances_val2017.json");
+        fs::write(&source_path, COCO_2017).unwrap();
+        let export_path = dir.join("instances_val2017.exported.json");
+        write_coco_json(&export_path, &load(&source_path), false).unwrap();
+
+        // 整数の面積・座標・キーポイントは整数のまま、省略された項目は省略されたまま出力される
+        let source: Value = serde_json::from_str(COCO_2017).unwrap();
+        let exported: Value =
+            serde_json::from_str(&fs::read_to_string(&export_path).unwrap()).unwrap();
+        assert_eq!(source, exported);
+        assert!(exported["annotations"][1]["area"].is_u64());
+        assert!(exported["annotations"][0]["area"].is_f64());
+    }
+
+    #[test]
+    fn omits_null_optional_fields() {
+        let coco_data: COCOData = serde_json::from_str(
+            r#"{
+                "info": null, "licenses": null,
+                "images": [{"id": 1, "width": 1, "height": 1, "file_name": "a.jpg",
+                            "license": null, "flickr_url": null, "coco_url": null,
+                            "date_captured": null}],
+                "annotations": [{"id": 1, "image_id": 1, "category_id": 1, "segmentation": null,
+                                 "area": 4, "bbox": [0, 0.5, 2, 2], "iscrowd": 0,
+                                 "option": null}],
+                "categories": [{"id": 1, "name": "a", "supercategory": null}]
+            }"#,
+        )
+        .unwrap();
+        let exported = serde_json::to_value(&coco_data).unwrap();
+        assert_eq!(
+            exported,
+            serde_json::json!({
+                "images": [{"id": 1, "width": 1, "height": 1, "file_name": "a.jpg"}],
+                "annotations": [{"id": 1, "image_id": 1, "category_id": 1,
+                                 "area": 4, "bbox": [0, 0.5, 2, 2], "iscrowd": 0}],
+                "categories": [{"id": 1, "name": "a"}]
+            })
+        );
+    }
+}
@@ -0,0 +1,573 @@
+use super::{ImportResult, Issues};
+use crate::error::{CoavError, CoavResult, ErrorLocation};
+use crate::models::segmentation::{polygon_area, polygons_bbox};
+use crate::models::{COCOAnnotation, COCOCategory, COCOData, COCOImage, Segmentation};
+use roxmltree::{Document, Node};
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::f64::consts::TAU;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 楕円をポリゴンに変換する際の頂点数
+const ELLIPSE_VERTICES: usize = 32;
+
+/// `<meta>` に定義された属性の入力形式
+#[derive(Clone, Copy)]
+enum InputType {
+    Checkbox,
+    Number,
+    Text,
+}
+
+/// 読み込んだ図形（座標はCOCOのピクセル座標）
+enum Geometry {
+    Box([f64; 4]),
+    Polygon(Vec<f64>),
+    Polyline(Vec<f64>),
+    Points(Vec<f64>),
+}
+
+/// CVAT for images 1.1 形式のXMLを読み込む
+///
+/// `path` には `annotations.xml`、またはそれを含むフォルダを指定する。
+/// カテゴリは `<meta>` のラベル定義の順にIDを振る（定義が無い場合はラベル名の順）。
+/// 属性・`occluded`・`z_order` などはアノテーションの `option` に保存する。
+/// ポリラインは `option.polyline` に座標を保存して矩形のみ、点は `keypoints` として読み込む。
+pub fn import(path: &Path) -> CoavResult<ImportResult> {
+    let xml_path = if path.is_dir() {
+        path.join("annotations.xml")
+    } else {
+        path.to_path_buf()
+    };
+    let text = fs::read_to_string(&xml_path)
+        .map_err(|e| CoavError::io(&xml_path, "Failed to read file", e))?;
+    let document = Document::parse(&text).map_err(|e| CoavError::Parse {
+        message: format!("Failed to parse XML: {e}"),
+        location: ErrorLocation {
+            file: Some(xml_path.display().to_string()),
+            line: Some(e.pos().row as usize),
+            column: Some(e.pos().col as usize),
+            json_path: None,
+        },
+    })?;
+    let root = document.root_element();
+    if !root.has_tag_name("annotations") {
+        return Err(CoavError::Parse {
+            message: format!(
+                "Not a CVAT XML file: unexpected root element <{}>",
+                root.tag_name().name()
+            ),
+            location: ErrorLocation::file(&xml_path),
+        });
+    }
+
+    let mut issues = Issues::default();
+    let (mut labels, input_types) = read_meta(root);
+    let image_nodes: Vec<Node> = elements(root, "image").collect();
+    if labels.is_empty() {
+        // ラベル定義が無い場合は使われているラベル名から作成する
+        let names: BTreeSet<&str> = image_nodes
+            .iter()
+            .flat_map(|image| image.children().filter(Node::is_element))
+            .filter_map(|shape| shape.attribute("label"))
+            .collect();
+        labels = names.into_iter().map(str::to_string).collect();
+    }
+    if elements(root, "track").next().is_some() {
+        issues.push(
+            &xml_path,
+            None,
+            "Tracks are not supported; export the task as \"CVAT for images\"".to_string(),
+        );
+    }
+
+    let mut category_ids: HashMap<String, i32> = labels
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.clone(), i as i32 + 1))
+        .collect();
+    let mut coco_data = COCOData::default();
+
+    for image_node in image_nodes {
+        let line = Some(document.text_pos_at(image_node.range().start).row as usize);
+        let Some(name) = image_node.attribute("name") else {
+            issues.push(&xml_path, line, "Missing name in <image>".to_string());
+            continue;
+        };
+        let size = |key: &str| {
+            image_node
+                .attribute(key)
+                .and_then(|v| v.parse::<i32>().ok())
+                .unwrap_or(0)
+        };
+        let image_id = coco_data.images.len() as i64 + 1;
+        let mut image = COCOImage {
+            id: image_id.into(),
+            width: size("width"),
+            height: size("height"),
+            file_name: name.to_string(),
+            ..Default::default()
+        };
+        if let Some(subset) = image_node.attribute("subset") {
+            image
+                .extra
+                .insert("subset".to_string(), Value::String(subset.to_string()));
+        }
+
+        let mut tags = Vec::new();
+        for shape in image_node.children().filter(Node::is_element) {
+            let line = Some(document.text_pos_at(shape.range().start).row as usize);
+            let kind = shape.tag_name().name();
+            if kind == "tag" {
+                tags.extend(
+                    shape
+                        .attribute("label")
+                        .map(|l| Value::String(l.to_string())),
+                );
+                continue;
+            }
+            let geometry = match read_geometry(shape) {
+                Ok(geometry) => geometry,
+                Err(message) => {
+                    issues.push(&xml_path, line, format!("{name}: {message}"));
+                    continue;
+                }
+            };
+            let Some(label) = shape.attribute("label") else {
+                issues.push(
+                    &xml_path,
+                    line,
+                    format!("{name}: missing label in <{kind}>"),
+                );
+                continue;
+            };
+            let category_id = match category_ids.get(label) {
+                Some(id) => *id,
+                None => {
+                    issues.push(
+                        &xml_path,
+                        line,
+                        format!("Label \"{label}\" is not defined in <meta>"),
+                    );
+                    let id = labels.len() as i32 + 1;
+                    labels.push(label.to_string());
+                    category_ids.insert(label.to_string(), id);
+                    id
+                }
+            };
+
+            let mut annotation = to_annotation(geometry);
+            annotation.id = (coco_data.annotations.len() as i64 + 1).into();
+            annotation.image_id = image_id.into();
+            annotation.category_id = category_id.into();
+            let mut option = match annotation.option.take() {
+                Some(Value::Object(option)) => option,
+                _ => Map::new(),
+            };
+            option.extend(shape_option(shape, &input_types));
+            annotation.option = Some(Value::Object(option));
+            coco_data.annotations.push(annotation);
+        }
+        if !tags.is_empty() {
+            image.extra.insert("tags".to_string(), Value::Array(tags));
+        }
+        coco_data.images.push(image);
+    }
+
+    coco_data.categories = labels
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| COCOCategory {
+            id: (i as i32 + 1).into(),
+            name,
+            ..Default::default()
+        })
+        .collect();
+
+    Ok(ImportResult {
+        coco_data,
+        image_root: image_root(&xml_path).display().to_string(),
+        issues: issues.into_vec(),
+    })
+}
+
+/// 画像を含めてエクスポートした場合は `images/` に画像が保存される
+fn image_root(xml_path: &Path) -> PathBuf {
+    let dir = xml_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    match dir.join("images") {
+        images if images.is_dir() => images,
+        _ => dir.to_path_buf(),
+    }
+}
+
+fn elements<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    tag: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children().filter(move |n| n.has_tag_name(tag))
+}
+
+/// `<meta>` からラベル名の一覧と属性の入力形式を読み取る
+///
+/// タスク・プロジェクト・ジョブのいずれのエクスポートでも `<labels>` 以下に定義される。
+fn read_meta(root: Node) -> (Vec<String>, HashMap<String, InputType>) {
+    let mut labels = Vec::new();
+    let mut input_types = HashMap::new();
+    let Some(meta) = elements(root, "meta").next() else {
+        return (labels, input_types);
+    };
+    let label_nodes = meta
+        .descendants()
+        .filter(|n| n.has_tag_name("labels"))
+        .flat_map(|n| elements(n, "label"));
+    for label in label_nodes {
+        let Some(name) = child_text(label, "name") else {
+            continue;
+        };
+        if !labels.iter().any(|l| l == name) {
+            labels.push(name.to_string());
+        }
+        let attributes = elements(label, "attributes").flat_map(|n| elements(n, "attribute"));
+        for attribute in attributes {
+            let Some(attribute_name) = child_text(attribute, "name") else {
+                continue;
+            };
+            let input_type = match child_text(attribute, "input_type") {
+                Some("checkbox") => InputType::Checkbox,
+                Some("number") => InputType::Number,
+                _ => InputType::Text,
+            };
+            input_types.insert(attribute_name.to_string(), input_type);
+        }
+    }
+    (labels, input_types)
+}
+
+fn child_text<'a>(node: Node<'a, '_>, tag: &str) -> Option<&'a str> {
+    node.children()
+        .find(|n| n.has_tag_name(tag))
+        .and_then(|n| n.text())
+        .map(str::trim)
+}
+
+fn read_geometry(shape: Node) -> Result<Geometry, String> {
+    let number = |key: &str| {
+        shape
+            .attribute(key)
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| format!("missing or invalid {key} in <{}>", shape.tag_name().name()))
+    };
+    match shape.tag_name().name() {
+        "box" => {
+            let (xtl, ytl, xbr, ybr) = (
+                number("xtl")?,
+                number("ytl")?,
+                number("xbr")?,
+                number("ybr")?,
+            );
+            let rotation = shape
+                .attribute("rotation")
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            if rotation == 0.0 {
+                return Ok(Geometry::Box([xtl, ytl, xbr - xtl, ybr - ytl]));
+            }
+            // 回転した矩形は中心を軸に回転させたポリゴンにする
+            let (cx, cy) = ((xtl + xbr) / 2.0, (ytl + ybr) / 2.0);
+            let (sin, cos) = rotation.to_radians().sin_cos();
+            let polygon = [(xtl, ytl), (xbr, ytl), (xbr, ybr), (xtl, ybr)]
+                .into_iter()
+                .flat_map(|(x, y)| {
+                    let (dx, dy) = (x - cx, y - cy);
+                    [cx + dx * cos - dy * sin, cy + dx * sin + dy * cos]
+                })
+                .collect();
+            Ok(Geometry::Polygon(polygon))
+        }
+        "ellipse" => {
+            let (cx, cy, rx, ry) = (number("cx")?, number("cy")?, number("rx")?, number("ry")?);
+            let polygon = (0..ELLIPSE_VERTICES)
+                .flat_map(|i| {
+                    let angle = TAU * i as f64 / ELLIPSE_VERTICES as f64;
+                    [cx + rx * angle.cos(), cy + ry * angle.sin()]
+                })
+                .collect();
+            Ok(Geometry::Polygon(polygon))
+        }
+        kind @ ("polygon" | "polyline" | "points") => {
+            let points = parse_points(shape.attribute("points").unwrap_or_default())?;
+            match kind {
+                "polygon" if points.len() >= 6 => Ok(Geometry::Polygon(points)),
+                "polygon" => Err("polygon has fewer than 3 points".to_string()),
+                "polyline" => Ok(Geometry::Polyline(points)),
+                _ => Ok(Geometry::Points(points)),
+            }
+        }
+        kind => Err(format!("<{kind}> is not supported")),
+    }
+}
+
+/// `x1,y1;x2,y2;...` 形式の座標を読み取る
+fn parse_points(text: &str) -> Result<Vec<f64>, String> {
+    let mut values = Vec::new();
+    for point in text.split(';').filter(|p| !p.trim().is_empty()) {
+        let (x, y) = point
+            .split_once(',')
+            .ok_or_else(|| format!("invalid point: {point}"))?;
+        for value in [x, y] {
+            let value = value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("invalid point: {point}"))?;
+            values.push(value);
+        }
+    }
+    if values.is_empty() {
+        return Err("missing points".to_string());
+    }
+    Ok(values)
+}
+
+fn to_annotation(geometry: Geometry) -> COCOAnnotation {
+    let points_bbox = |points: &[f64]| {
+        polygons_bbox(&[points.to_vec()]).map_or_else(|| vec![0.0; 4], |b| b.to_vec())
+    };
+    match geometry {
+        Geometry::Box(bbox) => COCOAnnotation {
+            area: bbox[2] * bbox[3],
+            bbox: bbox.to_vec(),
+            ..Default::default()
+        },
+        Geometry::Polygon(polygon) => COCOAnnotation {
+            area: polygon_area(&polygon),
+            bbox: points_bbox(&polygon),
+            segmentation: Some(Segmentation::Polygon(vec![polygon])),
+            ..Default::default()
+        },
+        Geometry::Polyline(points) => {
+            let mut option = Map::new();
+            option.insert("polyline".to_string(), Value::from(points.clone()));
+            COCOAnnotation {
+                bbox: points_bbox(&points),
+                option: Some(Value::Object(option)),
+                ..Default::default()
+            }
+        }
+        Geometry::Points(points) => {
+            let keypoints: Vec<f64> = points
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[1], 2.0])
+                .collect();
+            COCOAnnotation {
+                bbox: points_bbox(&points),
+                num_keypoints: Some((keypoints.len() / 3) as u32),
+                keypoints: Some(keypoints),
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// 図形の属性を `option` の項目に変換する
+fn shape_option(shape: Node, input_types: &HashMap<String, InputType>) -> Map<String, Value> {
+    let mut option = Map::new();
+    option.insert(
+        "occluded".to_string(),
+        Value::Bool(shape.attribute("occluded") == Some("1")),
+    );
+    if let Some(z_order) = shape
+        .attribute("z_order")
+        .and_then(|v| v.parse::<i64>().ok())
+    {
+        option.insert("z_order".to_string(), Value::from(z_order));
+    }
+    for key in ["source", "group_id"] {
+        if let Some(value) = shape.attribute(key) {
+            let value = value
+                .parse::<i64>()
+                .map_or_else(|_| Value::String(value.to_string()), Value::from);
+            option.insert(key.to_string(), value);
+        }
+    }
+    let attributes: Map<String, Value> = elements(shape, "attribute")
+        .filter_map(|attribute| {
+            let name = attribute.attribute("name")?;
+            let text = attribute.text().unwrap_or_default().trim();
+            let input_type = input_types.get(name).copied().unwrap_or(InputType::Text);
+            Some((name.to_string(), attribute_value(text, input_type)))
+        })
+        .collect();
+    if !attributes.is_empty() {
+        option.insert("attributes".to_string(), Value::Object(attributes));
+    }
+    option
+}
+
+fn attribute_value(text: &str, input_type: InputType) -> Value {
+    match input_type {
+        InputType::Checkbox => Value::Bool(text.eq_ignore_ascii_case("true")),
+        InputType::Number => text
+            .parse::<i64>()
+            .map(Value::from)
+            .or_else(|_| text.parse::<f64>().map(Value::from))
+            .unwrap_or_else(|_| Value::String(text.to_string())),
+        InputType::Text => Value::String(text.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::test_util::{temp_dir, write};
+    use crate::models::CocoId;
+
+    const XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<annotations>
+  <version>1.1</version>
+  <meta>
+    <task>
+      <labels>
+        <label>
+          <name>car</name>
+          <attributes>
+            <attribute><name>parked</name><input_type>checkbox</input_type></attribute>
+            <attribute><name>doors</name><input_type>number</input_type></attribute>
+            <attribute><name>color</name><input_type>select</input_type></attribute>
+          </attributes>
+        </label>
+        <label><name>person</name></label>
+      </labels>
+    </task>
+  </meta>
+  <image id="0" name="frame_000.jpg" width="640" height="480" subset="train">
+    <box label="car" occluded="1" source="manual" xtl="10" ytl="20" xbr="110" ybr="70" z_order="2">
+      <attribute name="parked">true</attribute>
+      <attribute name="doors">4</attribute>
+      <attribute name="color">red</attribute>
+    </box>
+    <box label="car" occluded="0" xtl="0" ytl="0" xbr="10" ybr="20" rotation="90"/>
+    <polygon label="person" occluded="0" points="0,0;4,0;4,3" group_id="1"/>
+    <polyline label="person" occluded="0" points="1,1;5,4"/>
+    <points label="person" occluded="0" points="2,3;6,7"/>
+    <polygon label="person" occluded="0" points="0,0;1,1"/>
+    <mask label="person" occluded="0" rle="1, 2" left="0" top="0" width="1" height="3"/>
+    <box label="truck" occluded="0" xtl="0" ytl="0" xbr="1" ybr="1"/>
+    <tag label="daytime" source="manual"/>
+  </image>
+  <image id="1" width="1" height="1"/>
+</annotations>
+"#;
+
+    #[test]
+    fn imports_shapes_and_attributes() {
+        let dir = temp_dir("cvat-shapes");
+        write(&dir.join("annotations.xml"), XML);
+        let result = import(&dir).unwrap();
+        let data = &result.coco_data;
+
+        // `<meta>` の順にIDを振り、定義の無いラベルは末尾に追加する
+        let names: Vec<_> = data.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["car", "person", "truck"]);
+
+        assert_eq!(data.images.len(), 1);
+        let image = &data.images[0];
+        assert_eq!((image.width, image.height), (640, 480));
+        assert_eq!(image.extra["subset"], "train");
+        assert_eq!(image.extra["tags"], serde_json::json!(["daytime"]));
+
+        let annotations = &data.annotations;
+        assert_eq!(annotations.len(), 6);
+        let car = &annotations[0];
+        assert_eq!(car.bbox, [10.0, 20.0, 100.0, 50.0]);
+        assert_eq!(car.area, 5000.0);
+        assert_eq!(
+            car.option,
+            Some(serde_json::json!({
+                "occluded": true,
+                "z_order": 2,
+                "source": "manual",
+                "attributes": {"parked": true, "doors": 4, "color": "red"}
+            }))
+        );
+
+        // 回転した矩形は中心を軸に回転したポリゴンになる
+        let rotated = &annotations[1];
+        let Some(Segmentation::Polygon(polygons)) = &rotated.segmentation else {
+            panic!("rotated box was not converted to a polygon");
+        };
+        let expected = [15.0, 5.0, 15.0, 15.0, -5.0, 15.0, -5.0, 5.0];
+        for (a, e) in polygons[0].iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{polygons:?}");
+        }
+
+        let polygon = &annotations[2];
+        assert_eq!(polygon.category_id, CocoId::from(2));
+        assert_eq!(polygon.area, 6.0);
+        assert_eq!(polygon.option.as_ref().unwrap()["group_id"], 1);
+
+        let polyline = &annotations[3];
+        assert_eq!(polyline.bbox, [1.0, 1.0, 4.0, 3.0]);
+        assert_eq!(
+            polyline.option.as_ref().unwrap()["polyline"],
+            serde_json::json!([1.0, 1.0, 5.0, 4.0])
+        );
+        assert!(polyline.segmentation.is_none());
+
+        let points = &annotations[4];
+        assert_eq!(points.keypoints, Some(vec![2.0, 3.0, 2.0, 6.0, 7.0, 2.0]));
+        assert_eq!(points.num_keypoints, Some(2));
+
+        assert_eq!(annotations[5].category_id, CocoId::from(3));
+
+        let lines: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| (i.line, i.message.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (Some(29), "frame_000.jpg: polygon has fewer than 3 points"),
+                (Some(30), "frame_000.jpg: <mask> is not supported"),
+                (Some(31), "Label \"truck\" is not defined in <meta>"),
+                (Some(34), "Missing name in <image>"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_tracks_and_invalid_files() {
+        let dir = temp_dir("cvat-tracks");
+        write(
+            &dir.join("video.xml"),
+            r#"<annotations><track id="0" label="car"/></annotations>"#,
+        );
+        let result = import(&dir.join("video.xml")).unwrap();
+        assert!(result.coco_data.images.is_empty());
+        assert!(result.issues[0]
+            .message
+            .starts_with("Tracks are not supported"));
+
+        write(&dir.join("other.xml"), "<annotation/>");
+        let error = import(&dir.join("other.xml")).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("unexpected root element <annotation>"));
+
+        write(
+            &dir.join("broken.xml"),
+            "<annotations>\n<image id=0/>\n</annotations>",
+        );
+        let CoavError::Parse { location, .. } = import(&dir.join("broken.xml")).unwrap_err() else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(location.line, Some(2));
+    }
+}
@@ -0,0 +1,297 @@
+use super::{image_size, ImportResult, Issues, IMAGE_EXTENSIONS};
+use crate::error::{CoavError, CoavResult};
+use crate::models::segmentation::{polygon_area, polygons_bbox};
+use crate::models::{COCOAnnotation, COCOCategory, COCOData, COCOImage, RotatedBox, Segmentation};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// DOTAのラベルファイルの1物体
+struct DotaObject {
+    /// 4頂点 `[x1, y1, ..., x4, y4]`
+    polygon: Vec<f64>,
+    category: String,
+    difficult: Option<bool>,
+}
+
+/// DOTAのラベルファイル（画像1枚分）
+#[derive(Default)]
+struct DotaLabel {
+    /// `imagesource:` や `gsd:` のヘッダー行
+    metadata: HashMap<String, Value>,
+    objects: Vec<DotaObject>,
+}
+
+/// DOTA形式のラベル（`labelTxt/*.txt`）を読み込む
+///
+/// `path` には `labelTxt/` と `images/` を含むフォルダ、またはラベルのフォルダを指定する。
+/// 4頂点はポリゴンとして `segmentation` に、面積最小の外接回転矩形を `rbox` に、
+/// 外接矩形を `bbox` に保存する。`difficult` はアノテーションの `extra` に保存する。
+pub fn import(path: &Path) -> CoavResult<ImportResult> {
+    if !path.is_dir() {
+        return Err(CoavError::not_found(
+            path,
+            format!("Folder not found: {}", path.display()),
+        ));
+    }
+    let label_dir = match path.join("labelTxt") {
+        dir if dir.is_dir() => dir,
+        _ => path.to_path_buf(),
+    };
+    let image_dir = [path.join("images"), label_dir.with_file_name("images")]
+        .into_iter()
+        .find(|dir| dir.is_dir())
+        .unwrap_or_else(|| label_dir.clone());
+
+    let mut label_paths: Vec<PathBuf> = fs::read_dir(&label_dir)
+        .map_err(|e| CoavError::io(&label_dir, "Failed to read folder", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("txt")))
+        .collect();
+    label_paths.sort();
+
+    let mut issues = Issues::default();
+    let mut files = Vec::new();
+    for label_path in label_paths {
+        match fs::read_to_string(&label_path) {
+            Ok(text) => {
+                let label = parse_label(&text, |line, message| {
+                    issues.push(&label_path, Some(line), message)
+                });
+                files.push((label_path, label));
+            }
+            Err(e) => issues.push(&label_path, None, format!("Failed to read file: {e}")),
+        }
+    }
+
+    // カテゴリは名前順にIDを振る
+    let names: BTreeSet<&str> = files
+        .iter()
+        .flat_map(|(_, label)| label.objects.iter().map(|o| o.category.as_str()))
+        .collect();
+    let category_ids: HashMap<&str, i32> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (*name, i as i32 + 1))
+        .collect();
+
+    let mut coco_data = COCOData {
+        categories: names
+            .iter()
+            .map(|name| COCOCategory {
+                id: category_ids[name].into(),
+                name: name.to_string(),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    for (label_path, label) in &files {
+        let stem = label_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let image_path = IMAGE_EXTENSIONS
+            .iter()
+            .map(|ext| image_dir.join(format!("{stem}.{ext}")))
+            .find(|p| p.is_file());
+        let (width, height) = match &image_path {
+            Some(image_path) => image_size(image_path).unwrap_or_else(|message| {
+                issues.push(image_path, None, message);
+                (0, 0)
+            }),
+            None => {
+                issues.push(label_path, None, format!("Image not found for {stem}"));
+                (0, 0)
+            }
+        };
+        let file_name = image_path
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| format!("{stem}.png"));
+
+        let image_id = coco_data.images.len() as i64 + 1;
+        coco_data.images.push(COCOImage {
+            id: image_id.into(),
+            width: width as i32,
+            height: height as i32,
+            file_name,
+            extra: label.metadata.clone(),
+            ..Default::default()
+        });
+
+        for object in &label.objects {
+            let polygons = vec![object.polygon.clone()];
+            let mut extra = HashMap::new();
+            if let Some(difficult) = object.difficult {
+                extra.insert("difficult".to_string(), Value::Bool(difficult));
+            }
+            coco_data.annotations.push(COCOAnnotation {
+                id: (coco_data.annotations.len() as i64 + 1).into(),
+                image_id: image_id.into(),
+                category_id: category_ids[object.category.as_str()].into(),
+                area: polygon_area(&polygons[0]),
+                bbox: polygons_bbox(&polygons).map_or_else(|| vec![0.0; 4], |b| b.to_vec()),
+                rbox: RotatedBox::from_polygon(&polygons[0]),
+                segmentation: Some(Segmentation::Polygon(polygons)),
+                extra,
+                ..Default::default()
+            });
+        }
+    }
+
+    Ok(ImportResult {
+        coco_data,
+        image_root: image_dir.display().to_string(),
+        issues: issues.into_vec(),
+    })
+}
+
+/// `x1 y1 x2 y2 x3 y3 x4 y4 category [difficult]` の行と、`key:value` のヘッダー行を読み込む
+fn parse_label(text: &str, mut on_issue: impl FnMut(usize, String)) -> DotaLabel {
+    let mut label = DotaLabel::default();
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.len() == 1 {
+            if let Some((key, value)) = line.split_once(':') {
+                let value = match value.trim() {
+                    "" | "null" | "None" => continue,
+                    value => value
+                        .parse::<f64>()
+                        .map_or_else(|_| Value::String(value.to_string()), Value::from),
+                };
+                label.metadata.insert(key.trim().to_string(), value);
+                continue;
+            }
+        }
+        if tokens.len() != 9 && tokens.len() != 10 {
+            on_issue(
+                line_number,
+                format!("Expected 9 or 10 values but found {}", tokens.len()),
+            );
+            continue;
+        }
+        let polygon = tokens[..8]
+            .iter()
+            .map(|token| {
+                token
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|v| v.is_finite())
+                    .ok_or_else(|| format!("Invalid number: {token}"))
+            })
+            .collect::<Result<Vec<f64>, String>>();
+        let polygon = match polygon {
+            Ok(polygon) => polygon,
+            Err(message) => {
+                on_issue(line_number, message);
+                continue;
+            }
+        };
+        let difficult = match tokens.get(9) {
+            Some(&"0") => Some(false),
+            Some(&"1") => Some(true),
+            Some(token) => {
+                on_issue(line_number, format!("Invalid difficult flag: {token}"));
+                None
+            }
+            None => None,
+        };
+        label.objects.push(DotaObject {
+            polygon,
+            category: tokens[8].to_string(),
+            difficult,
+        });
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::test_util::{temp_dir, write, write_image};
+
+    #[test]
+    fn imports_oriented_boxes() {
+        let dir = temp_dir("dota");
+        write(
+            &dir.join("labelTxt/P0001.txt"),
+            "imagesource:GoogleEarth\n\
+             gsd:0.146\n\
+             gsd:null\n\
+             10 10 50 10 50 30 10 30 plane 0\n\
+             \n\
+             30 0 40 10 30 20 20 10 small-vehicle 1\n\
+             1 2 3 4 5 6 7 8 ship x\n\
+             1 2 3 4 5 6 7 harbor\n\
+             1 2 3 4 5 6 7 NaN harbor\n",
+        );
+        write(&dir.join("labelTxt/P0002.txt"), "");
+        write_image(&dir.join("images/P0001.png"), 64, 48);
+
+        let result = import(&dir).unwrap();
+        let data = &result.coco_data;
+        assert_eq!(result.image_root, dir.join("images").display().to_string());
+
+        let names: Vec<_> = data.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["plane", "ship", "small-vehicle"]);
+
+        let image = &data.images[0];
+        assert_eq!((image.width, image.height), (64, 48));
+        assert_eq!(image.extra["imagesource"], "GoogleEarth");
+        assert_eq!(image.extra["gsd"], 0.146);
+        assert_eq!(data.images[1].file_name, "P0002.png");
+
+        let plane = &data.annotations[0];
+        assert_eq!(plane.bbox, [10.0, 10.0, 40.0, 20.0]);
+        assert_eq!(plane.area, 800.0);
+        assert_eq!(
+            plane.rbox,
+            Some(RotatedBox {
+                cx: 30.0,
+                cy: 20.0,
+                w: 40.0,
+                h: 20.0,
+                angle: 0.0
+            })
+        );
+        assert_eq!(plane.extra["difficult"], false);
+
+        // 45度回転した正方形
+        let vehicle = &data.annotations[1];
+        assert_eq!(vehicle.bbox, [20.0, 0.0, 20.0, 20.0]);
+        assert_eq!(vehicle.area, 200.0);
+        let rbox = vehicle.rbox.unwrap();
+        assert!((rbox.w - 200f64.sqrt()).abs() < 1e-9);
+        assert!((rbox.angle.abs() - 45.0).abs() < 1e-9);
+        assert_eq!(vehicle.extra["difficult"], true);
+
+        // 不正な difficult は読み込み、フラグのみ報告する
+        assert!(!data.annotations[2].extra.contains_key("difficult"));
+        assert_eq!(data.annotations.len(), 3);
+
+        let issues: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| (i.line, i.message.as_str()))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (Some(7), "Invalid difficult flag: x"),
+                (Some(8), "Expected 9 or 10 values but found 8"),
+                (Some(9), "Invalid number: NaN"),
+                (None, "Image not found for P0002"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use super::{image_size, ImportResult, Issues, IMAGE_EXTENSIONS};
+use crate::error::{CoavError, CoavResult};
+use crate::models::{COCOAnnotation, COCOCategory, COCOData, COCOImage};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 評価の対象外とする領域の種類
+const DONT_CARE: &str = "DontCare";
+
+/// KITTIのラベルファイルの1行
+///
+/// `type truncated occluded alpha x1 y1 x2 y2 h w l x y z rotation_y [score]`
+struct KittiObject {
+    kind: String,
+    truncated: f64,
+    occluded: i64,
+    alpha: f64,
+    /// 2Dの矩形 `[x1, y1, x2, y2]`
+    box_2d: [f64; 4],
+    /// 3Dの大きさ `[height, width, length]`（メートル）
+    dimensions: [f64; 3],
+    /// カメラ座標系での3Dの位置 `[x, y, z]`（メートル）
+    location: [f64; 3],
+    rotation_y: f64,
+    score: Option<f64>,
+}
+
+/// KITTI物体検出形式のラベル（`label_2/*.txt`）を読み込む
+///
+/// `path` にはラベルのフォルダ、または `label_2/` を含むフォルダ（`training/` など）を指定する。
+/// 2Dの矩形を `bbox` に、打ち切り・遮蔽・3Dの項目を `option` に保存する。
+/// `DontCare` の領域は `iscrowd: 1` として読み込む。
+pub fn import(path: &Path) -> CoavResult<ImportResult> {
+    if !path.is_dir() {
+        return Err(CoavError::not_found(
+            path,
+            format!("Folder not found: {}", path.display()),
+        ));
+    }
+    let label_dir = match path.join("label_2") {
+        dir if dir.is_dir() => dir,
+        _ => path.to_path_buf(),
+    };
+    let image_dir = label_dir
+        .parent()
+        .map(|parent| parent.join("image_2"))
+        .filter(|dir| dir.is_dir())
+        .unwrap_or_else(|| label_dir.clone());
+
+    let mut label_paths: Vec<PathBuf> = fs::read_dir(&label_dir)
+        .map_err(|e| CoavError::io(&label_dir, "Failed to read folder", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("txt")))
+        .collect();
+    label_paths.sort();
+
+    let mut issues = Issues::default();
+    let mut files = Vec::new();
+    for label_path in label_paths {
+        let text = match fs::read_to_string(&label_path) {
+            Ok(text) => text,
+            Err(e) => {
+                issues.push(&label_path, None, format!("Failed to read file: {e}"));
+                continue;
+            }
+        };
+        let mut objects = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match parse_line(line) {
+                Ok(object) => objects.push(object),
+                Err(message) => issues.push(&label_path, Some(i + 1), message),
+            }
+        }
+        files.push((label_path, objects));
+    }
+
+    // カテゴリは種類の名前順にIDを振る
+    let kinds: BTreeSet<&str> = files
+        .iter()
+        .flat_map(|(_, objects)| objects.iter().map(|o| o.kind.as_str()))
+        .collect();
+    let category_ids: HashMap<&str, i32> = kinds
+        .iter()
+        .enumerate()
+        .map(|(i, kind)| (*kind, i as i32 + 1))
+        .collect();
+
+    let mut coco_data = COCOData {
+        categories: kinds
+            .iter()
+            .map(|kind| COCOCategory {
+                id: category_ids[kind].into(),
+                name: kind.to_string(),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    for (label_path, objects) in &files {
+        let stem = label_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let image_path = find_image(&image_dir, &stem);
+        let (width, height) = match &image_path {
+            Some(image_path) => image_size(image_path).unwrap_or_else(|message| {
+                issues.push(image_path, None, message);
+                (0, 0)
+            }),
+            None => {
+                issues.push(label_path, None, format!("Image not found for {stem}"));
+                (0, 0)
+            }
+        };
+        let file_name = image_path
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| format!("{stem}.png"));
+
+        let image_id = coco_data.images.len() as i64 + 1;
+        coco_data.images.push(COCOImage {
+            id: image_id.into(),
+            width: width as i32,
+            height: height as i32,
+            file_name,
+            ..Default::default()
+        });
+
+        for object in objects {
+            let [x1, y1, x2, y2] = object.box_2d;
+            let (w, h) = (x2 - x1, y2 - y1);
+            coco_data.annotations.push(COCOAnnotation {
+                id: (coco_data.annotations.len() as i64 + 1).into(),
+                image_id: image_id.into(),
+                category_id: category_ids[object.kind.as_str()].into(),
+                area: w * h,
+                bbox: vec![x1, y1, w, h],
+                iscrowd: (object.kind == DONT_CARE) as i32,
+                option: object_option(object),
+                score: object.score,
+                ..Default::default()
+            });
+        }
+    }
+
+    Ok(ImportResult {
+        coco_data,
+        image_root: image_dir.display().to_string(),
+        issues: issues.into_vec(),
+    })
+}
+
+fn parse_line(line: &str) -> Result<KittiObject, String> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    if tokens.len() != 15 && tokens.len() != 16 {
+        return Err(format!(
+            "Expected 15 or 16 values but found {}",
+            tokens.len()
+        ));
+    }
+    let values = tokens[1..]
+        .iter()
+        .map(|token| {
+            token
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("Invalid number: {token}"))
+        })
+        .collect::<Result<Vec<f64>, String>>()?;
+
+    Ok(KittiObject {
+        kind: tokens[0].to_string(),
+        truncated: values[0],
+        occluded: values[1] as i64,
+        alpha: values[2],
+        box_2d: [values[3], values[4], values[5], values[6]],
+        dimensions: [values[7], values[8], values[9]],
+        location: [values[10], values[11], values[12]],
+        rotation_y: values[13],
+        score: values.get(14).copied(),
+    })
+}
+
+/// 打ち切り・遮蔽・3Dの項目を `option` に変換する
+///
+/// `DontCare` の項目はすべて無効な値（`-1`・`-1000` など）のため `None` とする。
+fn object_option(object: &KittiObject) -> Option<Value> {
+    if object.kind == DONT_CARE {
+        return None;
+    }
+    Some(json!({
+        "truncated": object.truncated,
+        "occluded": object.occluded,
+        "alpha": object.alpha,
+        "dimensions": object.dimensions,
+        "location": object.location,
+        "rotation_y": object.rotation_y,
+    }))
+}
+
+/// ラベルと同じ名前の画像を探す（KITTIは `.png`）
+fn find_image(image_dir: &Path, stem: &str) -> Option<PathBuf> {
+    let png = image_dir.join(format!("{stem}.png"));
+    if png.is_file() {
+        return Some(png);
+    }
+    IMAGE_EXTENSIONS
+        .iter()
+        .map(|ext| image_dir.join(format!("{stem}.{ext}")))
+        .find(|p| p.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::test_util::{temp_dir, write, write_image};
+
+    #[test]
+    fn imports_training_labels() {
+        let dir = temp_dir("kitti-training");
+        write(
+            &dir.join("training/label_2/000000.txt"),
+            "Pedestrian 0.00 0 -0.20 712.40 143.00 810.73 307.92 1.89 0.48 1.20 1.84 1.47 8.41 0.01\n\
+             \n\
+             DontCare -1 -1 -10 503.89 169.71 590.61 190.13 -1 -1 -1 -1000 -1000 -1000 -10\n\
+             Car 0.5 x\n",
+        );
+        write(
+            &dir.join("training/label_2/000001.txt"),
+            "Car 0.00 1 1.5 10 20 30 60 1.5 1.6 3.9 1.0 1.5 20.0 1.57 0.93\n\
+             Car 0.00 1 1.5 10 20 NaN 60 1.5 1.6 3.9 1.0 1.5 20.0 1.57\n",
+        );
+        write_image(&dir.join("training/image_2/000000.png"), 1242, 375);
+        write_image(&dir.join("training/image_2/000001.jpg"), 640, 480);
+        write(&dir.join("training/label_2/000002.txt"), "");
+
+        let result = import(&dir.join("training")).unwrap();
+        let data = &result.coco_data;
+        assert_eq!(
+            result.image_root,
+            dir.join("training/image_2").display().to_string()
+        );
+
+        let names: Vec<_> = data.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Car", "DontCare", "Pedestrian"]);
+
+        let files: Vec<_> = data.images.iter().map(|i| i.file_name.as_str()).collect();
+        assert_eq!(files, ["000000.png", "000001.jpg", "000002.png"]);
+        assert_eq!((data.images[0].width, data.images[0].height), (1242, 375));
+        assert_eq!((data.images[1].width, data.images[1].height), (640, 480));
+
+        let pedestrian = &data.annotations[0];
+        assert_eq!(pedestrian.category_id, category_ids(data)["Pedestrian"]);
+        let [x, y, w, h] = pedestrian.bbox[..] else {
+            panic!("invalid bbox");
+        };
+        assert_eq!((x, y), (712.40, 143.00));
+        assert!((w - 98.33).abs() < 1e-9 && (h - 164.92).abs() < 1e-9);
+        assert_eq!(pedestrian.iscrowd, 0);
+        assert_eq!(pedestrian.score, None);
+        let option = pedestrian.option.as_ref().unwrap();
+        assert_eq!(option["occluded"], 0);
+        assert_eq!(option["dimensions"], json!([1.89, 0.48, 1.20]));
+        assert_eq!(option["location"], json!([1.84, 1.47, 8.41]));
+        assert_eq!(option["rotation_y"], 0.01);
+
+        // DontCare は群衆領域として読み込み、3Dの項目は保存しない
+        let dont_care = &data.annotations[1];
+        assert_eq!(dont_care.iscrowd, 1);
+        assert_eq!(dont_care.option, None);
+
+        // 16列目は検出結果のスコア
+        let car = &data.annotations[2];
+        assert_eq!(car.bbox, [10.0, 20.0, 20.0, 40.0]);
+        assert_eq!(car.score, Some(0.93));
+        assert_eq!(data.annotations.len(), 3);
+
+        let issues: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| (i.line, i.message.as_str()))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (Some(4), "Expected 15 or 16 values but found 3"),
+                (Some(2), "Invalid number: NaN"),
+                (None, "Image not found for 000002"),
+            ]
+        );
+    }
+
+    fn category_ids(data: &COCOData) -> HashMap<&str, crate::models::CocoId> {
+        data.categories
+            .iter()
+            .map(|c| (c.name.as_str(), c.id.clone()))
+            .collect()
+    }
+}
@@ -0,0 +1,550 @@
+use super::{find_image_file, image_size, relative_file_name, ImportResult, Issues};
+use crate::error::{CoavError, CoavResult, ErrorLocation};
+use crate::models::segmentation::{polygon_area, polygons_bbox};
+use crate::models::{COCOAnnotation, COCOCategory, COCOData, COCOImage, Segmentation};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// Label Studioのタスク（画像1枚分）
+#[derive(Deserialize)]
+struct Task {
+    #[serde(default)]
+    id: Option<Value>,
+    data: Map<String, Value>,
+    #[serde(default)]
+    annotations: Vec<Annotation>,
+}
+
+/// アノテーター1人分のアノテーション
+#[derive(Deserialize)]
+struct Annotation {
+    #[serde(default)]
+    completed_by: Option<Value>,
+    #[serde(default)]
+    created_at: Option<Value>,
+    #[serde(default)]
+    updated_at: Option<Value>,
+    #[serde(default)]
+    was_cancelled: bool,
+    #[serde(default)]
+    result: Vec<Region>,
+}
+
+#[derive(Deserialize)]
+struct Region {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    from_name: Option<String>,
+    #[serde(default)]
+    original_width: Option<u32>,
+    #[serde(default)]
+    original_height: Option<u32>,
+    #[serde(default)]
+    value: Map<String, Value>,
+}
+
+/// Label StudioのJSON・JSONL形式のエクスポートを読み込む
+///
+/// 座標は画像サイズに対する百分率のため、`original_width`・`original_height`
+/// （無い場合は画像ファイル）のサイズでピクセル座標に変換する。
+/// 画像は `image_folder`（省略時はエクスポートファイルのフォルダ）から `scan_folder` と同じ規則で探す。
+/// アノテーターのIDと作成・更新日時はアノテーションの `extra` に保存する。
+pub fn import(path: &Path, image_folder: Option<&Path>) -> CoavResult<ImportResult> {
+    let image_folder = image_folder
+        .or_else(|| path.parent().filter(|p| !p.as_os_str().is_empty()))
+        .unwrap_or(Path::new("."));
+    let tasks = read_tasks(path)?;
+
+    // カテゴリはラベル名の順にIDを振る
+    let labels: BTreeSet<&str> = tasks
+        .iter()
+        .flat_map(|task| task.annotations.iter().filter(|a| !a.was_cancelled))
+        .flat_map(|annotation| annotation.result.iter())
+        .filter(|region| convert_region(region, 1.0, 1.0).is_some())
+        .filter_map(region_label)
+        .collect();
+    let category_ids: HashMap<&str, i32> = labels
+        .iter()
+        .enumerate()
+        .map(|(i, label)| (*label, i as i32 + 1))
+        .collect();
+
+    let mut issues = Issues::default();
+    let mut coco_data = COCOData {
+        categories: labels
+            .iter()
+            .map(|label| COCOCategory {
+                id: category_ids[label].into(),
+                name: label.to_string(),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    for (index, task) in tasks.iter().enumerate() {
+        let task_name = task
+            .id
+            .as_ref()
+            .map_or_else(|| format!("task #{}", index + 1), |id| format!("task {id}"));
+        let Some(source) = image_source(&task.data) else {
+            issues.push(path, None, format!("{task_name}: no image in data"));
+            continue;
+        };
+        let candidates = candidate_names(source);
+        let local_path = candidates
+            .iter()
+            .find_map(|name| find_image_file(image_folder, name));
+        let file_name = match &local_path {
+            Some(local_path) => relative_file_name(image_folder, local_path),
+            None => {
+                issues.push(
+                    path,
+                    None,
+                    format!("{task_name}: image not found: {source}"),
+                );
+                candidates[0].clone()
+            }
+        };
+
+        let original_size = task
+            .annotations
+            .iter()
+            .flat_map(|a| a.result.iter())
+            .find_map(|r| Some((r.original_width?, r.original_height?)));
+        let (width, height) = match (original_size, &local_path) {
+            (Some(size), _) => size,
+            (None, Some(local_path)) => image_size(local_path).unwrap_or_else(|message| {
+                issues.push(path, None, format!("{task_name}: {message}"));
+                (0, 0)
+            }),
+            (None, None) => (0, 0),
+        };
+
+        let image_id = coco_data.images.len() as i64 + 1;
+        let mut image = COCOImage {
+            id: image_id.into(),
+            width: width as i32,
+            height: height as i32,
+            file_name,
+            ..Default::default()
+        };
+        if let Some(id) = &task.id {
+            image.extra.insert("task_id".to_string(), id.clone());
+        }
+
+        let mut choices = Map::new();
+        let mut skipped: BTreeMap<&str, usize> = BTreeMap::new();
+        for annotation in task.annotations.iter().filter(|a| !a.was_cancelled) {
+            let extra = annotation_extra(annotation);
+            for region in &annotation.result {
+                if region.kind == "choices" {
+                    let from_name = region.from_name.clone().unwrap_or_default();
+                    choices.insert(
+                        from_name,
+                        region.value.get("choices").cloned().unwrap_or_default(),
+                    );
+                    continue;
+                }
+                let Some(converted) = convert_region(region, width as f64, height as f64) else {
+                    *skipped.entry(region.kind.as_str()).or_default() += 1;
+                    continue;
+                };
+                let Some(label) = region_label(region) else {
+                    issues.push(path, None, format!("{task_name}: region without a label"));
+                    continue;
+                };
+                coco_data.annotations.push(COCOAnnotation {
+                    id: (coco_data.annotations.len() as i64 + 1).into(),
+                    image_id: image_id.into(),
+                    category_id: category_ids[label].into(),
+                    extra: extra.clone(),
+                    ..converted
+                });
+            }
+        }
+        if !choices.is_empty() {
+            image
+                .extra
+                .insert("choices".to_string(), Value::Object(choices));
+        }
+        for (kind, count) in skipped {
+            issues.push(
+                path,
+                None,
+                format!("{task_name}: skipped {count} \"{kind}\" region(s)"),
+            );
+        }
+        let has_annotations = coco_data
+            .annotations
+            .last()
+            .is_some_and(|a| a.image_id == image_id);
+        if (width == 0 || height == 0) && has_annotations {
+            issues.push(
+                path,
+                None,
+                format!("{task_name}: image size is unknown; coordinates cannot be converted"),
+            );
+        }
+        coco_data.images.push(image);
+    }
+
+    Ok(ImportResult {
+        coco_data,
+        image_root: image_folder.display().to_string(),
+        issues: issues.into_vec(),
+    })
+}
+
+/// JSON（タスクの配列）またはJSONL（1行1タスク）を読み込む
+fn read_tasks(path: &Path) -> CoavResult<Vec<Task>> {
+    let text =
+        fs::read_to_string(path).map_err(|e| CoavError::io(path, "Failed to read file", e))?;
+    let parse_error = |e: serde_json::Error, line: Option<usize>| CoavError::Parse {
+        message: format!("Failed to parse Label Studio export: {e}"),
+        location: ErrorLocation {
+            file: Some(path.display().to_string()),
+            line: line.or(Some(e.line())),
+            column: Some(e.column()),
+            json_path: None,
+        },
+    };
+
+    let is_jsonl = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("jsonl"));
+    if !is_jsonl {
+        if text.trim_start().starts_with('[') {
+            return serde_json::from_str(&text).map_err(|e| parse_error(e, None));
+        }
+        return serde_json::from_str(&text)
+            .map(|task| vec![task])
+            .map_err(|e| parse_error(e, None));
+    }
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line).map_err(|e| parse_error(e, Some(i + 1))))
+        .collect()
+}
+
+/// タスクの `data` から画像のパス・URLを取り出す（`image` が無い場合は最初の文字列）
+fn image_source(data: &Map<String, Value>) -> Option<&str> {
+    data.get("image")
+        .and_then(Value::as_str)
+        .or_else(|| data.values().find_map(Value::as_str))
+}
+
+/// URLの最後の要素（クエリ文字列を除く）
+fn source_file_name(source: &str) -> &str {
+    let source = source.split(['?', '#']).next().unwrap_or(source);
+    source.rsplit('/').next().unwrap_or(source)
+}
+
+/// 画像を探すファイル名の候補
+///
+/// - `/data/local-files/?d=dir/a.jpg`: ローカルストレージのパス `dir/a.jpg`
+/// - `/data/upload/1/8f3a2c1d-a.jpg`: アップロード時に付けられる接頭辞を除いた `a.jpg`
+fn candidate_names(source: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Some((_, query)) = source.split_once("?d=") {
+        names.push(percent_decode(query));
+    }
+    let file_name = percent_decode(source_file_name(source));
+    if let Some((prefix, rest)) = file_name.split_once('-') {
+        if prefix.len() == 8 && prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+            names.push(rest.to_string());
+        }
+    }
+    // `?d=` 以外にファイル名が無い場合（`/data/local-files/?d=...`）は候補にしない
+    if !file_name.is_empty() || names.is_empty() {
+        names.push(file_name);
+    }
+    names
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn annotation_extra(annotation: &Annotation) -> HashMap<String, Value> {
+    let mut extra = HashMap::new();
+    // `completed_by` はエクスポートの設定によりユーザーIDまたはユーザー情報のオブジェクトになる
+    let annotator = match &annotation.completed_by {
+        Some(Value::Object(user)) => user.get("id").cloned(),
+        other => other.clone(),
+    };
+    for (key, value) in [
+        ("annotator_id", annotator),
+        ("created_at", annotation.created_at.clone()),
+        ("updated_at", annotation.updated_at.clone()),
+    ] {
+        if let Some(value) = value.filter(|v| !v.is_null()) {
+            extra.insert(key.to_string(), value);
+        }
+    }
+    extra
+}
+
+/// 領域のラベル（`rectanglelabels` などの最初の値）
+fn region_label(region: &Region) -> Option<&str> {
+    let key = match region.kind.as_str() {
+        "rectangle" | "polygon" | "keypoint" => "labels",
+        kind => kind,
+    };
+    region
+        .value
+        .get(key)
+        .or_else(|| region.value.get("labels"))
+        .and_then(Value::as_array)
+        .and_then(|labels| labels.first())
+        .and_then(Value::as_str)
+}
+
+/// 百分率の座標をピクセル座標のアノテーションに変換する（対応していない種類は `None`）
+fn convert_region(region: &Region, width: f64, height: f64) -> Option<COCOAnnotation> {
+    let number = |key: &str| region.value.get(key).and_then(Value::as_f64);
+    match region.kind.as_str() {
+        "rectanglelabels" | "rectangle" => {
+            let x = number("x")? / 100.0 * width;
+            let y = number("y")? / 100.0 * height;
+            let w = number("width")? / 100.0 * width;
+            let h = number("height")? / 100.0 * height;
+            let rotation = number("rotation").unwrap_or(0.0);
+            if rotation == 0.0 {
+                return Some(COCOAnnotation {
+                    area: w * h,
+                    bbox: vec![x, y, w, h],
+                    ..Default::default()
+                });
+            }
+            // Label Studioの矩形は左上の頂点を中心に回転する
+            let (sin, cos) = rotation.to_radians().sin_cos();
+            let polygon = [(0.0, 0.0), (w, 0.0), (w, h), (0.0, h)]
+                .into_iter()
+                .flat_map(|(dx, dy)| [x + dx * cos - dy * sin, y + dx * sin + dy * cos])
+                .collect();
+            Some(polygon_annotation(polygon))
+        }
+        "polygonlabels" | "polygon" => {
+            let polygon: Vec<f64> = region
+                .value
+                .get("points")?
+                .as_array()?
+                .iter()
+                .filter_map(|p| Some([p.get(0)?.as_f64()?, p.get(1)?.as_f64()?]))
+                .flat_map(|[px, py]| [px / 100.0 * width, py / 100.0 * height])
+                .collect();
+            (polygon.len() >= 6).then(|| polygon_annotation(polygon))
+        }
+        "keypointlabels" | "keypoint" => {
+            let x = number("x")? / 100.0 * width;
+            let y = number("y")? / 100.0 * height;
+            Some(COCOAnnotation {
+                bbox: vec![x, y, 0.0, 0.0],
+                keypoints: Some(vec![x, y, 2.0]),
+                num_keypoints: Some(1),
+                ..Default::default()
+            })
+        }
+        _ => None,
+    }
+}
+
+fn polygon_annotation(polygon: Vec<f64>) -> COCOAnnotation {
+    let polygons = vec![polygon];
+    COCOAnnotation {
+        area: polygon_area(&polygons[0]),
+        bbox: polygons_bbox(&polygons).map_or_else(|| vec![0.0; 4], |b| b.to_vec()),
+        segmentation: Some(Segmentation::Polygon(polygons)),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::test_util::{temp_dir, write, write_image};
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?} != {expected:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{actual:?} != {expected:?}");
+        }
+    }
+
+    const EXPORT: &str = r#"[
+        {
+            "id": 7,
+            "data": {"image": "/data/upload/1/8f3a2c1d-a.jpg"},
+            "annotations": [
+                {
+                    "completed_by": {"id": 3, "email": "a@example.com"},
+                    "created_at": "2024-01-02T03:04:05Z",
+                    "result": [
+                        {"type": "rectanglelabels", "from_name": "label",
+                         "original_width": 200, "original_height": 100,
+                         "value": {"x": 10, "y": 20, "width": 50, "height": 40,
+                                   "rotation": 0, "rectanglelabels": ["cat"]}},
+                        {"type": "rectanglelabels", "original_width": 200, "original_height": 100,
+                         "value": {"x": 50, "y": 50, "width": 10, "height": 20,
+                                   "rotation": 90, "rectanglelabels": ["cat"]}},
+                        {"type": "polygonlabels", "original_width": 200, "original_height": 100,
+                         "value": {"points": [[0, 0], [50, 0], [50, 50]],
+                                   "polygonlabels": ["dog"]}},
+                        {"type": "keypointlabels", "original_width": 200, "original_height": 100,
+                         "value": {"x": 25, "y": 75, "keypointlabels": ["nose"]}},
+                        {"type": "choices", "from_name": "weather",
+                         "value": {"choices": ["sunny"]}},
+                        {"type": "brushlabels", "value": {"rle": [1, 2], "brushlabels": ["cat"]}}
+                    ]
+                },
+                {"was_cancelled": true, "result": [
+                    {"type": "rectanglelabels", "original_width": 200, "original_height": 100,
+                     "value": {"x": 0, "y": 0, "width": 1, "height": 1,
+                               "rectanglelabels": ["cancelled"]}}
+                ]}
+            ]
+        },
+        {
+            "data": {"image": "/data/local-files/?d=photos%2Fb.png"},
+            "annotations": [{"completed_by": 5, "result": [
+                {"type": "rectanglelabels",
+                 "value": {"x": 0, "y": 0, "width": 50, "height": 50, "rectanglelabels": ["cat"]}}
+            ]}]
+        },
+        {"data": {"image": "https://example.com/missing.jpg?token=1"}, "annotations": [
+            {"result": [{"type": "rectanglelabels",
+                         "value": {"x": 0, "y": 0, "width": 1, "height": 1,
+                                   "rectanglelabels": ["cat"]}}]}
+        ]},
+        {"data": {"text": 1}}
+    ]"#;
+
+    #[test]
+    fn converts_percentages_to_pixels() {
+        let dir = temp_dir("label-studio-json");
+        write(&dir.join("export.json"), EXPORT);
+        write_image(&dir.join("images/a.jpg"), 200, 100);
+        write_image(&dir.join("images/photos/b.png"), 8, 4);
+
+        let result = import(&dir.join("export.json"), Some(&dir.join("images"))).unwrap();
+        let data = &result.coco_data;
+
+        // 取り消されたアノテーションと対応していない種類のラベルはカテゴリにしない
+        let names: Vec<_> = data.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["cat", "dog", "nose"]);
+
+        // アップロード時の接頭辞・ローカルストレージのパスから画像を探す
+        let files: Vec<_> = data.images.iter().map(|i| i.file_name.as_str()).collect();
+        assert_eq!(files, ["a.jpg", "photos/b.png", "missing.jpg"]);
+        assert_eq!((data.images[0].width, data.images[0].height), (200, 100));
+        assert_eq!((data.images[1].width, data.images[1].height), (8, 4));
+        assert_eq!(data.images[0].extra["task_id"], 7);
+        assert_eq!(
+            data.images[0].extra["choices"]["weather"],
+            serde_json::json!(["sunny"])
+        );
+
+        let annotations = &data.annotations;
+        assert_eq!(annotations.len(), 6);
+        assert_eq!(annotations[0].bbox, [20.0, 20.0, 100.0, 40.0]);
+        assert_eq!(annotations[0].area, 4000.0);
+        assert_eq!(annotations[0].extra["annotator_id"], 3);
+        assert_eq!(annotations[0].extra["created_at"], "2024-01-02T03:04:05Z");
+
+        // 回転は左上の頂点を中心にする
+        let Some(Segmentation::Polygon(rotated)) = &annotations[1].segmentation else {
+            panic!("rotated rectangle was not converted to a polygon");
+        };
+        assert_close(
+            &rotated[0],
+            &[100.0, 50.0, 100.0, 70.0, 80.0, 70.0, 80.0, 50.0],
+        );
+
+        assert_eq!(annotations[2].bbox, [0.0, 0.0, 100.0, 50.0]);
+        assert_eq!(annotations[2].area, 2500.0);
+        assert_eq!(annotations[3].keypoints, Some(vec![50.0, 75.0, 2.0]));
+
+        // `original_width` が無い場合は画像ファイルのサイズを使う
+        assert_eq!(annotations[4].bbox, [0.0, 0.0, 4.0, 2.0]);
+        assert_eq!(annotations[4].extra["annotator_id"], 5);
+
+        let messages: Vec<_> = result.issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "task 7: skipped 1 \"brushlabels\" region(s)",
+                "task #3: image not found: https://example.com/missing.jpg?token=1",
+                "task #3: image size is unknown; coordinates cannot be converted",
+                "task #4: no image in data",
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_jsonl_and_reports_line_of_parse_errors() {
+        let dir = temp_dir("label-studio-jsonl");
+        write(
+            &dir.join("tasks.jsonl"),
+            "{\"data\": {\"image\": \"a.jpg\"}}\n\n{\"data\": {\"image\": \"b.jpg\"}}\n",
+        );
+        let result = import(&dir.join("tasks.jsonl"), None).unwrap();
+        assert_eq!(result.coco_data.images.len(), 2);
+        assert_eq!(result.image_root, dir.display().to_string());
+
+        write(
+            &dir.join("broken.jsonl"),
+            "{\"data\": {}}\n{\"data\": {}}\n{\"data\": \n",
+        );
+        let CoavError::Parse { location, .. } =
+            import(&dir.join("broken.jsonl"), None).unwrap_err()
+        else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(location.line, Some(3));
+
+        // 配列ではないJSONは1タスクとして読み込む
+        write(&dir.join("task.json"), r#"{"data": {"image": "a.jpg"}}"#);
+        let result = import(&dir.join("task.json"), None).unwrap();
+        assert_eq!(result.coco_data.images.len(), 1);
+    }
+
+    #[test]
+    fn derives_candidate_file_names() {
+        assert_eq!(
+            candidate_names("/data/local-files/?d=dir%20x/a.jpg"),
+            ["dir x/a.jpg"]
+        );
+        assert_eq!(
+            candidate_names("/data/upload/1/8f3a2c1d-a-b.jpg"),
+            ["a-b.jpg", "8f3a2c1d-a-b.jpg"]
+        );
+        assert_eq!(candidate_names("s3://bucket/x-y.jpg#frag"), ["x-y.jpg"]);
+        assert_eq!(candidate_names(""), [""]);
+        assert_eq!(percent_decode("%E7%8C%AB%2"), "猫%2");
+    }
+}
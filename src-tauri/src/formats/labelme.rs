@@ -0,0 +1,370 @@
+use super::{
+    find_files, image_size, image_size_from_bytes, relative_file_name, ImportResult, Issues,
+};
+use crate::error::{CoavError, CoavResult};
+use crate::models::segmentation::{polygon_area, polygons_bbox};
+use crate::models::{COCOAnnotation, COCOCategory, COCOData, COCOImage, Segmentation};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::f64::consts::TAU;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// 円をポリゴンに変換する際の頂点数
+const CIRCLE_VERTICES: usize = 32;
+
+/// LabelMeのJSONファイル（画像1枚分）
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LabelMeFile {
+    shapes: Vec<Shape>,
+    /// JSONファイルからの相対パス
+    image_path: String,
+    /// base64でエンコードされた画像（保存しない設定の場合は `null`）
+    #[serde(default)]
+    image_data: Option<String>,
+    #[serde(default)]
+    image_width: Option<u32>,
+    #[serde(default)]
+    image_height: Option<u32>,
+    #[serde(default)]
+    flags: HashMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct Shape {
+    label: String,
+    points: Vec<[f64; 2]>,
+    #[serde(default)]
+    shape_type: Option<String>,
+    #[serde(default)]
+    group_id: Option<Value>,
+    #[serde(default)]
+    flags: Option<HashMap<String, Value>>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// LabelMe形式のJSONが保存されたフォルダを読み込む
+///
+/// フォルダ以下のJSONファイルを1枚の画像として読み込み、ラベル名の順にカテゴリIDを振る。
+/// `rectangle` と `circle` はポリゴンに変換し、それ以外の種類（`point`・`line` など）は読み込まずに報告する。
+/// `group_id`・`flags`・`description` はアノテーションの `extra` に保存する。
+pub fn import(path: &Path) -> CoavResult<ImportResult> {
+    if !path.is_dir() {
+        return Err(CoavError::not_found(
+            path,
+            format!("Folder not found: {}", path.display()),
+        ));
+    }
+    let json_paths = find_files(path, |p| {
+        p.extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+    });
+
+    let mut issues = Issues::default();
+    let mut files = Vec::new();
+    for json_path in json_paths {
+        let parsed = fs::read_to_string(&json_path)
+            .map_err(|e| format!("Failed to read file: {e}"))
+            .and_then(|text| {
+                serde_json::from_str::<LabelMeFile>(&text)
+                    .map_err(|e| format!("Not a LabelMe file: {e}"))
+            });
+        match parsed {
+            Ok(file) => files.push((json_path, file)),
+            Err(message) => issues.push(&json_path, None, message),
+        }
+    }
+
+    // カテゴリはラベル名の順にIDを振る
+    let labels: BTreeSet<&str> = files
+        .iter()
+        .flat_map(|(_, file)| file.shapes.iter())
+        .filter(|shape| to_polygon(shape).is_ok())
+        .map(|shape| shape.label.as_str())
+        .collect();
+    let category_ids: HashMap<&str, i32> = labels
+        .iter()
+        .enumerate()
+        .map(|(i, label)| (*label, i as i32 + 1))
+        .collect();
+
+    let mut coco_data = COCOData {
+        categories: labels
+            .iter()
+            .map(|label| COCOCategory {
+                id: category_ids[label].into(),
+                name: label.to_string(),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    for (json_path, file) in &files {
+        // Windowsで保存されたファイルは `\` 区切りになっている
+        let image_path = json_path
+            .parent()
+            .unwrap_or(path)
+            .join(file.image_path.replace('\\', "/"));
+        let image_path = normalize(&image_path);
+        let size = match (file.image_width, file.image_height) {
+            (Some(width), Some(height)) if width > 0 && height > 0 => Ok((width, height)),
+            // サイズが記録されていない場合は埋め込み画像、画像ファイルの順に取得する
+            _ => match &file.image_data {
+                Some(data) => STANDARD
+                    .decode(data)
+                    .map_err(|e| format!("Invalid imageData: {e}"))
+                    .and_then(|bytes| image_size_from_bytes(&bytes)),
+                None => image_size(&image_path),
+            },
+        };
+        let (width, height) = match size {
+            Ok(size) => size,
+            Err(message) => {
+                issues.push(json_path, None, message);
+                (0, 0)
+            }
+        };
+
+        let image_id = coco_data.images.len() as i64 + 1;
+        let mut extra = HashMap::new();
+        if !file.flags.is_empty() {
+            extra.insert(
+                "flags".to_string(),
+                Value::Object(file.flags.clone().into_iter().collect()),
+            );
+        }
+        coco_data.images.push(COCOImage {
+            id: image_id.into(),
+            width: width as i32,
+            height: height as i32,
+            file_name: relative_file_name(path, &image_path),
+            extra,
+            ..Default::default()
+        });
+
+        let mut skipped: BTreeMap<&str, usize> = BTreeMap::new();
+        for shape in &file.shapes {
+            let polygon = match to_polygon(shape) {
+                Ok(polygon) => polygon,
+                Err(Unsupported(shape_type)) => {
+                    *skipped.entry(shape_type).or_default() += 1;
+                    continue;
+                }
+            };
+            let polygons = vec![polygon];
+            let bbox = polygons_bbox(&polygons).map_or_else(|| vec![0.0; 4], |b| b.to_vec());
+            coco_data.annotations.push(COCOAnnotation {
+                id: (coco_data.annotations.len() as i64 + 1).into(),
+                image_id: image_id.into(),
+                category_id: category_ids[shape.label.as_str()].into(),
+                area: polygon_area(&polygons[0]),
+                bbox,
+                segmentation: Some(Segmentation::Polygon(polygons)),
+                extra: shape_extra(shape),
+                ..Default::default()
+            });
+        }
+        for (shape_type, count) in skipped {
+            issues.push(
+                json_path,
+                None,
+                format!("Skipped {count} \"{shape_type}\" shape(s) that cannot be converted to polygons"),
+            );
+        }
+    }
+
+    Ok(ImportResult {
+        coco_data,
+        image_root: path.display().to_string(),
+        issues: issues.into_vec(),
+    })
+}
+
+/// ポリゴンに変換できない図形の種類
+struct Unsupported<'a>(&'a str);
+
+/// 図形をCOCOのポリゴン `[x1, y1, x2, y2, ...]` に変換する
+fn to_polygon(shape: &Shape) -> Result<Vec<f64>, Unsupported> {
+    let shape_type = shape.shape_type.as_deref().unwrap_or("polygon");
+    let points = &shape.points;
+    match shape_type {
+        "polygon" if points.len() >= 3 => Ok(points.iter().flatten().copied().collect()),
+        "rectangle" if points.len() == 2 => {
+            let [[x1, y1], [x2, y2]] = [points[0], points[1]];
+            let (left, right) = (x1.min(x2), x1.max(x2));
+            let (top, bottom) = (y1.min(y2), y1.max(y2));
+            Ok(vec![left, top, right, top, right, bottom, left, bottom])
+        }
+        // 円は中心と円周上の1点で表される
+        "circle" if points.len() == 2 => {
+            let [[cx, cy], [px, py]] = [points[0], points[1]];
+            let radius = (px - cx).hypot(py - cy);
+            Ok((0..CIRCLE_VERTICES)
+                .flat_map(|i| {
+                    let angle = TAU * i as f64 / CIRCLE_VERTICES as f64;
+                    [cx + radius * angle.cos(), cy + radius * angle.sin()]
+                })
+                .collect())
+        }
+        _ => Err(Unsupported(shape_type)),
+    }
+}
+
+fn shape_extra(shape: &Shape) -> HashMap<String, Value> {
+    let mut extra = HashMap::new();
+    if let Some(group_id) = shape.group_id.as_ref().filter(|v| !v.is_null()) {
+        extra.insert("group_id".to_string(), group_id.clone());
+    }
+    if let Some(flags) = shape.flags.as_ref().filter(|f| !f.is_empty()) {
+        extra.insert(
+            "flags".to_string(),
+            Value::Object(flags.clone().into_iter().collect()),
+        );
+    }
+    if let Some(description) = shape.description.as_ref().filter(|d| !d.is_empty()) {
+        extra.insert(
+            "description".to_string(),
+            Value::String(description.clone()),
+        );
+    }
+    extra
+}
+
+/// `..` を含むパスを整理する（`imagePath` はJSONファイルからの相対パスのため）
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::test_util::{temp_dir, write, write_image};
+    use crate::models::CocoId;
+    use std::io::Cursor;
+
+    #[test]
+    fn converts_shapes_to_polygons() {
+        let dir = temp_dir("labelme-shapes");
+        write_image(&dir.join("images/a.jpg"), 40, 30);
+        write(
+            &dir.join("labels/a.json"),
+            r#"{
+                "version": "5.4.1",
+                "flags": {"reviewed": true},
+                "shapes": [
+                    {"label": "dog", "points": [[1, 2], [5, 2], [5, 6]], "shape_type": "polygon",
+                     "group_id": 3, "flags": {}, "description": "front"},
+                    {"label": "cat", "points": [[10, 8], [4, 2]], "shape_type": "rectangle",
+                     "group_id": null},
+                    {"label": "ball", "points": [[20, 20], [23, 24]], "shape_type": "circle"},
+                    {"label": "eye", "points": [[1, 1]], "shape_type": "point"},
+                    {"label": "eye", "points": [[1, 1]], "shape_type": "point"},
+                    {"label": "tail", "points": [[1, 1], [2, 2]], "shape_type": "line"}
+                ],
+                "imagePath": "..\\images\\a.jpg",
+                "imageData": null
+            }"#,
+        );
+        write(&dir.join("labels/broken.json"), r#"{"shapes": 1}"#);
+
+        let result = import(&dir).unwrap();
+        let data = &result.coco_data;
+
+        // 変換できない図形のラベルはカテゴリにしない
+        let names: Vec<_> = data.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["ball", "cat", "dog"]);
+
+        // `imagePath` はJSONファイルからの相対パス（`\` 区切り）
+        assert_eq!(data.images.len(), 1);
+        assert_eq!(data.images[0].file_name, "images/a.jpg");
+        assert_eq!((data.images[0].width, data.images[0].height), (40, 30));
+        assert_eq!(data.images[0].extra["flags"]["reviewed"], true);
+
+        let dog = &data.annotations[0];
+        assert_eq!(dog.category_id, CocoId::from(3));
+        assert_eq!(dog.bbox, [1.0, 2.0, 4.0, 4.0]);
+        assert_eq!(dog.area, 8.0);
+        assert_eq!(dog.extra["group_id"], 3);
+        assert_eq!(dog.extra["description"], "front");
+        assert!(!dog.extra.contains_key("flags"));
+
+        // 矩形は左上から時計回りの4頂点になる
+        let cat = &data.annotations[1];
+        assert_eq!(
+            cat.segmentation,
+            Some(Segmentation::Polygon(vec![vec![
+                4.0, 2.0, 10.0, 2.0, 10.0, 8.0, 4.0, 8.0
+            ]]))
+        );
+        assert_eq!(cat.bbox, [4.0, 2.0, 6.0, 6.0]);
+        assert!(cat.extra.is_empty());
+
+        // 円は半径5の32角形になる
+        let ball = &data.annotations[2];
+        let Some(Segmentation::Polygon(polygons)) = &ball.segmentation else {
+            panic!("circle was not converted to a polygon");
+        };
+        assert_eq!(polygons[0].len(), CIRCLE_VERTICES * 2);
+        assert!((ball.bbox[0] - 15.0).abs() < 1e-9);
+        assert!((ball.bbox[2] - 10.0).abs() < 1e-9);
+
+        let messages: Vec<_> = result.issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(messages.len(), 3);
+        assert!(messages[0].starts_with("Not a LabelMe file"));
+        assert_eq!(
+            messages[1..],
+            [
+                "Skipped 1 \"line\" shape(s) that cannot be converted to polygons",
+                "Skipped 2 \"point\" shape(s) that cannot be converted to polygons",
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_size_from_embedded_image() {
+        let mut png = Vec::new();
+        image::RgbImage::new(6, 4)
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let dir = temp_dir("labelme-image-data");
+        write(
+            &dir.join("a.json"),
+            serde_json::json!({
+                "shapes": [],
+                "imagePath": "missing.png",
+                "imageData": STANDARD.encode(&png),
+            })
+            .to_string(),
+        );
+        write(
+            &dir.join("b.json"),
+            r#"{"shapes": [], "imagePath": "missing.png", "imageData": null}"#,
+        );
+
+        let result = import(&dir).unwrap();
+        let images = &result.coco_data.images;
+        assert_eq!((images[0].width, images[0].height), (6, 4));
+        // 画像が見つからない場合はサイズ0として報告する
+        assert_eq!((images[1].width, images[1].height), (0, 0));
+        assert_eq!(result.issues.len(), 1);
+        assert!(result.issues[0].file.ends_with("b.json"));
+    }
+}
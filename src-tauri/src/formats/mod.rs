@@ -0,0 +1,224 @@
+use crate::models::COCOData;
+use serde::Serialize;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Component, Path, PathBuf};
+
+pub mod cvat;
+pub mod dota;
+pub mod kitti;
+pub mod label_studio;
+pub mod labelme;
+pub mod mot;
+pub mod open_images;
+pub mod panoptic;
+pub mod tfrecord;
+pub mod voc;
+pub mod yolo;
+
+/// 画像として扱う拡張子
+pub const IMAGE_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "bmp", "gif", "webp"];
+
+/// 変換できなかった項目（変換自体は続行される）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionIssue {
+    pub file: String,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// 変換中に見つかった問題の一覧
+#[derive(Debug, Default)]
+pub struct Issues(Vec<ConversionIssue>);
+
+impl Issues {
+    pub fn push(&mut self, file: impl AsRef<Path>, line: Option<usize>, message: String) {
+        self.0.push(ConversionIssue {
+            file: file.as_ref().display().to_string(),
+            line,
+            message,
+        });
+    }
+
+    pub fn into_vec(self) -> Vec<ConversionIssue> {
+        self.0
+    }
+}
+
+/// 他の形式から読み込んだ結果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    pub coco_data: COCOData,
+    /// 画像の `file_name` の基準になるフォルダ
+    pub image_root: String,
+    pub issues: Vec<ConversionIssue>,
+}
+
+/// 他の形式へのエクスポート結果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportReport {
+    pub output_dir: String,
+    /// 書き出したファイル数
+    pub files: usize,
+    pub annotations: usize,
+    /// 出力しなかった `iscrowd` のアノテーション数
+    pub skipped_crowd: usize,
+    /// 出力できなかったアノテーション（`file` は画像の `file_name`）
+    pub issues: Vec<ConversionIssue>,
+}
+
+pub fn is_image_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// フォルダ以下の画像ファイルをパス順に返す（隠しフォルダは除く）
+pub fn find_images(root: &Path) -> Vec<PathBuf> {
+    find_files(root, is_image_file)
+}
+
+/// フォルダ以下で条件に合うファイルをパス順に返す（隠しフォルダは除く）
+pub fn find_files(root: &Path, matches: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                let hidden = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with('.'));
+                if !hidden {
+                    dirs.push(path);
+                }
+            } else if matches(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// フォルダ内で画像ファイルを探す（`scan_folder` と同じ規則）
+///
+/// `file_name` のパスにファイルが無い場合は、フォルダ直下で同じファイル名のものを探す。
+pub fn find_image_file(folder: &Path, file_name: &str) -> Option<PathBuf> {
+    let image_path = folder.join(file_name);
+    if image_path.is_file() {
+        return Some(image_path);
+    }
+    let file_name_only = Path::new(file_name)
+        .file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or(file_name);
+    fs::read_dir(folder)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| entry.path().canonicalize().ok())
+        .find(|path| {
+            path.is_file() && path.file_name().and_then(|f| f.to_str()) == Some(file_name_only)
+        })
+}
+
+/// `root` からの相対パスを `/` 区切りで返す（COCOの `file_name` 用）
+pub fn relative_file_name(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 出力先のフォルダの外を指さないよう、`..` や絶対パスを取り除いた `/` 区切りの相対パスにする
+///
+/// 通常の名前の要素が残らない場合は `None` を返す。
+pub fn sanitize_file_name(name: &str) -> Option<String> {
+    let name = name.replace('\\', "/");
+    let parts: Vec<String> = Path::new(&name)
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// 画像のヘッダーから幅と高さを読み取る（画像全体はデコードしない）
+pub fn image_size(path: &Path) -> Result<(u32, u32), String> {
+    image::image_dimensions(path).map_err(|e| format!("Failed to read image size: {e}"))
+}
+
+/// メモリ上の画像データから幅と高さを読み取る
+pub fn image_size_from_bytes(bytes: &[u8]) -> Result<(u32, u32), String> {
+    image::ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to read image size: {e}"))?
+        .into_dimensions()
+        .map_err(|e| format!("Failed to read image size: {e}"))
+}
+
+#[cfg(test)]
+pub(crate) mod test_util {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// テストごとの空の一時フォルダを作成する
+    pub fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("coav-format-test-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 親フォルダを作成してファイルを書き込む
+    pub fn write(path: &Path, contents: impl AsRef<[u8]>) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    /// 指定した大きさの画像を書き込む（形式は拡張子で決まる）
+    pub fn write_image(path: &Path, width: u32, height: u32) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        image::RgbImage::new(width, height).save(path).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitizes_file_names() {
+        assert_eq!(
+            sanitize_file_name("images/a.jpg").as_deref(),
+            Some("images/a.jpg")
+        );
+        assert_eq!(
+            sanitize_file_name("../a/./b.jpg").as_deref(),
+            Some("a/b.jpg")
+        );
+        assert_eq!(
+            sanitize_file_name("/abs/x.png").as_deref(),
+            Some("abs/x.png")
+        );
+        assert_eq!(
+            sanitize_file_name("..\\..\\win\\c.jpg").as_deref(),
+            Some("win/c.jpg")
+        );
+        assert_eq!(sanitize_file_name(".."), None);
+        assert_eq!(sanitize_file_name("/"), None);
+        assert_eq!(sanitize_file_name(""), None);
+    }
+}
@@ -0,0 +1,512 @@
+use super::{find_images, image_size, ImportResult, Issues};
+use crate::error::{CoavError, CoavResult};
+use crate::models::{
+    COCOAnnotation, COCOCategory, COCOData, COCOImage, COCOTrack, COCOVideo, CocoId,
+};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// MOT17・MOT20の正解データのクラス（1始まり）
+const CLASS_NAMES: [&str; 13] = [
+    "pedestrian",
+    "person_on_vehicle",
+    "car",
+    "bicycle",
+    "motorbike",
+    "non_mot_vehicle",
+    "static_person",
+    "distractor",
+    "occluder",
+    "occluder_on_ground",
+    "occluder_full",
+    "reflection",
+    "crowd",
+];
+
+/// クラスの列が無い場合（MOT15など）のクラス
+const PEDESTRIAN: i32 = 1;
+
+/// 1シーケンスのフレーム数の上限（壊れた `seqLength` やフレーム番号で大量の画像を作らないため）
+const MAX_FRAMES: i64 = 100_000;
+
+/// `seqinfo.ini` の `[Sequence]`
+struct SequenceInfo {
+    name: String,
+    im_dir: String,
+    im_ext: String,
+    frame_rate: Option<f64>,
+    seq_length: Option<i64>,
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+/// `gt.txt` の1行
+///
+/// `frame, id, bb_left, bb_top, bb_width, bb_height, conf, class, visibility`
+struct MotRow {
+    /// `gt.txt` の行番号
+    line: usize,
+    frame: i64,
+    id: i64,
+    bbox: [f64; 4],
+    /// 評価の対象とするか（正解データの `conf` 列が0の場合は対象外）
+    consider: bool,
+    class: i32,
+    visibility: Option<f64>,
+}
+
+/// MOTChallenge形式の正解データ（`gt/gt.txt`）を読み込む
+///
+/// `path` にはシーケンスのフォルダ（`MOT17-02-FRCNN/` など）、複数のシーケンスを含むフォルダ
+/// （`train/` など）、または `gt.txt` を指定する。シーケンスごとに動画（`videos`）を作成し、
+/// 各フレームの画像に `video_id`・`frame_id` を、アノテーションに `track_id` を設定する。
+/// トラックIDはシーケンスをまたいで重複しないよう振り直し、元のIDは `tracks` の `mot_id` に保存する。
+/// 評価の対象外（`conf` が0）の領域は `iscrowd: 1` として読み込む。
+/// フレーム数は `seqLength`（無ければ画像の枚数）とし、その範囲外のフレームの行は読み込まずに報告する。
+pub fn import(path: &Path) -> CoavResult<ImportResult> {
+    let sequence_dirs = find_sequences(path)?;
+    let root = sequence_dirs[0]
+        .parent()
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+
+    let mut issues = Issues::default();
+    let mut coco_data = COCOData::default();
+    let mut videos = Vec::new();
+    let mut tracks = Vec::new();
+    let mut classes = BTreeSet::new();
+
+    for sequence_dir in &sequence_dirs {
+        let info = read_sequence_info(sequence_dir, &mut issues);
+        let gt_path = sequence_dir.join("gt").join("gt.txt");
+        let mut rows = match fs::read_to_string(&gt_path) {
+            Ok(text) => parse_gt(&text, |line, message| {
+                issues.push(&gt_path, Some(line), message)
+            }),
+            Err(e) => {
+                issues.push(&gt_path, None, format!("Failed to read file: {e}"));
+                Vec::new()
+            }
+        };
+        rows.sort_by_key(|row| (row.frame, row.id));
+
+        let image_dir = sequence_dir.join(&info.im_dir);
+        let image_count = find_images(&image_dir).len() as i64;
+        let frame_count = match info.seq_length {
+            Some(length) => length,
+            None if image_count > 0 => image_count,
+            // フレーム数が分からない場合は gt.txt の最大のフレーム番号までとする
+            None => rows
+                .iter()
+                .map(|row| row.frame)
+                .max()
+                .unwrap_or(0)
+                .min(MAX_FRAMES),
+        };
+        rows.retain(|row| {
+            let inside = row.frame <= frame_count;
+            if !inside {
+                issues.push(
+                    &gt_path,
+                    Some(row.line),
+                    format!(
+                        "Frame {} is outside the sequence ({frame_count} frames)",
+                        row.frame
+                    ),
+                );
+            }
+            inside
+        });
+
+        let (width, height) = match (info.width, info.height) {
+            (Some(width), Some(height)) => (width, height),
+            _ => {
+                let first = image_dir.join(format!("{:06}{}", 1, info.im_ext));
+                image_size(&first).unwrap_or_else(|message| {
+                    issues.push(&first, None, message);
+                    (0, 0)
+                })
+            }
+        };
+
+        let video_id = CocoId::from(videos.len() as i64 + 1);
+        let mut extra = HashMap::new();
+        if let Some(frame_rate) = info.frame_rate {
+            extra.insert("frame_rate".to_string(), Value::from(frame_rate));
+        }
+        videos.push(COCOVideo {
+            id: video_id.clone(),
+            name: Some(info.name.clone()),
+            width: Some(width as i32),
+            height: Some(height as i32),
+            extra,
+        });
+
+        let first_image_id = coco_data.images.len() as i64 + 1;
+        for frame in 1..=frame_count {
+            coco_data.images.push(COCOImage {
+                id: (first_image_id + frame - 1).into(),
+                width: width as i32,
+                height: height as i32,
+                file_name: format!("{}/{}/{frame:06}{}", info.name, info.im_dir, info.im_ext),
+                video_id: Some(video_id.clone()),
+                frame_id: Some(frame),
+                ..Default::default()
+            });
+        }
+
+        // トラックIDは元のIDの順に振る
+        let mut track_ids: BTreeMap<i64, (i64, i32)> = BTreeMap::new();
+        for row in &rows {
+            track_ids.entry(row.id).or_insert((0, row.class));
+        }
+        for (mot_id, (track_id, class)) in track_ids.iter_mut() {
+            *track_id = tracks.len() as i64 + 1;
+            tracks.push(COCOTrack {
+                id: (*track_id).into(),
+                category_id: (*class).into(),
+                video_id: Some(video_id.clone()),
+                extra: HashMap::from([("mot_id".to_string(), Value::from(*mot_id))]),
+            });
+        }
+
+        for row in &rows {
+            let [x, y, w, h] = row.bbox;
+            classes.insert(row.class);
+            let mut option = json!({ "consider": row.consider });
+            if let Some(visibility) = row.visibility {
+                option["visibility"] = Value::from(visibility);
+            }
+            coco_data.annotations.push(COCOAnnotation {
+                id: (coco_data.annotations.len() as i64 + 1).into(),
+                image_id: (first_image_id + row.frame - 1).into(),
+                category_id: row.class.into(),
+                area: w * h,
+                bbox: vec![x, y, w, h],
+                iscrowd: (!row.consider) as i32,
+                option: Some(option),
+                track_id: Some(track_ids[&row.id].0.into()),
+                ..Default::default()
+            });
+        }
+    }
+
+    coco_data.categories = classes
+        .into_iter()
+        .map(|class| COCOCategory {
+            id: class.into(),
+            name: class_name(class),
+            ..Default::default()
+        })
+        .collect();
+    coco_data.videos = Some(videos);
+    coco_data.tracks = Some(tracks);
+
+    Ok(ImportResult {
+        coco_data,
+        image_root: root.display().to_string(),
+        issues: issues.into_vec(),
+    })
+}
+
+/// シーケンスのフォルダ（`gt/gt.txt` を含むフォルダ）を探す
+fn find_sequences(path: &Path) -> CoavResult<Vec<PathBuf>> {
+    let is_sequence = |dir: &Path| dir.join("gt").join("gt.txt").is_file();
+    if path.is_file() {
+        // `<sequence>/gt/gt.txt`
+        return path
+            .parent()
+            .and_then(Path::parent)
+            .map(|dir| vec![dir.to_path_buf()])
+            .ok_or_else(|| CoavError::InvalidInput {
+                message: format!("{} is not in a MOT sequence folder", path.display()),
+            });
+    }
+    if !path.is_dir() {
+        return Err(CoavError::not_found(
+            path,
+            format!("Folder not found: {}", path.display()),
+        ));
+    }
+    if is_sequence(path) {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut sequences: Vec<PathBuf> = fs::read_dir(path)
+        .map_err(|e| CoavError::io(path, "Failed to read folder", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|dir| is_sequence(dir))
+        .collect();
+    sequences.sort();
+    if sequences.is_empty() {
+        return Err(CoavError::InvalidInput {
+            message: format!("No MOT sequences (gt/gt.txt) found in {}", path.display()),
+        });
+    }
+    Ok(sequences)
+}
+
+/// `seqinfo.ini` を読み込む（無い場合はフォルダ名と既定値を使う）
+fn read_sequence_info(sequence_dir: &Path, issues: &mut Issues) -> SequenceInfo {
+    let mut info = SequenceInfo {
+        name: sequence_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        im_dir: "img1".to_string(),
+        im_ext: ".jpg".to_string(),
+        frame_rate: None,
+        seq_length: None,
+        width: None,
+        height: None,
+    };
+    let ini_path = sequence_dir.join("seqinfo.ini");
+    let Ok(text) = fs::read_to_string(&ini_path) else {
+        return info;
+    };
+    for (i, line) in text.lines().enumerate() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        let invalid = || format!("Invalid value for {}: {value}", key.trim());
+        match key.trim() {
+            "imDir" => info.im_dir = value.to_string(),
+            "imExt" => info.im_ext = value.to_string(),
+            "frameRate" => info.frame_rate = value.parse().ok(),
+            "seqLength" => match value.parse() {
+                Ok(length) if (1..=MAX_FRAMES).contains(&length) => info.seq_length = Some(length),
+                _ => issues.push(&ini_path, Some(i + 1), invalid()),
+            },
+            "imWidth" => match value.parse() {
+                Ok(width) => info.width = Some(width),
+                Err(_) => issues.push(&ini_path, Some(i + 1), invalid()),
+            },
+            "imHeight" => match value.parse() {
+                Ok(height) => info.height = Some(height),
+                Err(_) => issues.push(&ini_path, Some(i + 1), invalid()),
+            },
+            _ => {}
+        }
+    }
+    info
+}
+
+fn parse_gt(text: &str, mut on_issue: impl FnMut(usize, String)) -> Vec<MotRow> {
+    let mut rows = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match parse_row(i + 1, line) {
+            Ok(row) => rows.push(row),
+            Err(message) => on_issue(i + 1, message),
+        }
+    }
+    rows
+}
+
+fn parse_row(line_number: usize, line: &str) -> Result<MotRow, String> {
+    let values = line
+        .split(',')
+        .map(|token| {
+            let token = token.trim();
+            token
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("Invalid number: {token}"))
+        })
+        .collect::<Result<Vec<f64>, String>>()?;
+    if values.len() < 6 {
+        return Err(format!(
+            "Expected at least 6 values but found {}",
+            values.len()
+        ));
+    }
+    let frame = values[0] as i64;
+    if frame < 1 {
+        return Err(format!("Invalid frame number: {frame}"));
+    }
+    // MOT15の10列の形式では7列目以降は信頼度と3Dの座標
+    let (class, visibility) = match values.len() {
+        9 | 11.. => (values[7] as i32, Some(values[8])),
+        8 => (values[7] as i32, None),
+        _ => (PEDESTRIAN, None),
+    };
+    Ok(MotRow {
+        line: line_number,
+        frame,
+        id: values[1] as i64,
+        bbox: [values[2], values[3], values[4], values[5]],
+        consider: values.get(6).map_or(true, |&conf| conf != 0.0),
+        class: if class < 1 { PEDESTRIAN } else { class },
+        visibility,
+    })
+}
+
+fn class_name(class: i32) -> String {
+    usize::try_from(class - 1)
+        .ok()
+        .and_then(|i| CLASS_NAMES.get(i))
+        .map_or_else(|| format!("class_{class}"), |name| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::test_util::{temp_dir, write, write_image};
+
+    #[test]
+    fn imports_sequences_with_tracks() {
+        let dir = temp_dir("mot-sequences");
+        write(
+            &dir.join("MOT17-02/seqinfo.ini"),
+            "[Sequence]\nname=MOT17-02\nimDir=img1\nframeRate=30\nseqLength=3\n\
+             imWidth=1920\nimHeight=1080\nimExt=.jpg\n",
+        );
+        write(
+            &dir.join("MOT17-02/gt/gt.txt"),
+            "2,7,10,20,30,40,1,1,0.5\n\
+             1,7,11,21,30,40,1,1,1\n\
+             1,3,0,0,5,5,0,8,0\n\
+             5,7,0,0,1,1,1,1,1\n\
+             0,1,0,0,1,1,1,1,1\n\
+             1,2,x,0,1,1\n",
+        );
+        // seqinfo.ini の無いMOT15形式（10列、クラスの列なし）は画像の枚数をフレーム数とする
+        write(
+            &dir.join("ADL-Rundle-6/gt/gt.txt"),
+            "1,7,1,2,3,4,1,-1,-1,-1\n2,7,1,2,3,4,1,-1,-1,-1\n3,7,1,2,3,4,1,-1,-1,-1\n",
+        );
+        write_image(&dir.join("ADL-Rundle-6/img1/000001.jpg"), 64, 48);
+        write_image(&dir.join("ADL-Rundle-6/img1/000002.jpg"), 64, 48);
+
+        let result = import(&dir).unwrap();
+        let data = &result.coco_data;
+        assert_eq!(result.image_root, dir.display().to_string());
+
+        let videos = data.videos.as_ref().unwrap();
+        assert_eq!(videos.len(), 2);
+        assert_eq!(videos[0].name.as_deref(), Some("ADL-Rundle-6"));
+        assert_eq!((videos[0].width, videos[0].height), (Some(64), Some(48)));
+        assert_eq!(videos[1].extra["frame_rate"], 30.0);
+
+        // フレームはseqLength・画像の枚数の分だけ作成する
+        let frames: Vec<_> = data
+            .images
+            .iter()
+            .map(|i| (i.video_id.as_ref().and_then(CocoId::as_int), i.frame_id))
+            .collect();
+        assert_eq!(
+            frames,
+            [
+                (Some(1), Some(1)),
+                (Some(1), Some(2)),
+                (Some(2), Some(1)),
+                (Some(2), Some(2)),
+                (Some(2), Some(3)),
+            ]
+        );
+        assert_eq!(data.images[2].file_name, "MOT17-02/img1/000001.jpg");
+        assert_eq!((data.images[2].width, data.images[2].height), (1920, 1080));
+
+        // トラックIDはシーケンスをまたいで振り直す
+        let tracks = data.tracks.as_ref().unwrap();
+        let mot_ids: Vec<_> = tracks
+            .iter()
+            .map(|t| {
+                (
+                    t.id.as_int(),
+                    t.video_id.as_ref().and_then(CocoId::as_int),
+                    t.extra["mot_id"].as_i64(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            mot_ids,
+            [
+                (Some(1), Some(1), Some(7)),
+                (Some(2), Some(2), Some(3)),
+                (Some(3), Some(2), Some(7)),
+            ]
+        );
+
+        let annotations = &data.annotations;
+        assert_eq!(annotations.len(), 5);
+        assert_eq!(annotations[0].category_id, CocoId::from(PEDESTRIAN));
+        // 行はフレーム・元のID順に並べる
+        let distractor = &annotations[2];
+        assert_eq!(distractor.image_id, CocoId::from(3i64));
+        assert_eq!(distractor.track_id, Some(CocoId::from(2)));
+        assert_eq!(distractor.iscrowd, 1);
+        assert_eq!(distractor.category_id, CocoId::from(8));
+        let second = &annotations[4];
+        assert_eq!(second.image_id, CocoId::from(4i64));
+        assert_eq!(second.bbox, [10.0, 20.0, 30.0, 40.0]);
+        assert_eq!(
+            second.option,
+            Some(json!({"consider": true, "visibility": 0.5}))
+        );
+
+        let names: Vec<_> = data.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["pedestrian", "distractor"]);
+
+        let issues: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| {
+                (
+                    i.file.rsplit(['/', '\\']).nth(2),
+                    i.line,
+                    i.message.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (
+                    Some("ADL-Rundle-6"),
+                    Some(3),
+                    "Frame 3 is outside the sequence (2 frames)"
+                ),
+                (Some("MOT17-02"), Some(5), "Invalid frame number: 0"),
+                (Some("MOT17-02"), Some(6), "Invalid number: x"),
+                (
+                    Some("MOT17-02"),
+                    Some(4),
+                    "Frame 5 is outside the sequence (3 frames)"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn bounds_frames_without_sequence_length() {
+        let dir = temp_dir("mot-unbounded");
+        write(
+            &dir.join("seq/seqinfo.ini"),
+            "[Sequence]\nseqLength=99999999999\nimWidth=10\nimHeight=10\n",
+        );
+        write(
+            &dir.join("seq/gt/gt.txt"),
+            "2,1,0,0,1,1,1,1,1\n9999999999,1,0,0,1,1,1,1,1\n",
+        );
+
+        let result = import(&dir.join("seq/gt/gt.txt")).unwrap();
+        assert_eq!(result.coco_data.images.len(), MAX_FRAMES as usize);
+        assert_eq!(result.coco_data.annotations.len(), 1);
+        let messages: Vec<_> = result.issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Invalid value for seqLength: 99999999999",
+                "Frame 9999999999 is outside the sequence (100000 frames)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,519 @@
+use super::{image_size, ImportResult, Issues, IMAGE_EXTENSIONS};
+use crate::error::{CoavError, CoavResult, ErrorLocation};
+use crate::models::segmentation::Rle;
+use crate::models::{COCOAnnotation, COCOCategory, COCOData, COCOImage, Segmentation};
+use image::imageops::{self, FilterType};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Open Imagesの読み込み元
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenImagesSources {
+    /// 矩形のCSV（`*-annotations-bbox.csv`）
+    #[serde(default)]
+    pub bbox_csv: Option<PathBuf>,
+    /// セグメンテーションのCSV（`*-annotations-object-segmentation.csv`）
+    #[serde(default)]
+    pub segmentation_csv: Option<PathBuf>,
+    /// セグメンテーションのマスク画像のフォルダ（省略時はCSVのフォルダ）
+    #[serde(default)]
+    pub mask_folder: Option<PathBuf>,
+    /// ラベル名のCSV（省略時はCSVのフォルダの `*class-descriptions*.csv`）
+    #[serde(default)]
+    pub class_descriptions_csv: Option<PathBuf>,
+    /// `<ImageID>.jpg` を含むフォルダ
+    pub image_folder: PathBuf,
+}
+
+/// `*-annotations-bbox.csv` の1行（座標は画像サイズで正規化されている）
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct BoxRow {
+    #[serde(rename = "ImageID")]
+    image_id: String,
+    #[serde(default)]
+    source: Option<String>,
+    label_name: String,
+    x_min: f64,
+    x_max: f64,
+    y_min: f64,
+    y_max: f64,
+    #[serde(default)]
+    is_occluded: Option<i8>,
+    #[serde(default)]
+    is_truncated: Option<i8>,
+    #[serde(default)]
+    is_group_of: Option<i8>,
+    #[serde(default)]
+    is_depiction: Option<i8>,
+    #[serde(default)]
+    is_inside: Option<i8>,
+}
+
+/// `*-annotations-object-segmentation.csv` の1行
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct MaskRow {
+    mask_path: String,
+    #[serde(rename = "ImageID")]
+    image_id: String,
+    label_name: String,
+    #[serde(rename = "BoxID", default)]
+    box_id: Option<String>,
+    #[serde(rename = "PredictedIoU", default)]
+    predicted_iou: Option<f64>,
+}
+
+/// 読み込み中の画像（IDは `ImageID` の初出順）
+struct LocalImage {
+    id: i64,
+    width: u32,
+    height: u32,
+}
+
+/// Open ImagesのCSV（矩形・セグメンテーション）を読み込む
+///
+/// 座標は正規化されているため、`image_folder` にある画像のサイズでピクセル座標に変換する。
+/// 画像が見つからない `ImageID` のアノテーションは読み込まない（サブセットでの利用を想定）。
+/// `IsGroupOf` は `iscrowd` に、`IsOccluded` などのフラグは `option` に保存する。
+/// セグメンテーションは矩形とは別のアノテーションとして、RLEで読み込む。
+pub fn import(sources: &OpenImagesSources) -> CoavResult<ImportResult> {
+    if sources.bbox_csv.is_none() && sources.segmentation_csv.is_none() {
+        return Err(CoavError::InvalidInput {
+            message: "Specify a bounding box or segmentation CSV".to_string(),
+        });
+    }
+    let csv_folder = sources
+        .bbox_csv
+        .iter()
+        .chain(&sources.segmentation_csv)
+        .find_map(|p| p.parent())
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+    let descriptions_path = sources
+        .class_descriptions_csv
+        .clone()
+        .or_else(|| find_class_descriptions(&csv_folder));
+    let display_names = match &descriptions_path {
+        Some(path) => read_class_descriptions(path)?,
+        None => Vec::new(),
+    };
+
+    let mut issues = Issues::default();
+    let mut coco_data = COCOData::default();
+    let mut images: HashMap<String, Option<LocalImage>> = HashMap::new();
+    let mut labels: BTreeMap<String, i32> = BTreeMap::new();
+    let mut missing_images = 0usize;
+
+    let mut local_image = |image_id: &str, coco_data: &mut COCOData, issues: &mut Issues| {
+        let entry = images.entry(image_id.to_string()).or_insert_with(|| {
+            let path = find_image(&sources.image_folder, image_id)?;
+            let (width, height) = match image_size(&path) {
+                Ok(size) => size,
+                Err(message) => {
+                    issues.push(&path, None, message);
+                    return None;
+                }
+            };
+            let id = coco_data.images.len() as i64 + 1;
+            coco_data.images.push(COCOImage {
+                id: id.into(),
+                width: width as i32,
+                height: height as i32,
+                file_name: path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                ..Default::default()
+            });
+            Some(LocalImage { id, width, height })
+        });
+        if entry.is_none() {
+            missing_images += 1;
+        }
+        entry
+            .as_ref()
+            .map(|image| (image.id, image.width, image.height))
+    };
+
+    if let Some(bbox_csv) = &sources.bbox_csv {
+        let mut reader = open_csv(bbox_csv)?;
+        for (i, row) in reader.deserialize::<BoxRow>().enumerate() {
+            let line = i + 2;
+            let row = match row {
+                Ok(row) => row,
+                Err(e) => {
+                    issues.push(bbox_csv, Some(line), format!("Invalid row: {e}"));
+                    continue;
+                }
+            };
+            let Some((image_id, width, height)) =
+                local_image(&row.image_id, &mut coco_data, &mut issues)
+            else {
+                continue;
+            };
+            let next_id = labels.len() as i32 + 1;
+            let category_id = *labels.entry(row.label_name.clone()).or_insert(next_id);
+            let (w, h) = (width as f64, height as f64);
+            let bbox = vec![
+                row.x_min * w,
+                row.y_min * h,
+                (row.x_max - row.x_min) * w,
+                (row.y_max - row.y_min) * h,
+            ];
+            coco_data.annotations.push(COCOAnnotation {
+                id: (coco_data.annotations.len() as i64 + 1).into(),
+                image_id: image_id.into(),
+                category_id: category_id.into(),
+                area: bbox[2] * bbox[3],
+                bbox,
+                iscrowd: (row.is_group_of == Some(1)) as i32,
+                option: Some(box_option(&row)),
+                ..Default::default()
+            });
+        }
+    }
+
+    if let Some(segmentation_csv) = &sources.segmentation_csv {
+        let mask_folder = sources
+            .mask_folder
+            .clone()
+            .unwrap_or_else(|| csv_folder.clone());
+        let mut reader = open_csv(segmentation_csv)?;
+        for (i, row) in reader.deserialize::<MaskRow>().enumerate() {
+            let line = i + 2;
+            let row = match row {
+                Ok(row) => row,
+                Err(e) => {
+                    issues.push(segmentation_csv, Some(line), format!("Invalid row: {e}"));
+                    continue;
+                }
+            };
+            let Some((image_id, width, height)) =
+                local_image(&row.image_id, &mut coco_data, &mut issues)
+            else {
+                continue;
+            };
+            let mask_path = mask_folder.join(&row.mask_path);
+            let rle = match read_mask(&mask_path, width, height) {
+                Ok(rle) => rle,
+                Err(message) => {
+                    issues.push(&mask_path, None, message);
+                    continue;
+                }
+            };
+            let Some(bbox) = rle.bbox() else {
+                issues.push(&mask_path, None, "Mask is empty".to_string());
+                continue;
+            };
+            let next_id = labels.len() as i32 + 1;
+            let category_id = *labels.entry(row.label_name.clone()).or_insert(next_id);
+            let mut option = Map::new();
+            if let Some(box_id) = row.box_id {
+                option.insert("box_id".to_string(), Value::String(box_id));
+            }
+            if let Some(predicted_iou) = row.predicted_iou {
+                option.insert("predicted_iou".to_string(), Value::from(predicted_iou));
+            }
+            coco_data.annotations.push(COCOAnnotation {
+                id: (coco_data.annotations.len() as i64 + 1).into(),
+                image_id: image_id.into(),
+                category_id: category_id.into(),
+                area: rle.area() as f64,
+                bbox: bbox.to_vec(),
+                segmentation: Some(Segmentation::CompressedRle(rle.compress())),
+                option: Some(Value::Object(option)),
+                ..Default::default()
+            });
+        }
+    }
+
+    if missing_images > 0 {
+        let unique_missing = images.values().filter(|image| image.is_none()).count();
+        issues.push(
+            &sources.image_folder,
+            None,
+            format!(
+                "{unique_missing} images were not found; {missing_images} annotations were skipped"
+            ),
+        );
+    }
+
+    // カテゴリIDは使われたラベルの初出順のまま、ラベル名のCSVの表示名を付ける
+    let names: HashMap<&str, &str> = display_names
+        .iter()
+        .map(|(mid, name)| (mid.as_str(), name.as_str()))
+        .collect();
+    let mut categories: Vec<(i32, String)> =
+        labels.into_iter().map(|(mid, id)| (id, mid)).collect();
+    categories.sort();
+    coco_data.categories = categories
+        .into_iter()
+        .map(|(id, mid)| {
+            let name = names
+                .get(mid.as_str())
+                .map_or_else(|| mid.clone(), |n| n.to_string());
+            let mut extra = HashMap::new();
+            extra.insert("label_name".to_string(), Value::String(mid));
+            COCOCategory {
+                id: id.into(),
+                name,
+                extra,
+                ..Default::default()
+            }
+        })
+        .collect();
+    if descriptions_path.is_none() && !coco_data.categories.is_empty() {
+        issues.push(
+            &csv_folder,
+            None,
+            "class-descriptions.csv was not found; label IDs are used as category names"
+                .to_string(),
+        );
+    }
+
+    Ok(ImportResult {
+        coco_data,
+        image_root: sources.image_folder.display().to_string(),
+        issues: issues.into_vec(),
+    })
+}
+
+fn open_csv(path: &Path) -> CoavResult<csv::Reader<fs::File>> {
+    let file = fs::File::open(path).map_err(|e| CoavError::io(path, "Failed to read file", e))?;
+    Ok(csv::Reader::from_reader(file))
+}
+
+/// `class-descriptions-boxable.csv` などを探す
+fn find_class_descriptions(folder: &Path) -> Option<PathBuf> {
+    let mut candidates: Vec<PathBuf> = fs::read_dir(folder)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.contains("class-descriptions") && n.ends_with(".csv"))
+        })
+        .collect();
+    candidates.sort();
+    candidates.into_iter().next()
+}
+
+/// `LabelName,DisplayName` の組を読み込む（ヘッダー行は無い場合もある）
+fn read_class_descriptions(path: &Path) -> CoavResult<Vec<(String, String)>> {
+    let file = fs::File::open(path).map_err(|e| CoavError::io(path, "Failed to read file", e))?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(file);
+    let mut names = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| CoavError::Parse {
+            message: format!("Failed to parse CSV: {e}"),
+            location: ErrorLocation {
+                file: Some(path.display().to_string()),
+                line: e.position().map(|p| p.line() as usize),
+                ..Default::default()
+            },
+        })?;
+        if let (Some(mid), Some(name)) = (record.get(0), record.get(1)) {
+            if mid != "LabelName" {
+                names.push((mid.to_string(), name.to_string()));
+            }
+        }
+    }
+    Ok(names)
+}
+
+fn find_image(folder: &Path, image_id: &str) -> Option<PathBuf> {
+    IMAGE_EXTENSIONS
+        .iter()
+        .map(|ext| folder.join(format!("{image_id}.{ext}")))
+        .find(|p| p.is_file())
+}
+
+/// `IsOccluded` などのフラグを `option` に変換する（不明を表す `-1` は含めない）
+fn box_option(row: &BoxRow) -> Value {
+    let mut option = Map::new();
+    if let Some(source) = &row.source {
+        option.insert("source".to_string(), Value::String(source.clone()));
+    }
+    for (key, flag) in [
+        ("occluded", row.is_occluded),
+        ("truncated", row.is_truncated),
+        ("group_of", row.is_group_of),
+        ("depiction", row.is_depiction),
+        ("inside", row.is_inside),
+    ] {
+        if let Some(flag @ (0 | 1)) = flag {
+            option.insert(key.to_string(), Value::Bool(flag == 1));
+        }
+    }
+    Value::Object(option)
+}
+
+/// マスク画像を読み込み、画像と同じサイズのRLEにする
+///
+/// Open Imagesのマスクは画像と解像度が異なる場合があるため、最近傍補間で拡大・縮小する。
+fn read_mask(path: &Path, width: u32, height: u32) -> Result<Rle, String> {
+    let mask = image::open(path)
+        .map_err(|e| format!("Failed to read mask: {e}"))?
+        .to_luma8();
+    let mask = if mask.dimensions() == (width, height) {
+        mask
+    } else {
+        imageops::resize(&mask, width, height, FilterType::Nearest)
+    };
+    let pixels: Vec<u8> = mask.pixels().map(|p| u8::from(p.0[0] > 0)).collect();
+    Rle::encode(&pixels, height, width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::test_util::{temp_dir, write, write_image};
+    use crate::models::CocoId;
+
+    #[test]
+    fn converts_normalized_boxes_and_flags() {
+        let dir = temp_dir("open-images-bbox");
+        write(
+            &dir.join("csv/validation-annotations-bbox.csv"),
+            "ImageID,Source,LabelName,Confidence,XMin,XMax,YMin,YMax,\
+             IsOccluded,IsTruncated,IsGroupOf,IsDepiction,IsInside\n\
+             img1,xclick,/m/01g317,1,0.25,0.75,0.5,1.0,1,0,0,-1,0\n\
+             img1,xclick,/m/0199g,1,0,1,0,0.5,0,0,1,0,0\n\
+             img1,xclick,/m/01g317,1,zero,1,0,1,0,0,0,0,0\n\
+             absent,xclick,/m/0k4j,1,0,1,0,1,0,0,0,0,0\n\
+             absent,xclick,/m/0k4j,1,0,1,0,1,0,0,0,0,0\n",
+        );
+        write(
+            &dir.join("csv/oidv7-class-descriptions.csv"),
+            "LabelName,DisplayName\n/m/01g317,Person\n/m/0199g,\"Bicycle, road\"\n",
+        );
+        write_image(&dir.join("images/img1.jpg"), 200, 100);
+
+        let result = import(&OpenImagesSources {
+            bbox_csv: Some(dir.join("csv/validation-annotations-bbox.csv")),
+            segmentation_csv: None,
+            mask_folder: None,
+            class_descriptions_csv: None,
+            image_folder: dir.join("images"),
+        })
+        .unwrap();
+        let data = &result.coco_data;
+
+        assert_eq!(data.images.len(), 1);
+        assert_eq!(data.images[0].file_name, "img1.jpg");
+
+        // カテゴリIDは初出順、名前はラベル名のCSVの表示名
+        let names: Vec<_> = data.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Person", "Bicycle, road"]);
+        assert_eq!(data.categories[0].extra["label_name"], "/m/01g317");
+
+        assert_eq!(data.annotations.len(), 2);
+        let person = &data.annotations[0];
+        assert_eq!(person.bbox, [50.0, 50.0, 100.0, 50.0]);
+        assert_eq!(person.area, 5000.0);
+        assert_eq!(person.iscrowd, 0);
+        // 不明を表す -1 のフラグは含めない
+        assert_eq!(
+            person.option,
+            Some(serde_json::json!({
+                "source": "xclick",
+                "occluded": true,
+                "truncated": false,
+                "group_of": false,
+                "inside": false
+            }))
+        );
+        assert_eq!(data.annotations[1].iscrowd, 1);
+        assert_eq!(data.annotations[1].category_id, CocoId::from(2));
+
+        let issues: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| (i.line, i.message.as_str()))
+            .collect();
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].0, Some(4));
+        assert!(issues[0].1.starts_with("Invalid row"));
+        assert_eq!(
+            issues[1],
+            (None, "1 images were not found; 2 annotations were skipped")
+        );
+    }
+
+    #[test]
+    fn reads_masks_scaled_to_image_size() {
+        let dir = temp_dir("open-images-masks");
+        write(
+            &dir.join("validation-annotations-object-segmentation.csv"),
+            "MaskPath,ImageID,LabelName,BoxID,BoxXMin,BoxXMax,BoxYMin,BoxYMax,PredictedIoU,Clicks\n\
+             masks/img1_a.png,img1,/m/01g317,b1,0,1,0,1,0.87,\n\
+             masks/img1_empty.png,img1,/m/01g317,b2,0,1,0,1,0.5,\n\
+             masks/missing.png,img1,/m/01g317,b3,0,1,0,1,0.5,\n",
+        );
+        // 画像（8x4）の半分の解像度のマスクで、右下の2x1ピクセルが前景
+        let mut mask = image::GrayImage::new(4, 2);
+        mask.put_pixel(2, 1, image::Luma([255]));
+        mask.put_pixel(3, 1, image::Luma([255]));
+        fs::create_dir_all(dir.join("masks")).unwrap();
+        mask.save(dir.join("masks/img1_a.png")).unwrap();
+        image::GrayImage::new(4, 2)
+            .save(dir.join("masks/img1_empty.png"))
+            .unwrap();
+        write_image(&dir.join("images/img1.png"), 8, 4);
+
+        let result = import(&OpenImagesSources {
+            bbox_csv: None,
+            segmentation_csv: Some(dir.join("validation-annotations-object-segmentation.csv")),
+            mask_folder: None,
+            class_descriptions_csv: None,
+            image_folder: dir.join("images"),
+        })
+        .unwrap();
+        let data = &result.coco_data;
+
+        assert_eq!(data.annotations.len(), 1);
+        let annotation = &data.annotations[0];
+        assert_eq!(annotation.bbox, [4.0, 2.0, 4.0, 2.0]);
+        assert_eq!(annotation.area, 8.0);
+        assert!(matches!(
+            annotation.segmentation,
+            Some(Segmentation::CompressedRle(_))
+        ));
+        assert_eq!(
+            annotation.option,
+            Some(serde_json::json!({"box_id": "b1", "predicted_iou": 0.87}))
+        );
+
+        let messages: Vec<_> = result.issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0], "Mask is empty");
+        assert!(messages[1].starts_with("Failed to read mask"));
+        assert_eq!(
+            messages[2],
+            "class-descriptions.csv was not found; label IDs are used as category names"
+        );
+        assert_eq!(data.categories[0].name, "/m/01g317");
+    }
+
+    #[test]
+    fn requires_a_csv() {
+        let error = import(&OpenImagesSources {
+            bbox_csv: None,
+            segmentation_csv: None,
+            mask_folder: None,
+            class_descriptions_csv: None,
+            image_folder: PathBuf::from("."),
+        })
+        .unwrap_err();
+        assert!(matches!(error, CoavError::InvalidInput { .. }));
+    }
+}
@@ -0,0 +1,454 @@
+use super::{ImportResult, Issues};
+use crate::error::{CoavError, CoavResult};
+use crate::models::segmentation::Rle;
+use crate::models::{
+    COCOAnnotation, COCOCategory, COCOData, COCOImage, COCOInfo, COCOLicense, CocoId, Segmentation,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// パノプティックセグメンテーションのJSON（`panoptic_val2017.json` や予測結果）
+#[derive(Deserialize)]
+struct PanopticFile {
+    #[serde(default)]
+    info: Option<COCOInfo>,
+    #[serde(default)]
+    licenses: Option<Vec<COCOLicense>>,
+    /// 予測結果のJSONには含まれない場合がある
+    #[serde(default)]
+    images: Vec<COCOImage>,
+    annotations: Vec<PanopticAnnotation>,
+    #[serde(default)]
+    categories: Vec<COCOCategory>,
+}
+
+/// 画像1枚分のセグメント
+#[derive(Deserialize)]
+struct PanopticAnnotation {
+    image_id: CocoId,
+    /// セグメントIDをRGBで表したPNGのファイル名
+    file_name: String,
+    segments_info: Vec<SegmentInfo>,
+}
+
+#[derive(Deserialize)]
+struct SegmentInfo {
+    /// `R + 256 * G + 256^2 * B`
+    id: u32,
+    category_id: CocoId,
+    #[serde(default)]
+    area: Option<f64>,
+    #[serde(default)]
+    bbox: Option<Vec<f64>>,
+    #[serde(default)]
+    iscrowd: i32,
+    #[serde(default)]
+    score: Option<f64>,
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+/// COCOパノプティック形式（JSONとセグメントIDのPNG）を読み込む
+///
+/// 各セグメントをPNGから切り出し、圧縮RLEの `segmentation` と、マスクから求めた `area`・`bbox` を持つ
+/// アノテーションに変換する。元のセグメントIDは `extra.segment_id` に、PNGのファイル名は画像の
+/// `extra.panoptic_file_name` に保存する。カテゴリの `isthing` はそのまま保持する。
+/// `png_folder` を省略した場合は、JSONと同じ名前のフォルダ（無ければJSONのフォルダ）からPNGを探す。
+pub fn import(path: &Path, png_folder: Option<&Path>) -> CoavResult<ImportResult> {
+    let file = File::open(path).map_err(|e| CoavError::io(path, "Failed to read file", e))?;
+    let panoptic: PanopticFile = serde_path_to_error::deserialize(
+        &mut serde_json::Deserializer::from_reader(BufReader::new(file)),
+    )
+    .map_err(|e| CoavError::json(path, e))?;
+
+    let json_dir = path.parent().unwrap_or(Path::new("."));
+    let png_folder = match png_folder {
+        Some(folder) => folder.to_path_buf(),
+        None => {
+            let folder = path.with_extension("");
+            if folder.is_dir() {
+                folder
+            } else {
+                json_dir.to_path_buf()
+            }
+        }
+    };
+
+    let mut issues = Issues::default();
+    let mut coco_data = COCOData {
+        info: panoptic.info,
+        images: panoptic.images,
+        categories: panoptic.categories,
+        licenses: panoptic.licenses,
+        ..Default::default()
+    };
+    let mut image_positions: HashMap<CocoId, usize> = coco_data
+        .images
+        .iter()
+        .enumerate()
+        .map(|(i, image)| (image.id.clone(), i))
+        .collect();
+
+    for annotation in panoptic.annotations {
+        let png_path = png_folder.join(&annotation.file_name);
+        let segment_ids: Vec<u32> = annotation.segments_info.iter().map(|s| s.id).collect();
+        let decoded = decode_segments(&png_path, &segment_ids);
+        let (png_size, mut rles) = match decoded {
+            Ok(decoded) => {
+                if decoded.unknown_pixels > 0 {
+                    issues.push(
+                        &png_path,
+                        None,
+                        format!(
+                            "{} pixels belong to segment ids not listed in segments_info",
+                            decoded.unknown_pixels
+                        ),
+                    );
+                }
+                (Some((decoded.width, decoded.height)), decoded.rles)
+            }
+            Err(message) => {
+                issues.push(&png_path, None, message);
+                (None, HashMap::new())
+            }
+        };
+
+        let position = match image_positions.get(&annotation.image_id) {
+            Some(&position) => position,
+            None => {
+                // 予測結果のJSONには画像が無いため、PNGから作成する
+                let (width, height) = png_size.unwrap_or((0, 0));
+                coco_data.images.push(COCOImage {
+                    id: annotation.image_id.clone(),
+                    width: width as i32,
+                    height: height as i32,
+                    file_name: Path::new(&annotation.file_name)
+                        .with_extension("jpg")
+                        .to_string_lossy()
+                        .into_owned(),
+                    ..Default::default()
+                });
+                image_positions.insert(annotation.image_id.clone(), coco_data.images.len() - 1);
+                coco_data.images.len() - 1
+            }
+        };
+        let image = &mut coco_data.images[position];
+        if let Some((width, height)) = png_size {
+            if (width as i32, height as i32) != (image.width, image.height) {
+                issues.push(
+                    &png_path,
+                    None,
+                    format!(
+                        "PNG size {width}x{height} does not match image {} ({}x{})",
+                        image.id, image.width, image.height
+                    ),
+                );
+            }
+        }
+        image.extra.insert(
+            "panoptic_file_name".to_string(),
+            Value::String(annotation.file_name.clone()),
+        );
+
+        for segment in annotation.segments_info {
+            let mut extra = segment.extra;
+            extra.insert("segment_id".to_string(), Value::from(segment.id));
+            let rle = rles.remove(&segment.id).filter(|rle| rle.area() > 0);
+            let (segmentation, area, bbox) = match rle {
+                Some(rle) => {
+                    let bbox = rle.bbox().map_or_else(|| vec![0.0; 4], |b| b.to_vec());
+                    let area = rle.area() as f64;
+                    (
+                        Some(Segmentation::CompressedRle(rle.compress())),
+                        area,
+                        bbox,
+                    )
+                }
+                None => {
+                    if png_size.is_some() {
+                        issues.push(
+                            &png_path,
+                            None,
+                            format!("Segment {} has no pixels in the PNG", segment.id),
+                        );
+                    }
+                    (
+                        None,
+                        segment.area.unwrap_or(0.0),
+                        segment.bbox.unwrap_or_else(|| vec![0.0; 4]),
+                    )
+                }
+            };
+            coco_data.annotations.push(COCOAnnotation {
+                id: (coco_data.annotations.len() as i64 + 1).into(),
+                image_id: annotation.image_id.clone(),
+                category_id: segment.category_id,
+                segmentation,
+                area,
+                bbox,
+                iscrowd: segment.iscrowd,
+                score: segment.score,
+                extra,
+                ..Default::default()
+            });
+        }
+    }
+
+    Ok(ImportResult {
+        coco_data,
+        image_root: json_dir.display().to_string(),
+        issues: issues.into_vec(),
+    })
+}
+
+/// PNGから切り出したセグメント
+struct DecodedSegments {
+    width: u32,
+    height: u32,
+    rles: HashMap<u32, Rle>,
+    /// `segments_info` に無いIDの画素数（0のvoidは除く）
+    unknown_pixels: u64,
+}
+
+/// セグメントIDのPNGを読み込み、セグメントごとのRLEを作成する
+///
+/// セグメントごとのマスクを展開せずに済むよう、画素を列優先で1回だけ走査してRLEを直接組み立てる。
+fn decode_segments(png_path: &Path, segment_ids: &[u32]) -> Result<DecodedSegments, String> {
+    let png = image::open(png_path)
+        .map_err(|e| format!("Failed to read PNG: {e}"))?
+        .to_rgb8();
+    let (width, height) = png.dimensions();
+    let total = width as usize * height as usize;
+
+    let mut builders: HashMap<u32, RleBuilder> = segment_ids
+        .iter()
+        .map(|&id| (id, RleBuilder::default()))
+        .collect();
+    let mut unknown_pixels = 0;
+    for x in 0..width {
+        for y in 0..height {
+            let [r, g, b] = png.get_pixel(x, y).0;
+            let id = r as u32 + 256 * g as u32 + 256 * 256 * b as u32;
+            if id == 0 {
+                continue;
+            }
+            match builders.get_mut(&id) {
+                Some(builder) => builder.push(x as usize * height as usize + y as usize),
+                None => unknown_pixels += 1,
+            }
+        }
+    }
+
+    let rles = builders
+        .into_iter()
+        .map(|(id, builder)| (id, builder.finish(total, height, width)))
+        .collect();
+    Ok(DecodedSegments {
+        width,
+        height,
+        rles,
+        unknown_pixels,
+    })
+}
+
+/// 列優先で昇順に渡される前景の画素からRLEを組み立てる
+#[derive(Default)]
+struct RleBuilder {
+    counts: Vec<u32>,
+    /// 直前の前景の連続の終端（この位置は含まない）
+    end: usize,
+}
+
+impl RleBuilder {
+    fn push(&mut self, position: usize) {
+        if !self.counts.is_empty() && self.end == position {
+            *self.counts.last_mut().expect("counts is not empty") += 1;
+        } else {
+            self.counts.push((position - self.end) as u32);
+            self.counts.push(1);
+        }
+        self.end = position + 1;
+    }
+
+    fn finish(mut self, total: usize, height: u32, width: u32) -> Rle {
+        if self.end < total || self.counts.is_empty() {
+            self.counts.push((total - self.end) as u32);
+        }
+        Rle {
+            size: [height, width],
+            counts: self.counts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::test_util::{temp_dir, write};
+
+    /// 行優先のマスクから列優先の順に画素を渡してRLEを組み立てる
+    fn build(mask: &[u8], height: u32, width: u32) -> Rle {
+        let mut builder = RleBuilder::default();
+        for x in 0..width as usize {
+            for y in 0..height as usize {
+                if mask[y * width as usize + x] != 0 {
+                    builder.push(x * height as usize + y);
+                }
+            }
+        }
+        builder.finish(mask.len(), height, width)
+    }
+
+    #[test]
+    fn builder_matches_rle_encode() {
+        let (height, width) = (5, 7);
+        let total = (height * width) as usize;
+        let mut masks = vec![
+            vec![0u8; total],
+            vec![1u8; total],
+            (0..total).map(|i| u8::from(i == 0)).collect(),
+            (0..total).map(|i| u8::from(i == total - 1)).collect(),
+        ];
+        // 疑似乱数のマスク
+        let mut state = 12345u32;
+        for _ in 0..50 {
+            masks.push(
+                (0..total)
+                    .map(|_| {
+                        state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                        u8::from(state >> 16 & 3 == 0)
+                    })
+                    .collect(),
+            );
+        }
+        for mask in masks {
+            let expected = Rle::encode(&mask, height, width).unwrap();
+            let built = build(&mask, height, width);
+            assert_eq!(built, expected, "{mask:?}");
+            assert_eq!(built.decode().unwrap(), mask);
+        }
+    }
+
+    /// セグメントIDを `R + 256 * G + 256^2 * B` で表したPNGを書き込む
+    fn write_png(path: &Path, width: u32, height: u32, segment: impl Fn(u32, u32) -> u32) {
+        let png = image::RgbImage::from_fn(width, height, |x, y| {
+            let id = segment(x, y);
+            image::Rgb([(id % 256) as u8, (id / 256 % 256) as u8, (id / 65536) as u8])
+        });
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        png.save(path).unwrap();
+    }
+
+    #[test]
+    fn imports_segments_from_png() {
+        let dir = temp_dir("panoptic");
+        // 左半分が things（ID 300）、右下がstuff（ID 70000）、1画素だけ未定義のID
+        write_png(&dir.join("panoptic_val/1.png"), 4, 3, |x, y| match (x, y) {
+            (0..=1, _) => 300,
+            (3, 0) => 5,
+            (2..=3, 2) => 70000,
+            _ => 0,
+        });
+        write_png(&dir.join("panoptic_val/2.png"), 2, 2, |_, _| 0);
+        write(
+            &dir.join("panoptic_val.json"),
+            r#"{
+                "images": [{"id": 1, "width": 4, "height": 3, "file_name": "1.jpg"}],
+                "annotations": [
+                    {"image_id": 1, "file_name": "1.png", "segments_info": [
+                        {"id": 300, "category_id": 1, "area": 1, "bbox": [0, 0, 1, 1],
+                         "iscrowd": 0, "note": "a"},
+                        {"id": 70000, "category_id": 184, "iscrowd": 0},
+                        {"id": 9, "category_id": 1, "area": 5, "bbox": [1, 1, 2, 2], "iscrowd": 1}
+                    ]},
+                    {"image_id": "pred-2", "file_name": "2.png", "segments_info": []},
+                    {"image_id": 3, "file_name": "missing.png", "segments_info": [
+                        {"id": 1, "category_id": 1, "area": 4, "bbox": [0, 0, 2, 2]}
+                    ]}
+                ],
+                "categories": [
+                    {"id": 1, "name": "person", "supercategory": "person", "isthing": 1},
+                    {"id": 184, "name": "tree-merged", "supercategory": "plant", "isthing": 0}
+                ]
+            }"#,
+        );
+
+        let result = import(&dir.join("panoptic_val.json"), None).unwrap();
+        let data = &result.coco_data;
+        assert_eq!(result.image_root, dir.display().to_string());
+        assert_eq!(data.categories[0].isthing, Some(1));
+        assert_eq!(data.categories[1].isthing, Some(0));
+
+        // マスクから面積・bboxを求める（JSONの値より優先）
+        let person = &data.annotations[0];
+        assert_eq!(person.area, 6.0);
+        assert_eq!(person.bbox, [0.0, 0.0, 2.0, 3.0]);
+        assert_eq!(person.extra["segment_id"], 300);
+        assert_eq!(person.extra["note"], "a");
+        let Some(Segmentation::CompressedRle(rle)) = &person.segmentation else {
+            panic!("segment was not converted to RLE");
+        };
+        assert_eq!(rle.size, [3, 4]);
+        assert_eq!(rle.decompress().unwrap().counts, [0, 6, 6]);
+
+        let stuff = &data.annotations[1];
+        assert_eq!(stuff.area, 2.0);
+        assert_eq!(stuff.bbox, [2.0, 2.0, 2.0, 1.0]);
+
+        // PNGに画素の無いセグメントはJSONの値を使う
+        let empty = &data.annotations[2];
+        assert!(empty.segmentation.is_none());
+        assert_eq!((empty.area, empty.iscrowd), (5.0, 1));
+
+        // 画像の無い予測結果はPNGから画像を作成する
+        let image = &data.images[1];
+        assert_eq!(image.id, CocoId::from("pred-2"));
+        assert_eq!(
+            (image.width, image.height, image.file_name.as_str()),
+            (2, 2, "2.jpg")
+        );
+        assert_eq!(image.extra["panoptic_file_name"], "2.png");
+        assert_eq!((data.images[2].width, data.images[2].height), (0, 0));
+        assert_eq!(data.annotations[3].area, 4.0);
+
+        let messages: Vec<_> = result.issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(
+            messages[0],
+            "1 pixels belong to segment ids not listed in segments_info"
+        );
+        assert_eq!(messages[1], "Segment 9 has no pixels in the PNG");
+        assert!(messages[2].starts_with("Failed to read PNG"));
+    }
+
+    #[test]
+    fn reports_png_size_mismatch() {
+        let dir = temp_dir("panoptic-size");
+        write_png(&dir.join("pngs/1.png"), 3, 3, |_, _| 1);
+        write(
+            &dir.join("panoptic.json"),
+            r#"{
+                "images": [{"id": 1, "width": 4, "height": 3, "file_name": "1.jpg"}],
+                "annotations": [{"image_id": 1, "file_name": "1.png",
+                                 "segments_info": [{"id": 1, "category_id": 1}]}]
+            }"#,
+        );
+        let result = import(&dir.join("panoptic.json"), Some(&dir.join("pngs"))).unwrap();
+        assert_eq!(result.coco_data.annotations[0].area, 9.0);
+        assert_eq!(
+            result.issues[0].message,
+            "PNG size 3x3 does not match image 1 (4x3)"
+        );
+
+        write(
+            &dir.join("broken.json"),
+            r#"{"annotations": [{"image_id": 1}]}"#,
+        );
+        let error = import(&dir.join("broken.json"), None).unwrap_err();
+        assert!(error.to_string().contains("file_name"), "{error}");
+    }
+}
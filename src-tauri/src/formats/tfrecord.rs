@@ -0,0 +1,1146 @@
+use super::{
+    find_image_file, image_size, image_size_from_bytes, sanitize_file_name, ExportReport,
+    ImportResult, Issues,
+};
+use crate::error::{CoavError, CoavResult};
+use crate::export::write_atomic;
+use crate::models::segmentation::Rle;
+use crate::models::{COCOAnnotation, COCOCategory, COCOData, COCOImage, CocoId, Segmentation};
+use flate2::read::MultiGzDecoder;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+/// 1レコードの最大サイズ（壊れた長さで巨大なメモリを確保しないため）
+const MAX_RECORD_SIZE: u64 = 1 << 30;
+
+/// `tf.train.Feature` の値
+#[derive(Debug, Clone, PartialEq)]
+enum Feature {
+    Bytes(Vec<Vec<u8>>),
+    Float(Vec<f32>),
+    Int64(Vec<i64>),
+}
+
+/// `tf.train.Example`（特徴量の名前と値）
+type Example = BTreeMap<String, Feature>;
+
+/// カテゴリの決め方（ラベル番号があればそれを、無ければクラス名を使う）
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum CategoryKey {
+    Label(i64),
+    Text(String),
+}
+
+/// TFRecord（TensorFlow Object Detection APIの `tf.train.Example`）を読み込む
+///
+/// `path` にはTFRecordファイル、またはシャード（`train.tfrecord-00000-of-00010` など）を含むフォルダを指定する。
+/// 矩形は正規化された `image/object/bbox/*` から、カテゴリは `image/object/class/label`
+/// （無ければ `image/object/class/text`）から作成する。レコードに埋め込まれた画像（`image/encoded`）は
+/// `image_dir` に書き出し、`image_root` をそのフォルダとする。GZIP圧縮されたファイルにも対応する。
+pub fn import(path: &Path, image_dir: &Path) -> CoavResult<ImportResult> {
+    let (record_paths, root) = if path.is_file() {
+        let root = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        (vec![path.to_path_buf()], root)
+    } else if path.is_dir() {
+        (
+            super::find_files(path, is_tfrecord_file),
+            path.to_path_buf(),
+        )
+    } else {
+        return Err(CoavError::not_found(
+            path,
+            format!("File not found: {}", path.display()),
+        ));
+    };
+    if record_paths.is_empty() {
+        return Err(CoavError::InvalidInput {
+            message: format!("No TFRecord files found in {}", path.display()),
+        });
+    }
+
+    let mut importer = Importer {
+        image_dir,
+        root: &root,
+        coco_data: COCOData::default(),
+        category_keys: Vec::new(),
+        label_names: BTreeMap::new(),
+        texts: BTreeSet::new(),
+        embedded: 0,
+        issues: Issues::default(),
+    };
+    for record_path in &record_paths {
+        let mut reader = match open_records(record_path) {
+            Ok(reader) => reader,
+            Err(e) => {
+                importer
+                    .issues
+                    .push(record_path, None, format!("Failed to open file: {e}"));
+                continue;
+            }
+        };
+        for index in 1.. {
+            let record = match reader.next_record() {
+                Ok(Some(record)) => record,
+                Ok(None) => break,
+                Err(message) => {
+                    importer.issues.push(
+                        record_path,
+                        None,
+                        format!("Record {index}: {message}; the rest of the file was skipped"),
+                    );
+                    break;
+                }
+            };
+            match decode_example(&record) {
+                Ok(example) => importer.add_example(record_path, index, &example)?,
+                Err(message) => importer.issues.push(
+                    record_path,
+                    None,
+                    format!("Record {index}: invalid tf.train.Example: {message}"),
+                ),
+            }
+        }
+    }
+    Ok(importer.finish())
+}
+
+/// 読み込み中の状態
+struct Importer<'a> {
+    image_dir: &'a Path,
+    /// 画像が埋め込まれていない場合に画像を探すフォルダ
+    root: &'a Path,
+    coco_data: COCOData,
+    /// アノテーションごとのカテゴリ（`category_id` は最後に決める）
+    category_keys: Vec<CategoryKey>,
+    /// ラベル番号とクラス名
+    label_names: BTreeMap<i64, Option<String>>,
+    /// ラベル番号が無いクラス名
+    texts: BTreeSet<String>,
+    /// 書き出した埋め込み画像の数
+    embedded: usize,
+    issues: Issues,
+}
+
+impl Importer<'_> {
+    fn add_example(
+        &mut self,
+        record_path: &Path,
+        index: usize,
+        example: &Example,
+    ) -> CoavResult<()> {
+        let image_id = self.coco_data.images.len() as i64 + 1;
+        let source_id = first_string(example, "image/source_id");
+        let encoded = bytes_list(example, "image/encoded").first();
+        let format = first_string(example, "image/format");
+        let file_name = first_string(example, "image/filename")
+            .and_then(|name| sanitize_file_name(&name))
+            .or_else(|| source_id.as_deref().and_then(sanitize_file_name))
+            .unwrap_or_else(|| format!("{image_id:06}.{}", extension_for(format.as_deref())));
+
+        let mut size = match (
+            int64_list(example, "image/width"),
+            int64_list(example, "image/height"),
+        ) {
+            (&[width, ..], &[height, ..]) if width > 0 && height > 0 => {
+                Some((width as u32, height as u32))
+            }
+            _ => None,
+        };
+        if let Some(encoded) = encoded {
+            if size.is_none() {
+                size = image_size_from_bytes(encoded)
+                    .map_err(|message| {
+                        self.issues
+                            .push(record_path, None, format!("Record {index}: {message}"))
+                    })
+                    .ok();
+            }
+            let image_path = self.image_dir.join(&file_name);
+            if let Some(parent) = image_path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| CoavError::io(parent, "Failed to create folder", e))?;
+            }
+            fs::write(&image_path, encoded)
+                .map_err(|e| CoavError::io(&image_path, "Failed to write image", e))?;
+            self.embedded += 1;
+        } else if size.is_none() {
+            size = find_image_file(self.root, &file_name).and_then(|p| image_size(&p).ok());
+        }
+        let (width, height) = size.unwrap_or_else(|| {
+            self.issues.push(
+                record_path,
+                None,
+                format!("Record {index}: image size is unknown ({file_name})"),
+            );
+            (0, 0)
+        });
+
+        let mut extra = HashMap::new();
+        if let Some(source_id) = source_id {
+            extra.insert("source_id".to_string(), Value::String(source_id));
+        }
+        self.coco_data.images.push(COCOImage {
+            id: image_id.into(),
+            width: width as i32,
+            height: height as i32,
+            file_name,
+            extra,
+            ..Default::default()
+        });
+
+        let xmins = float_list(example, "image/object/bbox/xmin");
+        let ymins = float_list(example, "image/object/bbox/ymin");
+        let xmaxs = float_list(example, "image/object/bbox/xmax");
+        let ymaxs = float_list(example, "image/object/bbox/ymax");
+        let count = xmins.len();
+        if [ymins.len(), xmaxs.len(), ymaxs.len()]
+            .iter()
+            .any(|&n| n != count)
+        {
+            self.issues.push(
+                record_path,
+                None,
+                format!("Record {index}: image/object/bbox/* have different lengths"),
+            );
+        }
+        let count = count.min(ymins.len()).min(xmaxs.len()).min(ymaxs.len());
+        if count > 0 && (width == 0 || height == 0) {
+            self.issues.push(
+                record_path,
+                None,
+                format!("Record {index}: {count} objects were not imported because the image size is unknown"),
+            );
+            return Ok(());
+        }
+
+        let labels = int64_list(example, "image/object/class/label");
+        let texts = bytes_list(example, "image/object/class/text");
+        let crowds = int64_list(example, "image/object/is_crowd");
+        let difficults = int64_list(example, "image/object/difficult");
+        let areas = float_list(example, "image/object/area");
+        let masks = bytes_list(example, "image/object/mask");
+        let (w, h) = (width as f64, height as f64);
+
+        for i in 0..count {
+            let text = texts
+                .get(i)
+                .map(|t| String::from_utf8_lossy(t).into_owned());
+            let key = match (labels.get(i), text) {
+                (Some(&label), text) => {
+                    let name = self.label_names.entry(label).or_default();
+                    if name.is_none() {
+                        *name = text;
+                    }
+                    CategoryKey::Label(label)
+                }
+                (None, Some(text)) => {
+                    self.texts.insert(text.clone());
+                    CategoryKey::Text(text)
+                }
+                (None, None) => {
+                    self.issues.push(
+                        record_path,
+                        None,
+                        format!("Record {index}: object {} has no class", i + 1),
+                    );
+                    continue;
+                }
+            };
+
+            let (x1, y1) = (xmins[i] as f64 * w, ymins[i] as f64 * h);
+            let (bw, bh) = (
+                (xmaxs[i] - xmins[i]) as f64 * w,
+                (ymaxs[i] - ymins[i]) as f64 * h,
+            );
+            let segmentation = match masks.get(i).map(|png| read_mask(png, width, height)) {
+                Some(Ok(rle)) => Some(Segmentation::CompressedRle(rle.compress())),
+                Some(Err(message)) => {
+                    self.issues.push(
+                        record_path,
+                        None,
+                        format!("Record {index}: object {}: {message}", i + 1),
+                    );
+                    None
+                }
+                None => None,
+            };
+            let mut extra = HashMap::new();
+            if let Some(&difficult) = difficults.get(i) {
+                extra.insert("difficult".to_string(), Value::Bool(difficult != 0));
+            }
+            self.coco_data.annotations.push(COCOAnnotation {
+                id: (self.coco_data.annotations.len() as i64 + 1).into(),
+                image_id: image_id.into(),
+                area: areas
+                    .get(i)
+                    .map(|&area| area as f64)
+                    .filter(|&area| area > 0.0)
+                    .unwrap_or(bw * bh),
+                bbox: vec![x1, y1, bw, bh],
+                iscrowd: crowds.get(i).is_some_and(|&c| c != 0) as i32,
+                segmentation,
+                extra,
+                ..Default::default()
+            });
+            self.category_keys.push(key);
+        }
+        Ok(())
+    }
+
+    /// カテゴリIDを決めて結果を返す
+    ///
+    /// ラベル番号はそのままカテゴリIDとし、ラベル番号の無いクラス名にはその後ろの番号を名前順に振る。
+    fn finish(mut self) -> ImportResult {
+        let mut categories: Vec<COCOCategory> = self
+            .label_names
+            .iter()
+            .map(|(&label, name)| COCOCategory {
+                id: CocoId::from(label),
+                name: name.clone().unwrap_or_else(|| label.to_string()),
+                ..Default::default()
+            })
+            .collect();
+        let mut text_ids: HashMap<String, CocoId> = categories
+            .iter()
+            .map(|c| (c.name.clone(), c.id.clone()))
+            .collect();
+        let mut next_id = self.label_names.keys().max().map_or(0, |&label| label) + 1;
+        for text in &self.texts {
+            if !text_ids.contains_key(text) {
+                text_ids.insert(text.clone(), next_id.into());
+                categories.push(COCOCategory {
+                    id: next_id.into(),
+                    name: text.clone(),
+                    ..Default::default()
+                });
+                next_id += 1;
+            }
+        }
+
+        for (annotation, key) in self
+            .coco_data
+            .annotations
+            .iter_mut()
+            .zip(&self.category_keys)
+        {
+            annotation.category_id = match key {
+                CategoryKey::Label(label) => CocoId::from(*label),
+                CategoryKey::Text(text) => text_ids[text].clone(),
+            };
+        }
+        self.coco_data.categories = categories;
+
+        let image_root = if self.embedded > 0 {
+            if self.embedded < self.coco_data.images.len() {
+                self.issues.push(
+                    self.root,
+                    None,
+                    format!(
+                        "{} of {} images are not embedded in the records",
+                        self.coco_data.images.len() - self.embedded,
+                        self.coco_data.images.len()
+                    ),
+                );
+            }
+            self.image_dir
+        } else {
+            self.root
+        };
+        ImportResult {
+            coco_data: self.coco_data,
+            image_root: image_root.display().to_string(),
+            issues: self.issues.into_vec(),
+        }
+    }
+}
+
+/// TFRecord（TensorFlow Object Detection APIの `tf.train.Example`）として書き出す
+///
+/// 画像1枚を1レコードとし、`image/object/class/label` にはカテゴリIDをそのまま使う
+/// （文字列のIDには、整数のIDの最大値より後ろの番号をカテゴリの順に振る）。
+/// `image_root` を指定した場合は画像ファイルを `image/encoded` に埋め込む。
+pub fn export(
+    coco_data: &COCOData,
+    output_path: &Path,
+    image_root: Option<&Path>,
+) -> CoavResult<ExportReport> {
+    let mut next_label = coco_data
+        .categories
+        .iter()
+        .filter_map(|c| c.id.as_int())
+        .max()
+        .unwrap_or(0);
+    let categories: HashMap<&CocoId, (&str, i64)> = coco_data
+        .categories
+        .iter()
+        .map(|c| {
+            let label = c.id.as_int().unwrap_or_else(|| {
+                next_label += 1;
+                next_label
+            });
+            (&c.id, (c.name.as_str(), label))
+        })
+        .collect();
+    let mut annotations_by_image: HashMap<&CocoId, Vec<&COCOAnnotation>> = HashMap::new();
+    for annotation in &coco_data.annotations {
+        annotations_by_image
+            .entry(&annotation.image_id)
+            .or_default()
+            .push(annotation);
+    }
+
+    let mut issues = Issues::default();
+    let mut report = ExportReport {
+        output_dir: output_path.display().to_string(),
+        files: 1,
+        annotations: 0,
+        skipped_crowd: 0,
+        issues: Vec::new(),
+    };
+
+    write_atomic(output_path, |writer| {
+        for image in &coco_data.images {
+            let annotations = annotations_by_image.remove(&image.id).unwrap_or_default();
+            let mut example = Example::new();
+            example.insert(
+                "image/height".into(),
+                Feature::Int64(vec![image.height as i64]),
+            );
+            example.insert(
+                "image/width".into(),
+                Feature::Int64(vec![image.width as i64]),
+            );
+            example.insert("image/filename".into(), bytes_feature(&image.file_name));
+            example.insert(
+                "image/source_id".into(),
+                bytes_feature(&image.id.to_string()),
+            );
+            if let Some(image_root) = image_root {
+                match find_image_file(image_root, &image.file_name) {
+                    Some(image_path) => {
+                        let encoded = fs::read(&image_path).map_err(|e| {
+                            CoavError::io(&image_path, "Failed to read image file", e)
+                        })?;
+                        example.insert("image/encoded".into(), Feature::Bytes(vec![encoded]));
+                        example.insert(
+                            "image/format".into(),
+                            bytes_feature(&format_for(&image_path)),
+                        );
+                    }
+                    None => issues.push(
+                        &image.file_name,
+                        None,
+                        "Image file not found; the record has no image/encoded".to_string(),
+                    ),
+                }
+            }
+
+            let (w, h) = (image.width as f64, image.height as f64);
+            let mut objects = ObjectFeatures::default();
+            for annotation in annotations {
+                let Some(&(name, label)) = categories.get(&annotation.category_id) else {
+                    issues.push(
+                        &image.file_name,
+                        None,
+                        format!(
+                            "Annotation {}: category {} is not defined",
+                            annotation.id, annotation.category_id
+                        ),
+                    );
+                    continue;
+                };
+                let &[x, y, bw, bh] = annotation.bbox.as_slice() else {
+                    issues.push(
+                        &image.file_name,
+                        None,
+                        format!("Annotation {}: invalid bbox", annotation.id),
+                    );
+                    continue;
+                };
+                if w <= 0.0 || h <= 0.0 {
+                    issues.push(
+                        &image.file_name,
+                        None,
+                        format!("Annotation {}: image size is unknown", annotation.id),
+                    );
+                    continue;
+                }
+                objects.xmin.push((x / w) as f32);
+                objects.ymin.push((y / h) as f32);
+                objects.xmax.push(((x + bw) / w) as f32);
+                objects.ymax.push(((y + bh) / h) as f32);
+                objects.text.push(name.as_bytes().to_vec());
+                objects.label.push(label);
+                objects.is_crowd.push((annotation.iscrowd != 0) as i64);
+                objects.difficult.push(
+                    annotation
+                        .extra
+                        .get("difficult")
+                        .is_some_and(|d| d.as_bool() == Some(true) || d.as_i64() == Some(1))
+                        as i64,
+                );
+                objects.area.push(annotation.area as f32);
+                report.annotations += 1;
+            }
+            objects.insert_into(&mut example);
+
+            write_record(writer, &encode_example(&example))
+                .map_err(|e| CoavError::io(output_path, "Failed to write file", e))?;
+        }
+        Ok(())
+    })?;
+
+    for (image_id, annotations) in annotations_by_image {
+        issues.push(
+            output_path,
+            None,
+            format!(
+                "Image {image_id} does not exist; {} annotations were not exported",
+                annotations.len()
+            ),
+        );
+    }
+
+    report.issues = issues.into_vec();
+    Ok(report)
+}
+
+/// 画像1枚分の `image/object/*` の特徴量
+#[derive(Default)]
+struct ObjectFeatures {
+    xmin: Vec<f32>,
+    ymin: Vec<f32>,
+    xmax: Vec<f32>,
+    ymax: Vec<f32>,
+    text: Vec<Vec<u8>>,
+    label: Vec<i64>,
+    is_crowd: Vec<i64>,
+    difficult: Vec<i64>,
+    area: Vec<f32>,
+}
+
+impl ObjectFeatures {
+    fn insert_into(self, example: &mut Example) {
+        let features = [
+            ("image/object/bbox/xmin", Feature::Float(self.xmin)),
+            ("image/object/bbox/ymin", Feature::Float(self.ymin)),
+            ("image/object/bbox/xmax", Feature::Float(self.xmax)),
+            ("image/object/bbox/ymax", Feature::Float(self.ymax)),
+            ("image/object/class/text", Feature::Bytes(self.text)),
+            ("image/object/class/label", Feature::Int64(self.label)),
+            ("image/object/is_crowd", Feature::Int64(self.is_crowd)),
+            ("image/object/difficult", Feature::Int64(self.difficult)),
+            ("image/object/area", Feature::Float(self.area)),
+        ];
+        for (key, feature) in features {
+            example.insert(key.to_string(), feature);
+        }
+    }
+}
+
+fn is_tfrecord_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.to_lowercase())
+        .is_some_and(|n| n.contains(".tfrecord") || n.contains(".record"))
+}
+
+/// `image/format` から拡張子を決める
+fn extension_for(format: Option<&str>) -> String {
+    match format.map(|f| f.to_lowercase()) {
+        Some(f) if f == "jpeg" || f.is_empty() => "jpg".to_string(),
+        Some(f) => f,
+        None => "jpg".to_string(),
+    }
+}
+
+/// 画像ファイルの拡張子から `image/format` の値を決める
+fn format_for(path: &Path) -> String {
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+    {
+        Some(e) if e == "jpg" || e == "jpeg" => "jpeg".to_string(),
+        Some(e) => e,
+        None => "jpeg".to_string(),
+    }
+}
+
+/// インスタンスマスク（`image/object/mask` のPNG）をRLEにする
+fn read_mask(png: &[u8], width: u32, height: u32) -> Result<Rle, String> {
+    let mask = image::load_from_memory(png)
+        .map_err(|e| format!("Failed to read mask: {e}"))?
+        .to_luma8();
+    if mask.dimensions() != (width, height) {
+        return Err(format!(
+            "Mask size {}x{} does not match image size {width}x{height}",
+            mask.width(),
+            mask.height()
+        ));
+    }
+    let pixels: Vec<u8> = mask.pixels().map(|p| u8::from(p.0[0] > 0)).collect();
+    Rle::encode(&pixels, height, width)
+}
+
+fn bytes_feature(value: &str) -> Feature {
+    Feature::Bytes(vec![value.as_bytes().to_vec()])
+}
+
+fn bytes_list<'a>(example: &'a Example, key: &str) -> &'a [Vec<u8>] {
+    match example.get(key) {
+        Some(Feature::Bytes(values)) => values,
+        _ => &[],
+    }
+}
+
+fn float_list<'a>(example: &'a Example, key: &str) -> &'a [f32] {
+    match example.get(key) {
+        Some(Feature::Float(values)) => values,
+        _ => &[],
+    }
+}
+
+fn int64_list<'a>(example: &'a Example, key: &str) -> &'a [i64] {
+    match example.get(key) {
+        Some(Feature::Int64(values)) => values,
+        _ => &[],
+    }
+}
+
+fn first_string(example: &Example, key: &str) -> Option<String> {
+    bytes_list(example, key)
+        .first()
+        .map(|value| String::from_utf8_lossy(value).into_owned())
+        .filter(|value| !value.is_empty())
+}
+
+// ---- TFRecordのファイル形式 ----
+//
+// 各レコードは `長さ(u64) | 長さのCRC(u32) | データ | データのCRC(u32)`（リトルエンディアン）で、
+// CRCはCRC-32Cをマスクしたもの。
+
+/// レコードを順に読み込む
+struct RecordReader {
+    reader: Box<dyn Read>,
+}
+
+/// TFRecordファイルを開く（GZIP圧縮は先頭のマジックナンバーで判定する）
+fn open_records(path: &Path) -> io::Result<RecordReader> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let gzip = reader.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+    let reader: Box<dyn Read> = if gzip {
+        Box::new(MultiGzDecoder::new(reader))
+    } else {
+        Box::new(reader)
+    };
+    Ok(RecordReader { reader })
+}
+
+impl RecordReader {
+    /// 次のレコードを返す（ファイルの終端では `None`）
+    fn next_record(&mut self) -> Result<Option<Vec<u8>>, String> {
+        let mut header = [0u8; 12];
+        if !read_exact_or_eof(&mut self.reader, &mut header)? {
+            return Ok(None);
+        }
+        let (length, length_crc) = header.split_at(8);
+        if masked_crc(length) != u32::from_le_bytes(length_crc.try_into().unwrap()) {
+            return Err("corrupted record length (CRC mismatch)".to_string());
+        }
+        let length = u64::from_le_bytes(length.try_into().unwrap());
+        if length > MAX_RECORD_SIZE {
+            return Err(format!("record is too large ({length} bytes)"));
+        }
+
+        let mut data = vec![0u8; length as usize];
+        let mut data_crc = [0u8; 4];
+        self.reader
+            .read_exact(&mut data)
+            .and_then(|_| self.reader.read_exact(&mut data_crc))
+            .map_err(|e| format!("failed to read record: {e}"))?;
+        if masked_crc(&data) != u32::from_le_bytes(data_crc) {
+            return Err("corrupted record data (CRC mismatch)".to_string());
+        }
+        Ok(Some(data))
+    }
+}
+
+/// `buf` を埋めるまで読む。1バイトも読めずに終端に達した場合は `false`
+fn read_exact_or_eof(reader: &mut impl Read, buf: &mut [u8]) -> Result<bool, String> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err("unexpected end of file".to_string()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(format!("failed to read record: {e}")),
+        }
+    }
+    Ok(true)
+}
+
+fn write_record(writer: &mut impl Write, data: &[u8]) -> io::Result<()> {
+    let length = (data.len() as u64).to_le_bytes();
+    writer.write_all(&length)?;
+    writer.write_all(&masked_crc(&length).to_le_bytes())?;
+    writer.write_all(data)?;
+    writer.write_all(&masked_crc(data).to_le_bytes())
+}
+
+/// CRC-32C（Castagnoli）の表
+const CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0x82f6_3b78
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32c(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &b| {
+        CRC32C_TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// TFRecordのマスク済みCRC
+fn masked_crc(data: &[u8]) -> u32 {
+    crc32c(data).rotate_right(15).wrapping_add(0xa282_ead8)
+}
+
+// ---- tf.train.Example のprotobuf ----
+//
+// Example { Features features = 1; }
+// Features { map<string, Feature> feature = 1; }
+// Feature { oneof { BytesList bytes_list = 1; FloatList float_list = 2; Int64List int64_list = 3; } }
+// BytesList / FloatList / Int64List { repeated ... value = 1; }
+
+const WIRE_VARINT: u8 = 0;
+const WIRE_FIXED64: u8 = 1;
+const WIRE_LEN: u8 = 2;
+const WIRE_FIXED32: u8 = 5;
+
+/// protobufのメッセージを先頭から読む
+struct ProtoReader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> ProtoReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        ProtoReader { buf }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if n > self.buf.len() {
+            return Err("unexpected end of message".to_string());
+        }
+        let (head, rest) = self.buf.split_at(n);
+        self.buf = rest;
+        Ok(head)
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("varint is too long".to_string())
+    }
+
+    /// フィールド番号とワイヤー型
+    fn key(&mut self) -> Result<(u64, u8), String> {
+        let key = self.varint()?;
+        Ok((key >> 3, (key & 0x7) as u8))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], String> {
+        let length = self.varint()?;
+        self.take(usize::try_from(length).map_err(|_| "length is too large".to_string())?)
+    }
+
+    fn fixed32(&mut self) -> Result<[u8; 4], String> {
+        Ok(self.take(4)?.try_into().unwrap())
+    }
+
+    fn skip(&mut self, wire_type: u8) -> Result<(), String> {
+        match wire_type {
+            WIRE_VARINT => self.varint().map(drop),
+            WIRE_FIXED64 => self.take(8).map(drop),
+            WIRE_LEN => self.bytes().map(drop),
+            WIRE_FIXED32 => self.take(4).map(drop),
+            _ => Err(format!("unsupported wire type {wire_type}")),
+        }
+    }
+}
+
+fn decode_example(buf: &[u8]) -> Result<Example, String> {
+    let mut example = Example::new();
+    let mut reader = ProtoReader::new(buf);
+    while !reader.is_empty() {
+        match reader.key()? {
+            (1, WIRE_LEN) => {
+                let mut features = ProtoReader::new(reader.bytes()?);
+                while !features.is_empty() {
+                    match features.key()? {
+                        (1, WIRE_LEN) => {
+                            let (key, feature) = decode_map_entry(features.bytes()?)?;
+                            example.insert(key, feature);
+                        }
+                        (_, wire_type) => features.skip(wire_type)?,
+                    }
+                }
+            }
+            (_, wire_type) => reader.skip(wire_type)?,
+        }
+    }
+    Ok(example)
+}
+
+fn decode_map_entry(buf: &[u8]) -> Result<(String, Feature), String> {
+    let mut reader = ProtoReader::new(buf);
+    let mut key = String::new();
+    let mut feature = Feature::Bytes(Vec::new());
+    while !reader.is_empty() {
+        match reader.key()? {
+            (1, WIRE_LEN) => {
+                key = String::from_utf8(reader.bytes()?.to_vec())
+                    .map_err(|_| "feature name is not UTF-8".to_string())?;
+            }
+            (2, WIRE_LEN) => feature = decode_feature(reader.bytes()?)?,
+            (_, wire_type) => reader.skip(wire_type)?,
+        }
+    }
+    Ok((key, feature))
+}
+
+fn decode_feature(buf: &[u8]) -> Result<Feature, String> {
+    let mut reader = ProtoReader::new(buf);
+    // oneofが1つも無い場合は空の値とする
+    let mut feature = Feature::Bytes(Vec::new());
+    while !reader.is_empty() {
+        let (field, wire_type) = reader.key()?;
+        if wire_type != WIRE_LEN || !(1..=3).contains(&field) {
+            reader.skip(wire_type)?;
+            continue;
+        }
+        let mut list = ProtoReader::new(reader.bytes()?);
+        feature = match field {
+            1 => {
+                let mut values = Vec::new();
+                while !list.is_empty() {
+                    match list.key()? {
+                        (1, WIRE_LEN) => values.push(list.bytes()?.to_vec()),
+                        (_, wire_type) => list.skip(wire_type)?,
+                    }
+                }
+                Feature::Bytes(values)
+            }
+            2 => {
+                let mut values = Vec::new();
+                while !list.is_empty() {
+                    match list.key()? {
+                        // packed（通常）と非packedの両方に対応する
+                        (1, WIRE_LEN) => {
+                            let mut packed = ProtoReader::new(list.bytes()?);
+                            while !packed.is_empty() {
+                                values.push(f32::from_le_bytes(packed.fixed32()?));
+                            }
+                        }
+                        (1, WIRE_FIXED32) => values.push(f32::from_le_bytes(list.fixed32()?)),
+                        (_, wire_type) => list.skip(wire_type)?,
+                    }
+                }
+                Feature::Float(values)
+            }
+            _ => {
+                let mut values = Vec::new();
+                while !list.is_empty() {
+                    match list.key()? {
+                        (1, WIRE_LEN) => {
+                            let mut packed = ProtoReader::new(list.bytes()?);
+                            while !packed.is_empty() {
+                                values.push(packed.varint()? as i64);
+                            }
+                        }
+                        (1, WIRE_VARINT) => values.push(list.varint()? as i64),
+                        (_, wire_type) => list.skip(wire_type)?,
+                    }
+                }
+                Feature::Int64(values)
+            }
+        };
+    }
+    Ok(feature)
+}
+
+fn encode_example(example: &Example) -> Vec<u8> {
+    let mut features = Vec::new();
+    for (key, feature) in example {
+        let mut entry = Vec::new();
+        put_bytes(&mut entry, 1, key.as_bytes());
+        put_bytes(&mut entry, 2, &encode_feature(feature));
+        put_bytes(&mut features, 1, &entry);
+    }
+    let mut buf = Vec::new();
+    put_bytes(&mut buf, 1, &features);
+    buf
+}
+
+fn encode_feature(feature: &Feature) -> Vec<u8> {
+    let mut list = Vec::new();
+    let field = match feature {
+        Feature::Bytes(values) => {
+            for value in values {
+                put_bytes(&mut list, 1, value);
+            }
+            1
+        }
+        Feature::Float(values) => {
+            let packed: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+            if !packed.is_empty() {
+                put_bytes(&mut list, 1, &packed);
+            }
+            2
+        }
+        Feature::Int64(values) => {
+            let mut packed = Vec::new();
+            for &value in values {
+                put_varint(&mut packed, value as u64);
+            }
+            if !packed.is_empty() {
+                put_bytes(&mut list, 1, &packed);
+            }
+            3
+        }
+    };
+    let mut buf = Vec::new();
+    put_bytes(&mut buf, field, &list);
+    buf
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn put_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    put_varint(buf, (field << 3) | WIRE_LEN as u64);
+    put_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::test_util::{temp_dir, write, write_image};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    #[test]
+    fn computes_crc32c() {
+        // RFC 3720 の検査値
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+        assert_eq!(crc32c(&[0u8; 32]), 0x8a91_36aa);
+        assert_eq!(crc32c(&[0xffu8; 32]), 0x62a8_ab43);
+        assert_eq!(
+            masked_crc(b"123456789"),
+            0xe306_9283u32.rotate_right(15).wrapping_add(0xa282_ead8)
+        );
+    }
+
+    #[test]
+    fn encodes_and_decodes_varints() {
+        let mut buf = Vec::new();
+        put_varint(&mut buf, 300);
+        assert_eq!(buf, [0xac, 0x02]);
+
+        for value in [0, 1, 127, 128, 5_000_000_000, i64::MAX, -1, i64::MIN] {
+            let mut buf = Vec::new();
+            put_varint(&mut buf, value as u64);
+            assert!(buf.len() <= 10);
+            assert_eq!(ProtoReader::new(&buf).varint().unwrap() as i64, value);
+        }
+        assert!(ProtoReader::new(&[0xff; 11]).varint().is_err());
+        assert!(ProtoReader::new(&[0x80]).varint().is_err());
+    }
+
+    #[test]
+    fn round_trips_examples() {
+        let mut example = Example::new();
+        example.insert("image/filename".into(), bytes_feature("a.jpg"));
+        example.insert(
+            "image/object/bbox/xmin".into(),
+            Feature::Float(vec![0.25, 0.5]),
+        );
+        example.insert(
+            "image/object/class/label".into(),
+            Feature::Int64(vec![1, -2, 5_000_000_000]),
+        );
+        example.insert("empty".into(), Feature::Int64(Vec::new()));
+        // 空のリストも型を保って読み込む
+        assert_eq!(decode_example(&encode_example(&example)), Ok(example));
+
+        // 非packedの数値のリスト
+        let mut list = Vec::new();
+        put_varint(&mut list, (1 << 3) | WIRE_FIXED32 as u64);
+        list.extend_from_slice(&1.5f32.to_le_bytes());
+        let mut feature = Vec::new();
+        put_bytes(&mut feature, 2, &list);
+        assert_eq!(decode_feature(&feature), Ok(Feature::Float(vec![1.5])));
+
+        assert!(decode_example(&[0x0a, 0x05, 0x0a]).is_err());
+    }
+
+    fn export_data() -> COCOData {
+        serde_json::from_str(
+            r#"{
+                "images": [
+                    {"id": 1, "width": 8, "height": 4, "file_name": "a.png"},
+                    {"id": "b", "width": 10, "height": 10, "file_name": "missing.jpg"}
+                ],
+                "annotations": [
+                    {"id": 1, "image_id": 1, "category_id": 5000000000, "bbox": [2, 1, 4, 2],
+                     "area": 8, "iscrowd": 0, "difficult": true},
+                    {"id": 2, "image_id": 1, "category_id": "person", "bbox": [0, 0, 8, 4],
+                     "area": 32, "iscrowd": 1},
+                    {"id": 3, "image_id": 1, "category_id": 99, "bbox": [0, 0, 1, 1],
+                     "area": 1, "iscrowd": 0}
+                ],
+                "categories": [
+                    {"id": 5000000000, "name": "car"},
+                    {"id": "person", "name": "person"}
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn exports_and_reimports_records() {
+        let dir = temp_dir("tfrecord-round-trip");
+        write_image(&dir.join("images/a.png"), 8, 4);
+        let record = dir.join("out/train.tfrecord");
+        fs::create_dir_all(record.parent().unwrap()).unwrap();
+
+        let report = export(&export_data(), &record, Some(&dir.join("images"))).unwrap();
+        assert_eq!((report.files, report.annotations), (1, 2));
+        let messages: Vec<_> = report.issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Annotation 3: category 99 is not defined",
+                "Image file not found; the record has no image/encoded",
+            ]
+        );
+
+        let result = import(&record, &dir.join("extracted")).unwrap();
+        let data = &result.coco_data;
+        assert_eq!(
+            result.image_root,
+            dir.join("extracted").display().to_string()
+        );
+        assert!(dir.join("extracted/a.png").is_file());
+        assert_eq!(data.images[0].extra["source_id"], "1");
+        assert_eq!((data.images[1].width, data.images[1].height), (10, 10));
+
+        // i32に収まらないラベル番号もそのままカテゴリIDにする
+        let names: Vec<_> = data
+            .categories
+            .iter()
+            .map(|c| (c.id.clone(), c.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                (CocoId::from(5_000_000_000i64), "car"),
+                (CocoId::from(5_000_000_001i64), "person"),
+            ]
+        );
+        let car = &data.annotations[0];
+        assert_eq!(car.category_id, CocoId::from(5_000_000_000i64));
+        assert_eq!(car.bbox, [2.0, 1.0, 4.0, 2.0]);
+        assert_eq!(car.area, 8.0);
+        assert_eq!(car.extra["difficult"], true);
+        assert_eq!(data.annotations[1].iscrowd, 1);
+        assert_eq!(
+            result.issues[0].message,
+            "1 of 2 images are not embedded in the records"
+        );
+    }
+
+    #[test]
+    fn reads_gzip_shards_and_reports_corruption() {
+        let dir = temp_dir("tfrecord-shards");
+        let mut example = Example::new();
+        example.insert("image/filename".into(), bytes_feature("../x.jpg"));
+        example.insert("image/width".into(), Feature::Int64(vec![4]));
+        example.insert("image/height".into(), Feature::Int64(vec![2]));
+        example.insert("image/object/bbox/xmin".into(), Feature::Float(vec![0.5]));
+        example.insert("image/object/bbox/ymin".into(), Feature::Float(vec![0.0]));
+        example.insert("image/object/bbox/xmax".into(), Feature::Float(vec![1.0]));
+        example.insert("image/object/bbox/ymax".into(), Feature::Float(vec![1.0]));
+        example.insert("image/object/class/text".into(), bytes_feature("dog"));
+        let mut records = Vec::new();
+        write_record(&mut records, &encode_example(&example)).unwrap();
+
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(&records).unwrap();
+        write(
+            &dir.join("train.tfrecord-00000-of-00002"),
+            gzip.finish().unwrap(),
+        );
+        // 2件目のデータのCRCを壊す
+        let mut corrupted = records.clone();
+        corrupted.extend_from_slice(&records);
+        let last = corrupted.len() - 5;
+        corrupted[last] ^= 0xff;
+        write(&dir.join("train.tfrecord-00001-of-00002"), &corrupted);
+        write(&dir.join("notes.txt"), "ignored");
+
+        let result = import(&dir, &dir.join("extracted")).unwrap();
+        let data = &result.coco_data;
+        assert_eq!(result.image_root, dir.display().to_string());
+        assert_eq!(data.images.len(), 2);
+        assert_eq!(data.images[0].file_name, "x.jpg");
+        assert_eq!(data.categories[0].name, "dog");
+        assert_eq!(data.annotations[0].bbox, [2.0, 0.0, 2.0, 2.0]);
+        assert_eq!(result.issues.len(), 1);
+        assert!(result.issues[0].file.ends_with("00001-of-00002"));
+        assert_eq!(
+            result.issues[0].message,
+            "Record 2: corrupted record data (CRC mismatch); the rest of the file was skipped"
+        );
+
+        assert!(matches!(
+            import(&dir.join("extracted"), &dir),
+            Err(CoavError::InvalidInput { .. }) | Err(CoavError::NotFound { .. })
+        ));
+    }
+}
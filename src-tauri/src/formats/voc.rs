@@ -0,0 +1,539 @@
+use super::{image_size, sanitize_file_name, ExportReport, ImportResult, Issues};
+use crate::error::{CoavError, CoavResult};
+use crate::export::write_atomic;
+use crate::models::{COCOAnnotation, COCOCategory, COCOData, COCOImage, CocoId};
+use roxmltree::{Document, Node};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// `extra` に保存するVOCのフラグ
+const FLAGS: [&str; 3] = ["difficult", "truncated", "occluded"];
+
+/// VOCの1オブジェクト
+struct VocObject {
+    name: String,
+    /// COCO形式（0始まり）の `[x, y, w, h]`
+    bbox: [f64; 4],
+    pose: Option<String>,
+    flags: Vec<(&'static str, bool)>,
+}
+
+/// VOCの1ファイル
+struct VocAnnotation {
+    filename: String,
+    size: Option<(u32, u32)>,
+    objects: Vec<VocObject>,
+}
+
+/// Pascal VOC形式のXMLを読み込む
+///
+/// `path` にはVOCのルート（`Annotations/` と `JPEGImages/` を含むフォルダ）か、
+/// XMLファイルを直接含むフォルダを指定する。
+/// `difficult`・`truncated`・`occluded` は真偽値として、`pose` は文字列としてアノテーションの `extra` に保存する。
+/// VOCの座標は1始まりのピクセル番号のため、`xmin - 1` をCOCOの `x` に、`xmax - xmin + 1` を幅に変換する。
+pub fn import(path: &Path) -> CoavResult<ImportResult> {
+    if !path.is_dir() {
+        return Err(CoavError::not_found(
+            path,
+            format!("Folder not found: {}", path.display()),
+        ));
+    }
+    let annotation_dir = match path.join("Annotations") {
+        dir if dir.is_dir() => dir,
+        _ => path.to_path_buf(),
+    };
+    let image_root = match path.join("JPEGImages") {
+        dir if dir.is_dir() => dir,
+        _ => path.to_path_buf(),
+    };
+
+    let mut xml_paths: Vec<PathBuf> = fs::read_dir(&annotation_dir)
+        .map_err(|e| CoavError::io(&annotation_dir, "Failed to read folder", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("xml")))
+        .collect();
+    xml_paths.sort();
+
+    let mut issues = Issues::default();
+    let mut parsed = Vec::new();
+    for xml_path in xml_paths {
+        let text = match fs::read_to_string(&xml_path) {
+            Ok(text) => text,
+            Err(e) => {
+                issues.push(&xml_path, None, format!("Failed to read file: {e}"));
+                continue;
+            }
+        };
+        match parse_annotation(&text) {
+            Ok(annotation) => parsed.push((xml_path, annotation)),
+            Err(message) => issues.push(&xml_path, None, message),
+        }
+    }
+
+    // カテゴリは名前順にIDを振る
+    let names: BTreeSet<&str> = parsed
+        .iter()
+        .flat_map(|(_, a)| a.objects.iter().map(|o| o.name.as_str()))
+        .collect();
+    let category_ids: HashMap<&str, i32> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (*name, i as i32 + 1))
+        .collect();
+
+    let mut coco_data = COCOData {
+        categories: names
+            .iter()
+            .map(|name| COCOCategory {
+                id: category_ids[name].into(),
+                name: name.to_string(),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    for (xml_path, annotation) in &parsed {
+        let size = match annotation.size {
+            Some(size) => Ok(size),
+            // サイズが記録されていない場合は画像ファイルから取得する
+            None => image_size(&image_root.join(&annotation.filename)),
+        };
+        let (width, height) = match size {
+            Ok(size) => size,
+            Err(message) => {
+                issues.push(xml_path, None, message);
+                (0, 0)
+            }
+        };
+
+        let image_id = coco_data.images.len() as i64 + 1;
+        coco_data.images.push(COCOImage {
+            id: image_id.into(),
+            width: width as i32,
+            height: height as i32,
+            file_name: annotation.filename.clone(),
+            ..Default::default()
+        });
+
+        for object in &annotation.objects {
+            let mut extra: HashMap<String, Value> = object
+                .flags
+                .iter()
+                .map(|(key, value)| (key.to_string(), Value::Bool(*value)))
+                .collect();
+            if let Some(pose) = &object.pose {
+                extra.insert("pose".to_string(), Value::String(pose.clone()));
+            }
+            let [x, y, w, h] = object.bbox;
+            coco_data.annotations.push(COCOAnnotation {
+                id: (coco_data.annotations.len() as i64 + 1).into(),
+                image_id: image_id.into(),
+                category_id: category_ids[object.name.as_str()].into(),
+                area: w * h,
+                bbox: vec![x, y, w, h],
+                extra,
+                ..Default::default()
+            });
+        }
+    }
+
+    Ok(ImportResult {
+        coco_data,
+        image_root: image_root.display().to_string(),
+        issues: issues.into_vec(),
+    })
+}
+
+fn parse_annotation(text: &str) -> Result<VocAnnotation, String> {
+    let document = Document::parse(text).map_err(|e| format!("Failed to parse XML: {e}"))?;
+    let root = document.root_element();
+    if !root.has_tag_name("annotation") {
+        return Err(format!(
+            "Unexpected root element: <{}>",
+            root.tag_name().name()
+        ));
+    }
+
+    let filename = child_text(root, "filename").ok_or("Missing <filename>")?;
+    let size = child(root, "size").and_then(|size| {
+        let width = child_number(size, "width")? as u32;
+        let height = child_number(size, "height")? as u32;
+        (width > 0 && height > 0).then_some((width, height))
+    });
+
+    let objects = root
+        .children()
+        .filter(|n| n.has_tag_name("object"))
+        .map(parse_object)
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(VocAnnotation {
+        filename: filename.to_string(),
+        size,
+        objects,
+    })
+}
+
+fn parse_object(node: Node) -> Result<VocObject, String> {
+    let name = child_text(node, "name").ok_or("Missing <name> in <object>")?;
+    let bndbox = child(node, "bndbox").ok_or_else(|| format!("Missing <bndbox> for {name}"))?;
+    let coordinate = |tag: &str| {
+        child_number(bndbox, tag).ok_or_else(|| format!("Missing or invalid <{tag}> for {name}"))
+    };
+    let (xmin, ymin, xmax, ymax) = (
+        coordinate("xmin")?,
+        coordinate("ymin")?,
+        coordinate("xmax")?,
+        coordinate("ymax")?,
+    );
+
+    let flags = FLAGS
+        .iter()
+        .filter_map(|&flag| child_number(node, flag).map(|value| (flag, value != 0.0)))
+        .collect();
+    let pose = child_text(node, "pose")
+        .filter(|pose| *pose != "Unspecified")
+        .map(str::to_string);
+
+    Ok(VocObject {
+        name: name.to_string(),
+        bbox: [xmin - 1.0, ymin - 1.0, xmax - xmin + 1.0, ymax - ymin + 1.0],
+        pose,
+        flags,
+    })
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(tag))
+}
+
+fn child_text<'a>(node: Node<'a, '_>, tag: &str) -> Option<&'a str> {
+    child(node, tag)
+        .and_then(|n| n.text())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+}
+
+fn child_number(node: Node, tag: &str) -> Option<f64> {
+    child_text(node, tag)?
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+}
+
+/// 画像ごとにPascal VOC形式のXMLを書き出す
+///
+/// XMLは画像の `file_name` の拡張子を `.xml` にしたパスに書き出す。
+/// `file_name` の `..` や絶対パスは取り除き、拡張子だけが異なる画像（`a.jpg` と `a.png`）は
+/// 先の画像のみ書き出して残りを報告する。
+/// `extra` の `difficult`・`truncated`・`occluded`・`pose` はVOCの項目として出力する。
+/// VOCは群衆領域を表せないため、`iscrowd` のアノテーションは出力しない。
+pub fn export(coco_data: &COCOData, output_dir: &Path) -> CoavResult<ExportReport> {
+    let category_names: HashMap<&CocoId, &str> = coco_data
+        .categories
+        .iter()
+        .map(|c| (&c.id, c.name.as_str()))
+        .collect();
+    let mut annotations_by_image: HashMap<&CocoId, Vec<&COCOAnnotation>> = HashMap::new();
+    for annotation in &coco_data.annotations {
+        annotations_by_image
+            .entry(&annotation.image_id)
+            .or_default()
+            .push(annotation);
+    }
+
+    let mut issues = Issues::default();
+    let mut report = ExportReport {
+        output_dir: output_dir.display().to_string(),
+        files: 0,
+        annotations: 0,
+        skipped_crowd: 0,
+        issues: Vec::new(),
+    };
+
+    // 書き出したXMLのパスと元の画像のファイル名
+    let mut written: HashMap<PathBuf, &str> = HashMap::new();
+    for image in &coco_data.images {
+        let annotations = annotations_by_image.remove(&image.id).unwrap_or_default();
+        // 出力先のフォルダの外や、他の画像と同じXMLには書き出さない
+        let Some(file_name) = sanitize_file_name(&image.file_name) else {
+            issues.push(
+                &image.file_name,
+                None,
+                format!(
+                    "Image {} has no usable file name; {} annotations were not exported",
+                    image.id,
+                    annotations.len()
+                ),
+            );
+            continue;
+        };
+        let mut xml_path = output_dir.join(&file_name);
+        xml_path.set_extension("xml");
+        if let Some(other) = written.get(&xml_path) {
+            issues.push(
+                &image.file_name,
+                None,
+                format!(
+                    "Image {} has the same XML path as {other}; {} annotations were not exported",
+                    image.id,
+                    annotations.len()
+                ),
+            );
+            continue;
+        }
+        let file_name = Path::new(&file_name);
+
+        let mut xml = String::from("<annotation>\n");
+        if let Some(folder) = file_name.parent().and_then(|p| p.to_str()) {
+            if !folder.is_empty() {
+                let _ = writeln!(xml, "\t<folder>{}</folder>", escape(folder));
+            }
+        }
+        let base_name = file_name
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+        let _ = writeln!(xml, "\t<filename>{}</filename>", escape(&base_name));
+        let _ = writeln!(
+            xml,
+            "\t<size>\n\t\t<width>{}</width>\n\t\t<height>{}</height>\n\t\t<depth>3</depth>\n\t</size>",
+            image.width, image.height
+        );
+        xml.push_str("\t<segmented>0</segmented>\n");
+
+        for annotation in annotations {
+            if annotation.iscrowd != 0 {
+                report.skipped_crowd += 1;
+                continue;
+            }
+            let Some(name) = category_names.get(&annotation.category_id) else {
+                issues.push(
+                    &image.file_name,
+                    None,
+                    format!(
+                        "Annotation {}: category {} is not defined",
+                        annotation.id, annotation.category_id
+                    ),
+                );
+                continue;
+            };
+            let &[x, y, w, h] = annotation.bbox.as_slice() else {
+                issues.push(
+                    &image.file_name,
+                    None,
+                    format!("Annotation {}: invalid bbox", annotation.id),
+                );
+                continue;
+            };
+            write_object(&mut xml, name, [x, y, w, h], &annotation.extra);
+            report.annotations += 1;
+        }
+        xml.push_str("</annotation>\n");
+
+        if let Some(parent) = xml_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| CoavError::io(parent, "Failed to create folder", e))?;
+        }
+        write_atomic(&xml_path, |writer| {
+            writer
+                .write_all(xml.as_bytes())
+                .map_err(|e| CoavError::io(&xml_path, "Failed to write file", e))
+        })?;
+        written.insert(xml_path, image.file_name.as_str());
+        report.files += 1;
+    }
+
+    for (image_id, annotations) in annotations_by_image {
+        issues.push(
+            output_dir,
+            None,
+            format!(
+                "Image {image_id} does not exist; {} annotations were not exported",
+                annotations.len()
+            ),
+        );
+    }
+
+    report.issues = issues.into_vec();
+    Ok(report)
+}
+
+fn write_object(xml: &mut String, name: &str, bbox: [f64; 4], extra: &HashMap<String, Value>) {
+    let [x, y, w, h] = bbox;
+    let pose = extra
+        .get("pose")
+        .and_then(Value::as_str)
+        .unwrap_or("Unspecified");
+    let flag = |key: &str| match extra.get(key) {
+        Some(Value::Bool(value)) => *value as u8,
+        Some(Value::Number(value)) => (value.as_f64() != Some(0.0)) as u8,
+        _ => 0,
+    };
+
+    let _ = writeln!(xml, "\t<object>");
+    let _ = writeln!(xml, "\t\t<name>{}</name>", escape(name));
+    let _ = writeln!(xml, "\t\t<pose>{}</pose>", escape(pose));
+    for key in FLAGS {
+        // occluded はVOC2007にはない項目のため、指定がある場合のみ出力する
+        if key != "occluded" || extra.contains_key(key) {
+            let _ = writeln!(xml, "\t\t<{key}>{}</{key}>", flag(key));
+        }
+    }
+    // COCOの0始まりの座標をVOCの1始まりのピクセル番号に戻す
+    let _ = writeln!(
+        xml,
+        "\t\t<bndbox>\n\t\t\t<xmin>{}</xmin>\n\t\t\t<ymin>{}</ymin>\n\t\t\t<xmax>{}</xmax>\n\t\t\t<ymax>{}</ymax>\n\t\t</bndbox>",
+        x.round() + 1.0,
+        y.round() + 1.0,
+        (x + w).round(),
+        (y + h).round()
+    );
+    let _ = writeln!(xml, "\t</object>");
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::test_util::{temp_dir, write, write_image};
+
+    const XML: &str = r#"<annotation>
+	<folder>VOC2007</folder>
+	<filename>000001.jpg</filename>
+	<size><width>353</width><height>500</height><depth>3</depth></size>
+	<object>
+		<name>dog</name>
+		<pose>Left</pose>
+		<truncated>1</truncated>
+		<difficult>0</difficult>
+		<bndbox><xmin>48</xmin><ymin>240</ymin><xmax>195</xmax><ymax>371</ymax></bndbox>
+	</object>
+	<object>
+		<name>person &amp; bike</name>
+		<pose>Unspecified</pose>
+		<bndbox><xmin>1</xmin><ymin>1</ymin><xmax>1</xmax><ymax>1</ymax></bndbox>
+	</object>
+</annotation>
+"#;
+
+    #[test]
+    fn imports_one_based_boxes() {
+        let dir = temp_dir("voc-import");
+        write(&dir.join("Annotations/000001.xml"), XML);
+        // サイズの無いXMLは画像ファイルから取得する
+        write(
+            &dir.join("Annotations/000002.xml"),
+            "<annotation><filename>000002.png</filename></annotation>",
+        );
+        write_image(&dir.join("JPEGImages/000002.png"), 7, 5);
+        write(&dir.join("Annotations/broken.xml"), "<annotation>");
+        write(&dir.join("Annotations/notes.txt"), "ignored");
+
+        let result = import(&dir).unwrap();
+        let data = &result.coco_data;
+        assert_eq!(
+            result.image_root,
+            dir.join("JPEGImages").display().to_string()
+        );
+
+        let names: Vec<_> = data.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["dog", "person & bike"]);
+        assert_eq!(data.images.len(), 2);
+        assert_eq!((data.images[0].width, data.images[0].height), (353, 500));
+        assert_eq!((data.images[1].width, data.images[1].height), (7, 5));
+
+        let dog = &data.annotations[0];
+        assert_eq!(dog.bbox, [47.0, 239.0, 148.0, 132.0]);
+        assert_eq!(dog.area, 148.0 * 132.0);
+        assert_eq!(dog.extra["pose"], "Left");
+        assert_eq!(dog.extra["truncated"], true);
+        assert_eq!(dog.extra["difficult"], false);
+        assert!(!dog.extra.contains_key("occluded"));
+
+        // 1ピクセルの矩形は幅・高さ1になる
+        let person = &data.annotations[1];
+        assert_eq!(person.bbox, [0.0, 0.0, 1.0, 1.0]);
+        assert!(!person.extra.contains_key("pose"));
+
+        assert_eq!(result.issues.len(), 1);
+        assert!(result.issues[0].file.ends_with("broken.xml"));
+        assert!(result.issues[0].message.starts_with("Failed to parse XML"));
+    }
+
+    #[test]
+    fn exports_and_reimports_same_boxes() {
+        let dir = temp_dir("voc-round-trip");
+        write(&dir.join("source/000001.xml"), XML);
+        let source = import(&dir.join("source")).unwrap().coco_data;
+
+        let report = export(&source, &dir.join("out")).unwrap();
+        assert_eq!((report.files, report.annotations), (1, 2));
+        let xml = fs::read_to_string(dir.join("out/000001.xml")).unwrap();
+        assert!(xml.contains("<xmin>48</xmin>"));
+        assert!(xml.contains("<xmax>195</xmax>"));
+        assert!(xml.contains("<name>person &amp; bike</name>"));
+        assert!(xml.contains("<truncated>1</truncated>"));
+        assert!(!xml.contains("<occluded>"));
+
+        let reimported = import(&dir.join("out")).unwrap().coco_data;
+        for (a, b) in source.annotations.iter().zip(&reimported.annotations) {
+            assert_eq!(a.bbox, b.bbox);
+        }
+        assert_eq!(source.annotations[0].extra, reimported.annotations[0].extra);
+    }
+
+    #[test]
+    fn reports_colliding_and_unsafe_file_names() {
+        let data: COCOData = serde_json::from_str(
+            r#"{
+                "images": [
+                    {"id": 1, "width": 10, "height": 10, "file_name": "images/a.jpg"},
+                    {"id": 2, "width": 10, "height": 10, "file_name": "images/a.png"},
+                    {"id": 3, "width": 10, "height": 10, "file_name": "../../b.jpg"},
+                    {"id": 4, "width": 10, "height": 10, "file_name": ".."}
+                ],
+                "annotations": [
+                    {"id": 1, "image_id": 2, "category_id": 1, "bbox": [0, 0, 2, 2],
+                     "area": 4, "iscrowd": 0},
+                    {"id": 2, "image_id": 1, "category_id": 1, "bbox": [0, 0, 2, 2],
+                     "area": 4, "iscrowd": 1}
+                ],
+                "categories": [{"id": 1, "name": "cat"}]
+            }"#,
+        )
+        .unwrap();
+        let dir = temp_dir("voc-collision");
+        let report = export(&data, &dir).unwrap();
+
+        assert_eq!(report.files, 2);
+        assert_eq!(report.skipped_crowd, 1);
+        assert!(dir.join("images/a.xml").is_file());
+        assert!(dir.join("b.xml").is_file());
+        assert!(!dir.join("../../b.xml").exists());
+
+        let messages: Vec<_> = report.issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Image 2 has the same XML path as images/a.jpg; 1 annotations were not exported",
+                "Image 4 has no usable file name; 0 annotations were not exported",
+            ]
+        );
+        // 先に書き出した画像のXMLは上書きしない
+        let xml = fs::read_to_string(dir.join("images/a.xml")).unwrap();
+        assert!(xml.contains("<filename>a.jpg</filename>"));
+    }
+}
@@ -0,0 +1,790 @@
+use super::{
+    find_images, image_size, relative_file_name, sanitize_file_name, ExportReport, ImportResult,
+    Issues,
+};
+use crate::error::{CoavError, CoavResult, ErrorLocation};
+use crate::export::write_atomic;
+use crate::models::segmentation::{polygon_area, polygons_bbox};
+use crate::models::{
+    COCOAnnotation, COCOCategory, COCOData, COCOImage, CocoId, RotatedBox, Segmentation,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsStr;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// `data.yaml` のうち変換に使用する項目
+#[derive(Debug, Default, Deserialize)]
+struct DataYaml {
+    #[serde(default)]
+    names: Option<ClassNames>,
+    #[serde(default)]
+    nc: Option<usize>,
+    /// キーポイントの数と次元（`[17, 3]` など）
+    #[serde(default)]
+    kpt_shape: Option<[usize; 2]>,
+}
+
+/// クラス名（リスト形式と `{0: name}` 形式の両方がある）
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ClassNames {
+    List(Vec<String>),
+    Map(BTreeMap<usize, String>),
+}
+
+impl ClassNames {
+    fn into_map(self) -> BTreeMap<usize, String> {
+        match self {
+            ClassNames::List(names) => names.into_iter().enumerate().collect(),
+            ClassNames::Map(names) => names,
+        }
+    }
+}
+
+/// YOLO形式（Ultralytics）のデータセットフォルダを読み込む
+///
+/// `images/` 以下の画像に対応するラベルを `labels/` 以下の同じ相対パスの `.txt` から読み込む。
+/// クラス名は `data.yaml`（無ければ `classes.txt`）から取得し、カテゴリIDはクラス番号+1とする。
+/// `path` に `data.yaml` を指定した場合は、そのフォルダをデータセットのルートとする。
+pub fn import(path: &Path) -> CoavResult<ImportResult> {
+    let (root, yaml_path) = if path.is_file() {
+        let root = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        (root, Some(path.to_path_buf()))
+    } else if path.is_dir() {
+        (path.to_path_buf(), find_data_yaml(path))
+    } else {
+        return Err(CoavError::not_found(
+            path,
+            format!("Folder not found: {}", path.display()),
+        ));
+    };
+
+    let mut issues = Issues::default();
+    let config = match &yaml_path {
+        Some(yaml_path) => read_data_yaml(yaml_path)?,
+        None => DataYaml::default(),
+    };
+    let mut class_names = match config.names {
+        Some(names) => names.into_map(),
+        None => read_classes_txt(&root),
+    };
+    if let Some(nc) = config.nc {
+        if !class_names.is_empty() && class_names.len() != nc {
+            issues.push(
+                yaml_path.as_deref().unwrap_or(&root),
+                None,
+                format!(
+                    "nc is {nc} but {} class names are defined",
+                    class_names.len()
+                ),
+            );
+        }
+        for index in 0..nc {
+            class_names
+                .entry(index)
+                .or_insert_with(|| format!("class_{index}"));
+        }
+    }
+
+    let mut coco_data = COCOData::default();
+    for image_path in find_images(&root) {
+        let file_name = relative_file_name(&root, &image_path);
+        let (width, height) = match image_size(&image_path) {
+            Ok(size) => size,
+            Err(message) => {
+                issues.push(&image_path, None, message);
+                continue;
+            }
+        };
+
+        let image_id = coco_data.images.len() as i64 + 1;
+        coco_data.images.push(COCOImage {
+            id: image_id.into(),
+            width: width as i32,
+            height: height as i32,
+            file_name,
+            ..Default::default()
+        });
+
+        let label_path = label_path_for(&image_path);
+        let Ok(text) = fs::read_to_string(&label_path) else {
+            // ラベルファイルが無い画像は背景画像として扱う
+            continue;
+        };
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            match parse_line(line, width as f64, height as f64, config.kpt_shape) {
+                Ok(None) => {}
+                Ok(Some((class_index, annotation))) => {
+                    if let Entry::Vacant(entry) = class_names.entry(class_index) {
+                        issues.push(
+                            &label_path,
+                            Some(line_number),
+                            format!("Class {class_index} is not defined in the class names"),
+                        );
+                        entry.insert(format!("class_{class_index}"));
+                    }
+                    coco_data.annotations.push(COCOAnnotation {
+                        id: (coco_data.annotations.len() as i64 + 1).into(),
+                        image_id: image_id.into(),
+                        category_id: (class_index as i32 + 1).into(),
+                        ..annotation
+                    });
+                }
+                Err(message) => issues.push(&label_path, Some(line_number), message),
+            }
+        }
+    }
+
+    coco_data.categories = class_names
+        .into_iter()
+        .map(|(index, name)| COCOCategory {
+            id: (index as i32 + 1).into(),
+            name,
+            ..Default::default()
+        })
+        .collect();
+
+    Ok(ImportResult {
+        coco_data,
+        image_root: root.display().to_string(),
+        issues: issues.into_vec(),
+    })
+}
+
+fn find_data_yaml(root: &Path) -> Option<PathBuf> {
+    let default = root.join("data.yaml");
+    if default.is_file() {
+        return Some(default);
+    }
+    let mut candidates: Vec<PathBuf> = fs::read_dir(root)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && matches!(
+                    path.extension().and_then(OsStr::to_str),
+                    Some("yaml" | "yml")
+                )
+        })
+        .collect();
+    candidates.sort();
+    candidates.into_iter().next()
+}
+
+fn read_data_yaml(path: &Path) -> CoavResult<DataYaml> {
+    let text =
+        fs::read_to_string(path).map_err(|e| CoavError::io(path, "Failed to read file", e))?;
+    serde_yaml::from_str(&text).map_err(|e| CoavError::Parse {
+        message: format!("Failed to parse YAML: {e}"),
+        location: ErrorLocation {
+            file: Some(path.display().to_string()),
+            line: e.location().map(|l| l.line()),
+            column: e.location().map(|l| l.column()),
+            json_path: None,
+        },
+    })
+}
+
+/// 1行に1クラス名を書いた `classes.txt`（labelImgなどが出力する）
+fn read_classes_txt(root: &Path) -> BTreeMap<usize, String> {
+    let Ok(text) = fs::read_to_string(root.join("classes.txt")) else {
+        return BTreeMap::new();
+    };
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .enumerate()
+        .collect()
+}
+
+/// 画像に対応するラベルファイルのパス
+///
+/// パス中の最後の `images` を `labels` に置き換え、拡張子を `.txt` にする（Ultralyticsと同じ規則）。
+/// `images` を含まない場合は画像と同じフォルダの `.txt` とする。
+pub fn label_path_for(image_path: &Path) -> PathBuf {
+    let components: Vec<&OsStr> = image_path.iter().collect();
+    let images_index = components.iter().rposition(|c| *c == OsStr::new("images"));
+
+    let mut label_path: PathBuf = match images_index {
+        Some(index) => components
+            .iter()
+            .enumerate()
+            .map(|(i, c)| if i == index { OsStr::new("labels") } else { c })
+            .collect(),
+        None => image_path.to_path_buf(),
+    };
+    label_path.set_extension("txt");
+    label_path
+}
+
+/// ラベルの1行を読み込む（空行は `None`）
+///
+/// - `class cx cy w h`: 矩形
+/// - `class cx cy w h conf`: 推論結果（信頼度付きの矩形）
+/// - `class x1 y1 x2 y2 ...`: ポリゴン
+/// - `class cx cy w h px py [v] ...`: キーポイント（`data.yaml` に `kpt_shape` がある場合）
+fn parse_line(
+    line: &str,
+    width: f64,
+    height: f64,
+    kpt_shape: Option<[usize; 2]>,
+) -> Result<Option<(usize, COCOAnnotation)>, String> {
+    let mut tokens = line.split_whitespace();
+    let Some(class_token) = tokens.next() else {
+        return Ok(None);
+    };
+    let class_index: usize = class_token
+        .parse()
+        .map_err(|_| format!("Invalid class index: {class_token}"))?;
+    let values = tokens
+        .map(|token| {
+            token
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("Invalid number: {token}"))
+        })
+        .collect::<Result<Vec<f64>, String>>()?;
+
+    let to_box = |v: &[f64]| {
+        let (w, h) = (v[2] * width, v[3] * height);
+        vec![v[0] * width - w / 2.0, v[1] * height - h / 2.0, w, h]
+    };
+
+    let annotation = match (values.len(), kpt_shape) {
+        (4, _) => {
+            let bbox = to_box(&values);
+            COCOAnnotation {
+                area: bbox[2] * bbox[3],
+                bbox,
+                ..Default::default()
+            }
+        }
+        (5, _) => {
+            let bbox = to_box(&values);
+            COCOAnnotation {
+                area: bbox[2] * bbox[3],
+                bbox,
+                score: Some(values[4]),
+                ..Default::default()
+            }
+        }
+        (n, Some([count, dims])) if (dims == 2 || dims == 3) && n == 4 + count * dims => {
+            let bbox = to_box(&values);
+            let keypoints: Vec<f64> = values[4..]
+                .chunks_exact(dims)
+                .flat_map(|kp| {
+                    let visibility = if dims == 3 { kp[2] } else { 2.0 };
+                    [kp[0] * width, kp[1] * height, visibility]
+                })
+                .collect();
+            let num_keypoints = keypoints.chunks_exact(3).filter(|kp| kp[2] > 0.0).count();
+            COCOAnnotation {
+                area: bbox[2] * bbox[3],
+                bbox,
+                keypoints: Some(keypoints),
+                num_keypoints: Some(num_keypoints as u32),
+                ..Default::default()
+            }
+        }
+        (n, _) if n >= 6 && n % 2 == 0 => {
+            let polygon: Vec<f64> = values
+                .chunks_exact(2)
+                .flat_map(|p| [p[0] * width, p[1] * height])
+                .collect();
+            let polygons = vec![polygon];
+            let bbox = polygons_bbox(&polygons).map_or_else(|| vec![0.0; 4], |b| b.to_vec());
+            COCOAnnotation {
+                area: polygon_area(&polygons[0]),
+                bbox,
+                segmentation: Some(Segmentation::Polygon(polygons)),
+                ..Default::default()
+            }
+        }
+        (n, _) => return Err(format!("Unexpected number of values: {n}")),
+    };
+
+    Ok(Some((class_index, annotation)))
+}
+
+/// YOLO形式で出力するアノテーションの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportMode {
+    /// `class cx cy w h`
+    Bbox,
+    /// `class x1 y1 x2 y2 ...`（セグメンテーションが無い場合は回転矩形・矩形をポリゴンとして出力する）
+    Polygon,
+}
+
+/// `data.yaml` の出力内容
+#[derive(Serialize)]
+struct DataYamlOutput<'a> {
+    nc: usize,
+    names: BTreeMap<usize, &'a str>,
+}
+
+/// YOLO形式のラベルファイルと `data.yaml` を書き出す
+///
+/// カテゴリはID順に0からの連番に振り直し、`iscrowd` のアノテーションは出力しない。
+/// ラベルファイルは画像の `file_name` の `images` を `labels` に置き換えたパス
+/// （含まない場合は `labels/` 以下の同じ相対パス）に書き出す。
+/// `file_name` の `..` や絶対パスは取り除き、`output_dir` の外には書き出さない。
+pub fn export(
+    coco_data: &COCOData,
+    output_dir: &Path,
+    mode: ExportMode,
+) -> CoavResult<ExportReport> {
+    let mut categories: Vec<&COCOCategory> = coco_data.categories.iter().collect();
+    categories.sort_by_key(|c| &c.id);
+    let class_indices: HashMap<&CocoId, usize> = categories
+        .iter()
+        .enumerate()
+        .map(|(index, c)| (&c.id, index))
+        .collect();
+
+    let mut annotations_by_image: HashMap<&CocoId, Vec<&COCOAnnotation>> = HashMap::new();
+    for annotation in &coco_data.annotations {
+        annotations_by_image
+            .entry(&annotation.image_id)
+            .or_default()
+            .push(annotation);
+    }
+
+    let mut issues = Issues::default();
+    let mut report = ExportReport {
+        output_dir: output_dir.display().to_string(),
+        files: 0,
+        annotations: 0,
+        skipped_crowd: 0,
+        issues: Vec::new(),
+    };
+
+    for image in &coco_data.images {
+        let annotations = annotations_by_image.remove(&image.id).unwrap_or_default();
+        if image.width <= 0 || image.height <= 0 {
+            if !annotations.is_empty() {
+                issues.push(
+                    &image.file_name,
+                    None,
+                    format!(
+                        "Image {} has no valid size; {} annotations were not exported",
+                        image.id,
+                        annotations.len()
+                    ),
+                );
+            }
+            continue;
+        }
+        // 出力先のフォルダの外に書き込まないよう、`..` や絶対パスを含む名前は取り除く
+        let Some(label_path) = export_label_path(output_dir, &image.file_name) else {
+            issues.push(
+                &image.file_name,
+                None,
+                format!(
+                    "Image {} has no usable file name; {} annotations were not exported",
+                    image.id,
+                    annotations.len()
+                ),
+            );
+            continue;
+        };
+        let (width, height) = (image.width as f64, image.height as f64);
+
+        let mut text = String::new();
+        for annotation in annotations {
+            if annotation.iscrowd != 0 {
+                report.skipped_crowd += 1;
+                continue;
+            }
+            let Some(&class_index) = class_indices.get(&annotation.category_id) else {
+                issues.push(
+                    &image.file_name,
+                    None,
+                    format!(
+                        "Annotation {}: category {} is not defined",
+                        annotation.id, annotation.category_id
+                    ),
+                );
+                continue;
+            };
+            match format_annotation(annotation, width, height, mode) {
+                Ok((values, warning)) => {
+                    text.push_str(&class_index.to_string());
+                    for value in values {
+                        text.push_str(&format!(" {value:.6}"));
+                    }
+                    text.push('\n');
+                    report.annotations += 1;
+                    if let Some(warning) = warning {
+                        issues.push(
+                            &image.file_name,
+                            None,
+                            format!("Annotation {}: {warning}", annotation.id),
+                        );
+                    }
+                }
+                Err(message) => issues.push(
+                    &image.file_name,
+                    None,
+                    format!("Annotation {}: {message}", annotation.id),
+                ),
+            }
+        }
+
+        if let Some(parent) = label_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| CoavError::io(parent, "Failed to create folder", e))?;
+        }
+        write_atomic(&label_path, |writer| {
+            writer
+                .write_all(text.as_bytes())
+                .map_err(|e| CoavError::io(&label_path, "Failed to write file", e))
+        })?;
+        report.files += 1;
+    }
+
+    for (image_id, annotations) in annotations_by_image {
+        issues.push(
+            output_dir,
+            None,
+            format!(
+                "Image {image_id} does not exist; {} annotations were not exported",
+                annotations.len()
+            ),
+        );
+    }
+
+    let data_yaml = DataYamlOutput {
+        nc: categories.len(),
+        names: categories
+            .iter()
+            .enumerate()
+            .map(|(index, c)| (index, c.name.as_str()))
+            .collect(),
+    };
+    let yaml = serde_yaml::to_string(&data_yaml).map_err(|e| CoavError::Serialization {
+        message: format!("Failed to serialize data.yaml: {e}"),
+    })?;
+    let yaml_path = output_dir.join("data.yaml");
+    write_atomic(&yaml_path, |writer| {
+        writer
+            .write_all(yaml.as_bytes())
+            .map_err(|e| CoavError::io(&yaml_path, "Failed to write file", e))
+    })?;
+
+    report.issues = issues.into_vec();
+    Ok(report)
+}
+
+fn export_label_path(output_dir: &Path, file_name: &str) -> Option<PathBuf> {
+    let file_name = sanitize_file_name(file_name)?;
+    let image_path = output_dir.join(&file_name);
+    if Path::new(&file_name)
+        .iter()
+        .any(|c| c == OsStr::new("images"))
+    {
+        Some(label_path_for(&image_path))
+    } else {
+        let mut label_path = output_dir.join("labels").join(&file_name);
+        label_path.set_extension("txt");
+        Some(label_path)
+    }
+}
+
+/// アノテーションを正規化した座標の列に変換する（2つ目の値は出力はできたが情報が欠けた場合の警告）
+fn format_annotation(
+    annotation: &COCOAnnotation,
+    width: f64,
+    height: f64,
+    mode: ExportMode,
+) -> Result<(Vec<f64>, Option<String>), String> {
+    let normalize = |x: f64, y: f64| [(x / width).clamp(0.0, 1.0), (y / height).clamp(0.0, 1.0)];
+
+    let bbox = match annotation.bbox.as_slice() {
+        &[x, y, w, h] if w > 0.0 && h > 0.0 => Some([x, y, w, h]),
+        _ => None,
+    };
+
+    match mode {
+        ExportMode::Bbox => {
+            let [x, y, w, h] = bbox.ok_or("invalid bbox")?;
+            let [x0, y0] = normalize(x, y);
+            let [x1, y1] = normalize(x + w, y + h);
+            Ok((
+                vec![(x0 + x1) / 2.0, (y0 + y1) / 2.0, x1 - x0, y1 - y0],
+                None,
+            ))
+        }
+        ExportMode::Polygon => match &annotation.segmentation {
+            Some(Segmentation::Polygon(polygons)) => {
+                let valid: Vec<&Vec<f64>> = polygons
+                    .iter()
+                    .filter(|p| p.len() >= 6 && p.len() % 2 == 0)
+                    .collect();
+                // YOLOは1つのオブジェクトに1つのポリゴンのみ持てるため、最も大きいものを出力する
+                let polygon = valid
+                    .iter()
+                    .max_by(|a, b| polygon_area(a).total_cmp(&polygon_area(b)))
+                    .ok_or("no valid polygon")?;
+                let values = polygon
+                    .chunks_exact(2)
+                    .flat_map(|p| normalize(p[0], p[1]))
+                    .collect();
+                let warning = (polygons.len() > 1).then(|| {
+                    format!(
+                        "only the largest of {} polygons was exported",
+                        polygons.len()
+                    )
+                });
+                Ok((values, warning))
+            }
+            Some(Segmentation::Rle(_) | Segmentation::CompressedRle(_)) => {
+                Err("RLE segmentation cannot be exported as a polygon".to_string())
+            }
+            // 回転矩形（YOLOのOBB形式と同じ4頂点）、または矩形の4頂点を出力する
+            None => {
+                let rbox = match annotation.rbox {
+                    Some(rbox) => rbox,
+                    None => RotatedBox::from_bbox(bbox.ok_or("invalid bbox")?),
+                };
+                Ok((
+                    rbox.to_polygon()
+                        .chunks_exact(2)
+                        .flat_map(|p| normalize(p[0], p[1]))
+                        .collect(),
+                    None,
+                ))
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::test_util::{temp_dir, write, write_image};
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?} != {expected:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn imports_boxes_and_polygons() {
+        let root = temp_dir("yolo-import");
+        write(&root.join("data.yaml"), "names: [cat, dog]\nnc: 2\n");
+        write_image(&root.join("images/train/a.png"), 100, 50);
+        write_image(&root.join("images/train/b.png"), 20, 20);
+        write(
+            &root.join("labels/train/a.txt"),
+            "0 0.5 0.5 0.2 0.4\n\
+             1 0.1 0.2 0.3 0.2 0.3 0.4\n\
+             \n\
+             5 0.5 0.5 0.1 0.1\n\
+             x 0.5 0.5 0.1 0.1\n\
+             0 0.5 0.5 0.2\n\
+             0 0.5 0.5 0.2 0.4 0.9\n",
+        );
+
+        let result = import(&root).unwrap();
+        let data = &result.coco_data;
+        assert_eq!(result.image_root, root.display().to_string());
+
+        // ラベルの無い画像も背景画像として読み込む
+        let file_names: Vec<_> = data.images.iter().map(|i| i.file_name.as_str()).collect();
+        assert_eq!(file_names, ["images/train/a.png", "images/train/b.png"]);
+        assert_eq!((data.images[0].width, data.images[0].height), (100, 50));
+
+        assert_eq!(data.annotations.len(), 4);
+        let bbox = &data.annotations[0];
+        assert_eq!(bbox.category_id, 1);
+        assert_close(&bbox.bbox, &[40.0, 15.0, 20.0, 20.0]);
+        assert_close(&[bbox.area], &[400.0]);
+
+        let polygon = &data.annotations[1];
+        assert_eq!(polygon.category_id, 2);
+        let Some(Segmentation::Polygon(polygons)) = &polygon.segmentation else {
+            panic!("expected a polygon");
+        };
+        assert_close(&polygons[0], &[10.0, 10.0, 30.0, 10.0, 30.0, 20.0]);
+        assert_close(&polygon.bbox, &[10.0, 10.0, 20.0, 10.0]);
+        assert_close(&[polygon.area], &[100.0]);
+
+        // 未定義のクラスはカテゴリを追加する
+        assert_eq!(data.annotations[2].category_id, 6);
+        assert_eq!(data.categories.len(), 3);
+        assert_eq!(data.categories[2].name, "class_5");
+
+        // 信頼度付きの行
+        assert_eq!(data.annotations[3].score, Some(0.9));
+
+        let lines: Vec<_> = result.issues.iter().map(|i| i.line).collect();
+        assert_eq!(lines, [Some(4), Some(5), Some(6)]);
+        assert!(result.issues[1].message.contains("Invalid class index: x"));
+        assert!(result.issues[2]
+            .message
+            .contains("Unexpected number of values: 3"));
+    }
+
+    #[test]
+    fn imports_keypoints_with_kpt_shape() {
+        let root = temp_dir("yolo-keypoints");
+        let yaml = root.join("pose.yaml");
+        write(&yaml, "names: {0: person}\nkpt_shape: [2, 3]\n");
+        write_image(&root.join("images/a.jpg"), 100, 50);
+        write(
+            &root.join("labels/a.txt"),
+            "0 0.5 0.5 1 1 0.1 0.2 2 0.3 0.4 0\n",
+        );
+
+        // data.yaml 以外の名前のYAMLを直接指定できる
+        let result = import(&yaml).unwrap();
+        let annotation = &result.coco_data.annotations[0];
+        assert_close(
+            annotation.keypoints.as_ref().unwrap(),
+            &[10.0, 10.0, 2.0, 30.0, 20.0, 0.0],
+        );
+        assert_eq!(annotation.num_keypoints, Some(1));
+        assert_eq!(result.coco_data.categories[0].name, "person");
+        assert!(result.issues.is_empty());
+    }
+
+    #[test]
+    fn reads_classes_txt_and_reports_nc_mismatch() {
+        let root = temp_dir("yolo-classes");
+        write(&root.join("classes.txt"), "cat\n\ndog\n");
+        write_image(&root.join("a.png"), 10, 10);
+        write(&root.join("a.txt"), "1 0.5 0.5 1 1\n");
+
+        let result = import(&root).unwrap();
+        let names: Vec<_> = result
+            .coco_data
+            .categories
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, ["cat", "dog"]);
+        assert_eq!(result.coco_data.annotations[0].category_id, 2);
+
+        write(&root.join("data.yaml"), "names: [cat]\nnc: 3\n");
+        let result = import(&root).unwrap();
+        assert_eq!(result.coco_data.categories.len(), 3);
+        assert!(result.issues[0]
+            .message
+            .contains("nc is 3 but 1 class names are defined"));
+
+        assert_eq!(
+            import(&root.join("missing")).err().unwrap().code(),
+            "not_found"
+        );
+    }
+
+    #[test]
+    fn maps_image_paths_to_label_paths() {
+        assert_eq!(
+            label_path_for(Path::new("/data/images/train/images/a.jpg")),
+            Path::new("/data/images/train/labels/a.txt")
+        );
+        assert_eq!(
+            label_path_for(Path::new("/data/a.b.png")),
+            Path::new("/data/a.b.txt")
+        );
+    }
+
+    fn export_data() -> COCOData {
+        serde_json::from_str(
+            r#"{
+                "images": [
+                    {"id": 1, "width": 200, "height": 100, "file_name": "images/val/a.jpg"},
+                    {"id": 2, "width": 200, "height": 100, "file_name": "../../outside/b.jpg"},
+                    {"id": 3, "width": 200, "height": 100, "file_name": "/"},
+                    {"id": 4, "width": 0, "height": 0, "file_name": "c.jpg"}
+                ],
+                "annotations": [
+                    {"id": 1, "image_id": 1, "category_id": 20, "bbox": [20, 10, 40, 50],
+                     "area": 2000, "iscrowd": 0,
+                     "segmentation": [[20, 10, 60, 10, 60, 60], [0, 0, 1, 0, 1, 1]]},
+                    {"id": 2, "image_id": 1, "category_id": 10, "bbox": [-10, 90, 20, 20],
+                     "area": 400, "iscrowd": 0},
+                    {"id": 3, "image_id": 1, "category_id": 10, "bbox": [0, 0, 10, 10],
+                     "area": 100, "iscrowd": 1},
+                    {"id": 4, "image_id": 1, "category_id": 99, "bbox": [0, 0, 10, 10],
+                     "area": 100, "iscrowd": 0},
+                    {"id": 5, "image_id": 2, "category_id": 10, "bbox": [0, 0, 200, 100],
+                     "area": 20000, "iscrowd": 0},
+                    {"id": 6, "image_id": 3, "category_id": 10, "bbox": [0, 0, 1, 1],
+                     "area": 1, "iscrowd": 0},
+                    {"id": 7, "image_id": 4, "category_id": 10, "bbox": [0, 0, 1, 1],
+                     "area": 1, "iscrowd": 0},
+                    {"id": 8, "image_id": 9, "category_id": 10, "bbox": [0, 0, 1, 1],
+                     "area": 1, "iscrowd": 0}
+                ],
+                "categories": [{"id": 20, "name": "dog"}, {"id": 10, "name": "cat"}]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn exports_normalized_boxes() {
+        let dir = temp_dir("yolo-export-bbox");
+        let report = export(&export_data(), &dir, ExportMode::Bbox).unwrap();
+
+        // カテゴリはID順に0から振り直す（cat=0, dog=1）
+        let label = fs::read_to_string(dir.join("labels/val/a.txt")).unwrap();
+        assert_eq!(
+            label,
+            "1 0.200000 0.350000 0.200000 0.500000\n\
+             0 0.025000 0.950000 0.050000 0.100000\n"
+        );
+        let yaml: serde_yaml::Value =
+            serde_yaml::from_str(&fs::read_to_string(dir.join("data.yaml")).unwrap()).unwrap();
+        assert_eq!(yaml["nc"], 2);
+        assert_eq!(yaml["names"][0], "cat");
+        assert_eq!(yaml["names"][1], "dog");
+
+        // `..` を含む名前は出力先のフォルダ内に書き出す
+        assert!(dir.join("labels/outside/b.txt").is_file());
+        assert!(!dir.join("../outside/b.txt").exists());
+
+        assert_eq!(report.files, 2);
+        assert_eq!(report.annotations, 3);
+        assert_eq!(report.skipped_crowd, 1);
+        let messages: Vec<_> = report.issues.iter().map(|i| i.message.as_str()).collect();
+        assert!(messages.contains(&"Annotation 4: category 99 is not defined"));
+        assert!(
+            messages.contains(&"Image 3 has no usable file name; 1 annotations were not exported")
+        );
+        assert!(messages.contains(&"Image 4 has no valid size; 1 annotations were not exported"));
+        assert!(messages.contains(&"Image 9 does not exist; 1 annotations were not exported"));
+    }
+
+    #[test]
+    fn exports_largest_polygon_or_box_corners() {
+        let dir = temp_dir("yolo-export-polygon");
+        let report = export(&export_data(), &dir, ExportMode::Polygon).unwrap();
+
+        let label = fs::read_to_string(dir.join("labels/val/a.txt")).unwrap();
+        let lines: Vec<&str> = label.lines().collect();
+        assert_eq!(
+            lines[0],
+            "1 0.100000 0.100000 0.300000 0.100000 0.300000 0.600000"
+        );
+        // セグメンテーションの無い矩形は4頂点を出力する（画像の外は切り詰める）
+        assert_eq!(lines[1].split_whitespace().count(), 9);
+        assert!(report
+            .issues
+            .iter()
+            .any(|i| i.message == "Annotation 1: only the largest of 2 polygons was exported"));
+    }
+}
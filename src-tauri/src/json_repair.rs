@@ -0,0 +1,517 @@
+use crate::models::{coerce, number};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, Read};
+
+/// 保持する診断の上限（超えた分は件数のみ数える）
+const MAX_DIAGNOSTICS: usize = 10_000;
+/// 1件ずつ読み込み、不正な要素を除外するトップレベルの配列
+pub const RECORD_ARRAYS: [&str; 3] = ["images", "annotations", "categories"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagnosticAction {
+    /// 値を書き換えて読み込んだ
+    Repaired,
+    /// 要素を読み込まずに除外した
+    Skipped,
+}
+
+/// 寛容モードで修復・除外した箇所
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseDiagnostic {
+    pub action: DiagnosticAction,
+    /// `annotations[12].bbox[1]` の形式（ルートは `.`）
+    pub json_path: String,
+    /// 展開後のデータの先頭からのバイト位置
+    pub byte_offset: u64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseDiagnostics {
+    pub items: Vec<ParseDiagnostic>,
+    /// 上限を超えたため省略した件数
+    pub omitted: usize,
+}
+
+impl ParseDiagnostics {
+    fn push(&mut self, diagnostic: ParseDiagnostic) {
+        if self.items.len() < MAX_DIAGNOSTICS {
+            self.items.push(diagnostic);
+        } else {
+            self.omitted += 1;
+        }
+    }
+}
+
+/// 寛容モードの読み込み状態（`RepairReader` と読み込み側で共有する）
+#[derive(Debug, Default)]
+pub struct LenientState {
+    diagnostics: RefCell<ParseDiagnostics>,
+    /// 読み込み側がまだ受け取っていないレコードの開始位置（ファイル内の順）
+    record_offsets: RefCell<VecDeque<u64>>,
+    /// トップレベルの項目の値の開始位置
+    member_offsets: RefCell<HashMap<String, u64>>,
+}
+
+impl LenientState {
+    /// 記録した診断をファイル内の位置の順に返す
+    ///
+    /// 修復は読み込み側より先に進むため、記録した順は位置の順と一致しない。
+    pub fn into_diagnostics(self) -> ParseDiagnostics {
+        let mut diagnostics = self.diagnostics.into_inner();
+        diagnostics.items.sort_by_key(|d| d.byte_offset);
+        diagnostics
+    }
+
+    /// 次のレコードの開始位置（`RECORD_ARRAYS` の要素、または検出結果の配列の要素を読むたびに呼ぶ）
+    pub fn next_record_offset(&self) -> u64 {
+        self.record_offsets.borrow_mut().pop_front().unwrap_or(0)
+    }
+
+    pub fn member_offset(&self, key: &str) -> u64 {
+        self.member_offsets.borrow().get(key).copied().unwrap_or(0)
+    }
+
+    /// 値を型に変換する（失敗した場合は除外して `None` を返す）
+    ///
+    /// `NaN` などを置き換えた座標・面積の `null` は `NaN` として読み込み、要素は除外しない。
+    /// 除外した値について記録された型の変換（`coerce`）は取り消す。
+    pub fn convert<T: DeserializeOwned>(
+        &self,
+        json_path: &str,
+        byte_offset: u64,
+        value: Value,
+    ) -> Option<T> {
+        let mark = coerce::mark();
+        match number::null_as_nan(|| serde_path_to_error::deserialize(value)) {
+            Ok(item) => Some(item),
+            Err(e) => {
+                coerce::discard(mark);
+                let inner_path = e.path().to_string();
+                let json_path = match inner_path.as_str() {
+                    "." => json_path.to_string(),
+                    path if path.starts_with('[') => format!("{json_path}{path}"),
+                    path => format!("{json_path}.{path}"),
+                };
+                self.push(
+                    DiagnosticAction::Skipped,
+                    json_path,
+                    byte_offset,
+                    e.into_inner().to_string(),
+                );
+                None
+            }
+        }
+    }
+
+    fn push(&self, action: DiagnosticAction, json_path: String, byte_offset: u64, message: String) {
+        self.diagnostics.borrow_mut().push(ParseDiagnostic {
+            action,
+            json_path,
+            byte_offset,
+            message,
+        });
+    }
+}
+
+/// 走査中のコンテナ
+enum Frame {
+    Object {
+        key: Option<String>,
+        expect_key: bool,
+    },
+    Array {
+        index: usize,
+        expect_value: bool,
+    },
+}
+
+/// JSONを読みながら、よくある書式の誤りを修復するリーダー
+///
+/// - Pythonの `json.dumps` が出力する `NaN`・`Infinity`・`-Infinity` を `null` に置き換える
+///   （座標・面積では `NaN` として読み込まれ、検証で報告される）
+/// - 配列・オブジェクトの末尾のカンマを取り除く
+///
+/// 構文を完全には検証せず、修復できない誤りはそのまま後段のパーサーに渡す。
+/// あわせてレコード（`RECORD_ARRAYS` の要素）とトップレベルの項目の開始位置を記録する。
+pub struct RepairReader<'s, R> {
+    inner: R,
+    scanner: Scanner<'s>,
+    eof: bool,
+}
+
+impl<'s, R: BufRead> RepairReader<'s, R> {
+    pub fn new(inner: R, state: &'s LenientState) -> Self {
+        RepairReader {
+            inner,
+            scanner: Scanner {
+                state,
+                offset: 0,
+                output: VecDeque::new(),
+                stack: Vec::new(),
+                in_string: false,
+                escaped: false,
+                key: None,
+                token: Vec::new(),
+                token_start: 0,
+                pending_comma: None,
+                pending_whitespace: Vec::new(),
+            },
+            eof: false,
+        }
+    }
+}
+
+impl<R: BufRead> Read for RepairReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.scanner.output.is_empty() && !self.eof {
+            let input = self.inner.fill_buf()?;
+            if input.is_empty() {
+                self.scanner.finish();
+                self.eof = true;
+                break;
+            }
+            for &byte in input {
+                self.scanner.push(byte);
+            }
+            let len = input.len();
+            self.inner.consume(len);
+        }
+        let n = buf.len().min(self.scanner.output.len());
+        for (dst, src) in buf.iter_mut().zip(self.scanner.output.drain(..n)) {
+            *dst = src;
+        }
+        Ok(n)
+    }
+}
+
+struct Scanner<'s> {
+    state: &'s LenientState,
+    /// 入力の先頭からのバイト位置
+    offset: u64,
+    output: VecDeque<u8>,
+    stack: Vec<Frame>,
+    in_string: bool,
+    escaped: bool,
+    /// 読み込み中のオブジェクトのキー
+    key: Option<Vec<u8>>,
+    /// 読み込み中の文字列以外の値（数値・リテラル）
+    token: Vec<u8>,
+    token_start: u64,
+    /// 次の文字を見るまで出力を保留しているカンマの位置
+    pending_comma: Option<u64>,
+    pending_whitespace: Vec<u8>,
+}
+
+impl Scanner<'_> {
+    fn push(&mut self, byte: u8) {
+        let offset = self.offset;
+        self.offset += 1;
+
+        if self.in_string {
+            self.output.push_back(byte);
+            if self.escaped {
+                self.escaped = false;
+            } else if byte == b'\\' {
+                self.escaped = true;
+            } else if byte == b'"' {
+                self.in_string = false;
+                if let Some(key) = self.key.take() {
+                    if let Some(Frame::Object { key: current, .. }) = self.stack.last_mut() {
+                        *current = Some(String::from_utf8_lossy(&key).into_owned());
+                    }
+                }
+                return;
+            }
+            if let Some(key) = &mut self.key {
+                key.push(byte);
+            }
+            return;
+        }
+
+        if byte.is_ascii_whitespace() {
+            self.flush_token();
+            if self.pending_comma.is_some() {
+                self.pending_whitespace.push(byte);
+            } else {
+                self.output.push_back(byte);
+            }
+            return;
+        }
+
+        if let Some(comma_offset) = self.pending_comma.take() {
+            if byte == b']' || byte == b'}' {
+                let json_path = self.path(self.stack.len().saturating_sub(1));
+                self.state.push(
+                    DiagnosticAction::Repaired,
+                    json_path,
+                    comma_offset,
+                    "Removed trailing comma".to_string(),
+                );
+            } else {
+                self.output.push_back(b',');
+                self.after_comma();
+            }
+            self.output.extend(self.pending_whitespace.drain(..));
+        }
+
+        if !matches!(byte, b'{' | b'}' | b'[' | b']' | b',' | b':' | b'"') {
+            if self.token.is_empty() {
+                self.begin_value(offset);
+                self.token_start = offset;
+            }
+            self.token.push(byte);
+            return;
+        }
+        self.flush_token();
+
+        match byte {
+            b',' => {
+                self.pending_comma = Some(offset);
+                return;
+            }
+            b'"' => {
+                if let Some(Frame::Object {
+                    expect_key: true, ..
+                }) = self.stack.last()
+                {
+                    self.key = Some(Vec::new());
+                } else {
+                    self.begin_value(offset);
+                }
+                self.in_string = true;
+            }
+            b'{' => {
+                self.begin_value(offset);
+                self.stack.push(Frame::Object {
+                    key: None,
+                    expect_key: true,
+                });
+            }
+            b'[' => {
+                self.begin_value(offset);
+                self.stack.push(Frame::Array {
+                    index: 0,
+                    expect_value: true,
+                });
+            }
+            b'}' | b']' => {
+                self.stack.pop();
+            }
+            b':' => {
+                if let Some(Frame::Object { expect_key, .. }) = self.stack.last_mut() {
+                    *expect_key = false;
+                }
+            }
+            _ => {}
+        }
+        self.output.push_back(byte);
+    }
+
+    fn finish(&mut self) {
+        self.flush_token();
+        if self.pending_comma.take().is_some() {
+            self.output.push_back(b',');
+        }
+        self.output.extend(self.pending_whitespace.drain(..));
+    }
+
+    /// 値の開始位置を記録する
+    fn begin_value(&mut self, offset: u64) {
+        let is_record = match self.stack.as_slice() {
+            [Frame::Array { .. }] => true,
+            [Frame::Object { key: Some(key), .. }, Frame::Array { .. }] => {
+                RECORD_ARRAYS.contains(&key.as_str())
+            }
+            [Frame::Object { key: Some(key), .. }] => {
+                self.state
+                    .member_offsets
+                    .borrow_mut()
+                    .insert(key.clone(), offset);
+                false
+            }
+            _ => false,
+        };
+        if let Some(Frame::Array { expect_value, .. }) = self.stack.last_mut() {
+            if *expect_value && is_record {
+                self.state.record_offsets.borrow_mut().push_back(offset);
+            }
+            *expect_value = false;
+        }
+    }
+
+    fn after_comma(&mut self) {
+        match self.stack.last_mut() {
+            Some(Frame::Object { expect_key, .. }) => *expect_key = true,
+            Some(Frame::Array {
+                index,
+                expect_value,
+            }) => {
+                *index += 1;
+                *expect_value = true;
+            }
+            None => {}
+        }
+    }
+
+    fn flush_token(&mut self) {
+        if self.token.is_empty() {
+            return;
+        }
+        match self.token.as_slice() {
+            b"NaN" | b"Infinity" | b"+Infinity" | b"-Infinity" => {
+                let message = format!(
+                    "Replaced {} with null",
+                    String::from_utf8_lossy(&self.token)
+                );
+                self.state.push(
+                    DiagnosticAction::Repaired,
+                    self.path(self.stack.len()),
+                    self.token_start,
+                    message,
+                );
+                self.output.extend(b"null");
+            }
+            token => self.output.extend(token),
+        }
+        self.token.clear();
+    }
+
+    /// 先頭から `depth` 個のコンテナまでのパス
+    fn path(&self, depth: usize) -> String {
+        let mut path = String::new();
+        for frame in &self.stack[..depth] {
+            match frame {
+                Frame::Object { key: Some(key), .. } => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(key);
+                }
+                Frame::Object { key: None, .. } => {}
+                Frame::Array { index, .. } => path.push_str(&format!("[{index}]")),
+            }
+        }
+        if path.is_empty() {
+            path.push('.');
+        }
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Diagnostic = (DiagnosticAction, String, u64);
+
+    fn repair(input: &[u8], state: &LenientState) -> String {
+        let mut output = String::new();
+        RepairReader::new(input, state)
+            .read_to_string(&mut output)
+            .unwrap();
+        output
+    }
+
+    fn diagnostics(state: LenientState) -> Vec<Diagnostic> {
+        state
+            .into_diagnostics()
+            .items
+            .into_iter()
+            .map(|d| (d.action, d.json_path, d.byte_offset))
+            .collect()
+    }
+
+    #[test]
+    fn removes_trailing_commas() {
+        let state = LenientState::default();
+        let input = br#"{"a": [1, 2, ], "b": {"c": "x,]",},}"#;
+        let output = repair(input, &state);
+        assert_eq!(output, r#"{"a": [1, 2 ], "b": {"c": "x,]"}}"#);
+        serde_json::from_str::<Value>(&output).unwrap();
+        assert_eq!(
+            diagnostics(state),
+            vec![
+                (DiagnosticAction::Repaired, "a".to_string(), 11),
+                (DiagnosticAction::Repaired, "b".to_string(), 32),
+                (DiagnosticAction::Repaired, ".".to_string(), 34),
+            ]
+        );
+    }
+
+    #[test]
+    fn replaces_non_finite_numbers_with_null() {
+        let state = LenientState::default();
+        let input = br#"{"annotations": [{"bbox": [NaN, 1, -Infinity, 2]}, {"area": Infinity}]}"#;
+        let output = repair(input, &state);
+        assert_eq!(
+            output,
+            r#"{"annotations": [{"bbox": [null, 1, null, 2]}, {"area": null}]}"#
+        );
+        assert_eq!(
+            diagnostics(state),
+            vec![
+                (
+                    DiagnosticAction::Repaired,
+                    "annotations[0].bbox[0]".to_string(),
+                    27
+                ),
+                (
+                    DiagnosticAction::Repaired,
+                    "annotations[0].bbox[2]".to_string(),
+                    35
+                ),
+                (
+                    DiagnosticAction::Repaired,
+                    "annotations[1].area".to_string(),
+                    60
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn leaves_strings_and_valid_json_unchanged() {
+        let state = LenientState::default();
+        let input = r#"{"name": "NaN, ]", "escaped": "a\"NaN,}", "n": -1.5e3, "ok": [true, null]}"#;
+        assert_eq!(repair(input.as_bytes(), &state), input);
+        assert!(diagnostics(state).is_empty());
+    }
+
+    #[test]
+    fn records_offsets_after_bom() {
+        let state = LenientState::default();
+        let mut input = b"\xEF\xBB\xBF".to_vec();
+        input.extend_from_slice(br#"{"info": {}, "images": [{"id": 1}, {"id": 2},]}"#);
+        let output = repair(&input, &state);
+        assert!(output.as_bytes().starts_with(b"\xEF\xBB\xBF{"));
+
+        // バイト位置はBOMを含めた先頭から数える
+        assert_eq!(state.member_offset("info"), 12);
+        assert_eq!(state.member_offset("images"), 26);
+        assert_eq!(state.next_record_offset(), 27);
+        assert_eq!(state.next_record_offset(), 38);
+        assert_eq!(state.next_record_offset(), 0);
+        assert_eq!(
+            diagnostics(state),
+            vec![(DiagnosticAction::Repaired, "images".to_string(), 47)]
+        );
+    }
+
+    #[test]
+    fn records_offsets_of_top_level_array_elements() {
+        let state = LenientState::default();
+        repair(br#"[{"bbox": [1]}, {"x": [2, 3]}, 4]"#, &state);
+        assert_eq!(state.next_record_offset(), 1);
+        assert_eq!(state.next_record_offset(), 16);
+        assert_eq!(state.next_record_offset(), 31);
+        assert_eq!(state.next_record_offset(), 0);
+    }
+}
@@ -1,8 +1,30 @@
+mod archive;
+mod cache;
 mod commands;
+mod error;
+mod export;
+mod formats;
+mod json_repair;
+mod loader;
 mod menu;
 mod models;
 
-use commands::{load_annotations, load_image, sample_generator::generate_sample_data, scan_folder};
+use commands::cache::{clear_annotation_cache, get_annotation_cache_info};
+use commands::dataset::{
+    close_dataset, get_categories_by_frequency, get_category_annotations, get_dataset,
+    get_dataset_summary, get_image_annotations, get_track_annotations, get_video_frames,
+    list_images, open_dataset, DatasetState,
+};
+use commands::export::{export_annotations, export_tfrecord, export_voc, export_yolo};
+use commands::import::{
+    import_cvat, import_dota, import_kitti, import_label_studio, import_labelme, import_mot,
+    import_open_images, import_panoptic, import_tfrecord, import_voc, import_yolo,
+};
+use commands::{
+    cancel_annotation_load, keypoints::get_image_keypoints, list_archive_entries, load_annotations,
+    load_image, sample_generator::generate_sample_data, scan_folder, validate_annotations,
+};
+use loader::LoadRegistry;
 use menu::create_menu_with_language;
 use tauri::{Emitter, Manager};
 
@@ -20,11 +42,44 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .manage(LoadRegistry::default())
+        .manage(DatasetState::default())
         .invoke_handler(tauri::generate_handler![
             load_annotations,
             load_image,
             scan_folder,
             generate_sample_data,
+            get_image_keypoints,
+            validate_annotations,
+            cancel_annotation_load,
+            list_archive_entries,
+            open_dataset,
+            close_dataset,
+            get_dataset_summary,
+            get_dataset,
+            get_image_annotations,
+            get_category_annotations,
+            get_video_frames,
+            get_track_annotations,
+            get_categories_by_frequency,
+            list_images,
+            get_annotation_cache_info,
+            clear_annotation_cache,
+            export_annotations,
+            export_yolo,
+            import_yolo,
+            import_voc,
+            export_voc,
+            export_tfrecord,
+            import_labelme,
+            import_cvat,
+            import_label_studio,
+            import_kitti,
+            import_open_images,
+            import_dota,
+            import_tfrecord,
+            import_mot,
+            import_panoptic,
             set_menu_language
         ])
         .setup(|app| {
@@ -0,0 +1,988 @@
+use crate::archive::{self, ArchivePath, ZIP_MAGIC};
+use crate::error::{CoavError, CoavResult};
+use crate::json_repair::{LenientState, ParseDiagnostics, RepairReader};
+use crate::models::{coerce, COCOData, Coercion, DetectionResult};
+use flate2::read::MultiGzDecoder;
+use ruzstd::StreamingDecoder;
+use serde::de::{DeserializeOwned, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use zip::result::ZipError;
+
+/// 読み込み進捗イベント名
+pub const LOAD_PROGRESS_EVENT: &str = "annotation-load-progress";
+
+/// 進捗イベントの最小送信間隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+/// 読み込みバッファのサイズ
+const READ_BUFFER_SIZE: usize = 1 << 20;
+
+/// 読み込み進捗（フロントエンドに送信される）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadProgress {
+    /// 読み込みID（`cancel_annotation_load` に指定する）
+    pub load_id: u64,
+    pub file_path: String,
+    pub bytes_read: u64,
+    pub total_bytes: u64,
+    pub images: usize,
+    pub annotations: usize,
+    pub done: bool,
+    /// キャッシュから読み込んだ
+    pub from_cache: bool,
+}
+
+impl LoadProgress {
+    /// キャッシュから読み込んだ場合の完了通知
+    pub fn cached(path: &Path, coco_data: &COCOData, control: &LoadControl) -> Self {
+        let total_bytes = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        LoadProgress {
+            load_id: control.id(),
+            file_path: path.display().to_string(),
+            bytes_read: total_bytes,
+            total_bytes,
+            images: coco_data.images.len(),
+            annotations: coco_data.annotations.len(),
+            done: true,
+            from_cache: true,
+        }
+    }
+}
+
+/// 1回の読み込みのキャンセル要求を保持する
+#[derive(Debug, Default)]
+pub struct LoadControl {
+    id: u64,
+    cancelled: AtomicBool,
+}
+
+impl LoadControl {
+    pub fn new(id: u64) -> Self {
+        LoadControl {
+            id,
+            cancelled: AtomicBool::new(false),
+        }
+    }
+
+    /// 読み込みID（進捗イベントに含まれ、キャンセル時に指定する）
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// 実行中の読み込みを読み込みIDごとに保持する（Tauriのmanaged stateとして共有）
+///
+/// 同時に複数の読み込みが実行されても、キャンセルは指定したIDの読み込みにだけ作用する。
+#[derive(Debug, Default)]
+pub struct LoadRegistry {
+    next_id: AtomicU64,
+    loads: Mutex<HashMap<u64, Arc<LoadControl>>>,
+}
+
+impl LoadRegistry {
+    /// 新しい読み込みを登録する（返されたハンドルを破棄すると登録が解除される）
+    pub fn begin(&self) -> LoadHandle<'_> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let control = Arc::new(LoadControl::new(id));
+        self.lock().insert(id, Arc::clone(&control));
+        LoadHandle {
+            registry: self,
+            control,
+        }
+    }
+
+    /// 指定した読み込みをキャンセルする（完了済み・不明なIDの場合は `false`）
+    pub fn cancel(&self, load_id: u64) -> bool {
+        match self.lock().get(&load_id) {
+            Some(control) => {
+                control.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<u64, Arc<LoadControl>>> {
+        // 保持しているのはフラグだけなので、ロックが汚染されていても続行できる
+        self.loads.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 登録中の読み込み（`LoadControl` として使用する）
+pub struct LoadHandle<'r> {
+    registry: &'r LoadRegistry,
+    control: Arc<LoadControl>,
+}
+
+impl Deref for LoadHandle<'_> {
+    type Target = LoadControl;
+
+    fn deref(&self) -> &LoadControl {
+        &self.control
+    }
+}
+
+impl Drop for LoadHandle<'_> {
+    fn drop(&mut self) {
+        self.registry.lock().remove(&self.control.id);
+    }
+}
+
+/// 読み込んだアノテーションファイルの内容
+pub enum AnnotationFile {
+    /// COCO形式のデータセット
+    Dataset(Box<COCOData>),
+    /// トップレベルが配列の検出結果ファイル（読み込み時に型を変換した値を含む）
+    Results(Vec<DetectionResult>, Vec<Coercion>),
+}
+
+/// アノテーションファイルをストリーミングで読み込む
+///
+/// ファイル全体を文字列として保持せず、読み込みながら直接 `COCOData` を構築する。
+/// gzip・zstdで圧縮されたファイルは先頭のバイト列から判定して展開する。
+/// zip内のファイルは `<zipのパス>/<zip内のパス>` の形式で指定する。
+/// 読み込み中は `on_progress` に進捗が通知され、`control` でキャンセルできる。
+pub fn read_annotation_file(
+    path: &Path,
+    control: &LoadControl,
+    on_progress: &mut dyn FnMut(LoadProgress),
+) -> CoavResult<AnnotationFile> {
+    read_file(path, None, control, on_progress)
+}
+
+/// アノテーションファイルを寛容モードで読み込む
+///
+/// `NaN`・`Infinity` や末尾のカンマを修復し、`images`・`annotations`・`categories`
+/// （検出結果ファイルでは各要素）のうち型が一致しない要素は除外して読み込みを続ける。
+/// 修復・除外した箇所はJSONパスとバイト位置付きで返す。構文として解釈できない誤りはエラーになる。
+pub fn read_annotation_file_lenient(
+    path: &Path,
+    control: &LoadControl,
+    on_progress: &mut dyn FnMut(LoadProgress),
+) -> CoavResult<(AnnotationFile, ParseDiagnostics)> {
+    let state = LenientState::default();
+    let file = read_file(path, Some(&state), control, on_progress)?;
+    Ok((file, state.into_diagnostics()))
+}
+
+fn read_file(
+    path: &Path,
+    lenient: Option<&LenientState>,
+    control: &LoadControl,
+    on_progress: &mut dyn FnMut(LoadProgress),
+) -> CoavResult<AnnotationFile> {
+    if let Some(archive_path) = ArchivePath::split(path) {
+        let mut archive = archive::open_archive(&archive_path.archive)?;
+        let entry = archive.by_name(&archive_path.entry).map_err(|e| match e {
+            ZipError::FileNotFound => CoavError::not_found(
+                path,
+                format!(
+                    "File not found in archive {}: {}",
+                    archive_path.archive.display(),
+                    archive_path.entry
+                ),
+            ),
+            e => archive::zip_error(&archive_path.archive, e),
+        })?;
+        let total_bytes = entry.size();
+        return read_stream(path, entry, total_bytes, lenient, control, on_progress);
+    }
+
+    if !path.exists() {
+        return Err(CoavError::not_found(
+            path,
+            format!("File not found: {}", path.display()),
+        ));
+    }
+
+    let mut file = File::open(path).map_err(|e| CoavError::io(path, "Failed to read file", e))?;
+    let total_bytes = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+    // zipファイルが直接指定された場合は、読み込むファイルが決まれば読み込む
+    if starts_with_magic(&mut file, ZIP_MAGIC)
+        .map_err(|e| CoavError::io(path, "Failed to read file", e))?
+    {
+        let entries = archive::list_entries(path)?;
+        let Some(entry) = archive::default_entry(&entries) else {
+            return Err(CoavError::ArchiveEntryRequired {
+                path: path.display().to_string(),
+                message: format!(
+                    "{} is a zip archive containing {} annotation files; select one of them",
+                    path.display(),
+                    entries.len()
+                ),
+            });
+        };
+        return read_file(Path::new(&entry.file_path), lenient, control, on_progress);
+    }
+
+    read_stream(path, file, total_bytes, lenient, control, on_progress)
+}
+
+fn read_stream<R: Read>(
+    path: &Path,
+    source: R,
+    total_bytes: u64,
+    lenient: Option<&LenientState>,
+    control: &LoadControl,
+    on_progress: &mut dyn FnMut(LoadProgress),
+) -> CoavResult<AnnotationFile> {
+    let tracker = Tracker {
+        file_path: path.display().to_string(),
+        total_bytes,
+        bytes_read: Cell::new(0),
+        images: Cell::new(0),
+        annotations: Cell::new(0),
+        last_emit: Cell::new(Instant::now()),
+        control,
+        on_progress: RefCell::new(on_progress),
+    };
+
+    // 進捗は圧縮されたままのバイト数で数える
+    let source = BufReader::new(ProgressReader {
+        inner: source,
+        tracker: &tracker,
+    });
+    let (result, coercions) = coerce::collect(|| {
+        decompress(source)
+            .map(|decoded| match lenient {
+                Some(state) => Box::new(RepairReader::new(BufReader::new(decoded), state)),
+                None => decoded,
+            })
+            .map(|decoded| BufReader::with_capacity(READ_BUFFER_SIZE, decoded))
+            .and_then(|mut reader| {
+                starts_with_array(&mut reader).map(|is_results| (reader, is_results))
+            })
+            .map_err(|e| CoavError::io(path, "Failed to read file", e))
+            .and_then(|(reader, is_results)| match (is_results, lenient) {
+                (true, None) => deserialize_tracked(
+                    path,
+                    reader,
+                    CountingSeq::<DetectionResult>::new(&tracker, |t| &t.annotations),
+                )
+                .map(|results| AnnotationFile::Results(results, Vec::new())),
+                (true, Some(state)) => deserialize_tracked(
+                    path,
+                    reader,
+                    LenientSeq::<DetectionResult>::new("", state, &tracker, |t| &t.annotations),
+                )
+                .map(|results| AnnotationFile::Results(results, Vec::new())),
+                (false, _) => deserialize_tracked(
+                    path,
+                    reader,
+                    DatasetSeed {
+                        tracker: &tracker,
+                        lenient,
+                    },
+                )
+                .map(|coco_data| AnnotationFile::Dataset(Box::new(coco_data))),
+            })
+    });
+
+    // 読み込みエラーよりキャンセルを優先して報告する
+    if control.is_cancelled() {
+        return Err(CoavError::Cancelled {
+            message: format!("Loading cancelled: {}", path.display()),
+        });
+    }
+
+    tracker.emit(true);
+    result.map(|file| match file {
+        AnnotationFile::Dataset(mut coco_data) => {
+            coco_data.coercions = coercions;
+            AnnotationFile::Dataset(coco_data)
+        }
+        AnnotationFile::Results(results, _) => AnnotationFile::Results(results, coercions),
+    })
+}
+
+/// 先頭のバイト列から圧縮形式を判定し、展開しながら読み込むリーダーを返す
+fn decompress<'r, R: BufRead + 'r>(mut reader: R) -> io::Result<Box<dyn Read + 'r>> {
+    const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+    const ZSTD_MAGIC: &[u8] = b"\x28\xb5\x2f\xfd";
+
+    let header = reader.fill_buf()?;
+    if header.starts_with(GZIP_MAGIC) {
+        Ok(Box::new(MultiGzDecoder::new(reader)))
+    } else if header.starts_with(ZSTD_MAGIC) {
+        let decoder = StreamingDecoder::new(reader)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Box::new(decoder))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+fn starts_with_magic(file: &mut File, magic: &[u8]) -> io::Result<bool> {
+    let mut header = vec![0u8; magic.len()];
+    let matched = match file.read_exact(&mut header) {
+        Ok(()) => header == magic,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => false,
+        Err(e) => return Err(e),
+    };
+    file.seek(SeekFrom::Start(0))?;
+    Ok(matched)
+}
+
+fn deserialize_tracked<R, S, T>(path: &Path, reader: R, seed: S) -> CoavResult<T>
+where
+    R: Read,
+    S: for<'de> DeserializeSeed<'de, Value = T>,
+{
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let mut track = serde_path_to_error::Track::new();
+    let value = seed
+        .deserialize(serde_path_to_error::Deserializer::new(
+            &mut deserializer,
+            &mut track,
+        ))
+        .and_then(|value| deserializer.end().map(|_| value))
+        .map_err(|e| CoavError::json(path, serde_path_to_error::Error::new(track.path(), e)))?;
+    Ok(value)
+}
+
+/// BOMと空白を読み飛ばし、トップレベルが配列かどうかを判定する
+fn starts_with_array<R: BufRead>(reader: &mut R) -> io::Result<bool> {
+    const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+    let buffer = reader.fill_buf()?;
+    if buffer.starts_with(BOM) {
+        reader.consume(BOM.len());
+    }
+
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(false);
+        }
+        match buffer.iter().position(|b| !b.is_ascii_whitespace()) {
+            Some(i) => return Ok(buffer[i] == b'['),
+            None => {
+                let len = buffer.len();
+                reader.consume(len);
+            }
+        }
+    }
+}
+
+/// 進捗の集計と通知
+struct Tracker<'a> {
+    file_path: String,
+    total_bytes: u64,
+    bytes_read: Cell<u64>,
+    images: Cell<usize>,
+    annotations: Cell<usize>,
+    last_emit: Cell<Instant>,
+    control: &'a LoadControl,
+    on_progress: RefCell<&'a mut dyn FnMut(LoadProgress)>,
+}
+
+impl Tracker<'_> {
+    fn tick(&self) {
+        if self.last_emit.get().elapsed() >= PROGRESS_INTERVAL {
+            self.emit(false);
+        }
+    }
+
+    fn emit(&self, done: bool) {
+        self.last_emit.set(Instant::now());
+        let progress = LoadProgress {
+            load_id: self.control.id(),
+            file_path: self.file_path.clone(),
+            bytes_read: self.bytes_read.get(),
+            total_bytes: self.total_bytes,
+            images: self.images.get(),
+            annotations: self.annotations.get(),
+            done,
+            from_cache: false,
+        };
+        (self.on_progress.borrow_mut())(progress);
+    }
+}
+
+/// 読み込んだバイト数を数え、キャンセル要求があれば読み込みを中断する
+struct ProgressReader<'t, 'a, R> {
+    inner: R,
+    tracker: &'t Tracker<'a>,
+}
+
+impl<R: Read> Read for ProgressReader<'_, '_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.tracker.control.is_cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Other, "loading cancelled"));
+        }
+        let n = self.inner.read(buf)?;
+        self.tracker
+            .bytes_read
+            .set(self.tracker.bytes_read.get() + n as u64);
+        self.tracker.tick();
+        Ok(n)
+    }
+}
+
+/// 要素数を数えながら配列を読み込む（型を変換した値には要素の位置を記録する）
+struct CountingSeq<'t, 'a, T> {
+    tracker: &'t Tracker<'a>,
+    counter: Option<fn(&'t Tracker<'a>) -> &'t Cell<usize>>,
+    marker: PhantomData<T>,
+}
+
+impl<'t, 'a, T> CountingSeq<'t, 'a, T> {
+    fn new(tracker: &'t Tracker<'a>, counter: fn(&'t Tracker<'a>) -> &'t Cell<usize>) -> Self {
+        CountingSeq {
+            tracker,
+            counter: Some(counter),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for CountingSeq<'_, '_, T> {
+    type Value = Vec<T>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for CountingSeq<'_, '_, T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let counter = self.counter.map(|counter| counter(self.tracker));
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        loop {
+            let mark = coerce::mark();
+            let Some(item) = seq.next_element()? else {
+                break;
+            };
+            coerce::locate(mark, format_args!("[{}]", items.len()));
+            items.push(item);
+            if let Some(counter) = counter {
+                counter.set(counter.get() + 1);
+            }
+        }
+        Ok(items)
+    }
+}
+
+/// 要素ごとに型を変換しながら配列を読み込み、変換できない要素は除外する（寛容モード）
+struct LenientSeq<'t, 'a, T> {
+    /// 配列のJSONパス（トップレベルの配列は空文字列）
+    key: &'t str,
+    state: &'t LenientState,
+    tracker: &'t Tracker<'a>,
+    counter: Option<fn(&'t Tracker<'a>) -> &'t Cell<usize>>,
+    marker: PhantomData<T>,
+}
+
+impl<'t, 'a, T> LenientSeq<'t, 'a, T> {
+    fn new(
+        key: &'t str,
+        state: &'t LenientState,
+        tracker: &'t Tracker<'a>,
+        counter: fn(&'t Tracker<'a>) -> &'t Cell<usize>,
+    ) -> Self {
+        LenientSeq {
+            key,
+            state,
+            tracker,
+            counter: Some(counter),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, T: DeserializeOwned> DeserializeSeed<'de> for LenientSeq<'_, '_, T> {
+    type Value = Vec<T>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for LenientSeq<'_, '_, T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let counter = self.counter.map(|counter| counter(self.tracker));
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        let mut index = 0;
+        while let Some(value) = seq.next_element::<Value>()? {
+            let offset = self.state.next_record_offset();
+            let json_path = format!("{}[{index}]", self.key);
+            let mark = coerce::mark();
+            if let Some(item) = self.state.convert(&json_path, offset, value) {
+                coerce::locate(mark, format_args!("[{index}]"));
+                items.push(item);
+                if let Some(counter) = counter {
+                    counter.set(counter.get() + 1);
+                }
+            }
+            index += 1;
+        }
+        Ok(items)
+    }
+}
+
+/// `COCOData` のトップレベルを読み込む（`images`・`annotations` は件数を数える）
+struct DatasetSeed<'t, 'a> {
+    tracker: &'t Tracker<'a>,
+    /// 寛容モードの場合は要素ごとに読み込み、不正な要素を除外する
+    lenient: Option<&'t LenientState>,
+}
+
+impl<'de> DeserializeSeed<'de> for DatasetSeed<'_, '_> {
+    type Value = COCOData;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for DatasetSeed<'_, '_> {
+    type Value = COCOData;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a COCO dataset object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut info = None;
+        let mut images = None;
+        let mut annotations = None;
+        let mut categories = None;
+        let mut licenses = None;
+        let mut videos = None;
+        let mut tracks = None;
+        let mut extra = HashMap::new();
+        let mut seen = HashSet::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            // 同じキーが複数ある場合は、deriveと同様にエラーにする
+            if !seen.insert(key.clone()) {
+                return Err(serde::de::Error::custom(format_args!(
+                    "duplicate field `{key}`"
+                )));
+            }
+            let mark = coerce::mark();
+            if let Some(state) = self.lenient {
+                let tracker = self.tracker;
+                match key.as_str() {
+                    "images" => {
+                        images = Some(map.next_value_seed(LenientSeq::new(
+                            &key,
+                            state,
+                            tracker,
+                            |t| &t.images,
+                        ))?)
+                    }
+                    "annotations" => {
+                        annotations = Some(map.next_value_seed(LenientSeq::new(
+                            &key,
+                            state,
+                            tracker,
+                            |t| &t.annotations,
+                        ))?)
+                    }
+                    "categories" => {
+                        categories = Some(map.next_value_seed(LenientSeq {
+                            key: &key,
+                            state,
+                            tracker,
+                            counter: None,
+                            marker: PhantomData,
+                        })?)
+                    }
+                    "info" | "licenses" | "videos" | "tracks" => {
+                        let value: Value = map.next_value()?;
+                        let offset = state.member_offset(&key);
+                        match key.as_str() {
+                            "info" => info = state.convert(&key, offset, value).flatten(),
+                            "licenses" => licenses = state.convert(&key, offset, value).flatten(),
+                            "videos" => videos = state.convert(&key, offset, value).flatten(),
+                            _ => tracks = state.convert(&key, offset, value).flatten(),
+                        }
+                    }
+                    _ => {
+                        let value: Value = map.next_value()?;
+                        extra.insert(key, value);
+                        continue;
+                    }
+                }
+            } else {
+                match key.as_str() {
+                    "info" => info = map.next_value()?,
+                    "images" => {
+                        images = Some(
+                            map.next_value_seed(CountingSeq::new(self.tracker, |t| &t.images))?,
+                        )
+                    }
+                    "annotations" => {
+                        annotations =
+                            Some(map.next_value_seed(CountingSeq::new(self.tracker, |t| {
+                                &t.annotations
+                            }))?)
+                    }
+                    "categories" => {
+                        categories = Some(map.next_value_seed(CountingSeq {
+                            tracker: self.tracker,
+                            counter: None,
+                            marker: PhantomData,
+                        })?)
+                    }
+                    "licenses" => licenses = map.next_value()?,
+                    "videos" => videos = map.next_value()?,
+                    "tracks" => tracks = map.next_value()?,
+                    _ => {
+                        let value: Value = map.next_value()?;
+                        extra.insert(key, value);
+                        continue;
+                    }
+                }
+            }
+            coerce::locate(mark, &key);
+        }
+
+        Ok(COCOData {
+            info,
+            images: images.ok_or_else(|| serde::de::Error::missing_field("images"))?,
+            annotations: annotations
+                .ok_or_else(|| serde::de::Error::missing_field("annotations"))?,
+            categories: categories.ok_or_else(|| serde::de::Error::missing_field("categories"))?,
+            licenses,
+            videos,
+            tracks,
+            extra,
+            coercions: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_repair::DiagnosticAction;
+    use crate::models::validation::IssueKind;
+    use crate::models::CocoId;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn write_temp(name: &str, contents: &[u8]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("coav-loader-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    const DATASET: &str = r#"{
+        "images": [
+            {"id": 1, "width": 10, "height": 10, "file_name": "a.jpg"},
+            {"id": 2, "width": 10, "height": 10, "file_name": "b.jpg"}
+        ],
+        "custom": {"a": 1},
+        "annotations": [
+            {"id": 1, "image_id": 1, "category_id": 1, "bbox": [0, 0, 1, 1], "area": 1, "iscrowd": 0}
+        ],
+        "categories": [{"id": 1, "name": "x"}]
+    }"#;
+
+    #[test]
+    fn streams_dataset_and_reports_progress() {
+        let mut contents = b"\xEF\xBB\xBF".to_vec();
+        contents.extend_from_slice(DATASET.as_bytes());
+        let path = write_temp("dataset.json", &contents);
+
+        let control = LoadControl::new(7);
+        let mut events = Vec::new();
+        let file = read_annotation_file(&path, &control, &mut |p| events.push(p)).unwrap();
+        let AnnotationFile::Dataset(coco_data) = file else {
+            panic!("expected a dataset");
+        };
+        assert_eq!(coco_data.images.len(), 2);
+        assert_eq!(coco_data.annotations.len(), 1);
+        assert_eq!(coco_data.extra["custom"]["a"], 1);
+
+        let last = events.last().unwrap();
+        assert!(last.done);
+        assert_eq!(last.load_id, 7);
+        assert_eq!((last.images, last.annotations), (2, 1));
+        assert_eq!(last.bytes_read, contents.len() as u64);
+        assert_eq!(last.total_bytes, contents.len() as u64);
+    }
+
+    #[test]
+    fn detects_results_array() {
+        let path = write_temp(
+            "results.json",
+            br#" [{"image_id": 1, "category_id": 1, "bbox": [0, 0, 1, 1], "score": 0.5}]"#,
+        );
+        let file = read_annotation_file(&path, &LoadControl::default(), &mut |_| {}).unwrap();
+        let AnnotationFile::Results(results, _) = file else {
+            panic!("expected detection results");
+        };
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn cancelled_load_stops_reading() {
+        let path = write_temp("cancelled.json", DATASET.as_bytes());
+        let control = LoadControl::new(1);
+        control.cancel();
+        let error = read_annotation_file(&path, &control, &mut |_| {})
+            .err()
+            .unwrap();
+        assert_eq!(error.code(), "cancelled");
+    }
+
+    #[test]
+    fn registry_cancels_only_the_requested_load() {
+        let registry = LoadRegistry::default();
+        let first = registry.begin();
+        let second = registry.begin();
+        assert_ne!(first.id(), second.id());
+
+        assert!(registry.cancel(first.id()));
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+
+        // 終了した読み込みはキャンセルできず、新しい読み込みにも影響しない
+        let finished = first.id();
+        drop(first);
+        assert!(!registry.cancel(finished));
+        let third = registry.begin();
+        assert!(!third.is_cancelled());
+    }
+
+    #[test]
+    fn rejects_duplicate_top_level_keys() {
+        let path = write_temp(
+            "duplicate.json",
+            br#"{"images": [], "categories": [], "annotations": [], "images": []}"#,
+        );
+        let error = read_annotation_file(&path, &LoadControl::default(), &mut |_| {})
+            .err()
+            .unwrap();
+        assert_eq!(error.code(), "parse_error");
+        assert!(error.message().contains("duplicate field `images`"));
+
+        let error = read_annotation_file_lenient(&path, &LoadControl::default(), &mut |_| {})
+            .err()
+            .unwrap();
+        assert!(error.message().contains("duplicate field `images`"));
+
+        let path = write_temp(
+            "duplicate_extra.json",
+            br#"{"images": [], "categories": [], "annotations": [], "x": 1, "x": 2}"#,
+        );
+        assert!(read_annotation_file(&path, &LoadControl::default(), &mut |_| {}).is_err());
+    }
+
+    #[test]
+    fn reports_missing_fields_and_trailing_data() {
+        let path = write_temp("missing.json", br#"{"images": [], "categories": []}"#);
+        let error = read_annotation_file(&path, &LoadControl::default(), &mut |_| {})
+            .err()
+            .unwrap();
+        assert!(error.message().contains("missing field `annotations`"));
+
+        let path = write_temp(
+            "trailing.json",
+            br#"{"images": [], "categories": [], "annotations": []} x"#,
+        );
+        let error = read_annotation_file(&path, &LoadControl::default(), &mut |_| {})
+            .err()
+            .unwrap();
+        assert_eq!(error.code(), "parse_error");
+    }
+
+    #[test]
+    fn lenient_mode_repairs_and_skips_records() {
+        let json = r#"{
+    "images": [{"id": 1, "width": 10, "height": 10, "file_name": "a.jpg"},],
+    "annotations": [
+        {"id": 1, "image_id": 1, "category_id": 1, "bbox": [NaN, 0, 1, 1], "area": 1, "iscrowd": 0},
+        {"id": 2, "image_id": 1, "category_id": 1, "bbox": "oops", "area": 1, "iscrowd": 0},
+        {"id": 3, "image_id": 1, "category_id": 1, "bbox": [0, 0, 2, 2], "area": Infinity, "iscrowd": 0},
+    ],
+    "categories": [{"id": 1, "name": "x"}]
+}"#;
+        let mut contents = b"\xEF\xBB\xBF".to_vec();
+        contents.extend_from_slice(json.as_bytes());
+        let path = write_temp("lenient.json", &contents);
+
+        assert!(read_annotation_file(&path, &LoadControl::default(), &mut |_| {}).is_err());
+        let (file, diagnostics) =
+            read_annotation_file_lenient(&path, &LoadControl::default(), &mut |_| {}).unwrap();
+        let AnnotationFile::Dataset(coco_data) = file else {
+            panic!("expected a dataset");
+        };
+
+        // NaN・Infinity を含むアノテーションは残し、型の合わない要素だけを除外する
+        let ids: Vec<_> = coco_data.annotations.iter().map(|a| a.id.clone()).collect();
+        assert_eq!(ids, [1, 3].map(CocoId::from));
+        assert!(coco_data.annotations[0].bbox[0].is_nan());
+        assert!(coco_data.annotations[1].area.is_nan());
+        let non_finite = coco_data
+            .validate()
+            .issues
+            .iter()
+            .filter(|issue| issue.kind == IssueKind::NonFiniteValue)
+            .count();
+        assert_eq!(non_finite, 2);
+
+        // バイト位置はBOMを含む展開後のデータの先頭から数える
+        let offset = |needle: &str| (3 + json.find(needle).unwrap()) as u64;
+        let items: Vec<_> = diagnostics
+            .items
+            .iter()
+            .map(|d| (d.action, d.json_path.as_str(), d.byte_offset))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                (DiagnosticAction::Repaired, "images", offset("},]") + 1),
+                (
+                    DiagnosticAction::Repaired,
+                    "annotations[0].bbox[0]",
+                    offset("NaN")
+                ),
+                (
+                    DiagnosticAction::Skipped,
+                    "annotations[1].bbox",
+                    offset(r#"{"id": 2"#)
+                ),
+                (
+                    DiagnosticAction::Repaired,
+                    "annotations[2].area",
+                    offset("Infinity")
+                ),
+                (
+                    DiagnosticAction::Repaired,
+                    "annotations",
+                    offset("},\n    ]") + 1
+                ),
+            ]
+        );
+        assert_eq!(diagnostics.omitted, 0);
+    }
+
+    fn dataset_images(file: AnnotationFile) -> usize {
+        match file {
+            AnnotationFile::Dataset(coco_data) => coco_data.images.len(),
+            AnnotationFile::Results(..) => panic!("expected a dataset"),
+        }
+    }
+
+    #[test]
+    fn decompresses_gzip() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(DATASET.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+        // 拡張子ではなく先頭のバイト列で判定する
+        let path = write_temp("gzip.json", &compressed);
+
+        let mut events = Vec::new();
+        let file =
+            read_annotation_file(&path, &LoadControl::default(), &mut |p| events.push(p)).unwrap();
+        assert_eq!(dataset_images(file), 2);
+        // 進捗は圧縮されたままのバイト数
+        assert_eq!(events.last().unwrap().bytes_read, compressed.len() as u64);
+    }
+
+    #[test]
+    fn decompresses_zstd() {
+        // zstd -19 で圧縮した1画像のデータセット
+        const COMPRESSED: &[u8] = &[
+            0x28, 0xb5, 0x2f, 0xfd, 0x24, 0x74, 0xb5, 0x02, 0x00, 0x92, 0x05, 0x12, 0x11, 0x90,
+            0x7d, 0x63, 0x22, 0x07, 0x71, 0xbb, 0x0c, 0xe9, 0xba, 0x6b, 0xa5, 0xc6, 0x6c, 0xec,
+            0x8a, 0x12, 0xdb, 0x6c, 0xcf, 0x5f, 0x67, 0x5b, 0x17, 0x25, 0x07, 0xa6, 0x10, 0xb7,
+            0x72, 0xfe, 0x45, 0x77, 0x4a, 0x89, 0x1c, 0x98, 0xed, 0x8d, 0xbe, 0x80, 0x9c, 0xf8,
+            0x8a, 0x48, 0x9e, 0x05, 0x9f, 0x5d, 0xd7, 0x7e, 0x39, 0x07, 0x62, 0x10, 0x77, 0x57,
+            0x3d, 0x76, 0x30, 0x88, 0xab, 0x77, 0x5d, 0x88, 0xdb, 0xd5, 0x50, 0x7c, 0xd7, 0x01,
+            0x03, 0x00, 0x5b, 0x33, 0xf0, 0x2b, 0xc8, 0xd2, 0x63, 0x85, 0x0c, 0x93, 0xb3, 0xe3,
+            0x6c,
+        ];
+        let path = write_temp("dataset.json.zst", COMPRESSED);
+        let file = read_annotation_file(&path, &LoadControl::default(), &mut |_| {}).unwrap();
+        assert_eq!(dataset_images(file), 1);
+    }
+
+    #[test]
+    fn reads_annotation_files_in_zip() {
+        use crate::archive::tests::write_zip;
+
+        // アノテーションファイルが1つだけのzipはそのまま読み込める
+        let path = write_zip("single.zip", &[("data/val.json", DATASET.as_bytes())]);
+        let file = read_annotation_file(&path, &LoadControl::default(), &mut |_| {}).unwrap();
+        assert_eq!(dataset_images(file), 2);
+
+        // COCOの配布形式では instances_*.json を読み込む
+        let path = write_zip(
+            "coco.zip",
+            &[
+                ("annotations/captions_val.json", b"{}"),
+                ("annotations/instances_val.json", DATASET.as_bytes()),
+            ],
+        );
+        let file = read_annotation_file(&path, &LoadControl::default(), &mut |_| {}).unwrap();
+        assert_eq!(dataset_images(file), 2);
+
+        // 選べない場合はファイルの指定を求める
+        let path = write_zip(
+            "ambiguous.zip",
+            &[("train.json", DATASET.as_bytes()), ("val.json", b"[]")],
+        );
+        let error = read_annotation_file(&path, &LoadControl::default(), &mut |_| {})
+            .err()
+            .unwrap();
+        assert_eq!(error.code(), "archive_entry_required");
+
+        // zip内のパスを指定して読み込む
+        let file =
+            read_annotation_file(&path.join("val.json"), &LoadControl::default(), &mut |_| {})
+                .unwrap();
+        assert!(matches!(file, AnnotationFile::Results(ref r, _) if r.is_empty()));
+        let error = read_annotation_file(
+            &path.join("test.json"),
+            &LoadControl::default(),
+            &mut |_| {},
+        )
+        .err()
+        .unwrap();
+        assert_eq!(error.code(), "not_found");
+    }
+}
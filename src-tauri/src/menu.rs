@@ -12,6 +12,7 @@ pub struct MenuLabels {
     pub open_image: &'static str,
     pub open_annotations: &'static str,
     pub generate_sample: &'static str,
+    pub import_dataset: &'static str,
     pub export_annotations: &'static str,
     // View menu
     pub zoom_in: &'static str,
@@ -48,6 +49,7 @@ impl MenuLabels {
             open_image: "画像を開く",
             open_annotations: "アノテーションを開く",
             generate_sample: "サンプルデータを生成...",
+            import_dataset: "データセットをインポート...",
             export_annotations: "アノテーションをエクスポート...",
             zoom_in: "拡大",
             zoom_out: "縮小",
@@ -80,6 +82,7 @@ impl MenuLabels {
             open_image: "Open Image",
             open_annotations: "Open Annotations",
             generate_sample: "Generate Sample Data...",
+            import_dataset: "Import Dataset...",
             export_annotations: "Export Annotations...",
             zoom_in: "Zoom In",
             zoom_out: "Zoom Out",
@@ -142,6 +145,13 @@ pub fn create_menu_with_language(
                     Some("CmdOrCtrl+G"),
                 )?)
                 .separator()
+                .item(&MenuItem::with_id(
+                    app,
+                    "import_dataset",
+                    l.import_dataset,
+                    true,
+                    Some("CmdOrCtrl+Shift+I"),
+                )?)
                 .item(&MenuItem::with_id(
                     app,
                     "export_annotations",
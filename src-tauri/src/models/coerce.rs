@@ -0,0 +1,168 @@
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fmt::{self, Display};
+
+/// 読み込み時に型を変換した値（検証で警告として報告する）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Coercion {
+    /// `images[3].width` の形式（IDの場合は要素までのパス）
+    pub json_path: String,
+    /// 元の値（JSONの表記）
+    pub original: String,
+    /// 変換後の値（JSONの表記）
+    pub value: String,
+    pub reason: String,
+}
+
+thread_local! {
+    /// 記録中の変換（`collect` の実行中のみ `Some`）
+    static COERCIONS: RefCell<Option<Vec<Coercion>>> = const { RefCell::new(None) };
+}
+
+/// `f` の実行中に行われた変換を記録して返す
+///
+/// 記録はスレッドごとに行われる。`collect` の外（IPCで受け取ったデータなど）での変換は記録しない。
+pub fn collect<T>(f: impl FnOnce() -> T) -> (T, Vec<Coercion>) {
+    let previous = COERCIONS.with(|c| c.replace(Some(Vec::new())));
+    let value = f();
+    let coercions = COERCIONS.with(|c| c.replace(previous)).unwrap_or_default();
+    (value, coercions)
+}
+
+/// 現在までに記録された件数（`locate`・`discard` に渡す）
+pub fn mark() -> usize {
+    COERCIONS.with(|c| c.borrow().as_ref().map_or(0, Vec::len))
+}
+
+/// `mark` 以降に記録された変換のパスの先頭に `prefix`（`images`・`[3]` など）を付ける
+pub fn locate(mark: usize, prefix: impl Display) {
+    COERCIONS.with(|c| {
+        let mut coercions = c.borrow_mut();
+        let Some(located) = coercions.as_mut().and_then(|v| v.get_mut(mark..)) else {
+            return;
+        };
+        if located.is_empty() {
+            return;
+        }
+        let prefix = prefix.to_string();
+        for coercion in located {
+            coercion.json_path = join_path(&prefix, &coercion.json_path);
+        }
+    });
+}
+
+/// `mark` 以降に記録された変換を取り消す（読み込まなかった要素の分）
+pub fn discard(mark: usize) {
+    COERCIONS.with(|c| {
+        if let Some(coercions) = c.borrow_mut().as_mut() {
+            coercions.truncate(mark);
+        }
+    });
+}
+
+pub(crate) fn record(field: &str, original: impl Display, value: impl Display, reason: &str) {
+    COERCIONS.with(|c| {
+        if let Some(coercions) = c.borrow_mut().as_mut() {
+            coercions.push(Coercion {
+                json_path: field.to_string(),
+                original: original.to_string(),
+                value: value.to_string(),
+                reason: reason.to_string(),
+            });
+        }
+    });
+}
+
+fn join_path(prefix: &str, path: &str) -> String {
+    if path.is_empty() || prefix.is_empty() || path.starts_with('[') {
+        format!("{prefix}{path}")
+    } else {
+        format!("{prefix}.{path}")
+    }
+}
+
+/// 画像の `width`（浮動小数点数・数値の文字列は整数に変換する）
+pub fn width<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    deserializer.deserialize_any(IntVisitor {
+        field: "width",
+        allow_bool: false,
+    })
+}
+
+/// 画像の `height`（浮動小数点数・数値の文字列は整数に変換する）
+pub fn height<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    deserializer.deserialize_any(IntVisitor {
+        field: "height",
+        allow_bool: false,
+    })
+}
+
+/// アノテーションの `iscrowd`（真偽値は0・1に変換する）
+pub fn iscrowd<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    deserializer.deserialize_any(IntVisitor {
+        field: "iscrowd",
+        allow_bool: true,
+    })
+}
+
+/// 整数の項目を読み込み、整数以外の表記は変換して記録する
+struct IntVisitor {
+    field: &'static str,
+    allow_bool: bool,
+}
+
+impl IntVisitor {
+    /// `kind` は元の値の種類（`float`・`string`）
+    fn coerce_number<E: de::Error>(&self, v: f64, original: String, kind: &str) -> Result<i32, E> {
+        let rounded = v.round();
+        if !rounded.is_finite() || rounded < i32::MIN as f64 || rounded > i32::MAX as f64 {
+            return Err(E::invalid_value(de::Unexpected::Float(v), self));
+        }
+        let reason = if v.fract() == 0.0 {
+            format!("{kind} converted to integer")
+        } else {
+            format!("{kind} rounded to integer")
+        };
+        record(self.field, original, rounded as i32, &reason);
+        Ok(rounded as i32)
+    }
+}
+
+impl<'de> Visitor<'de> for IntVisitor {
+    type Value = i32;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an integer")
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<i32, E> {
+        i32::try_from(v).map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<i32, E> {
+        i32::try_from(v).map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<i32, E> {
+        self.coerce_number(v, format!("{v:?}"), "float")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<i32, E> {
+        if !self.allow_bool {
+            return Err(E::invalid_type(de::Unexpected::Bool(v), &self));
+        }
+        record(self.field, v, v as i32, "boolean converted to integer");
+        Ok(v as i32)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<i32, E> {
+        match v.trim().parse::<f64>() {
+            Ok(number) => self
+                .coerce_number(number, format!("\"{v}\""), "string")
+                .map_err(|_: E| E::invalid_value(de::Unexpected::Str(v), &self)),
+            Err(_) => Err(E::invalid_value(de::Unexpected::Str(v), &self)),
+        }
+    }
+}
@@ -0,0 +1,412 @@
+use super::coerce;
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// JavaScriptの数値で誤差なく表せる整数の最大値（`Number.MAX_SAFE_INTEGER`）
+pub const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// 画像・アノテーション・カテゴリのID（整数または文字列）
+///
+/// ラベリングツールが出力するUUIDなどの文字列IDはそのまま保持し、整数のIDは範囲にかかわらず整数のまま保持する。
+/// 整数値の浮動小数点数（`3.0`）は読み込み時に整数に変換し、変換は `coerce` に記録される。
+///
+/// フロントエンドはJavaScriptの数値で表せない整数を文字列として送り返すため、
+/// 整数の10進表記と一致する文字列のID（`"123"`）は整数のID（`123`）と等しいものとして比較する。
+#[derive(Debug, Clone)]
+pub enum CocoId {
+    Int(i64),
+    /// `i64` に収まらない整数（u64のハッシュ値など）
+    Uint(u64),
+    Str(String),
+}
+
+/// 比較・ハッシュに使う値（整数として解釈できる文字列は整数として扱う）
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Key<'a> {
+    Int(i128),
+    Str(&'a str),
+}
+
+impl CocoId {
+    /// 整数のIDの値（文字列のIDと `i64` に収まらないIDは `None`）
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            CocoId::Int(id) => Some(*id),
+            CocoId::Uint(_) | CocoId::Str(_) => None,
+        }
+    }
+
+    fn key(&self) -> Key<'_> {
+        match self {
+            CocoId::Int(id) => Key::Int((*id).into()),
+            CocoId::Uint(id) => Key::Int((*id).into()),
+            CocoId::Str(id) => parse_integer(id).map_or(Key::Str(id), Key::Int),
+        }
+    }
+}
+
+/// 整数の10進表記（先頭の `0`・`+`・`-0` を含まない）であれば値を返す
+fn parse_integer(id: &str) -> Option<i128> {
+    let digits = id.strip_prefix('-').unwrap_or(id);
+    let canonical = !digits.is_empty()
+        && digits.bytes().all(|b| b.is_ascii_digit())
+        && (digits == "0" || !digits.starts_with('0'))
+        && id != "-0";
+    if canonical {
+        id.parse().ok()
+    } else {
+        None
+    }
+}
+
+impl Default for CocoId {
+    fn default() -> Self {
+        CocoId::Int(0)
+    }
+}
+
+impl PartialEq for CocoId {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for CocoId {}
+
+impl Hash for CocoId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl PartialOrd for CocoId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 整数のIDを数値の順に先に並べ、文字列のIDはその後に並べる
+impl Ord for CocoId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl fmt::Display for CocoId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CocoId::Int(id) => write!(f, "{id}"),
+            CocoId::Uint(id) => write!(f, "{id}"),
+            CocoId::Str(id) => f.write_str(id),
+        }
+    }
+}
+
+impl From<i64> for CocoId {
+    fn from(id: i64) -> Self {
+        CocoId::Int(id)
+    }
+}
+
+impl From<i32> for CocoId {
+    fn from(id: i32) -> Self {
+        CocoId::Int(id.into())
+    }
+}
+
+impl From<u64> for CocoId {
+    fn from(id: u64) -> Self {
+        match i64::try_from(id) {
+            Ok(id) => CocoId::Int(id),
+            Err(_) => CocoId::Uint(id),
+        }
+    }
+}
+
+impl From<String> for CocoId {
+    fn from(id: String) -> Self {
+        CocoId::Str(id)
+    }
+}
+
+impl From<&str> for CocoId {
+    fn from(id: &str) -> Self {
+        CocoId::Str(id.to_string())
+    }
+}
+
+impl PartialEq<i64> for CocoId {
+    fn eq(&self, other: &i64) -> bool {
+        self.key() == Key::Int((*other).into())
+    }
+}
+
+thread_local! {
+    /// フロントエンドに返す値を書き出し中か（`Ipc` の書き出し中のみ `true`）
+    static IPC: Cell<bool> = const { Cell::new(false) };
+}
+
+/// フロントエンドとの間でやり取りする値
+///
+/// 書き出す間だけ、JavaScriptの数値で表せない整数のIDを文字列として書き出し、
+/// 読み込む間は、その範囲の整数の10進表記の文字列のIDを整数に戻す。
+/// ファイルやキャッシュへの書き出しでは整数のIDは常に整数のまま書き出す。
+#[derive(Debug, Clone)]
+pub struct Ipc<T>(pub T);
+
+impl<T: Serialize> Serialize for Ipc<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let previous = IPC.with(|ipc| ipc.replace(true));
+        let result = self.0.serialize(serializer);
+        IPC.with(|ipc| ipc.set(previous));
+        result
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Ipc<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let previous = IPC.with(|ipc| ipc.replace(true));
+        let result = T::deserialize(deserializer).map(Ipc);
+        IPC.with(|ipc| ipc.set(previous));
+        result
+    }
+}
+
+/// 文字列のIDを読み込む（`Ipc` の読み込み中は、文字列として書き出した整数を整数に戻す）
+fn string_id(id: String) -> CocoId {
+    if IPC.with(|ipc| ipc.get()) {
+        let unsafe_integer =
+            parse_integer(&id).filter(|v| v.unsigned_abs() > MAX_SAFE_INTEGER as u128);
+        if let Some(v) = unsafe_integer {
+            if let Ok(v) = i64::try_from(v) {
+                return CocoId::Int(v);
+            }
+            if let Ok(v) = u64::try_from(v) {
+                return CocoId::Uint(v);
+            }
+        }
+    }
+    CocoId::Str(id)
+}
+
+impl Serialize for CocoId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ipc = IPC.with(|ipc| ipc.get());
+        match self {
+            CocoId::Int(id) if !ipc || id.unsigned_abs() <= MAX_SAFE_INTEGER as u64 => {
+                serializer.serialize_i64(*id)
+            }
+            CocoId::Uint(id) if !ipc => serializer.serialize_u64(*id),
+            CocoId::Int(_) | CocoId::Uint(_) => serializer.collect_str(self),
+            CocoId::Str(id) => serializer.serialize_str(id),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for CocoId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(CocoIdVisitor)
+    }
+}
+
+struct CocoIdVisitor;
+
+impl<'de> Visitor<'de> for CocoIdVisitor {
+    type Value = CocoId;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an integer or string id")
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<CocoId, E> {
+        Ok(CocoId::Int(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<CocoId, E> {
+        Ok(CocoId::from(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<CocoId, E> {
+        if v.fract() != 0.0 || v.abs() > MAX_SAFE_INTEGER as f64 {
+            return Err(E::invalid_value(de::Unexpected::Float(v), &self));
+        }
+        coerce::record(
+            "",
+            format!("{v:?}"),
+            v as i64,
+            "float id converted to integer",
+        );
+        Ok(CocoId::Int(v as i64))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<CocoId, E> {
+        Ok(string_id(v.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<CocoId, E> {
+        Ok(string_id(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn read(json: &str) -> CocoId {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn to_file(id: &CocoId) -> String {
+        serde_json::to_string(id).unwrap()
+    }
+
+    fn to_frontend(id: &CocoId) -> String {
+        serde_json::to_string(&Ipc(id)).unwrap()
+    }
+
+    #[test]
+    fn keeps_integers_at_safe_boundaries() {
+        for value in [MAX_SAFE_INTEGER, -MAX_SAFE_INTEGER] {
+            let id = read(&value.to_string());
+            assert_eq!(id.as_int(), Some(value));
+            assert_eq!(to_file(&id), value.to_string());
+            assert_eq!(to_frontend(&id), value.to_string());
+        }
+        for value in [MAX_SAFE_INTEGER + 1, -MAX_SAFE_INTEGER - 1] {
+            let id = read(&value.to_string());
+            assert_eq!(id.as_int(), Some(value));
+            assert_eq!(to_file(&id), value.to_string());
+            assert_eq!(to_frontend(&id), format!("\"{value}\""));
+        }
+    }
+
+    #[test]
+    fn keeps_64_bit_integers_in_files() {
+        for value in [i64::MIN, i64::MAX] {
+            let id = read(&value.to_string());
+            assert_eq!(id.as_int(), Some(value));
+            assert_eq!(to_file(&id), value.to_string());
+            assert_eq!(to_frontend(&id), format!("\"{value}\""));
+        }
+
+        let id = read(&u64::MAX.to_string());
+        assert!(matches!(id, CocoId::Uint(u64::MAX)));
+        assert_eq!(id.as_int(), None);
+        assert_eq!(to_file(&id), u64::MAX.to_string());
+        assert_eq!(to_frontend(&id), format!("\"{}\"", u64::MAX));
+        assert_eq!(CocoId::from(i64::MAX as u64), CocoId::Int(i64::MAX));
+
+        // 書き出し後は通常の書き出しに戻る
+        assert_eq!(to_file(&CocoId::Int(i64::MAX)), i64::MAX.to_string());
+    }
+
+    #[test]
+    fn keeps_string_ids() {
+        let id = read(r#""a1b2-c3""#);
+        assert_eq!(id, CocoId::from("a1b2-c3"));
+        assert_eq!(id.as_int(), None);
+        assert_eq!(to_file(&id), r#""a1b2-c3""#);
+        assert_eq!(to_frontend(&id), r#""a1b2-c3""#);
+
+        assert_eq!(to_file(&read(r#""123""#)), r#""123""#);
+    }
+
+    #[test]
+    fn converts_whole_floats_only() {
+        let (id, coercions) = coerce::collect(|| read("3.0"));
+        assert_eq!(id.as_int(), Some(3));
+        assert_eq!(coercions.len(), 1);
+        assert_eq!(
+            (coercions[0].original.as_str(), coercions[0].value.as_str()),
+            ("3.0", "3")
+        );
+
+        assert!(serde_json::from_str::<CocoId>("3.5").is_err());
+        assert!(serde_json::from_str::<CocoId>("1e300").is_err());
+        assert!(serde_json::from_str::<CocoId>("null").is_err());
+
+        let (_, coercions) = coerce::collect(|| read(&i64::MAX.to_string()));
+        assert!(coercions.is_empty());
+    }
+
+    #[test]
+    fn restores_unsafe_integers_sent_back_from_frontend() {
+        for id in [
+            CocoId::Int(MAX_SAFE_INTEGER + 1),
+            CocoId::Int(i64::MIN),
+            CocoId::Uint(u64::MAX),
+        ] {
+            let json = to_frontend(&id);
+            let Ipc(restored) = serde_json::from_str::<Ipc<CocoId>>(&json).unwrap();
+            assert_eq!(to_file(&restored), id.to_string());
+            // IPC以外では文字列のIDのまま
+            assert_eq!(to_file(&read(&json)), json);
+        }
+
+        // 整数として書き出さない値は文字列のまま
+        for json in [
+            r#""123""#,
+            r#""0123""#,
+            r#""1e20""#,
+            r#""99999999999999999999999""#,
+        ] {
+            let Ipc(id) = serde_json::from_str::<Ipc<CocoId>>(json).unwrap();
+            assert_eq!(to_file(&id), json);
+        }
+    }
+
+    #[test]
+    fn integer_strings_equal_integers() {
+        let pairs = [
+            (CocoId::from("123"), CocoId::Int(123)),
+            (CocoId::from("-5"), CocoId::Int(-5)),
+            (CocoId::from("0"), CocoId::Int(0)),
+            (CocoId::from(i64::MIN.to_string()), CocoId::Int(i64::MIN)),
+            (CocoId::from(u64::MAX.to_string()), CocoId::Uint(u64::MAX)),
+        ];
+        for (text, number) in &pairs {
+            assert_eq!(text, number);
+            assert_eq!(text.cmp(number), Ordering::Equal);
+            let set: HashSet<_> = [text.clone(), number.clone()].into_iter().collect();
+            assert_eq!(set.len(), 1, "{text}");
+        }
+        assert_eq!(CocoId::from("123"), 123);
+
+        for text in ["0123", "+123", "-0", "1.0", " 1", "", "-"] {
+            assert_ne!(
+                CocoId::from(text),
+                CocoId::Int(text.trim().parse().unwrap_or(1))
+            );
+        }
+    }
+
+    #[test]
+    fn orders_integers_numerically_before_strings() {
+        let mut ids = [
+            CocoId::from("b"),
+            CocoId::Uint(u64::MAX),
+            CocoId::from("10"),
+            CocoId::Int(9),
+            CocoId::from("a"),
+            CocoId::Int(i64::MIN),
+        ];
+        ids.sort();
+        let sorted: Vec<_> = ids.iter().map(CocoId::to_string).collect();
+        assert_eq!(
+            sorted,
+            [
+                &i64::MIN.to_string(),
+                "9",
+                "10",
+                &u64::MAX.to_string(),
+                "a",
+                "b"
+            ]
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use super::{COCOData, COCOImage, CocoId};
+use std::collections::HashMap;
+
+/// 画像・カテゴリからアノテーションを引くための索引
+///
+/// 値は `COCOData::annotations`（画像は `COCOData::images`）内の位置。
+#[derive(Debug, Clone, Default)]
+pub struct DatasetIndex {
+    image_positions: HashMap<CocoId, usize>,
+    image_annotations: HashMap<CocoId, Vec<usize>>,
+    category_annotations: HashMap<CocoId, Vec<usize>>,
+    /// 動画ごとの画像（フレーム番号順）
+    video_frames: HashMap<CocoId, Vec<usize>>,
+    /// トラックごとのアノテーション（フレーム番号順）
+    track_annotations: HashMap<CocoId, Vec<usize>>,
+}
+
+impl DatasetIndex {
+    pub fn build(data: &COCOData) -> Self {
+        let mut index = DatasetIndex::default();
+
+        for (position, image) in data.images.iter().enumerate() {
+            index
+                .image_positions
+                .entry(image.id.clone())
+                .or_insert(position);
+            if let Some(video_id) = &image.video_id {
+                index
+                    .video_frames
+                    .entry(video_id.clone())
+                    .or_default()
+                    .push(position);
+            }
+        }
+
+        for (position, annotation) in data.annotations.iter().enumerate() {
+            index
+                .image_annotations
+                .entry(annotation.image_id.clone())
+                .or_default()
+                .push(position);
+            index
+                .category_annotations
+                .entry(annotation.category_id.clone())
+                .or_default()
+                .push(position);
+            if let Some(track_id) = &annotation.track_id {
+                index
+                    .track_annotations
+                    .entry(track_id.clone())
+                    .or_default()
+                    .push(position);
+            }
+        }
+
+        // フレーム番号の無い画像は最後に、同じフレーム番号は画像IDの順に並べる
+        let frame_order = |image: &COCOImage| {
+            (
+                image.frame_number().is_none(),
+                image.frame_number(),
+                image.id.clone(),
+            )
+        };
+        for positions in index.video_frames.values_mut() {
+            positions.sort_by_key(|&i| frame_order(&data.images[i]));
+        }
+        for positions in index.track_annotations.values_mut() {
+            positions.sort_by_key(|&i| {
+                let annotation = &data.annotations[i];
+                let image = index
+                    .image_positions
+                    .get(&annotation.image_id)
+                    .map(|&p| frame_order(&data.images[p]));
+                (image.is_none(), image, annotation.id.clone())
+            });
+        }
+
+        index
+    }
+
+    pub fn image_position(&self, image_id: &CocoId) -> Option<usize> {
+        self.image_positions.get(image_id).copied()
+    }
+
+    pub fn image_annotations(&self, image_id: &CocoId) -> &[usize] {
+        self.image_annotations
+            .get(image_id)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    pub fn category_annotations(&self, category_id: &CocoId) -> &[usize] {
+        self.category_annotations
+            .get(category_id)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// 動画の画像の位置（フレーム番号順）
+    pub fn video_frames(&self, video_id: &CocoId) -> &[usize] {
+        self.video_frames.get(video_id).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn video_ids(&self) -> impl Iterator<Item = &CocoId> {
+        self.video_frames.keys()
+    }
+
+    /// トラックのアノテーションの位置（フレーム番号順）
+    pub fn track_annotations(&self, track_id: &CocoId) -> &[usize] {
+        self.track_annotations
+            .get(track_id)
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data() -> COCOData {
+        serde_json::from_str(
+            r#"{
+                "images": [
+                    {"id": 10, "width": 1, "height": 1, "file_name": "c.jpg", "video_id": 1, "frame_id": 3},
+                    {"id": 11, "width": 1, "height": 1, "file_name": "a.jpg", "video_id": 1, "frame_id": 1},
+                    {"id": 12, "width": 1, "height": 1, "file_name": "b.jpg", "video_id": 1},
+                    {"id": 10, "width": 1, "height": 1, "file_name": "dup.jpg"}
+                ],
+                "annotations": [
+                    {"id": 1, "image_id": 10, "category_id": 1, "bbox": [0, 0, 1, 1], "area": 1, "iscrowd": 0, "track_id": 5},
+                    {"id": 2, "image_id": 11, "category_id": 2, "bbox": [0, 0, 1, 1], "area": 1, "iscrowd": 0, "track_id": "5"},
+                    {"id": 3, "image_id": 10, "category_id": 1, "bbox": [0, 0, 1, 1], "area": 1, "iscrowd": 0},
+                    {"id": 4, "image_id": 99, "category_id": 1, "bbox": [0, 0, 1, 1], "area": 1, "iscrowd": 0, "track_id": 5}
+                ],
+                "categories": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn indexes_images_and_categories() {
+        let index = DatasetIndex::build(&data());
+
+        // 重複した画像IDは最初の画像を指す
+        assert_eq!(index.image_position(&CocoId::Int(10)), Some(0));
+        assert_eq!(index.image_position(&CocoId::Int(12)), Some(2));
+        assert_eq!(index.image_position(&CocoId::Int(99)), None);
+
+        assert_eq!(index.image_annotations(&CocoId::Int(10)), &[0, 2]);
+        assert_eq!(index.image_annotations(&CocoId::Int(12)), &[] as &[usize]);
+        assert_eq!(index.category_annotations(&CocoId::Int(1)), &[0, 2, 3]);
+        assert_eq!(index.category_annotations(&CocoId::Int(2)), &[1]);
+    }
+
+    #[test]
+    fn orders_video_frames_and_tracks_by_frame() {
+        let index = DatasetIndex::build(&data());
+
+        // フレーム番号順、番号の無い画像は最後
+        assert_eq!(index.video_frames(&CocoId::Int(1)), &[1, 0, 2]);
+        assert_eq!(index.video_ids().collect::<Vec<_>>(), vec![&CocoId::Int(1)]);
+        assert_eq!(index.video_frames(&CocoId::Int(2)), &[] as &[usize]);
+
+        // 画像の無いアノテーションは最後（文字列の "5" も同じトラック）
+        assert_eq!(index.track_annotations(&CocoId::Int(5)), &[1, 0, 3]);
+    }
+}
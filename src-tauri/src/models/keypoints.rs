@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+/// キーポイントの可視性フラグ（COCO: 0=未ラベル, 1=ラベル済み・不可視, 2=ラベル済み・可視）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum Visibility {
+    NotLabeled,
+    Occluded,
+    Visible,
+}
+
+impl Visibility {
+    pub fn is_labeled(self) -> bool {
+        self != Visibility::NotLabeled
+    }
+}
+
+impl From<Visibility> for u8 {
+    fn from(visibility: Visibility) -> Self {
+        match visibility {
+            Visibility::NotLabeled => 0,
+            Visibility::Occluded => 1,
+            Visibility::Visible => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for Visibility {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Visibility::NotLabeled),
+            1 => Ok(Visibility::Occluded),
+            2 => Ok(Visibility::Visible),
+            _ => Err(format!("Invalid keypoint visibility: {value}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Keypoint {
+    pub x: f64,
+    pub y: f64,
+    pub visibility: Visibility,
+}
+
+/// COCOの `[x1, y1, v1, x2, y2, v2, ...]` 形式を `Keypoint` の列に変換する
+pub fn parse_keypoints(values: &[f64]) -> Result<Vec<Keypoint>, String> {
+    if values.len() % 3 != 0 {
+        return Err(format!(
+            "Keypoints length {} is not a multiple of 3",
+            values.len()
+        ));
+    }
+
+    values
+        .chunks_exact(3)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let v = chunk[2];
+            if v.fract() != 0.0 || !(0.0..=2.0).contains(&v) {
+                return Err(format!("Invalid visibility {v} for keypoint {i}"));
+            }
+            Ok(Keypoint {
+                x: chunk[0],
+                y: chunk[1],
+                visibility: Visibility::try_from(v as u8)?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_coco_triplets() {
+        let keypoints = parse_keypoints(&[10.0, 20.0, 2.0, 0.0, 0.0, 0.0, 5.5, 6.5, 1.0]).unwrap();
+        assert_eq!(keypoints.len(), 3);
+        assert_eq!(
+            keypoints[0],
+            Keypoint {
+                x: 10.0,
+                y: 20.0,
+                visibility: Visibility::Visible,
+            }
+        );
+        assert_eq!(keypoints[1].visibility, Visibility::NotLabeled);
+        assert!(!keypoints[1].visibility.is_labeled());
+        assert_eq!(keypoints[2].visibility, Visibility::Occluded);
+        assert!(keypoints[2].visibility.is_labeled());
+        assert!(parse_keypoints(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_length_and_visibility() {
+        assert!(parse_keypoints(&[1.0, 2.0]).is_err());
+        assert!(parse_keypoints(&[1.0, 2.0, 3.0]).is_err());
+        assert!(parse_keypoints(&[1.0, 2.0, 1.5]).is_err());
+        assert!(parse_keypoints(&[1.0, 2.0, -1.0]).is_err());
+    }
+
+    #[test]
+    fn visibility_serializes_as_coco_flag() {
+        assert_eq!(serde_json::to_string(&Visibility::Occluded).unwrap(), "1");
+        assert_eq!(
+            serde_json::from_str::<Visibility>("2").unwrap(),
+            Visibility::Visible
+        );
+        assert!(serde_json::from_str::<Visibility>("3").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use super::{COCOImage, CocoId};
+use serde::{Deserialize, Serialize};
+
+/// LVISのカテゴリの出現頻度の区分
+///
+/// 学習データでそのカテゴリを含む画像の枚数によって決まる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CategoryFrequency {
+    /// 1〜10枚
+    #[serde(rename = "r")]
+    Rare,
+    /// 11〜100枚
+    #[serde(rename = "c")]
+    Common,
+    /// 101枚以上
+    #[serde(rename = "f")]
+    Frequent,
+}
+
+impl CategoryFrequency {
+    /// JSONでの表記（`r`・`c`・`f`）
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CategoryFrequency::Rare => "r",
+            CategoryFrequency::Common => "c",
+            CategoryFrequency::Frequent => "f",
+        }
+    }
+
+    /// 画像の枚数から区分を求める（0枚の場合は `None`）
+    pub fn from_image_count(count: u64) -> Option<Self> {
+        match count {
+            0 => None,
+            1..=10 => Some(CategoryFrequency::Rare),
+            11..=100 => Some(CategoryFrequency::Common),
+            _ => Some(CategoryFrequency::Frequent),
+        }
+    }
+}
+
+impl COCOImage {
+    /// 画像に存在しないことが確認されたカテゴリか（`neg_category_ids`）
+    pub fn is_negative_category(&self, category_id: &CocoId) -> bool {
+        self.neg_category_ids
+            .as_ref()
+            .is_some_and(|ids| ids.contains(category_id))
+    }
+
+    /// すべてのインスタンスがラベル付けされているとは限らないカテゴリか（`not_exhaustive_category_ids`）
+    pub fn is_not_exhaustive_category(&self, category_id: &CocoId) -> bool {
+        self.not_exhaustive_category_ids
+            .as_ref()
+            .is_some_and(|ids| ids.contains(category_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frequency_follows_image_count_boundaries() {
+        let expected = [
+            (0, None),
+            (1, Some(CategoryFrequency::Rare)),
+            (10, Some(CategoryFrequency::Rare)),
+            (11, Some(CategoryFrequency::Common)),
+            (100, Some(CategoryFrequency::Common)),
+            (101, Some(CategoryFrequency::Frequent)),
+        ];
+        for (count, frequency) in expected {
+            assert_eq!(
+                CategoryFrequency::from_image_count(count),
+                frequency,
+                "{count}"
+            );
+        }
+    }
+
+    #[test]
+    fn frequency_uses_single_letters_in_json() {
+        for frequency in [
+            CategoryFrequency::Rare,
+            CategoryFrequency::Common,
+            CategoryFrequency::Frequent,
+        ] {
+            let json = serde_json::to_string(&frequency).unwrap();
+            assert_eq!(json, format!("\"{}\"", frequency.as_str()));
+            assert_eq!(
+                serde_json::from_str::<CategoryFrequency>(&json).unwrap(),
+                frequency
+            );
+        }
+        assert!(serde_json::from_str::<CategoryFrequency>(r#""x""#).is_err());
+    }
+
+    #[test]
+    fn checks_image_category_labels() {
+        let image: COCOImage = serde_json::from_str(
+            r#"{"id": 1, "width": 1, "height": 1, "file_name": "a.jpg",
+                "neg_category_ids": [2], "not_exhaustive_category_ids": [3]}"#,
+        )
+        .unwrap();
+        assert!(image.is_negative_category(&CocoId::from(2)));
+        assert!(!image.is_negative_category(&CocoId::from(3)));
+        assert!(image.is_not_exhaustive_category(&CocoId::from(3)));
+        assert!(!image.is_not_exhaustive_category(&CocoId::from(2)));
+
+        let image: COCOImage =
+            serde_json::from_str(r#"{"id": 1, "width": 1, "height": 1, "file_name": "a.jpg"}"#)
+                .unwrap();
+        assert!(!image.is_negative_category(&CocoId::from(2)));
+        assert!(!image.is_not_exhaustive_category(&CocoId::from(2)));
+    }
+}
@@ -2,18 +2,50 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub mod coerce;
+pub mod id;
+pub mod index;
+pub mod keypoints;
+pub mod lvis;
+pub mod number;
+pub mod results;
+pub mod rotated;
+pub mod segmentation;
+pub mod validation;
+pub mod video;
+
+pub use coerce::Coercion;
+pub use id::{CocoId, Ipc};
+pub use keypoints::Keypoint;
+pub use lvis::CategoryFrequency;
+pub use number::NumberStyle;
+pub use results::DetectionResult;
+pub use rotated::RotatedBox;
+pub use segmentation::Segmentation;
+pub use validation::ValidationReport;
+pub use video::{COCOTrack, COCOVideo};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct COCOData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub info: Option<COCOInfo>,
     pub images: Vec<COCOImage>,
     pub annotations: Vec<COCOAnnotation>,
     pub categories: Vec<COCOCategory>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub licenses: Option<Vec<COCOLicense>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub videos: Option<Vec<COCOVideo>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracks: Option<Vec<COCOTrack>>,
     #[serde(flatten)]
     pub extra: HashMap<String, Value>,
+    /// 読み込み時に型を変換した値（ファイル・フロントエンドには出力しない）
+    #[serde(skip)]
+    pub coercions: Vec<Coercion>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct COCOInfo {
     pub description: Option<String>,
     pub url: Option<String>,
@@ -25,7 +57,7 @@ pub struct COCOInfo {
     pub extra: HashMap<String, Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct COCOLicense {
     pub url: Option<String>,
     pub id: i32,
@@ -34,65 +66,120 @@ pub struct COCOLicense {
     pub extra: HashMap<String, Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct COCOImage {
-    pub id: i64,
+    pub id: CocoId,
+    #[serde(deserialize_with = "coerce::width")]
     pub width: i32,
+    #[serde(deserialize_with = "coerce::height")]
     pub height: i32,
     pub file_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub license: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub flickr_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub coco_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub date_captured: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub video_id: Option<CocoId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<i64>, // 動画内のフレーム番号
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub neg_category_ids: Option<Vec<CocoId>>, // LVIS: 画像に存在しないことを確認済みのカテゴリ
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_exhaustive_category_ids: Option<Vec<CocoId>>, // LVIS: すべてはラベル付けされていないカテゴリ
     #[serde(flatten)]
     pub extra: HashMap<String, Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct COCOAnnotation {
-    pub id: i64,
-    pub image_id: i64,
-    pub category_id: i32,
-    pub segmentation: Option<Vec<Vec<f64>>>,
+    pub id: CocoId,
+    pub image_id: CocoId,
+    pub category_id: CocoId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segmentation: Option<Segmentation>,
+    #[serde(
+        deserialize_with = "number::area",
+        serialize_with = "number::write_area"
+    )]
     pub area: f64,
+    #[serde(
+        deserialize_with = "number::bbox",
+        serialize_with = "number::write_bbox"
+    )]
     pub bbox: Vec<f64>, // [x, y, width, height]
+    #[serde(deserialize_with = "coerce::iscrowd")]
     pub iscrowd: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub option: Option<Value>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "number::keypoints",
+        serialize_with = "number::write_keypoints"
+    )]
+    pub keypoints: Option<Vec<f64>>, // [x1, y1, v1, x2, y2, v2, ...]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_keypoints: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>, // 検出結果の信頼度
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rbox: Option<RotatedBox>, // 回転矩形 [cx, cy, w, h, angle]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_id: Option<CocoId>, // 動画内で同じ物体を表すID
     #[serde(flatten)]
     pub extra: HashMap<String, Value>,
+    /// 元のファイルでの数値の表記（ファイル・フロントエンドには出力しない）
+    #[serde(skip)]
+    pub number_style: NumberStyle,
+}
+
+impl Serialize for COCOAnnotation {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        number::with_style(self.number_style, || {
+            COCOAnnotation::serialize(self, serializer)
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for COCOAnnotation {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (annotation, number_style) =
+            number::collect_style(|| COCOAnnotation::deserialize(deserializer));
+        annotation.map(|annotation| COCOAnnotation {
+            number_style,
+            ..annotation
+        })
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct COCOCategory {
-    pub id: i32,
+    pub id: CocoId,
     pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub supercategory: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keypoints: Option<Vec<String>>, // キーポイント名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skeleton: Option<Vec<[u32; 2]>>, // 1始まりのキーポイント番号の組
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isthing: Option<u8>, // パノプティック: 1は物体（thing）、0は領域（stuff）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<CategoryFrequency>, // LVIS: 出現頻度の区分（r/c/f）
     #[serde(flatten)]
     pub extra: HashMap<String, Value>,
 }
 
-impl COCOData {
-    #[allow(dead_code)]
-    pub fn validate(&self) -> Result<(), String> {
-        // 基本的なバリデーション
-        if self.images.is_empty() {
-            return Err("No images found in COCO data".to_string());
-        }
-
-        if self.categories.is_empty() {
-            return Err("No categories found in COCO data".to_string());
-        }
-
-        // BBoxの検証
-        for annotation in &self.annotations {
-            if annotation.bbox.len() != 4 {
-                return Err(format!(
-                    "Invalid bbox format for annotation {}",
-                    annotation.id
-                ));
-            }
-        }
-
-        Ok(())
+impl COCOAnnotation {
+    /// キーポイントを型付きで取得する（キーポイントを持たない場合は `None`）
+    pub fn keypoint_list(&self) -> Option<Result<Vec<Keypoint>, String>> {
+        self.keypoints
+            .as_ref()
+            .map(|values| keypoints::parse_keypoints(values))
     }
 }
@@ -0,0 +1,347 @@
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Serialize, Serializer};
+use std::cell::Cell;
+use std::fmt;
+
+/// 元のファイルで整数値を小数（`416.0`）として記述していた項目
+///
+/// 座標・面積は `f64` として読み込むため、書き出し時に元の表記に戻すために使う。
+/// 既定値（読み込み以外で作成したアノテーション）では整数値は整数として書き出す。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NumberStyle(u8);
+
+impl NumberStyle {
+    pub const AREA: NumberStyle = NumberStyle(1);
+    pub const BBOX: NumberStyle = NumberStyle(1 << 1);
+    pub const SEGMENTATION: NumberStyle = NumberStyle(1 << 2);
+    pub const KEYPOINTS: NumberStyle = NumberStyle(1 << 3);
+
+    pub fn from_bits(bits: u8) -> Self {
+        NumberStyle(bits)
+    }
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub fn contains(self, field: NumberStyle) -> bool {
+        self.0 & field.0 == field.0
+    }
+
+    fn insert(&mut self, field: NumberStyle) {
+        self.0 |= field.0;
+    }
+}
+
+thread_local! {
+    /// 読み込み中に記録した表記（`collect_style` の実行中のみ `Some`）
+    static RECORDED: Cell<Option<NumberStyle>> = const { Cell::new(None) };
+    /// 書き出し中のアノテーションの表記
+    static STYLE: Cell<NumberStyle> = const { Cell::new(NumberStyle(0)) };
+    /// `null` を `NaN` として読み込むか（`null_as_nan` の実行中のみ `true`）
+    static NULL_AS_NAN: Cell<bool> = const { Cell::new(false) };
+}
+
+/// `f` の実行中に読み込んだ項目の表記を記録して返す
+pub fn collect_style<T>(f: impl FnOnce() -> T) -> (T, NumberStyle) {
+    let previous = RECORDED.with(|r| r.replace(Some(NumberStyle::default())));
+    let value = f();
+    let style = RECORDED.with(|r| r.replace(previous)).unwrap_or_default();
+    (value, style)
+}
+
+/// `f` の実行中は `style` に従って項目を書き出す
+pub fn with_style<T>(style: NumberStyle, f: impl FnOnce() -> T) -> T {
+    let previous = STYLE.with(|s| s.replace(style));
+    let value = f();
+    STYLE.with(|s| s.set(previous));
+    value
+}
+
+/// `f` の実行中は座標・面積の `null` を `NaN` として読み込む
+///
+/// 寛容モードで `NaN`・`Infinity` を `null` に置き換えた要素を、除外せずに読み込むために使う。
+pub fn null_as_nan<T>(f: impl FnOnce() -> T) -> T {
+    let previous = NULL_AS_NAN.with(|n| n.replace(true));
+    let value = f();
+    NULL_AS_NAN.with(|n| n.set(previous));
+    value
+}
+
+/// 整数として書き出す絶対値の上限（JavaScriptで正確に扱える整数の範囲）
+const MAX_EXACT_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+fn is_whole(value: f64) -> bool {
+    value.fract() == 0.0 && value.abs() <= MAX_EXACT_INTEGER
+}
+
+/// 数値を1つ読み込み、整数値が小数として記述されていれば `field` を記録する
+struct Tracked(NumberStyle);
+
+impl<'de> Visitor<'de> for Tracked {
+    type Value = f64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a number")
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<f64, E> {
+        Ok(value as f64)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<f64, E> {
+        Ok(value as f64)
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<f64, E> {
+        if is_whole(value) {
+            RECORDED.with(|r| {
+                if let Some(mut style) = r.get() {
+                    style.insert(self.0);
+                    r.set(Some(style));
+                }
+            });
+        }
+        Ok(value)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<f64, E> {
+        Ok(f64::NAN)
+    }
+}
+
+impl<'de> de::DeserializeSeed<'de> for Tracked {
+    type Value = f64;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<f64, D::Error> {
+        // `null` は `null_as_nan` の実行中のみ受け付ける（それ以外は型の誤りになる）
+        if NULL_AS_NAN.with(|n| n.get()) {
+            deserializer.deserialize_any(self)
+        } else {
+            deserializer.deserialize_f64(self)
+        }
+    }
+}
+
+/// 数値の配列を読み込む
+struct TrackedVec(NumberStyle);
+
+impl<'de> Visitor<'de> for TrackedVec {
+    type Value = Vec<f64>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of numbers")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<f64>, A::Error> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(value) = seq.next_element_seed(Tracked(self.0))? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+impl<'de> de::DeserializeSeed<'de> for TrackedVec {
+    type Value = Vec<f64>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Vec<f64>, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+pub fn area<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    de::DeserializeSeed::deserialize(Tracked(NumberStyle::AREA), deserializer)
+}
+
+pub fn bbox<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f64>, D::Error> {
+    de::DeserializeSeed::deserialize(TrackedVec(NumberStyle::BBOX), deserializer)
+}
+
+pub fn keypoints<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<f64>>, D::Error> {
+    struct Keypoints;
+
+    impl<'de> Visitor<'de> for Keypoints {
+        type Value = Option<Vec<f64>>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an array of numbers or null")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            de::DeserializeSeed::deserialize(TrackedVec(NumberStyle::KEYPOINTS), deserializer)
+                .map(Some)
+        }
+    }
+
+    deserializer.deserialize_option(Keypoints)
+}
+
+/// ポリゴンの配列を読み込む
+pub fn polygons<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Vec<f64>>, D::Error> {
+    struct Polygons;
+
+    impl<'de> Visitor<'de> for Polygons {
+        type Value = Vec<Vec<f64>>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an array of polygons")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut polygons = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+            while let Some(polygon) =
+                seq.next_element_seed(TrackedVec(NumberStyle::SEGMENTATION))?
+            {
+                polygons.push(polygon);
+            }
+            Ok(polygons)
+        }
+    }
+
+    deserializer.deserialize_seq(Polygons)
+}
+
+/// 数値を書き出す（整数値は、元のファイルで小数として記述されていなければ整数として書き出す）
+struct Number(f64, NumberStyle);
+
+impl Serialize for Number {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Number(value, field) = *self;
+        if is_whole(value) && !STYLE.with(|s| s.get()).contains(field) {
+            serializer.serialize_i64(value as i64)
+        } else {
+            serializer.serialize_f64(value)
+        }
+    }
+}
+
+struct Numbers<'a>(&'a [f64], NumberStyle);
+
+impl Serialize for Numbers<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for value in self.0 {
+            seq.serialize_element(&Number(*value, self.1))?;
+        }
+        seq.end()
+    }
+}
+
+pub fn write_area<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    Number(*value, NumberStyle::AREA).serialize(serializer)
+}
+
+pub fn write_bbox<S: Serializer>(values: &[f64], serializer: S) -> Result<S::Ok, S::Error> {
+    Numbers(values, NumberStyle::BBOX).serialize(serializer)
+}
+
+pub fn write_keypoints<S: Serializer>(
+    values: &Option<Vec<f64>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match values {
+        Some(values) => Numbers(values, NumberStyle::KEYPOINTS).serialize(serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn write_polygons<S: Serializer>(
+    polygons: &[Vec<f64>],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(Some(polygons.len()))?;
+    for polygon in polygons {
+        seq.serialize_element(&Numbers(polygon, NumberStyle::SEGMENTATION))?;
+    }
+    seq.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Sample {
+        #[serde(deserialize_with = "area")]
+        area: f64,
+        #[serde(deserialize_with = "bbox")]
+        bbox: Vec<f64>,
+    }
+
+    fn to_json(value: f64, style: NumberStyle) -> String {
+        with_style(style, || {
+            serde_json::to_string(&Number(value, NumberStyle::AREA)).unwrap()
+        })
+    }
+
+    #[test]
+    fn writes_whole_numbers_as_integers_by_default() {
+        let style = NumberStyle::default();
+        assert_eq!(to_json(12.0, style), "12");
+        assert_eq!(to_json(-3.0, style), "-3");
+        assert_eq!(to_json(12.5, style), "12.5");
+        assert_eq!(to_json(0.30000000000000004, style), "0.30000000000000004");
+        assert_eq!(to_json(9_007_199_254_740_991.0, style), "9007199254740991");
+        // 正確に扱えない大きさの値は小数のまま
+        assert_eq!(to_json(1e300, style), "1e300");
+        assert_eq!(to_json(f64::NAN, style), "null");
+    }
+
+    #[test]
+    fn keeps_float_notation_of_recorded_fields() {
+        assert_eq!(to_json(12.0, NumberStyle::AREA), "12.0");
+        assert_eq!(to_json(12.0, NumberStyle::BBOX), "12");
+    }
+
+    #[test]
+    fn records_whole_numbers_written_as_floats() {
+        let (sample, style) = collect_style(|| {
+            serde_json::from_str::<Sample>(r#"{"area": 12.5, "bbox": [1, 2.0, 3.5, 4]}"#).unwrap()
+        });
+        assert_eq!(sample.area, 12.5);
+        assert_eq!(sample.bbox, [1.0, 2.0, 3.5, 4.0]);
+        assert!(style.contains(NumberStyle::BBOX));
+        assert!(!style.contains(NumberStyle::AREA));
+
+        let (_, style) = collect_style(|| {
+            serde_json::from_str::<Sample>(r#"{"area": 12, "bbox": [1, 2, 3.5, 4]}"#).unwrap()
+        });
+        assert_eq!(style, NumberStyle::default());
+
+        // 記録は collect_style の中だけで行われる
+        serde_json::from_str::<Sample>(r#"{"area": 1.0, "bbox": []}"#).unwrap();
+        assert_eq!(RECORDED.with(|r| r.get()), None);
+    }
+
+    #[test]
+    fn reads_null_as_nan_only_when_enabled() {
+        let json = r#"{"area": null, "bbox": [1, null, 3, 4]}"#;
+        assert!(serde_json::from_str::<Sample>(json).is_err());
+
+        let sample = null_as_nan(|| serde_json::from_str::<Sample>(json)).unwrap();
+        assert!(sample.area.is_nan());
+        assert!(sample.bbox[1].is_nan());
+        assert_eq!(sample.bbox[3], 4.0);
+        assert!(
+            null_as_nan(|| serde_json::from_str::<Sample>(r#"{"area": "1", "bbox": []}"#)).is_err()
+        );
+
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert!(null_as_nan(|| Sample::deserialize(value)).is_ok());
+        assert!(!NULL_AS_NAN.with(|n| n.get()));
+    }
+}
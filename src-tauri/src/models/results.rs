@@ -0,0 +1,119 @@
+use super::{COCOAnnotation, COCOData, CocoId, RotatedBox, Segmentation};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 検出結果ファイル（pycocotools/detectron2/mmdet形式）の1要素
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionResult {
+    pub image_id: CocoId,
+    pub category_id: CocoId,
+    #[serde(default)]
+    pub bbox: Option<Vec<f64>>,
+    #[serde(default)]
+    pub score: Option<f64>,
+    #[serde(default)]
+    pub segmentation: Option<Segmentation>,
+    #[serde(default)]
+    pub keypoints: Option<Vec<f64>>,
+    /// 追跡結果（TAO・MOTなど）のトラックID
+    #[serde(default)]
+    pub track_id: Option<CocoId>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl COCOData {
+    /// 正解データの画像・カテゴリに検出結果を結合したデータセットを作成する
+    ///
+    /// pycocotools の `loadRes` と同様に、アノテーションIDは1からの連番を振り、
+    /// `bbox`・`area` が無い場合はセグメンテーションやキーポイントから求める。
+    /// `bbox` が5要素の場合（mmrotateなどの回転矩形）は `rbox` とし、外接矩形を `bbox` とする。
+    pub fn from_results(reference: &COCOData, results: Vec<DetectionResult>) -> COCOData {
+        let annotations = results
+            .into_iter()
+            .enumerate()
+            .map(|(i, result)| {
+                let rbox = match result.bbox.as_deref() {
+                    Some(&[cx, cy, w, h, angle]) => Some(RotatedBox::from([cx, cy, w, h, angle])),
+                    _ => None,
+                };
+                let bbox = rbox
+                    .map(|rbox| rbox.bbox().to_vec())
+                    .or(result.bbox)
+                    .or_else(|| {
+                        result
+                            .segmentation
+                            .as_ref()
+                            .and_then(|s| s.bbox())
+                            .map(|b| b.to_vec())
+                    })
+                    .or_else(|| result.keypoints.as_deref().and_then(keypoints_bbox))
+                    .unwrap_or_else(|| vec![0.0; 4]);
+
+                let area = match (&result.segmentation, rbox) {
+                    (Some(segmentation), _) => segmentation.area(),
+                    (None, Some(rbox)) => rbox.area(),
+                    (None, None) if bbox.len() == 4 => bbox[2] * bbox[3],
+                    (None, None) => 0.0,
+                };
+
+                let num_keypoints = result.keypoints.as_ref().map(|values| {
+                    values
+                        .chunks_exact(3)
+                        .filter(|chunk| chunk[2] > 0.0)
+                        .count() as u32
+                });
+
+                COCOAnnotation {
+                    id: CocoId::from(i as i64 + 1),
+                    image_id: result.image_id,
+                    category_id: result.category_id,
+                    segmentation: result.segmentation,
+                    area,
+                    bbox,
+                    iscrowd: 0,
+                    option: None,
+                    keypoints: result.keypoints,
+                    num_keypoints,
+                    score: result.score,
+                    rbox,
+                    track_id: result.track_id,
+                    extra: result.extra,
+                    number_style: Default::default(),
+                }
+            })
+            .collect();
+
+        COCOData {
+            info: reference.info.clone(),
+            images: reference.images.clone(),
+            annotations,
+            categories: reference.categories.clone(),
+            licenses: reference.licenses.clone(),
+            videos: reference.videos.clone(),
+            tracks: None,
+            extra: HashMap::new(),
+            // 検出結果自体の変換は読み込み側で追加する
+            coercions: reference
+                .coercions
+                .iter()
+                .filter(|c| !c.json_path.starts_with("annotations"))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// キーポイントの外接矩形（pycocotools と同様に座標の最小・最大から求める）
+fn keypoints_bbox(values: &[f64]) -> Option<Vec<f64>> {
+    let xs = values.iter().step_by(3);
+    let ys = values.iter().skip(1).step_by(3);
+    let (x0, x1) = xs.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &x| {
+        (lo.min(x), hi.max(x))
+    });
+    let (y0, y1) = ys.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &y| {
+        (lo.min(y), hi.max(y))
+    });
+    (x0 <= x1 && y0 <= y1).then(|| vec![x0, y0, x1 - x0, y1 - y0])
+}
@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+
+/// 回転矩形 `[cx, cy, w, h, angle]`
+///
+/// `angle` は度数法で、画像座標（y軸が下向き）での回転角（画面上では時計回りが正）。
+/// 幅 `w` は回転前のx軸方向、高さ `h` はy軸方向の長さ。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(from = "[f64; 5]", into = "[f64; 5]")]
+pub struct RotatedBox {
+    pub cx: f64,
+    pub cy: f64,
+    pub w: f64,
+    pub h: f64,
+    pub angle: f64,
+}
+
+impl From<[f64; 5]> for RotatedBox {
+    fn from([cx, cy, w, h, angle]: [f64; 5]) -> Self {
+        RotatedBox {
+            cx,
+            cy,
+            w,
+            h,
+            angle,
+        }
+    }
+}
+
+impl From<RotatedBox> for [f64; 5] {
+    fn from(rbox: RotatedBox) -> Self {
+        [rbox.cx, rbox.cy, rbox.w, rbox.h, rbox.angle]
+    }
+}
+
+impl RotatedBox {
+    /// 軸に平行な矩形 `[x, y, w, h]` から作成する（回転角は0）
+    pub fn from_bbox(bbox: [f64; 4]) -> Self {
+        let [x, y, w, h] = bbox;
+        RotatedBox {
+            cx: x + w / 2.0,
+            cy: y + h / 2.0,
+            w,
+            h,
+            angle: 0.0,
+        }
+    }
+
+    /// 4頂点（回転前の左上・右上・右下・左下の順）
+    pub fn corners(self) -> [[f64; 2]; 4] {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let (hw, hh) = (self.w / 2.0, self.h / 2.0);
+        [(-hw, -hh), (hw, -hh), (hw, hh), (-hw, hh)]
+            .map(|(dx, dy)| [self.cx + dx * cos - dy * sin, self.cy + dx * sin + dy * cos])
+    }
+
+    /// COCOのポリゴン `[x1, y1, ..., x4, y4]`
+    pub fn to_polygon(self) -> Vec<f64> {
+        self.corners().iter().flatten().copied().collect()
+    }
+
+    /// 回転矩形を囲む軸に平行な矩形 `[x, y, w, h]`
+    pub fn bbox(self) -> [f64; 4] {
+        let corners = self.corners();
+        let xs = corners.map(|c| c[0]);
+        let ys = corners.map(|c| c[1]);
+        let min_x = xs.iter().copied().fold(f64::INFINITY, f64::min);
+        let max_x = xs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let min_y = ys.iter().copied().fold(f64::INFINITY, f64::min);
+        let max_y = ys.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        [min_x, min_y, max_x - min_x, max_y - min_y]
+    }
+
+    pub fn area(self) -> f64 {
+        self.w * self.h
+    }
+
+    /// ポリゴンを囲む面積最小の回転矩形を求める（OpenCVの `minAreaRect` に相当）
+    ///
+    /// DOTAの4点は厳密な長方形とは限らないため、凸包の各辺に沿った外接矩形から選ぶ。
+    /// 頂点が3点未満の場合は `None` を返す。
+    pub fn from_polygon(polygon: &[f64]) -> Option<Self> {
+        let points: Vec<[f64; 2]> = polygon.chunks_exact(2).map(|p| [p[0], p[1]]).collect();
+        let hull = convex_hull(points);
+        if hull.len() < 3 {
+            return None;
+        }
+
+        let mut best: Option<(f64, RotatedBox)> = None;
+        for i in 0..hull.len() {
+            let [x1, y1] = hull[i];
+            let [x2, y2] = hull[(i + 1) % hull.len()];
+            let length = (x2 - x1).hypot(y2 - y1);
+            if length == 0.0 {
+                continue;
+            }
+            // 辺の方向を軸とした座標系で外接矩形を求める
+            let (ux, uy) = ((x2 - x1) / length, (y2 - y1) / length);
+            let (mut min_u, mut max_u, mut min_v, mut max_v) = (
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+            );
+            for &[x, y] in &hull {
+                let u = x * ux + y * uy;
+                let v = -x * uy + y * ux;
+                min_u = min_u.min(u);
+                max_u = max_u.max(u);
+                min_v = min_v.min(v);
+                max_v = max_v.max(v);
+            }
+            let area = (max_u - min_u) * (max_v - min_v);
+            if best
+                .as_ref()
+                .is_some_and(|(best_area, _)| *best_area <= area)
+            {
+                continue;
+            }
+            let (cu, cv) = ((min_u + max_u) / 2.0, (min_v + max_v) / 2.0);
+            let rbox = RotatedBox {
+                cx: cu * ux - cv * uy,
+                cy: cu * uy + cv * ux,
+                w: max_u - min_u,
+                h: max_v - min_v,
+                angle: uy.atan2(ux).to_degrees(),
+            };
+            best = Some((area, rbox));
+        }
+        best.map(|(_, rbox)| rbox.normalized())
+    }
+
+    /// 回転角を `[-45, 45)` 度に収める（幅と高さを入れ替えて同じ矩形を表す）
+    pub fn normalized(self) -> Self {
+        let mut rbox = self;
+        rbox.angle = (rbox.angle + 45.0).rem_euclid(180.0) - 45.0;
+        if rbox.angle >= 45.0 {
+            rbox.angle -= 90.0;
+            std::mem::swap(&mut rbox.w, &mut rbox.h);
+        }
+        rbox
+    }
+}
+
+/// 凸包（Andrewのモノトーンチェイン法、反時計回り）
+fn convex_hull(mut points: Vec<[f64; 2]>) -> Vec<[f64; 2]> {
+    points.retain(|p| p[0].is_finite() && p[1].is_finite());
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: [f64; 2], a: [f64; 2], b: [f64; 2]| {
+        (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+    };
+    let mut hull: Vec<[f64; 2]> = Vec::with_capacity(points.len() * 2);
+    for pass in 0..2 {
+        let start = hull.len();
+        let iter: Box<dyn Iterator<Item = &[f64; 2]>> = if pass == 0 {
+            Box::new(points.iter())
+        } else {
+            Box::new(points.iter().rev())
+        };
+        for &p in iter {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?} != {expected:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn converts_between_boxes_and_polygons() {
+        let rbox = RotatedBox::from_bbox([10.0, 20.0, 4.0, 2.0]);
+        assert_eq!(<[f64; 5]>::from(rbox), [12.0, 21.0, 4.0, 2.0, 0.0]);
+        assert_close(
+            &rbox.to_polygon(),
+            &[10.0, 20.0, 14.0, 20.0, 14.0, 22.0, 10.0, 22.0],
+        );
+
+        // 90度回転すると外接矩形の幅と高さが入れ替わる
+        let rotated = RotatedBox {
+            angle: 90.0,
+            ..rbox
+        };
+        assert_close(&rotated.bbox(), &[11.0, 19.0, 2.0, 4.0]);
+        assert_eq!(rotated.area(), 8.0);
+        assert_eq!(
+            serde_json::to_string(&rotated).unwrap(),
+            "[12.0,21.0,4.0,2.0,90.0]"
+        );
+    }
+
+    #[test]
+    fn finds_minimum_area_rect_of_polygon() {
+        // 30度回転した 4x2 の矩形
+        let source = RotatedBox {
+            cx: 5.0,
+            cy: 5.0,
+            w: 4.0,
+            h: 2.0,
+            angle: 30.0,
+        };
+        let rbox = RotatedBox::from_polygon(&source.to_polygon()).unwrap();
+        assert_close(&<[f64; 5]>::from(rbox), &<[f64; 5]>::from(source));
+
+        // 頂点の順序や向きによらず [-45, 45) 度に正規化する
+        let mut reversed: Vec<[f64; 2]> = RotatedBox {
+            angle: 60.0,
+            ..source
+        }
+        .corners()
+        .to_vec();
+        reversed.reverse();
+        let polygon: Vec<f64> = reversed.iter().flatten().copied().collect();
+        let rbox = RotatedBox::from_polygon(&polygon).unwrap();
+        assert_close(&<[f64; 5]>::from(rbox), &[5.0, 5.0, 2.0, 4.0, -30.0]);
+
+        assert_eq!(
+            RotatedBox::from_polygon(&[0.0, 0.0, 1.0, 1.0, 2.0, 2.0]),
+            None
+        );
+        assert_eq!(RotatedBox::from_polygon(&[0.0, 0.0, 1.0]), None);
+    }
+}
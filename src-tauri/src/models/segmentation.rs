@@ -0,0 +1,375 @@
+use serde::{Deserialize, Serialize};
+
+/// COCOのセグメンテーション表現
+///
+/// `iscrowd: 0` のアノテーションはポリゴン配列、`iscrowd: 1` のアノテーションは
+/// RLE（非圧縮の `counts` 配列、またはpycocotools形式の圧縮文字列）で記述される。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Segmentation {
+    Polygon(
+        #[serde(
+            deserialize_with = "super::number::polygons",
+            serialize_with = "super::number::write_polygons"
+        )]
+        Vec<Vec<f64>>,
+    ),
+    Rle(Rle),
+    CompressedRle(CompressedRle),
+}
+
+/// 非圧縮RLE（`counts` は列優先順で 0 の連長から始まる）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rle {
+    pub size: [u32; 2], // [height, width]
+    pub counts: Vec<u32>,
+}
+
+/// pycocotools形式で文字列に圧縮されたRLE
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompressedRle {
+    pub size: [u32; 2], // [height, width]
+    pub counts: String,
+}
+
+impl Segmentation {
+    /// ピクセル面積（ポリゴンはShoelace公式の合計、RLEは前景ピクセル数）
+    pub fn area(&self) -> f64 {
+        match self {
+            Segmentation::Polygon(polygons) => polygons.iter().map(|p| polygon_area(p)).sum(),
+            Segmentation::Rle(rle) => rle.area() as f64,
+            Segmentation::CompressedRle(rle) => {
+                rle.decompress().map(|rle| rle.area() as f64).unwrap_or(0.0)
+            }
+        }
+    }
+
+    /// セグメンテーションを囲む `[x, y, width, height]`（空の場合は `None`）
+    pub fn bbox(&self) -> Option<[f64; 4]> {
+        match self {
+            Segmentation::Polygon(polygons) => polygons_bbox(polygons),
+            Segmentation::Rle(rle) => rle.bbox(),
+            Segmentation::CompressedRle(rle) => rle.decompress().ok().and_then(|rle| rle.bbox()),
+        }
+    }
+}
+
+impl Rle {
+    /// 行優先（row-major）の `height * width` マスクからRLEを生成する
+    pub fn encode(mask: &[u8], height: u32, width: u32) -> Result<Self, String> {
+        let (h, w) = (height as usize, width as usize);
+        if mask.len() != h * w {
+            return Err(format!(
+                "Mask length {} does not match size {height}x{width}",
+                mask.len()
+            ));
+        }
+
+        let mut counts = Vec::new();
+        let mut current = 0u8;
+        let mut run = 0u32;
+        // COCOのRLEは列優先（Fortran順）で走査する
+        for x in 0..w {
+            for y in 0..h {
+                let value = u8::from(mask[y * w + x] != 0);
+                if value != current {
+                    counts.push(run);
+                    run = 0;
+                    current = value;
+                }
+                run += 1;
+            }
+        }
+        counts.push(run);
+
+        Ok(Rle {
+            size: [height, width],
+            counts,
+        })
+    }
+
+    /// pycocotools互換の文字列形式に圧縮する
+    pub fn compress(&self) -> CompressedRle {
+        let mut counts = String::new();
+        for i in 0..self.counts.len() {
+            let mut x = self.counts[i] as i64;
+            if i > 2 {
+                x -= self.counts[i - 2] as i64;
+            }
+            let mut more = true;
+            while more {
+                let mut c = x & 0x1f;
+                x >>= 5;
+                more = if c & 0x10 != 0 { x != -1 } else { x != 0 };
+                if more {
+                    c |= 0x20;
+                }
+                counts.push((c as u8 + 48) as char);
+            }
+        }
+
+        CompressedRle {
+            size: self.size,
+            counts,
+        }
+    }
+
+    /// 前景ピクセル数
+    pub fn area(&self) -> u64 {
+        self.counts
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .map(|&c| c as u64)
+            .sum()
+    }
+
+    /// 前景を囲む `[x, y, width, height]`（前景が無い場合は `None`）
+    pub fn bbox(&self) -> Option<[f64; 4]> {
+        let h = self.size[0] as u64;
+        if h == 0 || self.area() == 0 {
+            return None;
+        }
+
+        // pycocotools の rleToBbox と同じ走査（末尾の背景の連長は無視する）
+        let m = self.counts.len() / 2 * 2;
+        let (mut xs, mut ys) = (u64::MAX, u64::MAX);
+        let (mut xe, mut ye) = (0u64, 0u64);
+        let mut cc = 0u64;
+        let mut xp = 0u64;
+        for j in 0..m {
+            cc += self.counts[j] as u64;
+            let t = cc.saturating_sub((j % 2) as u64);
+            let y = t % h;
+            let x = t / h;
+            if j % 2 == 0 {
+                xp = x;
+            } else if xp < x {
+                // 前景が列をまたぐ場合は縦方向全体を含む
+                ys = 0;
+                ye = h - 1;
+            }
+            xs = xs.min(x);
+            xe = xe.max(x);
+            ys = ys.min(y);
+            ye = ye.max(y);
+        }
+
+        Some([
+            xs as f64,
+            ys as f64,
+            (xe - xs + 1) as f64,
+            (ye - ys + 1) as f64,
+        ])
+    }
+}
+
+impl CompressedRle {
+    /// pycocotools互換の文字列を非圧縮RLEに展開する
+    pub fn decompress(&self) -> Result<Rle, String> {
+        let bytes = self.counts.as_bytes();
+        let mut counts: Vec<u32> = Vec::new();
+        let mut p = 0;
+
+        while p < bytes.len() {
+            let mut x: i64 = 0;
+            let mut k = 0;
+            let mut more = true;
+            while more {
+                let byte = *bytes
+                    .get(p)
+                    .ok_or_else(|| "Truncated compressed RLE counts".to_string())?;
+                if !(48..48 + 64).contains(&byte) {
+                    return Err(format!(
+                        "Invalid character in compressed RLE counts: {byte}"
+                    ));
+                }
+                if k >= 12 {
+                    return Err("Compressed RLE count is too large".to_string());
+                }
+                let c = (byte - 48) as i64;
+                x |= (c & 0x1f) << (5 * k);
+                more = c & 0x20 != 0;
+                p += 1;
+                k += 1;
+                if !more && c & 0x10 != 0 {
+                    x |= -1i64 << (5 * k);
+                }
+            }
+            if counts.len() > 2 {
+                x += counts[counts.len() - 2] as i64;
+            }
+            let count = u32::try_from(x)
+                .map_err(|_| format!("Invalid run length in compressed RLE counts: {x}"))?;
+            counts.push(count);
+        }
+
+        Ok(Rle {
+            size: self.size,
+            counts,
+        })
+    }
+}
+
+/// 単一ポリゴン `[x1, y1, x2, y2, ...]` の面積（Shoelace公式）
+pub fn polygon_area(polygon: &[f64]) -> f64 {
+    let points: Vec<(f64, f64)> = polygon
+        .chunks_exact(2)
+        .map(|chunk| (chunk[0], chunk[1]))
+        .collect();
+    if points.len() < 3 {
+        return 0.0;
+    }
+
+    let mut sum = 0.0;
+    for i in 0..points.len() {
+        let (x0, y0) = points[i];
+        let (x1, y1) = points[(i + 1) % points.len()];
+        sum += x0 * y1 - x1 * y0;
+    }
+    sum.abs() / 2.0
+}
+
+/// ポリゴン群を囲む `[x, y, width, height]`
+pub fn polygons_bbox(polygons: &[Vec<f64>]) -> Option<[f64; 4]> {
+    let mut min_x = f64::INFINITY;
+    let mut min_y = f64::INFINITY;
+    let mut max_x = f64::NEG_INFINITY;
+    let mut max_y = f64::NEG_INFINITY;
+
+    for chunk in polygons.iter().flat_map(|p| p.chunks_exact(2)) {
+        min_x = min_x.min(chunk[0]);
+        min_y = min_y.min(chunk[1]);
+        max_x = max_x.max(chunk[0]);
+        max_y = max_y.max(chunk[1]);
+    }
+
+    if min_x > max_x || min_y > max_y {
+        return None;
+    }
+    Some([min_x, min_y, max_x - min_x, max_y - min_y])
+}
+
+#[cfg(test)]
+impl Rle {
+    /// 行優先（row-major）の `height * width` マスク（0/1）に展開する（テストでエンコード結果を確認する）
+    pub fn decode(&self) -> Result<Vec<u8>, String> {
+        let [h, w] = [self.size[0] as usize, self.size[1] as usize];
+        let total: u64 = self.counts.iter().map(|&c| c as u64).sum();
+        if total != (h * w) as u64 {
+            return Err(format!("RLE counts sum to {total}, expected {}", h * w));
+        }
+
+        let mut mask = vec![0u8; h * w];
+        let mut position = 0usize;
+        for (i, &count) in self.counts.iter().enumerate() {
+            if i % 2 == 1 {
+                for p in position..position + count as usize {
+                    // 列優先のインデックスを行優先に変換
+                    mask[(p % h) * w + p / h] = 1;
+                }
+            }
+            position += count as usize;
+        }
+
+        Ok(mask)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compressed(counts: &str, size: [u32; 2]) -> CompressedRle {
+        CompressedRle {
+            size,
+            counts: counts.to_string(),
+        }
+    }
+
+    #[test]
+    fn compress_matches_pycocotools_strings() {
+        // 期待値は pycocotools.mask.frPyObjects(...)["counts"] の出力
+        let cases: [(&[u32], &str); 3] = [
+            (&[2, 1, 1, 5], "2114"),
+            (&[0, 6, 1, 100, 3, 50], "061n22^N"),
+            (
+                &[123456, 50, 999, 10000, 3, 865532],
+                "Pbh3b1Wo0nf9lPO\\_Sj0",
+            ),
+        ];
+        for (counts, expected) in cases {
+            let total: u32 = counts.iter().sum();
+            let rle = Rle {
+                size: [1, total],
+                counts: counts.to_vec(),
+            };
+            assert_eq!(rle.compress().counts, expected);
+            assert_eq!(compressed(expected, [1, total]).decompress().unwrap(), rle);
+        }
+    }
+
+    #[test]
+    fn decompress_rejects_invalid_strings() {
+        assert!(compressed("2 1", [1, 3]).decompress().is_err());
+        assert!(compressed("o", [1, 3]).decompress().is_err());
+        assert!(compressed("oooooooooooo1", [1, 3]).decompress().is_err());
+    }
+
+    #[test]
+    fn encode_decode_round_trip_is_column_major() {
+        // 3x2 マスク（行優先）:
+        // 0 1
+        // 1 1
+        // 0 0
+        let mask = [0, 1, 1, 1, 0, 0];
+        let rle = Rle::encode(&mask, 3, 2).unwrap();
+        assert_eq!(rle.counts, vec![1, 1, 1, 2, 1]);
+        assert_eq!(rle.decode().unwrap(), mask);
+        assert_eq!(rle.area(), 3);
+        assert_eq!(rle.bbox(), Some([0.0, 0.0, 2.0, 2.0]));
+
+        assert!(Rle::encode(&mask, 2, 2).is_err());
+        let broken = Rle {
+            size: [3, 2],
+            counts: vec![1, 1],
+        };
+        assert!(broken.decode().is_err());
+    }
+
+    #[test]
+    fn empty_mask_has_no_bbox() {
+        let rle = Rle::encode(&[0; 4], 2, 2).unwrap();
+        assert_eq!(rle.counts, vec![4]);
+        assert_eq!(rle.area(), 0);
+        assert_eq!(rle.bbox(), None);
+    }
+
+    #[test]
+    fn polygon_helpers_ignore_degenerate_input() {
+        assert_eq!(polygon_area(&[0.0, 0.0, 4.0, 0.0, 4.0, 3.0]), 6.0);
+        assert_eq!(polygon_area(&[0.0, 0.0, 1.0, 1.0]), 0.0);
+        assert_eq!(polygons_bbox(&[]), None);
+
+        let square = Segmentation::Polygon(vec![vec![1.0, 1.0, 3.0, 1.0, 3.0, 3.0, 1.0, 3.0]]);
+        assert_eq!(square.area(), 4.0);
+        assert_eq!(square.bbox(), Some([1.0, 1.0, 2.0, 2.0]));
+    }
+
+    #[test]
+    fn deserializes_all_representations() {
+        let polygon: Segmentation = serde_json::from_str("[[0, 0, 4, 0, 4, 3]]").unwrap();
+        assert_eq!(polygon.area(), 6.0);
+
+        let rle: Segmentation =
+            serde_json::from_str(r#"{"size": [1, 9], "counts": [2, 1, 1, 5]}"#).unwrap();
+        assert!(matches!(rle, Segmentation::Rle(_)));
+        assert_eq!(rle.area(), 6.0);
+
+        let compressed: Segmentation =
+            serde_json::from_str(r#"{"size": [1, 9], "counts": "2114"}"#).unwrap();
+        assert!(matches!(compressed, Segmentation::CompressedRle(_)));
+        assert_eq!(compressed.area(), 6.0);
+        assert_eq!(compressed.bbox(), rle.bbox());
+    }
+}
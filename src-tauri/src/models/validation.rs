@@ -0,0 +1,859 @@
+use super::segmentation::{polygon_area, Rle};
+use super::{COCOAnnotation, COCOData, COCOImage, CategoryFrequency, CocoId, Segmentation};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+/// bboxが画像からはみ出しているとみなすまでの許容量（ピクセル）
+const BBOX_BOUNDS_TOLERANCE: f64 = 1.0;
+/// `area` とジオメトリから求めた面積の許容相対誤差
+const AREA_RELATIVE_TOLERANCE: f64 = 0.1;
+
+/// 問題の重大度
+///
+/// `Error` は読み込みを中止する問題（画像・カテゴリが無い、bboxの要素数が不正）に限り、
+/// それ以外はすべて `Warning` として読み込みを続行する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// 検証で検出される問題の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueKind {
+    NoImages,
+    NoCategories,
+    DuplicateImageId,
+    DuplicateAnnotationId,
+    DuplicateCategoryId,
+    MissingImage,
+    MissingCategory,
+    InvalidBbox,
+    BboxOutOfBounds,
+    NegativeSize,
+    OddPolygonLength,
+    TooFewPolygonPoints,
+    InvalidRle,
+    AreaMismatch,
+    NonFiniteValue,
+    InvalidKeypoints,
+    KeypointCountMismatch,
+    InvalidSkeleton,
+    MissingVideo,
+    DuplicateFrame,
+    MissingTrack,
+    TrackCategoryMismatch,
+    DuplicateTrackInImage,
+    MissingLabeledCategory,
+    ConflictingCategoryLabels,
+    NegativeCategoryAnnotated,
+    FrequencyMismatch,
+    CoercedValue,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub kind: IssueKind,
+    pub message: String,
+    pub image_id: Option<CocoId>,
+    pub annotation_id: Option<CocoId>,
+    pub category_id: Option<CocoId>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+    pub error_count: usize,
+    pub warning_count: usize,
+}
+
+impl ValidationReport {
+    pub fn push(&mut self, issue: ValidationIssue) {
+        match issue.severity {
+            Severity::Error => self.error_count += 1,
+            Severity::Warning => self.warning_count += 1,
+        }
+        self.issues.push(issue);
+    }
+
+    /// 最初のエラーを要約した文字列（エラーが無い場合は `None`）
+    pub fn error_summary(&self) -> Option<String> {
+        let first = self
+            .issues
+            .iter()
+            .find(|issue| issue.severity == Severity::Error)?;
+        if self.error_count == 1 {
+            Some(first.message.clone())
+        } else {
+            Some(format!(
+                "{} (and {} more errors)",
+                first.message,
+                self.error_count - 1
+            ))
+        }
+    }
+
+    fn error(&mut self, kind: IssueKind, message: String) -> &mut ValidationIssue {
+        self.add(Severity::Error, kind, message)
+    }
+
+    fn warning(&mut self, kind: IssueKind, message: String) -> &mut ValidationIssue {
+        self.add(Severity::Warning, kind, message)
+    }
+
+    fn add(
+        &mut self,
+        severity: Severity,
+        kind: IssueKind,
+        message: String,
+    ) -> &mut ValidationIssue {
+        self.push(ValidationIssue {
+            severity,
+            kind,
+            message,
+            image_id: None,
+            annotation_id: None,
+            category_id: None,
+        });
+        self.issues.last_mut().expect("issue was just pushed")
+    }
+}
+
+impl ValidationIssue {
+    fn image(&mut self, image_id: &CocoId) -> &mut Self {
+        self.image_id = Some(image_id.clone());
+        self
+    }
+
+    fn annotation(&mut self, annotation: &COCOAnnotation) -> &mut Self {
+        self.annotation_id = Some(annotation.id.clone());
+        self.image_id = Some(annotation.image_id.clone());
+        self.category_id = Some(annotation.category_id.clone());
+        self
+    }
+
+    fn category(&mut self, category_id: &CocoId) -> &mut Self {
+        self.category_id = Some(category_id.clone());
+        self
+    }
+}
+
+impl COCOData {
+    /// データセット全体を検証し、検出した問題をすべて報告する
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        if self.images.is_empty() {
+            report.error(
+                IssueKind::NoImages,
+                "No images found in COCO data".to_string(),
+            );
+        }
+
+        if self.categories.is_empty() {
+            report.error(
+                IssueKind::NoCategories,
+                "No categories found in COCO data".to_string(),
+            );
+        }
+
+        // 読み込み時の型の変換
+        for coercion in &self.coercions {
+            report.warning(
+                IssueKind::CoercedValue,
+                format!(
+                    "{}: {} was converted to {} ({})",
+                    coercion.json_path, coercion.original, coercion.value, coercion.reason
+                ),
+            );
+        }
+
+        // 画像の検証
+        let video_ids: Option<HashSet<&CocoId>> = self
+            .videos
+            .as_ref()
+            .map(|videos| videos.iter().map(|v| &v.id).collect());
+        let mut frames = HashSet::new();
+        let mut images = HashMap::new();
+        for image in &self.images {
+            if let Entry::Vacant(entry) = images.entry(&image.id) {
+                entry.insert(image);
+            } else {
+                report
+                    .warning(
+                        IssueKind::DuplicateImageId,
+                        format!("Duplicate image id {}", image.id),
+                    )
+                    .image(&image.id);
+            }
+            if image.width <= 0 || image.height <= 0 {
+                report
+                    .warning(
+                        IssueKind::NegativeSize,
+                        format!(
+                            "Image {} has invalid size {}x{}",
+                            image.id, image.width, image.height
+                        ),
+                    )
+                    .image(&image.id);
+            }
+            if let Some(video_id) = &image.video_id {
+                if video_ids
+                    .as_ref()
+                    .is_some_and(|ids| !ids.contains(video_id))
+                {
+                    report
+                        .warning(
+                            IssueKind::MissingVideo,
+                            format!("Image {} refers to missing video {video_id}", image.id),
+                        )
+                        .image(&image.id);
+                }
+                if let Some(frame) = image.frame_number() {
+                    if !frames.insert((video_id, frame)) {
+                        report
+                            .warning(
+                                IssueKind::DuplicateFrame,
+                                format!(
+                                    "Image {} duplicates frame {frame} of video {video_id}",
+                                    image.id
+                                ),
+                            )
+                            .image(&image.id);
+                    }
+                }
+            }
+        }
+
+        // カテゴリの検証
+        let mut categories = HashMap::new();
+        for category in &self.categories {
+            if let Entry::Vacant(entry) = categories.entry(&category.id) {
+                entry.insert(category);
+            } else {
+                report
+                    .warning(
+                        IssueKind::DuplicateCategoryId,
+                        format!("Duplicate category id {}", category.id),
+                    )
+                    .category(&category.id);
+            }
+            if let (Some(skeleton), Some(names)) = (&category.skeleton, &category.keypoints) {
+                let in_range = |i: u32| i >= 1 && i as usize <= names.len();
+                for [a, b] in skeleton {
+                    if !in_range(*a) || !in_range(*b) {
+                        report
+                            .warning(
+                                IssueKind::InvalidSkeleton,
+                                format!(
+                                    "Invalid skeleton edge [{a}, {b}] for category {}",
+                                    category.id
+                                ),
+                            )
+                            .category(&category.id);
+                    }
+                }
+            }
+            // LVISの区分は学習データの `image_count` から決まる
+            let image_count = category.extra.get("image_count").and_then(Value::as_u64);
+            if let (Some(frequency), Some(image_count)) = (category.frequency, image_count) {
+                if CategoryFrequency::from_image_count(image_count) != Some(frequency) {
+                    report
+                        .warning(
+                            IssueKind::FrequencyMismatch,
+                            format!(
+                                "Category {} has frequency '{}' but image_count {image_count}",
+                                category.id,
+                                frequency.as_str()
+                            ),
+                        )
+                        .category(&category.id);
+                }
+            }
+        }
+
+        // LVISの画像単位のラベル（否定・非網羅）の検証
+        for image in &self.images {
+            let labels = [
+                ("neg_category_ids", &image.neg_category_ids),
+                (
+                    "not_exhaustive_category_ids",
+                    &image.not_exhaustive_category_ids,
+                ),
+            ];
+            for (field, ids) in labels {
+                for category_id in ids.iter().flatten() {
+                    if !categories.contains_key(category_id) {
+                        report
+                            .warning(
+                                IssueKind::MissingLabeledCategory,
+                                format!(
+                                    "Image {} lists missing category {category_id} in {field}",
+                                    image.id
+                                ),
+                            )
+                            .image(&image.id)
+                            .category(category_id);
+                    }
+                }
+            }
+            for category_id in image.neg_category_ids.iter().flatten() {
+                if image.is_not_exhaustive_category(category_id) {
+                    report
+                        .warning(
+                            IssueKind::ConflictingCategoryLabels,
+                            format!(
+                                "Image {} lists category {category_id} in both neg_category_ids and not_exhaustive_category_ids",
+                                image.id
+                            ),
+                        )
+                        .image(&image.id)
+                        .category(category_id);
+                }
+            }
+        }
+
+        // アノテーションの検証
+        let tracks: Option<HashMap<&CocoId, &CocoId>> = self
+            .tracks
+            .as_ref()
+            .map(|tracks| tracks.iter().map(|t| (&t.id, &t.category_id)).collect());
+        let mut track_images = HashSet::new();
+        let mut annotation_ids = HashSet::new();
+        for annotation in &self.annotations {
+            let id = &annotation.id;
+
+            if !annotation_ids.insert(id) {
+                report
+                    .warning(
+                        IssueKind::DuplicateAnnotationId,
+                        format!("Duplicate annotation id {id}"),
+                    )
+                    .annotation(annotation);
+            }
+
+            let image = images.get(&annotation.image_id);
+            if image.is_none() {
+                report
+                    .warning(
+                        IssueKind::MissingImage,
+                        format!(
+                            "Annotation {id} refers to missing image {}",
+                            annotation.image_id
+                        ),
+                    )
+                    .annotation(annotation);
+            }
+
+            let category = categories.get(&annotation.category_id);
+            if category.is_none() {
+                report
+                    .warning(
+                        IssueKind::MissingCategory,
+                        format!(
+                            "Annotation {id} refers to missing category {}",
+                            annotation.category_id
+                        ),
+                    )
+                    .annotation(annotation);
+            }
+            if image.is_some_and(|image| image.is_negative_category(&annotation.category_id)) {
+                report
+                    .warning(
+                        IssueKind::NegativeCategoryAnnotated,
+                        format!(
+                            "Annotation {id} has category {} which image {} lists in neg_category_ids",
+                            annotation.category_id, annotation.image_id
+                        ),
+                    )
+                    .annotation(annotation);
+            }
+
+            // トラックの検証
+            if let Some(track_id) = &annotation.track_id {
+                match tracks.as_ref().map(|tracks| tracks.get(track_id)) {
+                    Some(None) => {
+                        report
+                            .warning(
+                                IssueKind::MissingTrack,
+                                format!("Annotation {id} refers to missing track {track_id}"),
+                            )
+                            .annotation(annotation);
+                    }
+                    Some(Some(&track_category)) if *track_category != annotation.category_id => {
+                        report
+                            .warning(
+                                IssueKind::TrackCategoryMismatch,
+                                format!(
+                                    "Annotation {id} has category {} but track {track_id} has category {track_category}",
+                                    annotation.category_id
+                                ),
+                            )
+                            .annotation(annotation);
+                    }
+                    _ => {}
+                }
+                if !track_images.insert((track_id, &annotation.image_id)) {
+                    report
+                        .warning(
+                            IssueKind::DuplicateTrackInImage,
+                            format!(
+                                "Track {track_id} appears more than once in image {}",
+                                annotation.image_id
+                            ),
+                        )
+                        .annotation(annotation);
+                }
+            }
+
+            // BBoxの要素数（読み込みを中止する唯一のアノテーション単位のエラー）
+            let bbox_valid = annotation.bbox.len() == 4;
+            if !bbox_valid {
+                report
+                    .error(
+                        IssueKind::InvalidBbox,
+                        format!("Invalid bbox format for annotation {id}"),
+                    )
+                    .annotation(annotation);
+            }
+
+            // 数値の検証
+            let segmentation_values: &[Vec<f64>] = match &annotation.segmentation {
+                Some(Segmentation::Polygon(polygons)) => polygons,
+                _ => &[],
+            };
+            let non_finite = annotation.bbox.iter().any(|v| !v.is_finite())
+                || !annotation.area.is_finite()
+                || segmentation_values.iter().flatten().any(|v| !v.is_finite())
+                || annotation
+                    .keypoints
+                    .iter()
+                    .flatten()
+                    .any(|v| !v.is_finite())
+                || annotation
+                    .rbox
+                    .is_some_and(|rbox| <[f64; 5]>::from(rbox).iter().any(|v| !v.is_finite()));
+            if non_finite {
+                report
+                    .warning(
+                        IssueKind::NonFiniteValue,
+                        format!("Annotation {id} contains NaN or infinite values"),
+                    )
+                    .annotation(annotation);
+                continue;
+            }
+
+            // BBoxの検証
+            if bbox_valid {
+                let [x, y, w, h] = [
+                    annotation.bbox[0],
+                    annotation.bbox[1],
+                    annotation.bbox[2],
+                    annotation.bbox[3],
+                ];
+                if w < 0.0 || h < 0.0 {
+                    report
+                        .warning(
+                            IssueKind::NegativeSize,
+                            format!("Annotation {id} has negative bbox size {w}x{h}"),
+                        )
+                        .annotation(annotation);
+                }
+                if let Some(image) = image {
+                    let out_of_bounds = x < -BBOX_BOUNDS_TOLERANCE
+                        || y < -BBOX_BOUNDS_TOLERANCE
+                        || x + w > image.width as f64 + BBOX_BOUNDS_TOLERANCE
+                        || y + h > image.height as f64 + BBOX_BOUNDS_TOLERANCE;
+                    if out_of_bounds {
+                        report
+                            .warning(
+                                IssueKind::BboxOutOfBounds,
+                                format!(
+                                    "Annotation {id} bbox [{x}, {y}, {w}, {h}] exceeds image {} ({}x{})",
+                                    image.id, image.width, image.height
+                                ),
+                            )
+                            .annotation(annotation);
+                    }
+                }
+            }
+
+            if annotation.area < 0.0 {
+                report
+                    .warning(
+                        IssueKind::NegativeSize,
+                        format!("Annotation {id} has negative area {}", annotation.area),
+                    )
+                    .annotation(annotation);
+            }
+
+            // セグメンテーションの検証
+            let mut geometry_area = None;
+            match &annotation.segmentation {
+                Some(Segmentation::Polygon(polygons)) => {
+                    for (i, polygon) in polygons.iter().enumerate() {
+                        if polygon.len() % 2 != 0 {
+                            report
+                                .warning(
+                                    IssueKind::OddPolygonLength,
+                                    format!(
+                                        "Polygon {i} of annotation {id} has odd length {}",
+                                        polygon.len()
+                                    ),
+                                )
+                                .annotation(annotation);
+                        } else if polygon.len() < 6 {
+                            report
+                                .warning(
+                                    IssueKind::TooFewPolygonPoints,
+                                    format!(
+                                        "Polygon {i} of annotation {id} has only {} points",
+                                        polygon.len() / 2
+                                    ),
+                                )
+                                .annotation(annotation);
+                        }
+                    }
+                    if !polygons.is_empty() {
+                        geometry_area = Some(polygons.iter().map(|p| polygon_area(p)).sum());
+                    }
+                }
+                Some(Segmentation::Rle(rle)) => {
+                    geometry_area = check_rle(&mut report, annotation, image, Ok(rle.clone()));
+                }
+                Some(Segmentation::CompressedRle(rle)) => {
+                    geometry_area = check_rle(&mut report, annotation, image, rle.decompress());
+                }
+                None => {}
+            }
+
+            // 面積の整合性（セグメンテーションが無い場合は回転矩形、またはbboxの面積と比較）
+            let expected_area = geometry_area
+                .or_else(|| annotation.rbox.map(|rbox| rbox.area()))
+                .or_else(|| {
+                    (annotation.bbox.len() == 4).then(|| annotation.bbox[2] * annotation.bbox[3])
+                });
+            if let Some(expected) = expected_area {
+                // ラスタライズによる誤差として、bboxの半周長までは許容する
+                let perimeter_slack = if annotation.bbox.len() == 4 {
+                    annotation.bbox[2].abs() + annotation.bbox[3].abs()
+                } else {
+                    0.0
+                };
+                let tolerance = expected.abs() * AREA_RELATIVE_TOLERANCE + perimeter_slack;
+                if (annotation.area - expected).abs() > tolerance {
+                    report
+                        .warning(
+                            IssueKind::AreaMismatch,
+                            format!(
+                                "Annotation {id} has area {} but its geometry covers {expected:.1}",
+                                annotation.area
+                            ),
+                        )
+                        .annotation(annotation);
+                }
+            }
+
+            // キーポイントの検証
+            match annotation.keypoint_list() {
+                Some(Ok(keypoints)) => {
+                    if let Some(names) = category.and_then(|c| c.keypoints.as_ref()) {
+                        if keypoints.len() != names.len() {
+                            report
+                                .warning(
+                                    IssueKind::KeypointCountMismatch,
+                                    format!(
+                                        "Annotation {id} has {} keypoints but category {} defines {}",
+                                        keypoints.len(),
+                                        annotation.category_id,
+                                        names.len()
+                                    ),
+                                )
+                                .annotation(annotation);
+                        }
+                    }
+                    if let Some(num_keypoints) = annotation.num_keypoints {
+                        let labeled = keypoints
+                            .iter()
+                            .filter(|k| k.visibility.is_labeled())
+                            .count();
+                        if labeled != num_keypoints as usize {
+                            report
+                                .warning(
+                                    IssueKind::KeypointCountMismatch,
+                                    format!(
+                                        "Annotation {id} has num_keypoints {num_keypoints} but {labeled} labeled keypoints"
+                                    ),
+                                )
+                                .annotation(annotation);
+                        }
+                    }
+                }
+                Some(Err(e)) => {
+                    report
+                        .warning(
+                            IssueKind::InvalidKeypoints,
+                            format!("Invalid keypoints for annotation {id}: {e}"),
+                        )
+                        .annotation(annotation);
+                }
+                None => {}
+            }
+        }
+
+        report
+    }
+}
+
+/// RLEの整合性を検証し、正しければ前景の面積を返す
+fn check_rle(
+    report: &mut ValidationReport,
+    annotation: &COCOAnnotation,
+    image: Option<&&COCOImage>,
+    rle: Result<Rle, String>,
+) -> Option<f64> {
+    let id = &annotation.id;
+    let rle = match rle {
+        Ok(rle) => rle,
+        Err(e) => {
+            report
+                .warning(
+                    IssueKind::InvalidRle,
+                    format!("Invalid RLE for annotation {id}: {e}"),
+                )
+                .annotation(annotation);
+            return None;
+        }
+    };
+
+    let [h, w] = rle.size;
+    if let Some(image) = image {
+        if h as i64 != image.height as i64 || w as i64 != image.width as i64 {
+            report
+                .warning(
+                    IssueKind::InvalidRle,
+                    format!(
+                        "RLE size {h}x{w} of annotation {id} does not match image {} ({}x{})",
+                        image.id, image.height, image.width
+                    ),
+                )
+                .annotation(annotation);
+        }
+    }
+
+    let total: u64 = rle.counts.iter().map(|&c| c as u64).sum();
+    if total != h as u64 * w as u64 {
+        report
+            .warning(
+                IssueKind::InvalidRle,
+                format!(
+                    "RLE counts of annotation {id} sum to {total}, expected {}",
+                    h as u64 * w as u64
+                ),
+            )
+            .annotation(annotation);
+        return None;
+    }
+
+    Some(rle.area() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(annotations: &str) -> COCOData {
+        serde_json::from_str(&format!(
+            r#"{{
+                "images": [{{"id": 1, "width": 100, "height": 50, "file_name": "a.jpg"}}],
+                "categories": [{{"id": 1, "name": "person", "keypoints": ["nose", "eye"]}}],
+                "annotations": {annotations}
+            }}"#
+        ))
+        .unwrap()
+    }
+
+    fn annotation(extra: &str) -> String {
+        format!(
+            r#"{{"id": 1, "image_id": 1, "category_id": 1, "bbox": [10, 10, 20, 10],
+                 "area": 200, "iscrowd": 0{extra}}}"#
+        )
+    }
+
+    fn kinds(report: &ValidationReport) -> Vec<(Severity, IssueKind)> {
+        report
+            .issues
+            .iter()
+            .map(|issue| (issue.severity, issue.kind))
+            .collect()
+    }
+
+    #[test]
+    fn valid_data_has_no_issues() {
+        let report = data(&format!("[{}]", annotation(""))).validate();
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+        assert_eq!(report.error_summary(), None);
+    }
+
+    #[test]
+    fn only_baseline_failures_are_errors() {
+        let mut coco = data("[]");
+        coco.images.clear();
+        coco.categories.clear();
+        let report = coco.validate();
+        assert_eq!(
+            kinds(&report),
+            vec![
+                (Severity::Error, IssueKind::NoImages),
+                (Severity::Error, IssueKind::NoCategories)
+            ]
+        );
+        assert_eq!(
+            report.error_summary().as_deref(),
+            Some("No images found in COCO data (and 1 more errors)")
+        );
+
+        let mut coco = data(&format!("[{}]", annotation("")));
+        coco.annotations[0].bbox.pop();
+        let report = coco.validate();
+        assert_eq!(report.error_count, 1);
+        assert_eq!(kinds(&report)[0], (Severity::Error, IssueKind::InvalidBbox));
+    }
+
+    #[test]
+    fn duplicates_and_broken_geometry_are_warnings() {
+        let mut coco = data(&format!(
+            "[{}, {}, {}]",
+            annotation(r#", "segmentation": {"size": [50, 100], "counts": [1, 2]}"#),
+            annotation(r#", "keypoints": [1, 2, 7]"#),
+            annotation(r#", "segmentation": [[1, 2, 3]]"#),
+        ));
+        coco.images.push(coco.images[0].clone());
+        coco.categories.push(coco.categories[0].clone());
+        let report = coco.validate();
+
+        assert_eq!(report.error_count, 0);
+        let found = kinds(&report);
+        for kind in [
+            IssueKind::DuplicateImageId,
+            IssueKind::DuplicateCategoryId,
+            IssueKind::DuplicateAnnotationId,
+            IssueKind::InvalidRle,
+            IssueKind::InvalidKeypoints,
+            IssueKind::OddPolygonLength,
+        ] {
+            assert!(found.contains(&(Severity::Warning, kind)), "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn reports_references_bounds_and_area() {
+        let coco = data(&format!(
+            "[{}]",
+            r#"{"id": 1, "image_id": 9, "category_id": 9, "bbox": [90, 40, 20, 20],
+                "area": 1, "iscrowd": 0}"#
+        ));
+        let report = coco.validate();
+        let found = kinds(&report);
+        assert!(found.contains(&(Severity::Warning, IssueKind::MissingImage)));
+        assert!(found.contains(&(Severity::Warning, IssueKind::MissingCategory)));
+        assert!(found.contains(&(Severity::Warning, IssueKind::AreaMismatch)));
+
+        let mut coco = data(&format!(
+            "[{}]",
+            r#"{"id": 1, "image_id": 1, "category_id": 1, "bbox": [90, 40, 20, 20],
+                "area": 400, "iscrowd": 0}"#
+        ));
+        coco.annotations[0].bbox[2] = -20.0;
+        let found = kinds(&coco.validate());
+        assert!(found.contains(&(Severity::Warning, IssueKind::NegativeSize)));
+
+        coco.annotations[0].bbox[2] = 20.0;
+        let issue = coco.validate().issues.remove(0);
+        assert_eq!(issue.kind, IssueKind::BboxOutOfBounds);
+        assert_eq!(issue.annotation_id, Some(CocoId::Int(1)));
+        assert_eq!(issue.image_id, Some(CocoId::Int(1)));
+    }
+
+    #[test]
+    fn reports_keypoint_count_mismatch() {
+        let coco = data(&format!(
+            "[{}]",
+            annotation(r#", "keypoints": [1, 2, 2, 0, 0, 0, 5, 5, 1], "num_keypoints": 1"#)
+        ));
+        let report = coco.validate();
+        let mismatches = report
+            .issues
+            .iter()
+            .filter(|issue| issue.kind == IssueKind::KeypointCountMismatch)
+            .count();
+        // カテゴリの定義数（2）と num_keypoints（1）の両方と一致しない
+        assert_eq!(mismatches, 2);
+    }
+
+    #[test]
+    fn non_finite_values_are_reported_once() {
+        let mut coco = data(&format!("[{}]", annotation("")));
+        coco.annotations[0].bbox[0] = f64::NAN;
+        coco.annotations[0].area = f64::INFINITY;
+        let report = coco.validate();
+        assert_eq!(
+            kinds(&report),
+            vec![(Severity::Warning, IssueKind::NonFiniteValue)]
+        );
+    }
+
+    #[test]
+    fn reports_lvis_label_issues() {
+        let coco: COCOData = serde_json::from_str(
+            r#"{
+                "images": [{"id": 1, "width": 100, "height": 50, "file_name": "a.jpg",
+                            "neg_category_ids": [2, 9], "not_exhaustive_category_ids": [2, 8]}],
+                "categories": [
+                    {"id": 1, "name": "cat", "frequency": "r", "image_count": 10},
+                    {"id": 2, "name": "dog", "frequency": "c", "image_count": 101}
+                ],
+                "annotations": [
+                    {"id": 1, "image_id": 1, "category_id": 1, "bbox": [10, 10, 20, 10], "area": 200, "iscrowd": 0},
+                    {"id": 2, "image_id": 1, "category_id": 2, "bbox": [10, 10, 20, 10], "area": 200, "iscrowd": 0}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let report = coco.validate();
+        let labeled: Vec<_> = report
+            .issues
+            .iter()
+            .map(|issue| {
+                (
+                    issue.kind,
+                    issue.image_id.clone(),
+                    issue.category_id.clone(),
+                    issue.annotation_id.clone(),
+                )
+            })
+            .collect();
+        let id = |id: i64| Some(CocoId::from(id));
+        assert_eq!(
+            labeled,
+            vec![
+                (IssueKind::FrequencyMismatch, None, id(2), None),
+                (IssueKind::MissingLabeledCategory, id(1), id(9), None),
+                (IssueKind::MissingLabeledCategory, id(1), id(8), None),
+                (IssueKind::ConflictingCategoryLabels, id(1), id(2), None),
+                (IssueKind::NegativeCategoryAnnotated, id(1), id(2), id(2)),
+            ]
+        );
+        assert!(report
+            .issues
+            .iter()
+            .all(|i| i.severity == Severity::Warning));
+    }
+}
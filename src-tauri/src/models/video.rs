@@ -0,0 +1,39 @@
+use super::{COCOImage, CocoId};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 動画（TAO・CocoVIDなどの `videos`）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct COCOVideo {
+    pub id: CocoId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<i32>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// 追跡対象（TAOの `tracks`）
+///
+/// アノテーションの `track_id` から参照される。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct COCOTrack {
+    pub id: CocoId,
+    pub category_id: CocoId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub video_id: Option<CocoId>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl COCOImage {
+    /// 動画内でのフレーム番号（`frame_id`、無ければTAOの `frame_index`）
+    pub fn frame_number(&self) -> Option<i64> {
+        self.frame_id
+            .or_else(|| self.extra.get("frame_index").and_then(Value::as_i64))
+    }
+}
@@ -8,6 +8,8 @@ import SampleGeneratorDialog from './components/SampleGeneratorDialog';
 import StatisticsDialog from './components/StatisticsDialog';
 import SettingsModal from './components/SettingsModal';
 import ImageSelectionDialog from './components/ImageSelectionDialog';
+import ArchiveEntryDialog from './components/ArchiveEntryDialog';
+import ImportDialog from './components/ImportDialog';
 import { ComparisonDialog } from './components/ComparisonDialog';
 import { CommonModal } from './components/CommonModal';
 import { HistogramPanel } from './components/HistogramPanel';
@@ -31,6 +33,7 @@ import {
   toast,
 } from './stores';
 import type { TabType } from './stores';
+import type { CocoId } from './types/coco';
 import { useMenuEvents } from './hooks/useMenuEvents';
 import { useDragAndDrop } from './hooks/useDragAndDrop';
 import { useResponsiveLayout } from './hooks/useResponsiveLayout';
@@ -44,7 +47,7 @@ function App() {
   const { toasts, removeToast } = useToastStore();
   const { isSettingsModalOpen, openSettingsModal, closeSettingsModal, panelLayout } =
     useSettingsStore();
-  const { isLoading, message, subMessage, progress } = useLoadingStore();
+  const { isLoading, message, subMessage, progress, onCancel } = useLoadingStore();
   const { openModal: openHeatmapModal } = useHeatmapStore();
 
   // File operations hook
@@ -106,6 +109,7 @@ function App() {
     onOpenAnnotations: fileOps.handleOpenAnnotations,
     onGenerateSample: fileOps.handleGenerateSample,
     onExportAnnotations: fileOps.handleExportAnnotations,
+    onImportDataset: () => fileOps.setShowImportDialog(true),
     onShowStatistics: fileOps.handleShowStatistics,
     onShowSettings: openSettingsModal,
     onShowComparison: () => fileOps.setShowComparisonDialog(true),
@@ -181,7 +185,7 @@ function App() {
   ]);
 
   // Auto-show right panel when annotation is selected
-  const prevSelectedAnnotationIdsRef = useRef<(number | string)[]>([]);
+  const prevSelectedAnnotationIdsRef = useRef<CocoId[]>([]);
 
   useEffect(() => {
     const prevLength = prevSelectedAnnotationIdsRef.current.length;
@@ -296,7 +300,7 @@ function App() {
         isOpen={fileOps.showImageSelection && fileOps.tempCocoData !== null}
         onClose={() => {
           fileOps.setShowImageSelection(false);
-          fileOps.setTempCocoData(null);
+          fileOps.discardTempCocoData();
         }}
         images={fileOps.tempCocoData?.data.images || []}
         currentImageFileName={
@@ -305,7 +309,7 @@ function App() {
         }
         onSelect={async (image) => {
           if (fileOps.tempCocoData) {
-            setCocoData(fileOps.tempCocoData.data);
+            setCocoData(fileOps.tempCocoData.data, fileOps.tempCocoData.dataset);
             setCurrentImageId(image.id);
             fileOps.setShowImageSelection(false);
             fileOps.setTempCocoData(null);
@@ -314,6 +318,14 @@ function App() {
         }}
       />
 
+      <ArchiveEntryDialog />
+
+      <ImportDialog
+        isOpen={fileOps.showImportDialog}
+        onClose={() => fileOps.setShowImportDialog(false)}
+        onSelect={fileOps.handleImportDataset}
+      />
+
       <ComparisonDialog
         isOpen={fileOps.showComparisonDialog}
         onClose={() => fileOps.setShowComparisonDialog(false)}
@@ -337,6 +349,8 @@ function App() {
         message={message}
         subMessage={subMessage}
         progress={progress}
+        onCancel={onCancel}
+        cancelLabel={t('common.cancel')}
       />
     </div>
   );
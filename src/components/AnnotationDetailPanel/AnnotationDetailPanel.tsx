@@ -1,8 +1,8 @@
 import React, { useState } from 'react';
 import { useTranslation } from 'react-i18next';
 import { useAnnotationStore, useSettingsStore } from '../../stores';
-import { getValueByPath, hasFieldPath } from '../../utils';
-import { COCOAnnotation } from '../../types/coco';
+import { calculateRleArea, cocoIdKey, getValueByPath, hasFieldPath, isRle } from '../../utils';
+import { COCOAnnotation, Segmentation } from '../../types/coco';
 import './AnnotationDetailPanel.css';
 
 type ViewMode = 'formatted' | 'json';
@@ -45,17 +45,10 @@ const AnnotationDetailPanel: React.FC = () => {
 
     const selected = taggedAnnotations.filter((ann) => {
       for (const selectedId of selectedAnnotationIds) {
-        if (typeof selectedId === 'string') {
-          // Handle unique IDs like "primary-123" or "comparison-456"
-          const [source, id] = selectedId.split('-');
-          if (ann._source === source && ann.id === parseInt(id)) {
-            return true;
-          }
-        } else {
-          // Handle direct number IDs (non-comparison mode)
-          if (ann.id === selectedId) {
-            return true;
-          }
+        // Handle unique IDs like "primary-123" or "comparison-456" and direct IDs (non-comparison
+        // mode). IDs may be strings, so compare the whole value instead of splitting it
+        if (selectedId === `${ann._source}-${ann.id}` || selectedId === ann.id) {
+          return true;
         }
       }
       return false;
@@ -65,7 +58,7 @@ const AnnotationDetailPanel: React.FC = () => {
     let comparison = null;
     if (isComparing && selected.length > 0 && diffResults && comparisonSettings) {
       const firstAnnotation = selected[0];
-      const imageDiff = diffResults.get(firstAnnotation.image_id);
+      const imageDiff = diffResults.get(cocoIdKey(firstAnnotation.image_id));
 
       if (imageDiff) {
         const matches = [];
@@ -228,7 +221,24 @@ const AnnotationDetailPanel: React.FC = () => {
   };
 
   // Render segmentation info
-  const renderSegmentation = (segmentation: number[][]) => {
+  const renderSegmentation = (segmentation: Segmentation) => {
+    if (isRle(segmentation)) {
+      const [height, width] = segmentation.size;
+      return (
+        <div>
+          <div className="segmentation-summary">
+            {t('detail.rle')} (
+            {typeof segmentation.counts === 'string'
+              ? t('detail.rleCompressed')
+              : t('detail.rleUncompressed')}
+            )
+          </div>
+          {renderField(t('detail.rleSize'), `${height} × ${width}`)}
+          {renderField(t('detail.rleArea'), calculateRleArea(segmentation))}
+        </div>
+      );
+    }
+
     const totalPoints = segmentation.reduce((sum, poly) => sum + poly.length / 2, 0);
     const polygons = segmentation.length;
 
@@ -551,7 +561,8 @@ const AnnotationDetailPanel: React.FC = () => {
         </div>
 
         {/* Segmentation */}
-        {annotation.segmentation && annotation.segmentation.length > 0 && (
+        {(isRle(annotation.segmentation) ||
+          (Array.isArray(annotation.segmentation) && annotation.segmentation.length > 0)) && (
           <div className="segmentation-info">
             <strong>{t('detail.segmentation')}: </strong>
             {renderSegmentation(annotation.segmentation)}
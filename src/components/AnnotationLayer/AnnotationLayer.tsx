@@ -1,13 +1,19 @@
-import React, { useCallback, useMemo } from 'react';
-import { Layer, Line, Rect, Text, Group } from 'react-konva';
+import React, { useCallback, useEffect, useMemo, useState } from 'react';
+import { Layer, Line, Rect, Text, Group, Circle, Image as KonvaImage } from 'react-konva';
 import Konva from 'konva';
+import { invoke } from '@tauri-apps/api/core';
 import { useAnnotationStore, useSettingsStore, generateCategoryColor } from '../../stores';
-import { COCOAnnotation } from '../../types/coco';
+import { COCOAnnotation, CocoId, CocoIdKey } from '../../types/coco';
+import { AnnotationKeypoints } from '../../types/app';
 import { DiffFilter } from '../../types/diff';
+import { cocoIdKey } from '../../utils/cocoId';
 import { hslToRgb } from '../../utils/colorConverter';
+import { datasetKey } from '../../utils/dataset';
+import { rotatedBoxPoints } from '../../utils/geometry';
+import { getRleMaskCanvas, isRle } from '../../utils/segmentation';
 
 interface AnnotationLayerProps {
-  imageId: number;
+  imageId: CocoId;
   scale?: number;
   viewport?: {
     x: number;
@@ -31,6 +37,7 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
     isComparing,
     diffResults,
     diffFilters,
+    dataset,
   } = useAnnotationStore();
 
   const settingsStore = useSettingsStore();
@@ -41,6 +48,30 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
       : settingsStore.display;
   const colors = settingsStore.colors;
 
+  // キーポイントは開いているデータセットからバックエンドで画像ごとに取得する
+  const [keypoints, setKeypoints] = useState<Map<CocoIdKey, AnnotationKeypoints>>(new Map());
+  useEffect(() => {
+    setKeypoints(new Map());
+    if (!dataset) return;
+
+    let stale = false;
+    invoke<AnnotationKeypoints[]>('get_image_keypoints', {
+      dataset: datasetKey(dataset.summary),
+      imageId,
+    })
+      .then((result) => {
+        if (!stale) {
+          setKeypoints(new Map(result.map((item) => [cocoIdKey(item.annotationId), item])));
+        }
+      })
+      .catch((error) => {
+        console.error('Failed to load keypoints:', error);
+      });
+    return () => {
+      stale = true;
+    };
+  }, [dataset, imageId]);
+
   // Get diff status for an annotation
   const getDiffStatus = useCallback(
     (
@@ -51,7 +82,7 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
         return { status: null, isGT: false };
       }
 
-      const imageDiff = diffResults.get(annotation.image_id);
+      const imageDiff = diffResults.get(cocoIdKey(annotation.image_id));
       if (!imageDiff) return { status: null, isGT: false };
 
       // Use the source tag to determine if this annotation is from GT or Pred
@@ -103,7 +134,7 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
 
   const getAnnotationColor = useCallback(
     (
-      categoryId: number,
+      categoryId: CocoId,
       opacity: number,
       diffInfo?: { status: 'tp' | 'fp' | 'fn' | null; isGT: boolean }
     ) => {
@@ -128,11 +159,13 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
             baseColor = comparisonColors.gtColors.fn;
             break;
           default:
-            baseColor = colors.categoryColors[categoryId] || generateCategoryColor(categoryId);
+            baseColor =
+              colors.categoryColors[cocoIdKey(categoryId)] || generateCategoryColor(categoryId);
         }
       } else {
         // Use normal category color
-        baseColor = colors.categoryColors[categoryId] || generateCategoryColor(categoryId);
+        baseColor =
+          colors.categoryColors[cocoIdKey(categoryId)] || generateCategoryColor(categoryId);
       }
 
       let r: number, g: number, b: number;
@@ -178,7 +211,7 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
     (
       annotation: COCOAnnotation,
       e: Konva.KonvaEventObject<MouseEvent>,
-      selectionId?: number | string
+      selectionId?: CocoId
     ) => {
       e.cancelBubble = true;
       const multiSelect = e.evt.ctrlKey || e.evt.metaKey;
@@ -190,7 +223,7 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
   );
 
   const handleMouseEnter = useCallback(
-    (annotationId: number | string) => {
+    (annotationId: CocoId) => {
       setHoveredAnnotation(annotationId);
     },
     [setHoveredAnnotation]
@@ -257,9 +290,11 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
   }, [cocoData, comparisonData, isComparing, diffResults, diffFilters]);
 
   const annotations = useMemo(() => {
+    // The comparison file may send the same integer image ID as a number or a string
+    const imageKey = cocoIdKey(imageId);
     const filtered = allAnnotations.filter((ann) => {
       // Filter 1: Must be current image
-      if (ann.image_id !== imageId) {
+      if (cocoIdKey(ann.image_id) !== imageKey) {
         return false;
       }
 
@@ -326,13 +361,14 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
   // Group annotations by category for batching
   const annotationsByCategory = annotations.reduce(
     (acc, annotation) => {
-      if (!acc[annotation.category_id]) {
-        acc[annotation.category_id] = [];
+      const key = cocoIdKey(annotation.category_id);
+      if (!acc[key]) {
+        acc[key] = [];
       }
-      acc[annotation.category_id].push(annotation);
+      acc[key].push(annotation);
       return acc;
     },
-    {} as Record<number, COCOAnnotation[]>
+    {} as Record<CocoIdKey, COCOAnnotation[]>
   );
 
   // Generate unique keys for comparison mode to prevent React key conflicts
@@ -352,7 +388,47 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
     if (isComparing && annotation._source) {
       return `${annotation._source}-${annotation.id}`;
     }
-    return annotation.id; // Return number for single mode
+    return annotation.id; // Return the annotation ID as is for single mode
+  };
+
+  // Render keypoints and skeleton (only the primary dataset is open in the backend)
+  const renderKeypoints = (annotation: COCOAnnotation & { _source?: 'primary' | 'comparison' }) => {
+    const item =
+      annotation._source === 'primary' ? keypoints.get(cocoIdKey(annotation.id)) : undefined;
+    if (!item) return null;
+
+    const color = getAnnotationColor(annotation.category_id, 1, getDiffStatus(annotation));
+    return (
+      <Group listening={false}>
+        {item.skeleton.map(([from, to], idx) => {
+          const a = item.keypoints[from - 1];
+          const b = item.keypoints[to - 1];
+          // 未ラベルのキーポイントにつながる辺は描画しない
+          if (!a || !b || a.visibility === 0 || b.visibility === 0) return null;
+          return (
+            <Line
+              key={getUniqueKey(annotation, `-skeleton-${idx}`)}
+              points={[a.x, a.y, b.x, b.y]}
+              stroke={color}
+              strokeWidth={display.lineWidth}
+            />
+          );
+        })}
+        {item.keypoints.map((point, idx) =>
+          point.visibility === 0 ? null : (
+            <Circle
+              key={getUniqueKey(annotation, `-keypoint-${idx}`)}
+              x={point.x}
+              y={point.y}
+              radius={display.lineWidth + 2}
+              stroke={color}
+              strokeWidth={display.lineWidth}
+              fill={point.visibility === 2 ? color : 'white'} // 不可視のキーポイントは白抜き
+            />
+          )
+        )}
+      </Group>
+    );
   };
 
   return (
@@ -390,7 +466,11 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
                       height={annotation.bbox[3]}
                       stroke={strokeColor}
                       strokeWidth={strokeWidth}
-                      fill="transparent"
+                      fill={
+                        annotation.iscrowd === 1
+                          ? getAnnotationColor(annotation.category_id, colors.fillOpacity, diffInfo)
+                          : 'transparent'
+                      } // Crowd / don't-care regions are filled to stand out
                       listening={false} // Disable events for performance
                     />
                   );
@@ -410,6 +490,23 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
         // Always render selected or hovered annotations at full detail
         const forceHighDetail = isSelected || isHovered;
         const effectiveLod = forceHighDetail ? 'high' : lodLevel;
+        // RLE masks are only drawn in medium/high LOD, like polygons
+        const rleFillOpacity = isSelected
+          ? colors.selectedFillOpacity
+          : isHovered
+            ? colors.hoverFillOpacity
+            : colors.fillOpacity;
+        const rleMask =
+          effectiveLod !== 'low' && rleFillOpacity > 0 && isRle(annotation.segmentation)
+            ? getRleMaskCanvas(
+                annotation.segmentation,
+                getAnnotationColor(
+                  annotation.category_id,
+                  rleFillOpacity,
+                  getDiffStatus(annotation)
+                )
+              )
+            : null;
 
         return (
           <Group
@@ -420,7 +517,7 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
           >
             {/* Polygon rendering - only in medium/high LOD or for selected/hovered */}
             {effectiveLod !== 'low' &&
-              annotation.segmentation &&
+              Array.isArray(annotation.segmentation) &&
               annotation.segmentation.length > 0 && (
                 <>
                   {annotation.segmentation.map((points, idx) => {
@@ -465,6 +562,16 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
                 </>
               )}
 
+            {/* RLE mask rendering (iscrowd: 1) - drawn as an image in image coordinates */}
+            {rleMask && (
+              <KonvaImage
+                image={rleMask}
+                x={0}
+                y={0}
+                listening={false} // The mask covers the whole image; clicks go to the bbox
+              />
+            )}
+
             {/* Bounding box rendering - skip if already rendered in batch */}
             {display.showBoundingBoxes &&
               (effectiveLod !== 'low' || forceHighDetail) &&
@@ -481,10 +588,38 @@ const AnnotationLayer: React.FC<AnnotationLayerProps> = ({ imageId, scale = 1, v
                   )}
                   strokeWidth={isSelected ? display.lineWidth + 1 : display.lineWidth}
                   dash={effectiveLod !== 'low' ? [5, 5] : undefined}
-                  fill="transparent"
+                  fill={
+                    annotation.iscrowd === 1
+                      ? getAnnotationColor(
+                          annotation.category_id,
+                          colors.fillOpacity,
+                          getDiffStatus(annotation)
+                        )
+                      : 'transparent'
+                  }
                 />
               )}
 
+            {/* Rotated box rendering (DOTA etc.) */}
+            {display.showBoundingBoxes &&
+              annotation.rbox &&
+              (effectiveLod !== 'low' || forceHighDetail) &&
+              colors.strokeOpacity > 0 && (
+                <Line
+                  points={rotatedBoxPoints(annotation.rbox)}
+                  closed
+                  stroke={getAnnotationColor(
+                    annotation.category_id,
+                    colors.strokeOpacity,
+                    getDiffStatus(annotation)
+                  )}
+                  strokeWidth={isSelected ? display.lineWidth + 1 : display.lineWidth}
+                />
+              )}
+
+            {/* Keypoint and skeleton rendering - only in medium/high LOD */}
+            {effectiveLod !== 'low' && renderKeypoints(annotation)}
+
             {/* Label rendering - only show for selected/hovered or at higher zoom levels */}
             {display.showLabels && category && (forceHighDetail || effectiveLod === 'high') && (
               <Group>
@@ -0,0 +1,77 @@
+import React, { useState, useEffect } from 'react';
+import { useTranslation } from 'react-i18next';
+import { useArchiveEntryStore } from '../../stores';
+import { formatFileSize } from '../../utils';
+import { CommonModal } from '../CommonModal';
+import '../ImageSelectionDialog/ImageSelectionDialog.css';
+
+// zip内に複数のアノテーションファイルがある場合に、読み込むファイルを選択する
+const ArchiveEntryDialog: React.FC = () => {
+  const { t } = useTranslation();
+  const { archivePath, entries, close } = useArchiveEntryStore();
+  const [selectedName, setSelectedName] = useState<string | null>(null);
+
+  useEffect(() => {
+    setSelectedName(entries.length > 0 ? entries[0].name : null);
+  }, [entries]);
+
+  const handleSelect = () => {
+    const entry = entries.find((e) => e.name === selectedName);
+    if (entry) {
+      close(entry);
+    }
+  };
+
+  return (
+    <CommonModal
+      isOpen={archivePath !== null}
+      onClose={() => close(null)}
+      title={t('archiveEntry.title')}
+      size="lg"
+      hasBlur={true}
+      footer={
+        <>
+          <button className="btn btn-secondary" onClick={() => close(null)}>
+            {t('archiveEntry.cancel')}
+          </button>
+          <button className="btn btn-primary" onClick={handleSelect} disabled={!selectedName}>
+            {t('archiveEntry.select')}
+          </button>
+        </>
+      }
+    >
+      <p className="dialog-description">
+        {t('archiveEntry.description', { name: archivePath?.split(/[\\/]/).pop() })}
+      </p>
+
+      <div className="image-list">
+        {entries.map((entry) => (
+          <div
+            key={entry.name}
+            className={`image-item ${selectedName === entry.name ? 'selected' : ''}`}
+            onClick={() => setSelectedName(entry.name)}
+          >
+            <div className="image-radio">
+              <input
+                type="radio"
+                id={`archive-entry-${entry.name}`}
+                name="archive-entry-selection"
+                checked={selectedName === entry.name}
+                onChange={() => setSelectedName(entry.name)}
+              />
+            </div>
+            <label htmlFor={`archive-entry-${entry.name}`} className="image-info">
+              <div className="image-name">{entry.name}</div>
+              <div className="image-details">
+                {formatFileSize(entry.size)} ({formatFileSize(entry.compressedSize)}{' '}
+                {t('archiveEntry.compressed')})
+              </div>
+            </label>
+          </div>
+        ))}
+      </div>
+    </CommonModal>
+  );
+};
+
+export default ArchiveEntryDialog;
@@ -2,6 +2,8 @@ import React, { useCallback } from 'react';
 import { useTranslation } from 'react-i18next';
 import { useSettingsStore, generateCategoryColor } from '../../stores';
 import { useAnnotationStore } from '../../stores';
+import { cocoIdKey } from '../../utils/cocoId';
+import type { CocoId } from '../../types/coco';
 import './ColorSettings.css';
 
 export const ColorSettings: React.FC = () => {
@@ -12,7 +14,7 @@ export const ColorSettings: React.FC = () => {
   const categories = cocoData?.categories || [];
 
   const handleColorChange = useCallback(
-    (categoryId: number, color: string) => {
+    (categoryId: CocoId, color: string) => {
       setCategoryColor(categoryId, color);
     },
     [setCategoryColor]
@@ -42,7 +44,10 @@ export const ColorSettings: React.FC = () => {
               <div className="color-picker-wrapper">
                 <input
                   type="color"
-                  value={colors.categoryColors[category.id] || generateCategoryColor(category.id)}
+                  value={
+                    colors.categoryColors[cocoIdKey(category.id)] ||
+                    generateCategoryColor(category.id)
+                  }
                   onChange={(e) => handleColorChange(category.id, e.target.value)}
                   className="color-picker"
                 />
@@ -50,7 +55,8 @@ export const ColorSettings: React.FC = () => {
                   className="color-preview"
                   style={{
                     backgroundColor:
-                      colors.categoryColors[category.id] || generateCategoryColor(category.id),
+                      colors.categoryColors[cocoIdKey(category.id)] ||
+                      generateCategoryColor(category.id),
                   }}
                 />
               </div>
@@ -1,14 +1,15 @@
 import { useState, useEffect } from 'react';
 import { useTranslation } from 'react-i18next';
 import { open } from '@tauri-apps/plugin-dialog';
-import { invoke } from '@tauri-apps/api/core';
 import { useAnnotationStore } from '../../stores/useAnnotationStore';
 import { useNavigationStore } from '../../stores/useNavigationStore';
 import { useSettingsStore } from '../../stores/useSettingsStore';
 import { toast } from '../../stores/useToastStore';
 import { useLoadingStore } from '../../stores/useLoadingStore';
 import { CommonModal } from '../CommonModal';
-import type { COCOData } from '../../types/coco';
+import { useAnnotationLoader } from '../../hooks/useAnnotationLoader';
+import { cocoIdKey } from '../../utils/cocoId';
+import type { COCOData, CocoId, CocoIdKey } from '../../types/coco';
 import type { ComparisonSettings, DiffDisplaySettings } from '../../types/diff';
 import './ComparisonDialog.css';
 
@@ -19,6 +20,7 @@ interface Props {
 
 export const ComparisonDialog = ({ isOpen, onClose }: Props) => {
   const { t } = useTranslation();
+  const { loadAnnotationFile } = useAnnotationLoader();
   const {
     cocoData,
     setComparisonData: setStoreComparisonData,
@@ -34,10 +36,10 @@ export const ComparisonDialog = ({ isOpen, onClose }: Props) => {
 
   const [selectedFile, setSelectedFile] = useState<string | null>(null);
   const [comparisonData, setComparisonData] = useState<COCOData | null>(null);
-  const [selectedImageId, setSelectedImageId] = useState<number | null>(null);
+  const [selectedImageId, setSelectedImageId] = useState<CocoId | null>(null);
   const [roleSelection, setRoleSelection] = useState<'current_gt' | 'current_pred'>('current_gt');
   const [iouThreshold, setIouThreshold] = useState(0.5);
-  const [categoryMapping, setCategoryMapping] = useState<Map<number, number[]>>(new Map());
+  const [categoryMapping, setCategoryMapping] = useState<Map<CocoIdKey, CocoId[]>>(new Map());
   const [isLoading, setIsLoading] = useState(false);
   const [maxMatchesPerAnnotation, setMaxMatchesPerAnnotation] = useState(1);
   const [iouMethod, setIouMethod] = useState<'bbox' | 'polygon'>('bbox');
@@ -45,7 +47,7 @@ export const ComparisonDialog = ({ isOpen, onClose }: Props) => {
 
   // Category selection dialog state
   const [showCategoryDialog, setShowCategoryDialog] = useState(false);
-  const [currentGtCategoryId, setCurrentGtCategoryId] = useState<number | null>(null);
+  const [currentGtCategoryId, setCurrentGtCategoryId] = useState<CocoId | null>(null);
 
   // Display settings
   const [displaySettings, setDisplaySettings] = useState<DiffDisplaySettings>({
@@ -92,14 +94,14 @@ export const ComparisonDialog = ({ isOpen, onClose }: Props) => {
     if (isOpen && cocoData && comparisonData) {
       // Only initialize category mapping if we're not restoring from existing settings and no manual mapping exists
       if ((!isComparing || !currentComparisonSettings) && !hasManualMapping) {
-        const mapping = new Map<number, number[]>();
+        const mapping = new Map<CocoIdKey, CocoId[]>();
         cocoData.categories.forEach((cat) => {
           // Try to find matching category by name
           const match = comparisonData.categories.find(
             (compCat) => compCat.name.toLowerCase() === cat.name.toLowerCase()
           );
           if (match) {
-            mapping.set(cat.id, [match.id]);
+            mapping.set(cocoIdKey(cat.id), [match.id]);
           }
           // 割り当てなしの場合はマッピングを作成しない
         });
@@ -115,16 +117,14 @@ export const ComparisonDialog = ({ isOpen, onClose }: Props) => {
         filters: [
           {
             name: 'COCO JSON',
-            extensions: ['json'],
+            extensions: ['json', 'gz', 'zst', 'zip'],
           },
         ],
       });
 
       if (selected) {
         setIsLoading(true);
-        const data = await invoke<COCOData>('load_annotations', {
-          filePath: selected,
-        });
+        const data = await loadAnnotationFile(selected);
 
         setSelectedFile(selected);
         setComparisonData(data);
@@ -155,7 +155,10 @@ export const ComparisonDialog = ({ isOpen, onClose }: Props) => {
       }
     } catch (error) {
       console.error('Error loading comparison file:', error);
-      const errorMessage = error instanceof Error ? error.message : t('comparison.fileLoadError');
+      const errorMessage =
+        error && typeof error === 'object' && 'message' in error
+          ? (error.message as string)
+          : t('comparison.fileLoadError');
       toast.error(t('comparison.fileLoadError'), errorMessage);
       setLoading(false);
     }
@@ -234,34 +237,36 @@ export const ComparisonDialog = ({ isOpen, onClose }: Props) => {
     }, 100);
   };
 
-  const invertMapping = (mapping: Map<number, number[]>): Map<number, number[]> => {
-    const inverted = new Map<number, number[]>();
-    mapping.forEach((values, key) => {
-      values.forEach((value) => {
-        const existing = inverted.get(value) || [];
-        inverted.set(value, [...existing, key]);
+  const invertMapping = (mapping: Map<CocoIdKey, CocoId[]>): Map<CocoIdKey, CocoId[]> => {
+    const inverted = new Map<CocoIdKey, CocoId[]>();
+    cocoData?.categories.forEach((cat) => {
+      mapping.get(cocoIdKey(cat.id))?.forEach((value) => {
+        const key = cocoIdKey(value);
+        const existing = inverted.get(key) || [];
+        inverted.set(key, [...existing, cat.id]);
       });
     });
     return inverted;
   };
 
-  const updateCategoryMapping = (gtCatId: number, predCatId: number, isAdd: boolean = true) => {
+  const updateCategoryMapping = (gtCatId: CocoId, predCatId: CocoId, isAdd: boolean = true) => {
     const newMapping = new Map(categoryMapping);
-    const currentMappings = newMapping.get(gtCatId) || [];
+    const gtKey = cocoIdKey(gtCatId);
+    const currentMappings = newMapping.get(gtKey) || [];
 
     if (isAdd) {
       // 追加：重複チェックして追加
       if (!currentMappings.includes(predCatId)) {
         const newMappings = [...currentMappings, predCatId];
-        newMapping.set(gtCatId, newMappings);
+        newMapping.set(gtKey, newMappings);
       }
     } else {
       // 削除：指定されたマッピングを削除
       const filteredMappings = currentMappings.filter((id) => id !== predCatId);
       if (filteredMappings.length > 0) {
-        newMapping.set(gtCatId, filteredMappings);
+        newMapping.set(gtKey, filteredMappings);
       } else {
-        newMapping.delete(gtCatId);
+        newMapping.delete(gtKey);
       }
     }
 
@@ -270,13 +275,13 @@ export const ComparisonDialog = ({ isOpen, onClose }: Props) => {
   };
 
   // カテゴリ選択ダイアログを開く
-  const openCategoryDialog = (gtCategoryId: number) => {
+  const openCategoryDialog = (gtCategoryId: CocoId) => {
     setCurrentGtCategoryId(gtCategoryId);
     setShowCategoryDialog(true);
   };
 
   // カテゴリ選択ダイアログでカテゴリを選択
-  const handleCategorySelect = (predCategoryId: number) => {
+  const handleCategorySelect = (predCategoryId: CocoId) => {
     if (currentGtCategoryId !== null) {
       updateCategoryMapping(currentGtCategoryId, predCategoryId, true);
     }
@@ -295,17 +300,21 @@ export const ComparisonDialog = ({ isOpen, onClose }: Props) => {
   // 表示用：使用済みでないカテゴリのみを取得
   const getUnusedCategories = () => {
     if (!comparisonData) return [];
-    const usedCategoryIds = new Set<number>();
+    const usedCategoryIds = new Set<CocoIdKey>();
 
     // 現在のマッピングで使用されているカテゴリIDを収集
     categoryMapping.forEach((predIds) => {
-      predIds.forEach((id) => usedCategoryIds.add(id));
+      predIds.forEach((id) => usedCategoryIds.add(cocoIdKey(id)));
     });
 
     // 使用されていないカテゴリのみを返す
-    return comparisonData.categories.filter((cat) => !usedCategoryIds.has(cat.id));
+    return comparisonData.categories.filter((cat) => !usedCategoryIds.has(cocoIdKey(cat.id)));
   };
 
+  // セレクトボックスの値（文字列）から画像IDを取得する
+  const imageIdFromValue = (value: string): CocoId | null =>
+    comparisonData?.images.find((img) => String(img.id) === value)?.id ?? null;
+
   return (
     <>
       <CommonModal
@@ -394,9 +403,7 @@ export const ComparisonDialog = ({ isOpen, onClose }: Props) => {
             <div className="image-selection">
               <select
                 value={selectedImageId || ''}
-                onChange={(e) =>
-                  setSelectedImageId(e.target.value ? parseInt(e.target.value) : null)
-                }
+                onChange={(e) => setSelectedImageId(imageIdFromValue(e.target.value))}
                 className="image-select-dropdown"
               >
                 <option value="">{t('comparison.selectImage')}</option>
@@ -475,7 +482,7 @@ export const ComparisonDialog = ({ isOpen, onClose }: Props) => {
 
               {/* 割り当て済みカテゴリ */}
               {cocoData.categories.map((cat) => {
-                const mappedCategories = categoryMapping.get(cat.id) || [];
+                const mappedCategories = categoryMapping.get(cocoIdKey(cat.id)) || [];
                 if (mappedCategories.length === 0) return null;
 
                 return (
@@ -522,9 +529,7 @@ export const ComparisonDialog = ({ isOpen, onClose }: Props) => {
 
               {/* 未割当カテゴリの行追加 */}
               {cocoData.categories
-                .filter(
-                  (cat) => !categoryMapping.has(cat.id) || categoryMapping.get(cat.id)?.length === 0
-                )
+                .filter((cat) => !categoryMapping.get(cocoIdKey(cat.id))?.length)
                 .map((cat) => (
                   <div key={cat.id} className="mapping-row">
                     <div className="category-name">
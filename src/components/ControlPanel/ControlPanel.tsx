@@ -6,10 +6,14 @@ import {
   useSettingsStore,
   generateCategoryColor,
 } from '../../stores';
-import { extractFieldsFromAnnotation, groupFieldsByCategory } from '../../utils';
+import type { CategoryFrequency, COCOCategory, CocoIdKey } from '../../types/coco';
+import { cocoIdKey, extractFieldsFromAnnotation, groupFieldsByCategory } from '../../utils';
 import SearchBox, { SearchBoxRef } from '../SearchBox/SearchBox';
 import './ControlPanel.css';
 
+// LVISの出現頻度の区分の表示順（区分なしは最後）
+const FREQUENCY_ORDER: (CategoryFrequency | null)[] = ['r', 'c', 'f', null];
+
 interface ControlPanelProps {
   onOpenComparisonDialog?: () => void;
 }
@@ -37,6 +41,7 @@ const ControlPanel: React.FC<ControlPanelProps> = ({ onOpenComparisonDialog }) =
   const { detail, updateDetailSettings, colors } = useSettingsStore();
 
   const [expandedCategories, setExpandedCategories] = useState<Set<string>>(new Set(['option']));
+  const [groupByFrequency, setGroupByFrequency] = useState(false);
 
   // Handle Ctrl+F keyboard shortcut
   useEffect(() => {
@@ -59,11 +64,31 @@ const ControlPanel: React.FC<ControlPanelProps> = ({ onOpenComparisonDialog }) =
 
   const annotationCounts = currentImageAnnotations.reduce(
     (acc, ann) => {
-      acc[ann.category_id] = (acc[ann.category_id] || 0) + 1;
+      const key = cocoIdKey(ann.category_id);
+      acc[key] = (acc[key] || 0) + 1;
       return acc;
     },
-    {} as Record<number, number>
+    {} as Record<CocoIdKey, number>
+  );
+
+  const hasFrequency = useMemo(
+    () => cocoData?.categories.some((category) => category.frequency) ?? false,
+    [cocoData]
+  );
+
+  // Only show categories that have annotations in the current image
+  const imageCategories = (cocoData?.categories ?? []).filter(
+    (category) => annotationCounts[cocoIdKey(category.id)] > 0
   );
+  const categoryGroups: { frequency?: CategoryFrequency | null; categories: COCOCategory[] }[] =
+    groupByFrequency && hasFrequency
+      ? FREQUENCY_ORDER.map((frequency) => ({
+          frequency,
+          categories: imageCategories.filter(
+            (category) => (category.frequency ?? null) === frequency
+          ),
+        })).filter((group) => group.categories.length > 0)
+      : [{ categories: imageCategories }];
 
   // Extract available fields from first annotation
   const availableFields = useMemo(() => {
@@ -292,6 +317,17 @@ const ControlPanel: React.FC<ControlPanelProps> = ({ onOpenComparisonDialog }) =
               <div className="section-header">
                 <h4>{t('controls.categories')}</h4>
                 <div className="section-actions">
+                  {hasFrequency && (
+                    <>
+                      <button
+                        className={`btn-text ${groupByFrequency ? 'active' : ''}`}
+                        onClick={() => setGroupByFrequency(!groupByFrequency)}
+                      >
+                        {t('controls.groupByFrequency')}
+                      </button>
+                      <span className="separator">|</span>
+                    </>
+                  )}
                   <button className="btn-text" onClick={showAllCategories}>
                     {t('controls.all')}
                   </button>
@@ -302,37 +338,42 @@ const ControlPanel: React.FC<ControlPanelProps> = ({ onOpenComparisonDialog }) =
                 </div>
               </div>
               <div className="category-list">
-                {cocoData.categories
-                  .filter((category) => {
-                    // Only show categories that have annotations in the current image
-                    return annotationCounts[category.id] > 0;
-                  })
-                  .map((category) => {
-                    const count = annotationCounts[category.id] || 0;
-                    const isVisible = visibleCategoryIds.includes(category.id);
-                    const categoryColor =
-                      colors.categoryColors[category.id] || generateCategoryColor(category.id);
-
-                    return (
-                      <label
-                        key={category.id}
-                        className={`category-item ${!isVisible ? 'disabled' : ''}`}
-                      >
-                        <input
-                          type="checkbox"
-                          className="checkbox"
-                          checked={isVisible}
-                          onChange={() => toggleCategoryVisibility(category.id)}
-                        />
-                        <div
-                          className="category-color"
-                          style={{ backgroundColor: categoryColor }}
-                        />
-                        <span className="category-name">{category.name}</span>
-                        <span className="category-count badge">{count}</span>
-                      </label>
-                    );
-                  })}
+                {categoryGroups.map((group) => (
+                  <React.Fragment key={group.frequency ?? 'none'}>
+                    {group.frequency !== undefined && (
+                      <div className="category-group-header">
+                        {t(`controls.frequency.${group.frequency ?? 'none'}`)}
+                      </div>
+                    )}
+                    {group.categories.map((category) => {
+                      const count = annotationCounts[cocoIdKey(category.id)] || 0;
+                      const isVisible = visibleCategoryIds.includes(category.id);
+                      const categoryColor =
+                        colors.categoryColors[cocoIdKey(category.id)] ||
+                        generateCategoryColor(category.id);
+
+                      return (
+                        <label
+                          key={category.id}
+                          className={`category-item ${!isVisible ? 'disabled' : ''}`}
+                        >
+                          <input
+                            type="checkbox"
+                            className="checkbox"
+                            checked={isVisible}
+                            onChange={() => toggleCategoryVisibility(category.id)}
+                          />
+                          <div
+                            className="category-color"
+                            style={{ backgroundColor: categoryColor }}
+                          />
+                          <span className="category-name">{category.name}</span>
+                          <span className="category-count badge">{count}</span>
+                        </label>
+                      );
+                    })}
+                  </React.Fragment>
+                ))}
               </div>
             </div>
           )}
@@ -4,11 +4,13 @@ import { useAnnotationStore } from '../../stores/useAnnotationStore';
 import { useHeatmapStore, HeatmapType, HeatmapSettings } from '../../stores/useHeatmapStore';
 import { HeatmapChart } from '../HeatmapChart';
 import { calculateHeatmap, exportHeatmapAsCSV } from '../../utils/heatmap';
+import { cocoIdKey } from '../../utils/cocoId';
+import type { CocoIdKey } from '../../types/coco';
 import './HeatmapPanel.css';
 
 export const HeatmapPanel: React.FC = () => {
   const { t } = useTranslation();
-  const { cocoData, currentImageId } = useAnnotationStore();
+  const { cocoData, currentImageId, dataset } = useAnnotationStore();
   const {
     settings,
     heatmapType,
@@ -24,10 +26,10 @@ export const HeatmapPanel: React.FC = () => {
 
   // カテゴリマップを作成
   const categoryMap = useMemo(() => {
-    const map = new Map<number, string>();
+    const map = new Map<CocoIdKey, string>();
     if (cocoData?.categories) {
       cocoData.categories.forEach((cat) => {
-        map.set(cat.id, cat.name);
+        map.set(cocoIdKey(cat.id), cat.name);
       });
     }
     return map;
@@ -144,7 +146,9 @@ export const HeatmapPanel: React.FC = () => {
   };
 
   // 複数画像があるかチェック
-  const hasMultipleImages = cocoData && cocoData.images && cocoData.images.length > 1;
+  // 大きなデータセットは表示中の画像のアノテーションだけを保持するため、全画像の集計は行わない
+  const hasMultipleImages =
+    !dataset?.partial && cocoData && cocoData.images && cocoData.images.length > 1;
 
   if (!cocoData || !cocoData.annotations || cocoData.annotations.length === 0) {
     return (
@@ -268,7 +272,7 @@ export const HeatmapPanel: React.FC = () => {
               onClick={() => {
                 // 全カテゴリ選択をクリア
                 cocoData.categories?.forEach((cat) => {
-                  if (settings.selectedCategories.has(cat.id)) {
+                  if (settings.selectedCategories.has(cocoIdKey(cat.id))) {
                     toggleCategory(cat.id);
                   }
                 });
@@ -279,7 +283,7 @@ export const HeatmapPanel: React.FC = () => {
             {cocoData.categories.map((category) => (
               <button
                 key={category.id}
-                className={`chip ${settings.selectedCategories.has(category.id) ? 'chip--active' : ''}`}
+                className={`chip ${settings.selectedCategories.has(cocoIdKey(category.id)) ? 'chip--active' : ''}`}
                 onClick={() => toggleCategory(category.id)}
               >
                 {category.name}
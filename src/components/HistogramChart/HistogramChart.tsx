@@ -12,7 +12,7 @@ import {
 } from 'recharts';
 import { useTranslation } from 'react-i18next';
 import { HistogramData, HistogramType } from '../../stores/useHistogramStore';
-import { COCOCategory } from '../../types/coco';
+import { COCOCategory, CocoId } from '../../types/coco';
 import './HistogramChart.css';
 
 interface HistogramChartProps {
@@ -28,7 +28,7 @@ interface ChartDataPoint {
   range: string;
   count: number;
   percentage: number;
-  categoryBreakdown: Array<{ categoryId: number; count: number }>;
+  categoryBreakdown: Array<{ categoryId: CocoId; count: number }>;
 }
 
 const CustomTooltip: React.FC<TooltipProps<number, string>> = ({ active, payload }) => {
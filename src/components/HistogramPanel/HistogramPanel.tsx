@@ -4,6 +4,8 @@ import { useAnnotationStore } from '../../stores/useAnnotationStore';
 import { useHistogramStore, HistogramType } from '../../stores/useHistogramStore';
 import { HistogramChart } from '../HistogramChart';
 import { calculateHistogram, exportHistogramAsCSV } from '../../utils/histogram';
+import { cocoIdKey } from '../../utils/cocoId';
+import type { CocoIdKey } from '../../types/coco';
 import './HistogramPanel.css';
 
 // 値のフォーマット関数
@@ -27,7 +29,7 @@ function formatValue(value: number, type: HistogramType): string {
 
 export const HistogramPanel: React.FC = () => {
   const { t } = useTranslation();
-  const { cocoData, currentImageId } = useAnnotationStore();
+  const { cocoData, currentImageId, dataset } = useAnnotationStore();
   const {
     settings,
     histogramType,
@@ -42,10 +44,10 @@ export const HistogramPanel: React.FC = () => {
 
   // カテゴリマップを作成
   const categoryMap = useMemo(() => {
-    const map = new Map<number, string>();
+    const map = new Map<CocoIdKey, string>();
     if (cocoData?.categories) {
       cocoData.categories.forEach((cat) => {
-        map.set(cat.id, cat.name);
+        map.set(cocoIdKey(cat.id), cat.name);
       });
     }
     return map;
@@ -181,7 +183,9 @@ export const HistogramPanel: React.FC = () => {
   };
 
   // 複数画像があるかチェック
-  const hasMultipleImages = cocoData && cocoData.images && cocoData.images.length > 1;
+  // 大きなデータセットは表示中の画像のアノテーションだけを保持するため、全画像の集計は行わない
+  const hasMultipleImages =
+    !dataset?.partial && cocoData && cocoData.images && cocoData.images.length > 1;
 
   if (!cocoData || !cocoData.annotations || cocoData.annotations.length === 0) {
     return (
@@ -300,7 +304,7 @@ export const HistogramPanel: React.FC = () => {
               onClick={() => {
                 // 全カテゴリ選択をクリア
                 cocoData.categories?.forEach((cat) => {
-                  if (settings.selectedCategories.has(cat.id)) {
+                  if (settings.selectedCategories.has(cocoIdKey(cat.id))) {
                     toggleCategory(cat.id);
                   }
                 });
@@ -311,7 +315,7 @@ export const HistogramPanel: React.FC = () => {
             {cocoData.categories.map((category) => (
               <button
                 key={category.id}
-                className={`chip ${settings.selectedCategories.has(category.id) ? 'chip--active' : ''}`}
+                className={`chip ${settings.selectedCategories.has(cocoIdKey(category.id)) ? 'chip--active' : ''}`}
                 onClick={() => toggleCategory(category.id)}
               >
                 {category.name}
@@ -1,6 +1,6 @@
 import React, { useState, useEffect } from 'react';
 import { useTranslation } from 'react-i18next';
-import { COCOImage } from '../../types/coco';
+import { COCOImage, CocoId } from '../../types/coco';
 import { CommonModal } from '../CommonModal';
 import './ImageSelectionDialog.css';
 
@@ -20,7 +20,7 @@ const ImageSelectionDialog: React.FC<ImageSelectionDialogProps> = ({
   onSelect,
 }) => {
   const { t } = useTranslation();
-  const [selectedImageId, setSelectedImageId] = useState<number | null>(null);
+  const [selectedImageId, setSelectedImageId] = useState<CocoId | null>(null);
 
   useEffect(() => {
     if (isOpen && images.length > 0) {
@@ -0,0 +1,105 @@
+import React, { useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { CommonModal } from '../CommonModal';
+import '../ImageSelectionDialog/ImageSelectionDialog.css';
+
+export interface ImportFormat {
+  id: string;
+  // 読み込みに使用するバックエンドのコマンド（引数は `path`）
+  command: string;
+  // フォルダを選択する形式か（false の場合はファイルを選択する）
+  directory: boolean;
+  extensions?: string[];
+}
+
+// Open ImagesはCSVを複数指定する必要があるため、ここでは扱わない
+export const IMPORT_FORMATS: ImportFormat[] = [
+  { id: 'yolo', command: 'import_yolo', directory: true },
+  { id: 'voc', command: 'import_voc', directory: true },
+  { id: 'labelme', command: 'import_labelme', directory: true },
+  { id: 'cvat', command: 'import_cvat', directory: false, extensions: ['xml'] },
+  {
+    id: 'labelStudio',
+    command: 'import_label_studio',
+    directory: false,
+    extensions: ['json', 'jsonl'],
+  },
+  { id: 'kitti', command: 'import_kitti', directory: true },
+  { id: 'dota', command: 'import_dota', directory: true },
+  { id: 'mot', command: 'import_mot', directory: true },
+  { id: 'panoptic', command: 'import_panoptic', directory: false, extensions: ['json'] },
+  {
+    id: 'tfrecord',
+    command: 'import_tfrecord',
+    directory: false,
+    extensions: ['tfrecord', 'record', 'gz'],
+  },
+];
+
+interface ImportDialogProps {
+  isOpen: boolean;
+  onClose: () => void;
+  onSelect: (format: ImportFormat) => void;
+}
+
+// 読み込む形式を選択する（パスの選択は形式に応じて呼び出し元で行う）
+const ImportDialog: React.FC<ImportDialogProps> = ({ isOpen, onClose, onSelect }) => {
+  const { t } = useTranslation();
+  const [selectedId, setSelectedId] = useState(IMPORT_FORMATS[0].id);
+
+  const handleSelect = () => {
+    const format = IMPORT_FORMATS.find((f) => f.id === selectedId);
+    if (format) {
+      onClose();
+      onSelect(format);
+    }
+  };
+
+  return (
+    <CommonModal
+      isOpen={isOpen}
+      onClose={onClose}
+      title={t('importDialog.title')}
+      size="lg"
+      hasBlur={true}
+      footer={
+        <>
+          <button className="btn btn-secondary" onClick={onClose}>
+            {t('importDialog.cancel')}
+          </button>
+          <button className="btn btn-primary" onClick={handleSelect}>
+            {t('importDialog.select')}
+          </button>
+        </>
+      }
+    >
+      <p className="dialog-description">{t('importDialog.description')}</p>
+
+      <div className="image-list">
+        {IMPORT_FORMATS.map((format) => (
+          <div
+            key={format.id}
+            className={`image-item ${selectedId === format.id ? 'selected' : ''}`}
+            onClick={() => setSelectedId(format.id)}
+          >
+            <div className="image-radio">
+              <input
+                type="radio"
+                id={`import-format-${format.id}`}
+                name="import-format"
+                checked={selectedId === format.id}
+                onChange={() => setSelectedId(format.id)}
+              />
+            </div>
+            <label htmlFor={`import-format-${format.id}`} className="image-info">
+              <div className="image-name">{t(`importDialog.formats.${format.id}.name`)}</div>
+              <div className="image-details">{t(`importDialog.formats.${format.id}.hint`)}</div>
+            </label>
+          </div>
+        ))}
+      </div>
+    </CommonModal>
+  );
+};
+
+export default ImportDialog;
@@ -0,0 +1,3 @@
+export { default } from './ImportDialog';
+export { IMPORT_FORMATS } from './ImportDialog';
+export type { ImportFormat } from './ImportDialog';
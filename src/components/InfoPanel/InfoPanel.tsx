@@ -7,6 +7,7 @@ import {
   generateCategoryColor,
 } from '../../stores';
 import { calculateAnnotationStatistics, formatNumber } from '../../utils/statistics';
+import { cocoIdKey } from '../../utils/cocoId';
 import './InfoPanel.css';
 
 const InfoPanel: React.FC = () => {
@@ -18,6 +19,7 @@ const InfoPanel: React.FC = () => {
     currentImageId,
     isComparing,
     diffResults,
+    dataset,
   } = useAnnotationStore();
 
   const { colors } = useSettingsStore();
@@ -58,8 +60,8 @@ const InfoPanel: React.FC = () => {
 
     // Try to get result for current image, or use first available result
     let resultToUse = null;
-    if (currentImageId && diffResults.has(currentImageId)) {
-      resultToUse = diffResults.get(currentImageId);
+    if (currentImageId && diffResults.has(cocoIdKey(currentImageId))) {
+      resultToUse = diffResults.get(cocoIdKey(currentImageId));
     } else {
       // Use first available result
       const firstEntry = diffResults.entries().next().value;
@@ -99,7 +101,9 @@ const InfoPanel: React.FC = () => {
   }, [isComparing, diffResults, currentImageId]);
 
   // Check if there are multiple images
-  const hasMultipleImages = cocoData && cocoData.images && cocoData.images.length > 1;
+  // 大きなデータセットは表示中の画像のアノテーションだけを保持するため、全画像の集計は行わない
+  const hasMultipleImages =
+    !dataset?.partial && cocoData && cocoData.images && cocoData.images.length > 1;
 
   // Show empty state when no image is loaded
   if (!currentImage || !cocoData) {
@@ -349,7 +353,7 @@ const InfoPanel: React.FC = () => {
                 <div className="category-list">
                   {statistics.categoryStats.slice(0, 5).map((stat) => {
                     const categoryColor =
-                      colors.categoryColors[stat.categoryId] ||
+                      colors.categoryColors[cocoIdKey(stat.categoryId)] ||
                       generateCategoryColor(stat.categoryId);
                     const isVisible = visibleCategoryIds.includes(stat.categoryId);
 
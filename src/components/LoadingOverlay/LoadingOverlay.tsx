@@ -6,6 +6,8 @@ interface LoadingOverlayProps {
   message?: string;
   progress?: number;
   subMessage?: string;
+  onCancel?: () => void;
+  cancelLabel?: string;
 }
 
 export const LoadingOverlay: React.FC<LoadingOverlayProps> = ({
@@ -13,6 +15,8 @@ export const LoadingOverlay: React.FC<LoadingOverlayProps> = ({
   message = 'Processing...',
   progress,
   subMessage,
+  onCancel,
+  cancelLabel = 'Cancel',
 }) => {
   if (!isLoading) return null;
 
@@ -33,6 +37,11 @@ export const LoadingOverlay: React.FC<LoadingOverlayProps> = ({
             <div className="progress-text">{Math.round(progress)}%</div>
           </div>
         )}
+        {onCancel && (
+          <button className="btn btn-secondary loading-cancel" onClick={onCancel}>
+            {cancelLabel}
+          </button>
+        )}
       </div>
     </div>
   );
@@ -4,10 +4,11 @@ import { useTranslation } from 'react-i18next';
 import { useNavigationStore } from '../../stores/useNavigationStore';
 import { useImageStore, useAnnotationStore, useLoadingStore } from '../../stores';
 import { invoke } from '@tauri-apps/api/core';
+import type { CocoId } from '../../types/coco';
 import './NavigationPanel.css';
 
 interface ImageMetadata {
-  id: number;
+  id: CocoId;
   fileName: string;
   filePath: string;
   width: number;
@@ -175,14 +176,9 @@ export const NavigationPanel: React.FC = () => {
         return;
       }
 
-      // Check if it's a valid number (only half-width digits)
-      if (!/^\d+$/.test(value.trim())) {
-        setJumpError(t('navigation.invalidIdFormat'));
-        return;
-      }
-
-      const id = parseInt(value.trim());
-      const image = imageList.find((img) => img.id === id);
+      // IDは文字列の場合もあるため、入力された表記のまま比較する
+      const id = value.trim();
+      const image = imageList.find((img) => String(img.id) === id);
 
       if (!image) {
         setJumpError(t('navigation.idNotFound'));
@@ -204,8 +200,7 @@ export const NavigationPanel: React.FC = () => {
     const trimmedId = jumpId.trim();
     if (!trimmedId || jumpError) return;
 
-    const id = parseInt(trimmedId);
-    const imageIndex = imageList.findIndex((img) => img.id === id);
+    const imageIndex = imageList.findIndex((img) => String(img.id) === trimmedId);
     if (imageIndex !== -1 && imageList[imageIndex].exists) {
       setCurrentIndex(imageIndex);
       setShouldAutoUpdate(true);
@@ -294,7 +289,7 @@ export const NavigationPanel: React.FC = () => {
   const [prevFolderPath, setPrevFolderPath] = useState<string | null>(null);
 
   // Track the previous currentImageId to detect tab switching
-  const [prevCurrentImageId, setPrevCurrentImageId] = useState<number | null>(null);
+  const [prevCurrentImageId, setPrevCurrentImageId] = useState<CocoId | null>(null);
 
   // Ensure currentIndex matches annotation's currentImageId when tab becomes visible
   useEffect(() => {
@@ -309,17 +304,17 @@ export const NavigationPanel: React.FC = () => {
         // Case 1: Different index but same image (tab switching) - just sync index
         if (imageIndex !== currentIndex && isImageAlreadyLoaded) {
           setCurrentIndex(imageIndex);
-          setPrevCurrentImageId(currentAnnotation.currentImageId as number | null);
+          setPrevCurrentImageId(currentAnnotation.currentImageId);
         }
         // Case 2: Different image - need to load
         else if (!isImageAlreadyLoaded) {
           setCurrentIndex(imageIndex);
-          setPrevCurrentImageId(currentAnnotation.currentImageId as number | null);
+          setPrevCurrentImageId(currentAnnotation.currentImageId);
           setShouldAutoUpdate(true);
         }
         // Case 3: currentImageId changed but same image (annotation updated) - just update tracker
         else if (currentAnnotation.currentImageId !== prevCurrentImageId) {
-          setPrevCurrentImageId(currentAnnotation.currentImageId as number | null);
+          setPrevCurrentImageId(currentAnnotation.currentImageId);
         }
       }
     }
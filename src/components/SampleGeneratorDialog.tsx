@@ -168,7 +168,11 @@ const SampleGeneratorDialog: React.FC<SampleGeneratorDialogProps> = ({
       onClose();
     } catch (error) {
       console.error('Error generating sample data:', error);
-      alert(`${t('sampleGenerator.generationFailed')}: ${error}`);
+      const errorMessage =
+        error && typeof error === 'object' && 'message' in error
+          ? (error.message as string)
+          : String(error);
+      alert(`${t('sampleGenerator.generationFailed')}: ${errorMessage}`);
     } finally {
       setIsGenerating(false);
       setLoading(false);
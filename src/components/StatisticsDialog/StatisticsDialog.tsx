@@ -2,6 +2,8 @@ import React, { useMemo, useState } from 'react';
 import { useTranslation } from 'react-i18next';
 import { useAnnotationStore } from '../../stores';
 import { calculateAnnotationStatistics } from '../../utils/statistics';
+import { cocoIdKey } from '../../utils/cocoId';
+import type { CocoIdKey } from '../../types/coco';
 import { CommonModal } from '../CommonModal';
 import './StatisticsDialog.css';
 
@@ -19,13 +21,18 @@ const StatisticsDialog: React.FC<StatisticsDialogProps> = ({ isOpen, onClose })
     currentImageId,
     isComparing,
     diffResults,
+    dataset,
   } = useAnnotationStore();
+  // 大きなデータセットは表示中の画像のアノテーションだけを保持するため、全画像の集計は行わない
+  const partial = dataset?.partial ?? false;
+
   // Determine initial view mode based on dataset
   const initialViewMode = useMemo(() => {
+    if (partial) return 'current';
     if (!cocoData) return 'all';
     // If only one image, default to 'current' mode
     return cocoData.images.length === 1 ? 'current' : 'all';
-  }, [cocoData]);
+  }, [cocoData, partial]);
 
   const [viewMode, setViewMode] = useState<'current' | 'all'>(initialViewMode);
 
@@ -56,18 +63,23 @@ const StatisticsDialog: React.FC<StatisticsDialogProps> = ({ isOpen, onClose })
     }
   }, [cocoData, currentImageId, viewMode, visibleCategoryIds, selectedAnnotationIds]);
 
-  const hasMultipleImages = cocoData && cocoData.images && cocoData.images.length > 1;
+  const hasMultipleImages = !partial && cocoData && cocoData.images && cocoData.images.length > 1;
 
   // Calculate dataset-level statistics (always for all data)
   const datasetStats = useMemo(() => {
     if (!cocoData) return null;
 
-    const totalAnnotations = cocoData.annotations.length;
+    // 大きなデータセットは、バックエンドの概要と画像一覧のアノテーション数から集計する
+    const totalAnnotations = dataset?.partial
+      ? dataset.summary.annotationCount
+      : cocoData.annotations.length;
     const totalImages = cocoData.images.length;
     const totalCategories = cocoData.categories.length;
 
     // Images with/without annotations
-    const imagesWithAnnotations = new Set(cocoData.annotations.map((ann) => ann.image_id)).size;
+    const imagesWithAnnotations = dataset?.partial
+      ? cocoData.images.filter((img) => Number(img.annotationCount) > 0).length
+      : new Set(cocoData.annotations.map((ann) => cocoIdKey(ann.image_id))).size;
     const imagesWithoutAnnotations = totalImages - imagesWithAnnotations;
 
     // Average annotations per image
@@ -81,7 +93,7 @@ const StatisticsDialog: React.FC<StatisticsDialogProps> = ({ isOpen, onClose })
       imagesWithoutAnnotations,
       avgAnnotationsPerImage,
     };
-  }, [cocoData]);
+  }, [cocoData, dataset]);
 
   // Calculate comparison metrics for current image
   const currentImageComparisonMetrics = useMemo(() => {
@@ -95,7 +107,7 @@ const StatisticsDialog: React.FC<StatisticsDialogProps> = ({ isOpen, onClose })
     }
 
     // Get diff result for current image
-    let resultToUse = diffResults.get(currentImageId);
+    let resultToUse = diffResults.get(cocoIdKey(currentImageId));
 
     // If no result for current image, try to get the first available result
     if (!resultToUse && diffResults.size > 0) {
@@ -110,6 +122,7 @@ const StatisticsDialog: React.FC<StatisticsDialogProps> = ({ isOpen, onClose })
     const tp = resultToUse.truePositives.length;
     const fp = resultToUse.falsePositives.length;
     const fn = resultToUse.falseNegatives.length;
+    const unknown = resultToUse.unknownPredictions.length;
 
     // Calculate metrics
     const precision = tp + fp > 0 ? tp / (tp + fp) : 0;
@@ -120,6 +133,7 @@ const StatisticsDialog: React.FC<StatisticsDialogProps> = ({ isOpen, onClose })
       tp,
       fp,
       fn,
+      unknown,
       precision,
       recall,
       f1,
@@ -140,15 +154,15 @@ const StatisticsDialog: React.FC<StatisticsDialogProps> = ({ isOpen, onClose })
 
     // Category distribution
     const categoryDistribution = new Map<
-      number,
+      CocoIdKey,
       { name: string; count: number; percentage: number }
     >();
     cocoData.categories.forEach((cat) => {
-      categoryDistribution.set(cat.id, { name: cat.name, count: 0, percentage: 0 });
+      categoryDistribution.set(cocoIdKey(cat.id), { name: cat.name, count: 0, percentage: 0 });
     });
 
     annotations.forEach((ann) => {
-      const catData = categoryDistribution.get(ann.category_id);
+      const catData = categoryDistribution.get(cocoIdKey(ann.category_id));
       if (catData) {
         catData.count++;
       }
@@ -240,6 +254,9 @@ const StatisticsDialog: React.FC<StatisticsDialogProps> = ({ isOpen, onClose })
         text += `True Positives (TP): ${currentImageComparisonMetrics.tp}\n`;
         text += `False Positives (FP): ${currentImageComparisonMetrics.fp}\n`;
         text += `False Negatives (FN): ${currentImageComparisonMetrics.fn}\n`;
+        if (currentImageComparisonMetrics.unknown > 0) {
+          text += `Unknown (not exhaustively labeled): ${currentImageComparisonMetrics.unknown}\n`;
+        }
         text += `Precision: ${(currentImageComparisonMetrics.precision * 100).toFixed(1)}%\n`;
         text += `Recall: ${(currentImageComparisonMetrics.recall * 100).toFixed(1)}%\n`;
         text += `F1 Score: ${(currentImageComparisonMetrics.f1 * 100).toFixed(1)}%\n`;
@@ -451,6 +468,13 @@ const StatisticsDialog: React.FC<StatisticsDialogProps> = ({ isOpen, onClose })
                       <div className="statistic-label">{t('statistics.f1Score')}</div>
                     </div>
                   </div>
+                  {currentImageComparisonMetrics.unknown > 0 && (
+                    <div className="comparison-mode-notice">
+                      {t('statistics.unknownPredictions', {
+                        count: currentImageComparisonMetrics.unknown,
+                      })}
+                    </div>
+                  )}
                 </>
               ) : (
                 <>
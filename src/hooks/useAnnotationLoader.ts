@@ -0,0 +1,199 @@
+import { useCallback } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { open } from '@tauri-apps/plugin-dialog';
+import { useTranslation } from 'react-i18next';
+import { toast, useArchiveEntryStore, useLoadingStore } from '../stores';
+import { datasetKey, formatFileSize } from '../utils';
+import type { COCOData } from '../types/coco';
+import type {
+  ArchiveEntry,
+  DatasetSummary,
+  ImageListItem,
+  LoadProgress,
+  OpenDataset,
+  Page,
+} from '../types/app';
+
+const ANNOTATION_EXTENSIONS = ['json', 'gz', 'zst', 'zip'];
+const LOAD_PROGRESS_EVENT = 'annotation-load-progress';
+
+// アノテーション数がこれを超えるデータセットは、表示する画像のアノテーションだけを取得する
+const FULL_LOAD_ANNOTATION_LIMIT = 200_000;
+
+export interface LoadedAnnotations {
+  data: COCOData;
+  dataset: OpenDataset;
+}
+
+export function hasErrorCode(error: unknown, code: string): boolean {
+  return !!error && typeof error === 'object' && 'code' in error && error.code === code;
+}
+
+export function useAnnotationLoader() {
+  const { t } = useTranslation();
+
+  // 検出結果ファイルの場合は、結合する正解データを選択してもらう
+  const pickReferenceFile = useCallback(async (): Promise<string | null> => {
+    if (!window.confirm(t('errors.referenceRequired'))) {
+      return null;
+    }
+    const selected = await open({
+      multiple: false,
+      title: t('files.selectReference'),
+      filters: [{ name: 'COCO JSON', extensions: ANNOTATION_EXTENSIONS }],
+    });
+    return typeof selected === 'string' ? selected : null;
+  }, [t]);
+
+  // データセットを開く（読み込み中は進捗を表示し、中止ボタンで cancel_annotation_load を呼び出す）
+  const openDataset = useCallback(
+    async (args: { filePath: string; referencePath?: string; lenient: boolean }) => {
+      const { setLoading, setCancelHandler } = useLoadingStore.getState();
+      let loadId: number | null = null;
+      let cancelRequested = false;
+      const cancel = () => {
+        cancelRequested = true;
+        if (loadId !== null) {
+          invoke('cancel_annotation_load', { loadId }).catch((error) => {
+            console.error('Failed to cancel loading:', error);
+          });
+        }
+      };
+
+      setLoading(true, t('loader.loading'));
+      setCancelHandler(cancel);
+      let unlisten: UnlistenFn | undefined;
+      try {
+        unlisten = await listen<LoadProgress>(LOAD_PROGRESS_EVENT, ({ payload }) => {
+          // 同時に実行中の他の読み込みの進捗は無視する（zip内のファイルはzipのパスから始まる）
+          const paths = [args.filePath, args.referencePath];
+          if (!paths.some((path) => path && payload.filePath.startsWith(path))) {
+            return;
+          }
+          // 進捗が届く前に中止された場合は、読み込みIDが分かった時点で中止する
+          const first = loadId === null;
+          loadId = payload.loadId;
+          if (first && cancelRequested) {
+            cancel();
+          }
+          setLoading(
+            true,
+            t('loader.loading'),
+            t('loader.progress', {
+              read: formatFileSize(payload.bytesRead),
+              total: formatFileSize(payload.totalBytes),
+              images: payload.images.toLocaleString(),
+              annotations: payload.annotations.toLocaleString(),
+            }),
+            payload.totalBytes > 0 ? (payload.bytesRead / payload.totalBytes) * 100 : undefined
+          );
+        });
+        return await invoke<DatasetSummary>('open_dataset', args);
+      } finally {
+        unlisten?.();
+        setLoading(false);
+      }
+    },
+    [t]
+  );
+
+  // データセットを開き、正解データが必要な場合は選択後に再実行する
+  const openWithReference = useCallback(
+    async (filePath: string, lenient = false): Promise<DatasetSummary> => {
+      try {
+        return await openDataset({ filePath, lenient });
+      } catch (error) {
+        if (!hasErrorCode(error, 'reference_required')) {
+          throw error;
+        }
+        const referencePath = await pickReferenceFile();
+        if (!referencePath) {
+          throw error;
+        }
+        return await openDataset({ filePath, referencePath, lenient });
+      }
+    },
+    [openDataset, pickReferenceFile]
+  );
+
+  // 小さなデータセットはすべてのアノテーションを、大きなデータセットは画像の一覧だけを取得する
+  const fetchDataset = useCallback(
+    async (summary: DatasetSummary): Promise<LoadedAnnotations> => {
+      const dataset = datasetKey(summary);
+      const { setLoading } = useLoadingStore.getState();
+      setLoading(true, t('loader.loading'));
+      try {
+        if (summary.annotationCount <= FULL_LOAD_ANNOTATION_LIMIT) {
+          const data = await invoke<COCOData>('get_dataset', { dataset });
+          return { data, dataset: { summary, partial: false } };
+        }
+
+        const page = await invoke<Page<ImageListItem>>('list_images', {
+          dataset,
+          offset: 0,
+          limit: summary.imageCount,
+        });
+        const data: COCOData = {
+          info: summary.info ?? undefined,
+          images: page.items,
+          annotations: [],
+          categories: summary.categories,
+          videos: summary.videos,
+        };
+        return { data, dataset: { summary, partial: true } };
+      } finally {
+        setLoading(false);
+      }
+    },
+    [t]
+  );
+
+  // JSONとして読み込めない場合は、確認のうえ寛容モードで読み込み直す
+  const loadFile = useCallback(
+    async (filePath: string): Promise<LoadedAnnotations> => {
+      try {
+        return await fetchDataset(await openWithReference(filePath));
+      } catch (error) {
+        if (!hasErrorCode(error, 'parse_error') || !window.confirm(t('errors.retryLenient'))) {
+          throw error;
+        }
+      }
+
+      const summary = await openWithReference(filePath, true);
+      const diagnostics = summary.diagnostics;
+      const count = diagnostics ? diagnostics.items.length + diagnostics.omitted : 0;
+      if (diagnostics && count > 0) {
+        const first = diagnostics.items[0];
+        toast.warning(
+          t('errors.lenientLoadIssues', { count }),
+          `${first.jsonPath} (byte ${first.byteOffset}): ${first.message}`
+        );
+      }
+      return await fetchDataset(summary);
+    },
+    [openWithReference, fetchDataset, t]
+  );
+
+  // zip内に複数のアノテーションファイルがある場合は、読み込むファイルを選択してもらう
+  const loadAnnotationFile = useCallback(
+    async (filePath: string): Promise<LoadedAnnotations> => {
+      try {
+        return await loadFile(filePath);
+      } catch (error) {
+        if (!hasErrorCode(error, 'archive_entry_required')) {
+          throw error;
+        }
+        const entries = await invoke<ArchiveEntry[]>('list_archive_entries', { filePath });
+        const entry = await useArchiveEntryStore.getState().requestEntry(filePath, entries);
+        if (!entry) {
+          throw error;
+        }
+        return await loadFile(entry.filePath);
+      }
+    },
+    [loadFile]
+  );
+
+  return { loadAnnotationFile };
+}
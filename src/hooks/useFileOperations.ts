@@ -1,6 +1,6 @@
-import { useCallback, useState } from 'react';
+import { useCallback, useRef, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import { open } from '@tauri-apps/plugin-dialog';
+import { open, save } from '@tauri-apps/plugin-dialog';
 import { useTranslation } from 'react-i18next';
 import {
   useAnnotationStore,
@@ -11,9 +11,14 @@ import {
 } from '../stores';
 import type { RecentFile } from '../stores';
 import type { COCOData } from '../types/coco';
+import type { ImportResult, OpenDataset } from '../types/app';
+import type { ImportFormat } from '../components/ImportDialog';
+import { closeDataset, datasetKey, isSameDataset } from '../utils';
+import { hasErrorCode, useAnnotationLoader } from './useAnnotationLoader';
 
 interface TempCocoData {
   data: COCOData;
+  dataset: OpenDataset;
   annotationDir: string;
 }
 
@@ -24,13 +29,27 @@ export function useFileOperations() {
   const { setImagePath, setImageData, setLoading, setError } = useImageStore();
   const { addRecentFile } = useRecentFilesStore();
   const { navigationMode } = useNavigationStore();
+  const { loadAnnotationFile } = useAnnotationLoader();
 
   const [showSampleGenerator, setShowSampleGenerator] = useState(false);
   const [showStatistics, setShowStatistics] = useState(false);
   const [showImageSelection, setShowImageSelection] = useState(false);
   const [showComparisonDialog, setShowComparisonDialog] = useState(false);
   const [showHistogramDialog, setShowHistogramDialog] = useState(false);
+  const [showImportDialog, setShowImportDialog] = useState(false);
   const [tempCocoData, setTempCocoData] = useState<TempCocoData | null>(null);
+  const tempCocoDataRef = useRef<TempCocoData | null>(null);
+  tempCocoDataRef.current = tempCocoData;
+
+  // 画像の選択を待っているデータセットを、表示中のものでなければバックエンドから破棄する
+  const discardTempCocoData = useCallback(() => {
+    const temp = tempCocoDataRef.current;
+    const { dataset } = useAnnotationStore.getState();
+    if (temp && !(dataset && isSameDataset(dataset, temp.dataset))) {
+      closeDataset(temp.dataset);
+    }
+    setTempCocoData(null);
+  }, []);
 
   // Check if images are available
   const { imageData } = useImageStore.getState();
@@ -113,7 +132,7 @@ export function useFileOperations() {
         filters: [
           {
             name: 'JSON',
-            extensions: ['json'],
+            extensions: ['json', 'gz', 'zst', 'zip'],
           },
         ],
       });
@@ -122,25 +141,25 @@ export function useFileOperations() {
 
       const jsonPath = selected as string;
       setShowImageSelection(false);
-      setTempCocoData(null);
+      discardTempCocoData();
 
-      const data = await invoke<COCOData>('load_annotations', { filePath: jsonPath });
+      const { data, dataset } = await loadAnnotationFile(jsonPath);
       const annotationDir =
         jsonPath.substring(0, jsonPath.lastIndexOf('/')) ||
         jsonPath.substring(0, jsonPath.lastIndexOf('\\'));
 
       if (data.images && data.images.length > 1) {
         if (navigationMode === 'folder') {
-          setCocoData(data);
+          setCocoData(data, dataset);
           setCurrentImageId(data.images[0].id);
         } else {
-          setTempCocoData({ data, annotationDir });
+          setTempCocoData({ data, dataset, annotationDir });
         }
       } else if (data.images && data.images.length === 1) {
-        setCocoData(data);
+        setCocoData(data, dataset);
         setCurrentImageId(data.images[0].id);
       } else {
-        setCocoData(data);
+        setCocoData(data, dataset);
         // Set first available image ID from annotations if images array is empty/missing
         if (data.annotations && data.annotations.length > 0) {
           const firstImageId = data.annotations[0].image_id;
@@ -155,9 +174,13 @@ export function useFileOperations() {
       });
       toast.success(
         t('success.annotationsLoaded'),
-        `${t('success.loaded')} ${data.annotations.length} ${t('controls.annotations').toLowerCase()}`
+        `${t('success.loaded')} ${dataset.summary.annotationCount} ${t('controls.annotations').toLowerCase()}`
       );
     } catch (error) {
+      if (hasErrorCode(error, 'cancelled')) {
+        toast.info(t('info.loadCancelled'));
+        return;
+      }
       console.error('Error loading annotations:', error);
       let errorMessage = 'Failed to load annotations';
       if (error && typeof error === 'object' && 'message' in error) {
@@ -168,7 +191,15 @@ export function useFileOperations() {
       setError(errorMessage);
       toast.error(t('errors.loadAnnotationsFailed'), errorMessage);
     }
-  }, [setCocoData, setCurrentImageId, setError, addRecentFile, t]);
+  }, [
+    setCocoData,
+    setCurrentImageId,
+    setError,
+    addRecentFile,
+    loadAnnotationFile,
+    discardTempCocoData,
+    t,
+  ]);
 
   const handleOpenFolder = useCallback(async () => {
     try {
@@ -211,25 +242,93 @@ export function useFileOperations() {
     }
   }, [t, clearCocoData]);
 
-  const handleExportAnnotations = useCallback(() => {
-    const { cocoData } = useAnnotationStore.getState();
+  const handleExportAnnotations = useCallback(async () => {
+    const { cocoData, dataset } = useAnnotationStore.getState();
     if (!cocoData) return;
 
-    const dataStr = JSON.stringify(cocoData, null, 2);
-    const dataBlob = new Blob([dataStr], { type: 'application/json' });
-    const url = URL.createObjectURL(dataBlob);
+    try {
+      const outputPath = await save({
+        defaultPath: 'annotations.json',
+        filters: [
+          {
+            name: 'JSON',
+            extensions: ['json'],
+          },
+        ],
+      });
 
-    const link = document.createElement('a');
-    link.href = url;
-    link.download = 'annotations.json';
-    document.body.appendChild(link);
-    link.click();
-    document.body.removeChild(link);
-    URL.revokeObjectURL(url);
+      if (!outputPath) return;
 
-    toast.success(t('success.annotationsExported'), t('success.exported'));
+      // バックエンドで開いているデータセットは、アノテーションを送らずにそのまま書き出す
+      const source = dataset ? { dataset: datasetKey(dataset.summary) } : { cocoData };
+      await invoke('export_annotations', { outputPath, ...source, options: { pretty: true } });
+      toast.success(t('success.annotationsExported'), t('success.exported'));
+    } catch (error) {
+      console.error('Error exporting annotations:', error);
+      let errorMessage = 'Failed to export annotations';
+      if (error && typeof error === 'object' && 'message' in error) {
+        errorMessage = error.message as string;
+      } else if (typeof error === 'string') {
+        errorMessage = error;
+      }
+      toast.error(t('errors.exportFailed'), errorMessage);
+    }
   }, [t]);
 
+  // 他の形式のデータセットをCOCO形式に変換して読み込み、画像のフォルダを開く
+  const handleImportDataset = useCallback(
+    async (format: ImportFormat) => {
+      try {
+        const selected = await open({
+          multiple: false,
+          directory: format.directory,
+          title: t(`importDialog.formats.${format.id}.hint`),
+          filters: format.extensions
+            ? [{ name: t(`importDialog.formats.${format.id}.name`), extensions: format.extensions }]
+            : undefined,
+        });
+        if (typeof selected !== 'string') return;
+
+        setLoading(true);
+        const result = await invoke<ImportResult>(format.command, { path: selected });
+        const { cocoData, imageRoot, issues } = result;
+
+        const { setSelectedFolderPath, clearImageList } = useNavigationStore.getState();
+        useImageStore.getState().clearImage();
+        clearImageList();
+        setSelectedFolderPath(imageRoot);
+        setCocoData(cocoData);
+        if (cocoData.images.length > 0) {
+          setCurrentImageId(cocoData.images[0].id);
+        }
+
+        toast.success(
+          t('success.datasetImported'),
+          `${t('success.loaded')} ${cocoData.annotations.length} ${t('controls.annotations').toLowerCase()}`
+        );
+        if (issues.length > 0) {
+          const first = issues[0];
+          toast.warning(
+            t('info.importIssues', { count: issues.length }),
+            `${first.file}${first.line ? `:${first.line}` : ''}: ${first.message}`
+          );
+        }
+      } catch (error) {
+        console.error('Error importing dataset:', error);
+        let errorMessage = 'Failed to import dataset';
+        if (error && typeof error === 'object' && 'message' in error) {
+          errorMessage = error.message as string;
+        } else if (typeof error === 'string') {
+          errorMessage = error;
+        }
+        toast.error(t('errors.importFailed'), errorMessage);
+      } finally {
+        setLoading(false);
+      }
+    },
+    [setCocoData, setCurrentImageId, setLoading, t]
+  );
+
   const handleGenerateSample = useCallback(() => {
     setShowSampleGenerator(true);
   }, []);
@@ -280,7 +379,7 @@ export function useFileOperations() {
       try {
         setLoading(true);
         setShowImageSelection(false);
-        setTempCocoData(null);
+        discardTempCocoData();
 
         const { resetToSingleMode } = useNavigationStore.getState();
 
@@ -334,6 +433,7 @@ export function useFileOperations() {
       addRecentFile,
       isComparing,
       clearComparison,
+      discardTempCocoData,
       t,
     ]
   );
@@ -351,9 +451,9 @@ export function useFileOperations() {
 
       try {
         setShowImageSelection(false);
-        setTempCocoData(null);
+        discardTempCocoData();
 
-        const data = await invoke<COCOData>('load_annotations', { filePath: jsonPath });
+        const { data, dataset } = await loadAnnotationFile(jsonPath);
         const annotationDir =
           jsonPath.substring(0, jsonPath.lastIndexOf('/')) ||
           jsonPath.substring(0, jsonPath.lastIndexOf('\\'));
@@ -364,16 +464,16 @@ export function useFileOperations() {
 
         if (data.images && data.images.length > 1) {
           if (navigationMode === 'folder') {
-            setCocoData(data);
+            setCocoData(data, dataset);
             setCurrentImageId(data.images[0].id);
           } else {
-            setTempCocoData({ data, annotationDir });
+            setTempCocoData({ data, dataset, annotationDir });
           }
         } else if (data.images && data.images.length === 1) {
-          setCocoData(data);
+          setCocoData(data, dataset);
           setCurrentImageId(data.images[0].id);
         } else {
-          setCocoData(data);
+          setCocoData(data, dataset);
           // Set first available image ID from annotations if images array is empty/missing
           if (data.annotations && data.annotations.length > 0) {
             const firstImageId = data.annotations[0].image_id;
@@ -388,9 +488,13 @@ export function useFileOperations() {
         });
         toast.success(
           t('success.annotationsLoaded'),
-          `${t('success.loaded')} ${data.annotations.length} ${t('controls.annotations').toLowerCase()}`
+          `${t('success.loaded')} ${dataset.summary.annotationCount} ${t('controls.annotations').toLowerCase()}`
         );
       } catch (error) {
+        if (hasErrorCode(error, 'cancelled')) {
+          toast.info(t('info.loadCancelled'));
+          return;
+        }
         console.error('Error loading annotations:', error);
         let errorMessage = 'Failed to load annotations';
         if (error && typeof error === 'object' && 'message' in error) {
@@ -402,7 +506,17 @@ export function useFileOperations() {
         toast.error(t('errors.loadAnnotationsFailed'), errorMessage);
       }
     },
-    [setCocoData, setError, addRecentFile, isComparing, clearComparison, setCurrentImageId, t]
+    [
+      setCocoData,
+      setError,
+      addRecentFile,
+      isComparing,
+      clearComparison,
+      setCurrentImageId,
+      loadAnnotationFile,
+      discardTempCocoData,
+      t,
+    ]
   );
 
   const handleRecentFileSelect = useCallback(
@@ -428,8 +542,11 @@ export function useFileOperations() {
     setShowComparisonDialog,
     showHistogramDialog,
     setShowHistogramDialog,
+    showImportDialog,
+    setShowImportDialog,
     tempCocoData,
     setTempCocoData,
+    discardTempCocoData,
     hasImagesAvailable,
 
     // Handlers
@@ -437,6 +554,7 @@ export function useFileOperations() {
     handleOpenAnnotations,
     handleOpenFolder,
     handleExportAnnotations,
+    handleImportDataset,
     handleGenerateSample,
     handleShowStatistics,
     handleSampleGenerated,
@@ -7,6 +7,7 @@ interface MenuEventHandlers {
   onOpenAnnotations: () => void;
   onGenerateSample: () => void;
   onExportAnnotations: () => void;
+  onImportDataset?: () => void;
   onShowStatistics: () => void;
   onShowSettings: () => void;
   onShowComparison?: () => void;
@@ -23,6 +24,7 @@ export const useMenuEvents = (handlers: MenuEventHandlers) => {
     onOpenAnnotations,
     onGenerateSample,
     onExportAnnotations,
+    onImportDataset,
     onShowStatistics,
     onShowSettings,
     onShowComparison,
@@ -71,6 +73,9 @@ export const useMenuEvents = (handlers: MenuEventHandlers) => {
       );
 
       // Optional event listeners
+      if (onImportDataset) {
+        unsubscribers.push(await listen('menu-import_dataset', onImportDataset));
+      }
       if (onShowComparison) {
         unsubscribers.push(await listen('menu-compare', onShowComparison));
       }
@@ -102,6 +107,7 @@ export const useMenuEvents = (handlers: MenuEventHandlers) => {
     onOpenAnnotations,
     onGenerateSample,
     onExportAnnotations,
+    onImportDataset,
     onShowStatistics,
     onShowSettings,
     onShowComparison,
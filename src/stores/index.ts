@@ -7,6 +7,7 @@ export { useLoadingStore } from './useLoadingStore';
 export { useHistogramStore } from './useHistogramStore';
 export { useHeatmapStore } from './useHeatmapStore';
 export { useNavigationStore } from './useNavigationStore';
+export { useArchiveEntryStore } from './useArchiveEntryStore';
 export type { RecentFile } from './useRecentFilesStore';
 export type { Language, Theme, TabType } from './useSettingsStore';
 export type {
@@ -1,13 +1,17 @@
 import { create } from 'zustand';
-import { COCOData, COCOAnnotation, COCOCategory } from '../types/coco';
+import { invoke } from '@tauri-apps/api/core';
+import { COCOData, COCOAnnotation, COCOCategory, CocoId, CocoIdKey } from '../types/coco';
 import { DiffResult, DiffStatistics, DiffFilter, ComparisonSettings } from '../types/diff';
+import type { OpenDataset } from '../types/app';
+import { closeDataset, datasetKey, isSameDataset } from '../utils/dataset';
 
 interface AnnotationState {
   cocoData: COCOData | null;
-  selectedAnnotationIds: (number | string)[];
-  visibleCategoryIds: number[];
-  hoveredAnnotationId: number | string | null;
-  currentImageId: number | null;
+  dataset: OpenDataset | null; // open_dataset で開いたデータセット（インポートなどの場合は null）
+  selectedAnnotationIds: CocoId[];
+  visibleCategoryIds: CocoId[];
+  hoveredAnnotationId: CocoId | null;
+  currentImageId: CocoId | null;
 
   // Detail panel state
   isDetailPanelOpen: boolean;
@@ -15,7 +19,7 @@ interface AnnotationState {
 
   // Search state
   searchQuery: string;
-  searchResults: number[];
+  searchResults: CocoId[];
   currentSearchIndex: number;
   shouldCenterOnSelection: boolean;
 
@@ -24,19 +28,19 @@ interface AnnotationState {
   originalComparisonData: COCOData | null; // Store original unfiltered data
   isComparing: boolean;
   comparisonSettings: ComparisonSettings | null;
-  diffResults: Map<number, DiffResult>;
+  diffResults: Map<CocoIdKey, DiffResult>; // 画像IDのキー（cocoIdKey）ごとの差分
   diffStatistics: DiffStatistics | null;
   diffFilters: Set<DiffFilter>;
 
   // Actions
-  setCocoData: (data: COCOData) => void;
+  setCocoData: (data: COCOData, dataset?: OpenDataset | null) => void;
   clearCocoData: () => void;
-  selectAnnotation: (id: number | string, multiSelect?: boolean) => void;
+  selectAnnotation: (id: CocoId, multiSelect?: boolean) => void;
   clearSelection: () => void;
-  toggleCategoryVisibility: (categoryId: number) => void;
+  toggleCategoryVisibility: (categoryId: CocoId) => void;
   showAllCategories: () => void;
   hideAllCategories: () => void;
-  setHoveredAnnotation: (id: number | string | null) => void;
+  setHoveredAnnotation: (id: CocoId | null) => void;
 
   // Detail panel actions
   setDetailPanelOpen: (open: boolean) => void;
@@ -51,12 +55,13 @@ interface AnnotationState {
   clearSearch: () => void;
 
   // Image actions
-  setCurrentImageId: (id: number | null) => void;
+  setCurrentImageId: (id: CocoId | null) => void;
+  fetchImageAnnotations: (imageId: CocoId) => Promise<void>;
 
   // Getters
   getVisibleAnnotations: () => COCOAnnotation[];
   getSelectedAnnotations: () => COCOAnnotation[];
-  getCategoryById: (id: number) => COCOCategory | undefined;
+  getCategoryById: (id: CocoId) => COCOCategory | undefined;
   getAnnotationsForCurrentImage: () => COCOAnnotation[];
 
   // Comparison actions
@@ -75,6 +80,7 @@ interface AnnotationState {
 
 export const useAnnotationStore = create<AnnotationState>((set, get) => ({
   cocoData: null,
+  dataset: null,
   selectedAnnotationIds: [],
   visibleCategoryIds: [],
   hoveredAnnotationId: null,
@@ -93,9 +99,14 @@ export const useAnnotationStore = create<AnnotationState>((set, get) => ({
   diffStatistics: null,
   diffFilters: new Set<DiffFilter>(),
 
-  setCocoData: (data) => {
+  setCocoData: (data, dataset = null) => {
+    const previous = get().dataset;
+    if (previous && !(dataset && isSameDataset(previous, dataset))) {
+      closeDataset(previous);
+    }
     set({
       cocoData: data,
+      dataset,
       visibleCategoryIds: data.categories.map((cat) => cat.id),
       selectedAnnotationIds: [],
       hoveredAnnotationId: null,
@@ -103,8 +114,13 @@ export const useAnnotationStore = create<AnnotationState>((set, get) => ({
   },
 
   clearCocoData: () => {
+    const { dataset } = get();
+    if (dataset) {
+      closeDataset(dataset);
+    }
     set({
       cocoData: null,
+      dataset: null,
       selectedAnnotationIds: [],
       visibleCategoryIds: [],
       hoveredAnnotationId: null,
@@ -181,17 +197,14 @@ export const useAnnotationStore = create<AnnotationState>((set, get) => ({
       return;
     }
 
-    const results: number[] = [];
+    const results: CocoId[] = [];
     const normalizedQuery = query.toLowerCase().trim();
 
-    // Search by annotation ID
-    if (/^\d+$/.test(normalizedQuery)) {
-      const targetId = parseInt(normalizedQuery);
-      const annotation = state.cocoData.annotations.find((ann) => ann.id === targetId);
-      if (annotation) {
-        results.push(annotation.id);
-      }
-    } else {
+    // Search by annotation ID (文字列のIDも一致すれば対象にする)
+    const annotation = state.cocoData.annotations.find((ann) => String(ann.id) === query.trim());
+    if (annotation) {
+      results.push(annotation.id);
+    } else if (!/^\d+$/.test(normalizedQuery)) {
       // Search by category name
       state.cocoData.annotations.forEach((annotation) => {
         const category = state.cocoData?.categories.find(
@@ -283,10 +296,40 @@ export const useAnnotationStore = create<AnnotationState>((set, get) => ({
 
   setCurrentImageId: (id) => {
     set({ currentImageId: id });
+    if (id !== null && get().dataset?.partial) {
+      get().fetchImageAnnotations(id);
+    }
     // If in comparison mode, update comparison annotations for the new image
     get().updateComparisonForCurrentImage();
   },
 
+  // 大きなデータセットは、表示する画像のアノテーションをバックエンドから取得する
+  fetchImageAnnotations: async (imageId) => {
+    const { dataset } = get();
+    if (!dataset) return;
+
+    let annotations: COCOAnnotation[] = [];
+    try {
+      annotations = await invoke<COCOAnnotation[]>('get_image_annotations', {
+        dataset: datasetKey(dataset.summary),
+        imageId,
+      });
+    } catch (error) {
+      // images に無い画像IDの場合はアノテーション無しとして扱う
+      console.error('Failed to fetch image annotations:', error);
+    }
+
+    // 取得中に画像やデータセットが切り替わった場合は破棄する
+    const state = get();
+    if (state.dataset !== dataset || state.currentImageId !== imageId || !state.cocoData) {
+      return;
+    }
+    set({ cocoData: { ...state.cocoData, annotations } });
+    if (state.isComparing) {
+      get().calculateDiff();
+    }
+  },
+
   getAnnotationsForCurrentImage: () => {
     const state = get();
     if (!state.cocoData || !state.currentImageId) return [];
@@ -372,7 +415,7 @@ export const useAnnotationStore = create<AnnotationState>((set, get) => ({
   },
 
   updateComparisonForCurrentImage: (() => {
-    let lastToastImageId: number | null = null;
+    let lastToastImageId: CocoId | null = null;
     let toastTimeout: NodeJS.Timeout | null = null;
 
     return () => {
@@ -0,0 +1,29 @@
+import { create } from 'zustand';
+import type { ArchiveEntry } from '../types/app';
+
+interface ArchiveEntryState {
+  archivePath: string | null;
+  entries: ArchiveEntry[];
+  resolve: ((entry: ArchiveEntry | null) => void) | null;
+  // zip内のファイルを選択してもらい、選択結果（キャンセル時は null）を返す
+  requestEntry: (archivePath: string, entries: ArchiveEntry[]) => Promise<ArchiveEntry | null>;
+  close: (entry: ArchiveEntry | null) => void;
+}
+
+export const useArchiveEntryStore = create<ArchiveEntryState>((set, get) => ({
+  archivePath: null,
+  entries: [],
+  resolve: null,
+
+  requestEntry: (archivePath, entries) =>
+    new Promise((resolve) => {
+      // 前の選択が残っている場合はキャンセルとして扱う
+      get().resolve?.(null);
+      set({ archivePath, entries, resolve });
+    }),
+
+  close: (entry) => {
+    get().resolve?.(entry);
+    set({ archivePath: null, entries: [], resolve: null });
+  },
+}));
@@ -1,4 +1,6 @@
 import { create } from 'zustand';
+import type { CocoId, CocoIdKey } from '../types/coco';
+import { cocoIdKey } from '../utils/cocoId';
 
 export type HeatmapType = 'widthHeight' | 'centerXY' | 'areaAspectRatio' | 'polygonAreaAspectRatio';
 
@@ -6,7 +8,7 @@ export interface HeatmapSettings {
   xBins: number;
   yBins: number;
   colorScale: 'viridis' | 'plasma' | 'inferno' | 'magma' | 'cividis';
-  selectedCategories: Set<number>;
+  selectedCategories: Set<CocoIdKey>; // カテゴリIDのキー（cocoIdKey）
   viewMode: 'current' | 'all';
 }
 
@@ -14,8 +16,8 @@ export interface HeatmapBin {
   xRange: [number, number];
   yRange: [number, number];
   count: number;
-  annotations: number[];
-  categoryBreakdown: Map<number, number>;
+  annotations: CocoId[];
+  categoryBreakdown: Map<CocoIdKey, number>;
 }
 
 export interface HeatmapData {
@@ -43,7 +45,7 @@ interface HeatmapStore {
   setXBins: (bins: number) => void;
   setYBins: (bins: number) => void;
   setColorScale: (scale: HeatmapSettings['colorScale']) => void;
-  toggleCategory: (categoryId: number) => void;
+  toggleCategory: (categoryId: CocoId) => void;
   setViewMode: (mode: 'current' | 'all') => void;
   setHeatmapData: (data: HeatmapData | null) => void;
 }
@@ -83,10 +85,11 @@ export const useHeatmapStore = create<HeatmapStore>((set) => ({
   toggleCategory: (categoryId) =>
     set((state) => {
       const newCategories = new Set(state.settings.selectedCategories);
-      if (newCategories.has(categoryId)) {
-        newCategories.delete(categoryId);
+      const key = cocoIdKey(categoryId);
+      if (newCategories.has(key)) {
+        newCategories.delete(key);
       } else {
-        newCategories.add(categoryId);
+        newCategories.add(key);
       }
       return {
         settings: { ...state.settings, selectedCategories: newCategories },
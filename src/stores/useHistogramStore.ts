@@ -1,5 +1,7 @@
 import { create } from 'zustand';
 import { subscribeWithSelector } from 'zustand/middleware';
+import type { CocoId, CocoIdKey } from '../types/coco';
+import { cocoIdKey } from '../utils/cocoId';
 
 export type HistogramType = 'width' | 'height' | 'area' | 'polygonArea' | 'aspectRatio';
 export type ScaleType = 'linear' | 'log';
@@ -7,8 +9,8 @@ export type ScaleType = 'linear' | 'log';
 export interface HistogramBin {
   range: [number, number];
   count: number;
-  categoryBreakdown: Map<number, number>;
-  annotationIds: number[];
+  categoryBreakdown: Map<CocoIdKey, number>;
+  annotationIds: CocoId[];
 }
 
 export interface HistogramData {
@@ -33,7 +35,7 @@ export interface HistogramStatistics {
 export interface HistogramSettings {
   binCount: number;
   scale: ScaleType;
-  selectedCategories: Set<number>;
+  selectedCategories: Set<CocoIdKey>; // カテゴリIDのキー（cocoIdKey）
   sizeRange: [number, number] | null;
   viewMode: 'current' | 'all';
 }
@@ -45,13 +47,13 @@ interface HistogramStore {
 
   // 計算結果
   histogramData: HistogramData | null;
-  highlightedAnnotations: Set<number>;
+  highlightedAnnotations: Set<CocoIdKey>;
 
   // アクション
   setHistogramType: (type: HistogramType) => void;
   setBinCount: (count: number) => void;
   setScale: (scale: ScaleType) => void;
-  toggleCategory: (categoryId: number) => void;
+  toggleCategory: (categoryId: CocoId) => void;
   setSizeRange: (range: [number, number] | null) => void;
   highlightBin: (binIndex: number) => void;
   clearHighlight: () => void;
@@ -105,10 +107,11 @@ export const useHistogramStore = create<HistogramStore>()(
     toggleCategory: (categoryId) =>
       set((state) => {
         const newCategories = new Set(state.settings.selectedCategories);
-        if (newCategories.has(categoryId)) {
-          newCategories.delete(categoryId);
+        const key = cocoIdKey(categoryId);
+        if (newCategories.has(key)) {
+          newCategories.delete(key);
         } else {
-          newCategories.add(categoryId);
+          newCategories.add(key);
         }
         return {
           settings: {
@@ -137,7 +140,7 @@ export const useHistogramStore = create<HistogramStore>()(
         }
         const bin = state.histogramData.bins[binIndex];
         return {
-          highlightedAnnotations: new Set(bin.annotationIds),
+          highlightedAnnotations: new Set(bin.annotationIds.map(cocoIdKey)),
         };
       }),
 
@@ -5,7 +5,9 @@ interface LoadingState {
   message: string;
   subMessage?: string;
   progress?: number;
+  onCancel?: () => void; // 指定した場合は中止ボタンを表示する
   setLoading: (loading: boolean, message?: string, subMessage?: string, progress?: number) => void;
+  setCancelHandler: (onCancel?: () => void) => void;
 }
 
 export const useLoadingStore = create<LoadingState>((set) => ({
@@ -13,7 +15,16 @@ export const useLoadingStore = create<LoadingState>((set) => ({
   message: '',
   subMessage: undefined,
   progress: undefined,
+  onCancel: undefined,
 
   setLoading: (loading, message = '', subMessage = undefined, progress = undefined) =>
-    set({ isLoading: loading, message, subMessage, progress }),
+    set({
+      isLoading: loading,
+      message,
+      subMessage,
+      progress,
+      ...(loading ? {} : { onCancel: undefined }),
+    }),
+
+  setCancelHandler: (onCancel) => set({ onCancel }),
 }));
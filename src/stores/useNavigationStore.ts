@@ -1,8 +1,9 @@
 import { create } from 'zustand';
 import { devtools } from 'zustand/middleware';
+import type { CocoId } from '../types/coco';
 
 interface ImageMetadata {
-  id: number;
+  id: CocoId;
   fileName: string;
   filePath: string;
   width: number;
@@ -36,7 +37,7 @@ interface NavigationStore {
   resetToSingleMode: () => void;
 
   // Filtering
-  filterByCategory: (categoryIds: number[]) => void;
+  filterByCategory: (categoryIds: CocoId[]) => void;
   filterByExistence: (existsOnly: boolean) => void;
   clearFilters: () => void;
 }
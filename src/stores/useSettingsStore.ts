@@ -3,6 +3,8 @@ import { persist } from 'zustand/middleware';
 import { invoke } from '@tauri-apps/api/core';
 import i18n from '../i18n/config';
 import { hslToRgb } from '../utils/colorConverter';
+import { cocoIdKey } from '../utils/cocoId';
+import type { CocoId, CocoIdKey } from '../types/coco';
 
 export type Language = 'ja' | 'en';
 export type Theme = 'light' | 'dark' | 'system';
@@ -30,7 +32,7 @@ interface PanelLayoutSettings {
 
 interface ColorSettings {
   // Category colors (categoryId -> hex color)
-  categoryColors: Record<number, string>;
+  categoryColors: Record<CocoIdKey, string>; // カテゴリIDのキー（cocoIdKey）ごとの色
   // Global opacity settings (0-1)
   fillOpacity: number;
   selectedFillOpacity: number;
@@ -80,16 +82,32 @@ interface SettingsState {
   updatePanelLayout: (layout: Partial<PanelLayoutSettings>) => void;
   moveTabToPanel: (tab: TabType, side: PanelSide) => void;
   updateColorSettings: (settings: Partial<ColorSettings>) => void;
-  setCategoryColor: (categoryId: number, color: string) => void;
+  setCategoryColor: (categoryId: CocoId, color: string) => void;
   resetCategoryColors: () => void;
   toggleAnnotations: () => void;
   toggleLabels: () => void;
   toggleBoundingBoxes: () => void;
 }
 
+// 文字列のIDは色相を決めるための数値に変換する（整数の文字列は数値のIDと同じ色にする）
+const categoryIdSeed = (categoryId: CocoId): number => {
+  if (typeof categoryId === 'number') {
+    return categoryId;
+  }
+  const key = cocoIdKey(categoryId);
+  if (Number.isSafeInteger(Number(key)) && String(Number(key)) === key) {
+    return Number(key);
+  }
+  let hash = 0;
+  for (let i = 0; i < key.length; i++) {
+    hash = (hash * 31 + key.charCodeAt(i)) % 65536;
+  }
+  return hash;
+};
+
 // Helper function to generate color from category ID (returns HEX format for color picker compatibility)
-export const generateCategoryColor = (categoryId: number): string => {
-  const hue = (categoryId * 137.5) % 360;
+export const generateCategoryColor = (categoryId: CocoId): string => {
+  const hue = (categoryIdSeed(categoryId) * 137.5) % 360;
   const [r, g, b] = hslToRgb(hue, 50, 50);
   return `#${r.toString(16).padStart(2, '0')}${g.toString(16).padStart(2, '0')}${b.toString(16).padStart(2, '0')}`;
 };
@@ -235,7 +253,7 @@ export const useSettingsStore = create<SettingsState>()(
             ...state.colors,
             categoryColors: {
               ...state.colors.categoryColors,
-              [categoryId]: color,
+              [cocoIdKey(categoryId)]: color,
             },
           },
         }));
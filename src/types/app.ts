@@ -1,4 +1,13 @@
-import { COCOData } from './coco';
+import {
+  CategoryFrequency,
+  COCOCategory,
+  COCOData,
+  COCOImage,
+  COCOInfo,
+  COCOVideo,
+  CocoId,
+  CocoIdKey,
+} from './coco';
 
 // アプリケーション全体の型定義
 export interface Point {
@@ -29,13 +38,13 @@ export interface ImageState {
 
 export interface AnnotationState {
   data: COCOData | null;
-  selected: number[];
-  visible: number[];
-  hovering: number | null;
+  selected: CocoId[];
+  visible: CocoId[];
+  hovering: CocoId | null;
 }
 
 export interface FilterState {
-  categories: Set<number>;
+  categories: Set<CocoIdKey>;
   minArea: number;
   maxArea: number;
   searchQuery: string;
@@ -47,3 +56,178 @@ export interface AppState {
   view: ViewState;
   filter: FilterState;
 }
+
+// Rustコマンドが返すエラー
+export interface CoavErrorLocation {
+  file: string | null;
+  line: number | null;
+  column: number | null;
+  jsonPath: string | null;
+}
+
+export interface CoavError {
+  code: string;
+  message: string;
+  location: CoavErrorLocation | null;
+}
+
+// 寛容モードで修復・除外した箇所（open_dataset の lenient）
+export interface ParseDiagnostic {
+  action: 'repaired' | 'skipped';
+  jsonPath: string;
+  byteOffset: number; // 展開後のデータの先頭からのバイト位置
+  message: string;
+}
+
+export interface ParseDiagnostics {
+  items: ParseDiagnostic[];
+  omitted: number; // 上限を超えて省略した件数
+}
+
+// 読み込みの進捗（annotation-load-progress イベント）
+export interface LoadProgress {
+  loadId: number; // cancel_annotation_load に指定する
+  filePath: string;
+  bytesRead: number;
+  totalBytes: number;
+  images: number;
+  annotations: number;
+  done: boolean;
+  fromCache: boolean;
+}
+
+// バックエンドで保持しているデータセットの識別子（検出結果は正解データごとに別のデータセット）
+export interface DatasetKey {
+  filePath: string;
+  referencePath?: string | null;
+}
+
+// バックエンドで保持しているデータセット（open_dataset など）
+export interface DatasetSummary extends DatasetKey {
+  info: COCOInfo | null;
+  categories: COCOCategory[];
+  imageCount: number;
+  annotationCount: number;
+  annotationCountsByCategory: Record<CocoIdKey, number>;
+  videos: COCOVideo[];
+  diagnostics?: ParseDiagnostics; // 寛容モードで開いた場合のみ
+}
+
+// バックエンドで開いているデータセット
+export interface OpenDataset {
+  summary: DatasetSummary;
+  partial: boolean; // true の場合、cocoData.annotations は表示中の画像の分だけを保持する
+}
+
+// 出現頻度の区分ごとのカテゴリ（get_categories_by_frequency）
+export interface FrequencyGroup {
+  frequency: CategoryFrequency | null;
+  categories: COCOCategory[];
+  annotationCount: number;
+}
+
+export interface Page<T> {
+  items: T[];
+  offset: number;
+  total: number;
+}
+
+export interface ImageListItem extends COCOImage {
+  annotationCount: number;
+}
+
+export interface ImageSort {
+  key: 'id' | 'fileName' | 'annotationCount';
+  descending?: boolean;
+}
+
+export interface ImageFilter {
+  fileName?: string;
+  categoryIds?: CocoId[];
+  hasAnnotations?: boolean;
+}
+
+// キーポイントの可視性（0: 未ラベル、1: ラベル済み・不可視、2: ラベル済み・可視）
+export type KeypointVisibility = 0 | 1 | 2;
+
+export interface NamedKeypoint {
+  name: string | null; // カテゴリの keypoints に対応する名前
+  x: number;
+  y: number;
+  visibility: KeypointVisibility;
+}
+
+// 描画用にまとめたアノテーションのキーポイント（get_image_keypoints）
+export interface AnnotationKeypoints {
+  annotationId: CocoId;
+  categoryId: CocoId;
+  keypoints: NamedKeypoint[];
+  skeleton: [number, number][]; // 1始まりのキーポイント番号の組
+}
+
+// 解析済みアノテーションのキャッシュ（get_annotation_cache_info）
+export interface CacheEntry {
+  sourcePath: string;
+  cachePath: string;
+  cacheSize: number;
+  images: number;
+  annotations: number;
+  stale: boolean;
+}
+
+export interface CacheInfo {
+  directory: string;
+  // アノテーションキャッシュとTFRecordから書き出した画像の合計
+  totalBytes: number;
+  entries: CacheEntry[];
+  // TFRecordから書き出した画像
+  imageDirectory: string;
+  imageBytes: number;
+}
+
+// zip内のアノテーションファイル（list_archive_entries）
+export interface ArchiveEntry {
+  name: string;
+  filePath: string;
+  size: number;
+  compressedSize: number;
+}
+
+// export_annotations の設定と結果
+export interface ExportOptions {
+  pretty?: boolean;
+  imageIds?: CocoId[];
+  categoryIds?: CocoId[];
+}
+
+export interface ExportSummary {
+  filePath: string;
+  images: number;
+  annotations: number;
+  bytes: number;
+}
+
+// 他の形式からの読み込み結果（import_yolo など）
+export interface ConversionIssue {
+  file: string;
+  line: number | null;
+  message: string;
+}
+
+export interface ImportResult {
+  cocoData: COCOData;
+  imageRoot: string;
+  issues: ConversionIssue[];
+}
+
+// export_yolo の出力形式
+export type YoloExportMode = 'bbox' | 'polygon';
+
+// 他の形式へのエクスポート結果（export_yolo など）
+export interface ExportReport {
+  outputDir: string;
+  files: number;
+  annotations: number;
+  skippedCrowd: number;
+  issues: ConversionIssue[];
+}
@@ -16,8 +16,14 @@ export interface COCOLicense {
   [key: string]: unknown; // 任意の追加フィールド
 }
 
+// 画像・アノテーション・カテゴリのID（JavaScriptの数値で表せない整数は文字列で渡される）
+export type CocoId = number | string;
+
+// Map・Setのキーに使うIDの表記（utils の cocoIdKey で作る）
+export type CocoIdKey = string;
+
 export interface COCOImage {
-  id: number;
+  id: CocoId;
   file_name: string;
   width: number;
   height: number;
@@ -25,34 +31,75 @@ export interface COCOImage {
   flickr_url?: string;
   coco_url?: string;
   date_captured?: string;
+  video_id?: CocoId;
+  frame_id?: number; // 動画内のフレーム番号
+  neg_category_ids?: CocoId[]; // LVIS: 画像に存在しないことを確認済みのカテゴリ
+  not_exhaustive_category_ids?: CocoId[]; // LVIS: すべてはラベル付けされていないカテゴリ
   [key: string]: unknown; // 任意の追加フィールド
 }
 
+// RLE（counts は列優先の連長。文字列はpycocotools形式の圧縮表現）
+export interface RLE {
+  size: [number, number]; // [height, width]
+  counts: number[] | string;
+}
+
+export type Segmentation = number[][] | RLE;
+
 export interface COCOAnnotation {
-  id: number;
-  image_id: number;
-  category_id: number;
-  segmentation: number[][];
+  id: CocoId;
+  image_id: CocoId;
+  category_id: CocoId;
+  segmentation?: Segmentation | null; // ポリゴン配列、またはRLE（iscrowd: 1）
   bbox: [number, number, number, number]; // [x, y, width, height]
   area: number;
   iscrowd: 0 | 1;
   option?: Record<string, unknown>; // 独自定義フィールド
+  score?: number; // 検出結果の信頼度
+  rbox?: [number, number, number, number, number]; // 回転矩形 [cx, cy, width, height, angle(度)]
+  track_id?: CocoId; // 動画内で同じ物体を表すID
+  keypoints?: number[]; // [x1, y1, v1, x2, y2, v2, ...]
+  num_keypoints?: number; // ラベル付けされたキーポイントの数
   [key: string]: unknown; // その他の任意フィールド
 }
 
 export interface COCOCategory {
-  id: number;
+  id: CocoId;
   name: string;
-  supercategory: string;
-  color?: string; // 拡張: 表示用の色
+  supercategory?: string;
+  color?: string | [number, number, number]; // 拡張: 表示用の色（パノプティックはRGB）
+  isthing?: 0 | 1; // パノプティック: 1は物体（thing）、0は領域（stuff）
+  frequency?: CategoryFrequency; // LVIS: 出現頻度の区分
+  keypoints?: string[]; // キーポイントの名前
+  skeleton?: [number, number][]; // 1始まりのキーポイント番号の組
   [key: string]: unknown; // 任意の追加フィールド
 }
 
+// LVISの出現頻度の区分（r: rare, c: common, f: frequent）
+export type CategoryFrequency = 'r' | 'c' | 'f';
+
 export interface COCOData {
   info?: COCOInfo;
   images: COCOImage[];
   annotations: COCOAnnotation[];
   categories: COCOCategory[];
   licenses?: COCOLicense[];
+  videos?: COCOVideo[];
+  tracks?: COCOTrack[];
+  [key: string]: unknown; // 任意の追加フィールド
+}
+
+export interface COCOVideo {
+  id: CocoId;
+  name?: string;
+  width?: number;
+  height?: number;
+  [key: string]: unknown; // 任意の追加フィールド
+}
+
+export interface COCOTrack {
+  id: CocoId;
+  category_id: CocoId;
+  video_id?: CocoId;
   [key: string]: unknown; // 任意の追加フィールド
 }
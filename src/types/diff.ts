@@ -1,4 +1,4 @@
-import type { COCOAnnotation } from './coco';
+import type { COCOAnnotation, CocoId, CocoIdKey } from './coco';
 
 export interface MatchedAnnotation {
   gtAnnotation: COCOAnnotation;
@@ -7,10 +7,12 @@ export interface MatchedAnnotation {
 }
 
 export interface DiffResult {
-  imageId: number;
+  imageId: CocoId;
   truePositives: MatchedAnnotation[];
   falsePositives: COCOAnnotation[];
   falseNegatives: COCOAnnotation[];
+  // Unmatched predictions for categories not exhaustively labeled in GT (LVIS), neither TP nor FP
+  unknownPredictions: COCOAnnotation[];
   // Matches below threshold but with IoU > 0
   belowThresholdMatches: MatchedAnnotation[];
 }
@@ -19,6 +21,7 @@ export interface CategoryStats {
   tp: number;
   fp: number;
   fn: number;
+  unknown: number;
   precision: number;
   recall: number;
   f1: number;
@@ -26,7 +29,7 @@ export interface CategoryStats {
 
 export interface DiffStatistics {
   total: CategoryStats;
-  byCategory: Map<number, CategoryStats & { categoryName: string }>;
+  byCategory: Map<CocoIdKey, CategoryStats & { categoryName: string }>;
 }
 
 export type DiffFilter = 'tp-gt' | 'tp-pred' | 'fp' | 'fn';
@@ -53,7 +56,7 @@ export interface ComparisonSettings {
   gtFileId: string;
   predFileId: string;
   iouThreshold: number;
-  categoryMapping: Map<number, number[]>; // GT category ID -> Pred category IDs (1対多)
+  categoryMapping: Map<CocoIdKey, CocoId[]>; // GT category ID -> Pred category IDs (1対多)
   colorSettings: DiffColorSettings;
   displaySettings: DiffDisplaySettings;
   maxMatchesPerAnnotation?: number; // Maximum number of matches allowed per annotation (default: 1)
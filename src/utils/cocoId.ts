@@ -0,0 +1,26 @@
+import type { CocoId, CocoIdKey } from '../types/coco';
+
+// 整数の10進表記の文字列（JavaScriptの数値で表せない整数のIDはRust側から文字列で届く）
+const INTEGER_PATTERN = /^(0|-?[1-9][0-9]*)$/;
+
+function toBigInt(id: CocoId): bigint | null {
+  if (typeof id === 'number') return Number.isInteger(id) ? BigInt(id) : null;
+  return INTEGER_PATTERN.test(id) ? BigInt(id) : null;
+}
+
+// IDの並び順（Rust側と同じく整数のIDを数値の順に先に、文字列のIDはその後に並べる）
+export function compareCocoId(a: CocoId, b: CocoId): number {
+  if (typeof a === 'number' && typeof b === 'number') return a - b;
+  const x = toBigInt(a);
+  const y = toBigInt(b);
+  if (x !== null && y !== null) return x < y ? -1 : x > y ? 1 : 0;
+  if (x !== null) return -1;
+  if (y !== null) return 1;
+  return a < b ? -1 : a > b ? 1 : 0;
+}
+
+// Map・Setのキーに使うIDの表記（整数のIDは数値・文字列のどちらで届いても同じキーにする）
+export function cocoIdKey(id: CocoId): CocoIdKey {
+  const value = toBigInt(id);
+  return value !== null ? value.toString() : String(id);
+}
@@ -0,0 +1,21 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { DatasetKey, DatasetSummary, OpenDataset } from '../types/app';
+
+// バックエンドのコマンドに渡すデータセットの識別子
+export function datasetKey(summary: DatasetSummary): DatasetKey {
+  return { filePath: summary.filePath, referencePath: summary.referencePath ?? null };
+}
+
+export function isSameDataset(a: OpenDataset, b: OpenDataset): boolean {
+  return (
+    a.summary.filePath === b.summary.filePath &&
+    (a.summary.referencePath ?? null) === (b.summary.referencePath ?? null)
+  );
+}
+
+// バックエンドで保持しているデータセットを破棄する
+export function closeDataset(dataset: OpenDataset) {
+  invoke('close_dataset', { dataset: datasetKey(dataset.summary) }).catch((error) => {
+    console.error('Failed to close dataset:', error);
+  });
+}
@@ -1,4 +1,11 @@
-import type { COCOData, COCOAnnotation } from '../types/coco';
+import type {
+  COCOData,
+  COCOAnnotation,
+  COCOImage,
+  CocoId,
+  CocoIdKey,
+  Segmentation,
+} from '../types/coco';
 import type {
   DiffResult,
   MatchedAnnotation,
@@ -7,6 +14,8 @@ import type {
   ComparisonSettings,
   IoUMethod,
 } from '../types/diff';
+import { cocoIdKey } from './cocoId';
+import { decodeRleMask, isPointInRle, isRle } from './segmentation';
 
 /**
  * Calculate IoU (Intersection over Union) between two bounding boxes
@@ -54,22 +63,39 @@ function isPointInPolygon(x: number, y: number, polygon: number[]): boolean {
 }
 
 /**
- * Calculate IoU between two polygons using grid-based approximation
+ * Build a point-inclusion test for a segmentation (first polygon, or the RLE mask)
+ */
+function segmentationContains(
+  segmentation: Segmentation | null | undefined
+): ((x: number, y: number) => boolean) | null {
+  if (isRle(segmentation)) {
+    const rle = segmentation;
+    const mask = decodeRleMask(rle);
+    return (x, y) => isPointInRle(x, y, rle, mask);
+  }
+  if (!Array.isArray(segmentation) || segmentation.length === 0) {
+    return null;
+  }
+  const polygon = segmentation[0];
+  if (polygon.length < 6) {
+    return null;
+  }
+  return (x, y) => isPointInPolygon(x, y, polygon);
+}
+
+/**
+ * Calculate IoU between two segmentations (polygon or RLE) using grid-based approximation
  */
 function calculatePolygonIoU(
-  seg1: number[][],
-  seg2: number[][],
+  seg1: Segmentation | null | undefined,
+  seg2: Segmentation | null | undefined,
   bbox1: number[],
   bbox2: number[]
 ): number {
-  if (!seg1 || seg1.length === 0 || !seg2 || seg2.length === 0) {
-    return calculateBBoxIoU(bbox1, bbox2);
-  }
+  const inSeg1 = segmentationContains(seg1);
+  const inSeg2 = segmentationContains(seg2);
 
-  const poly1 = seg1[0];
-  const poly2 = seg2[0];
-
-  if (poly1.length < 6 || poly2.length < 6) {
+  if (!inSeg1 || !inSeg2) {
     return calculateBBoxIoU(bbox1, bbox2);
   }
 
@@ -88,8 +114,8 @@ function calculatePolygonIoU(
 
   for (let x = minX; x <= maxX; x += gridSize) {
     for (let y = minY; y <= maxY; y += gridSize) {
-      const inPoly1 = isPointInPolygon(x, y, poly1);
-      const inPoly2 = isPointInPolygon(x, y, poly2);
+      const inPoly1 = inSeg1(x, y);
+      const inPoly2 = inSeg2(x, y);
 
       if (inPoly1 && inPoly2) {
         intersectionCount++;
@@ -133,7 +159,7 @@ function findOverlappingAnnotationsMultiple(
   datasetA: COCOAnnotation[],
   datasetB: COCOAnnotation[],
   iouThreshold: number,
-  categoryMapping: Map<number, number[]>,
+  categoryMapping: Map<CocoIdKey, CocoId[]>,
   maxMatchesPerAnnotation: number = 1,
   iouMethod: IoUMethod = 'bbox'
 ): {
@@ -152,8 +178,8 @@ function findOverlappingAnnotationsMultiple(
     annotationB: COCOAnnotation;
     iou: number;
   }[] = [];
-  const matchCountA = new Map<number, number>();
-  const matchCountB = new Map<number, number>();
+  const matchCountA = new Map<CocoIdKey, number>();
+  const matchCountB = new Map<CocoIdKey, number>();
 
   const potentialMatches: {
     annotationA: COCOAnnotation;
@@ -168,7 +194,7 @@ function findOverlappingAnnotationsMultiple(
 
   datasetA.forEach((annA) => {
     datasetB.forEach((annB) => {
-      const mappedCategories = categoryMapping.get(annA.category_id);
+      const mappedCategories = categoryMapping.get(cocoIdKey(annA.category_id));
       if (mappedCategories !== undefined) {
         if (!mappedCategories.includes(annB.category_id)) return;
       } else {
@@ -196,29 +222,31 @@ function findOverlappingAnnotationsMultiple(
   potentialMatches.sort((a, b) => b.iou - a.iou);
 
   potentialMatches.forEach((match) => {
-    const countA = matchCountA.get(match.annotationA.id) || 0;
-    const countB = matchCountB.get(match.annotationB.id) || 0;
+    const keyA = cocoIdKey(match.annotationA.id);
+    const keyB = cocoIdKey(match.annotationB.id);
+    const countA = matchCountA.get(keyA) || 0;
+    const countB = matchCountB.get(keyB) || 0;
 
     if (countA < maxMatchesPerAnnotation && countB < maxMatchesPerAnnotation) {
       matches.push(match);
-      matchCountA.set(match.annotationA.id, countA + 1);
-      matchCountB.set(match.annotationB.id, countB + 1);
+      matchCountA.set(keyA, countA + 1);
+      matchCountB.set(keyB, countB + 1);
     }
   });
 
   potentialBelowThreshold.sort((a, b) => b.iou - a.iou);
 
   potentialBelowThreshold.forEach((match) => {
-    const hasMatchA = matchCountA.has(match.annotationA.id);
-    const hasMatchB = matchCountB.has(match.annotationB.id);
+    const hasMatchA = matchCountA.has(cocoIdKey(match.annotationA.id));
+    const hasMatchB = matchCountB.has(cocoIdKey(match.annotationB.id));
 
     if (!hasMatchA || !hasMatchB) {
       belowThresholdMatches.push(match);
     }
   });
 
-  const unmatchedA = datasetA.filter((ann) => !matchCountA.has(ann.id));
-  const unmatchedB = datasetB.filter((ann) => !matchCountB.has(ann.id));
+  const unmatchedA = datasetA.filter((ann) => !matchCountA.has(cocoIdKey(ann.id)));
+  const unmatchedB = datasetB.filter((ann) => !matchCountB.has(cocoIdKey(ann.id)));
 
   return { matches, unmatchedA, unmatchedB, belowThresholdMatches };
 }
@@ -230,7 +258,7 @@ function findOverlappingAnnotations(
   datasetA: COCOAnnotation[],
   datasetB: COCOAnnotation[],
   iouThreshold: number,
-  categoryMapping: Map<number, number[]>,
+  categoryMapping: Map<CocoIdKey, CocoId[]>,
   iouMethod: IoUMethod = 'bbox'
 ): {
   matches: { annotationA: COCOAnnotation; annotationB: COCOAnnotation; iou: number }[];
@@ -248,16 +276,16 @@ function findOverlappingAnnotations(
     annotationB: COCOAnnotation;
     iou: number;
   }[] = [];
-  const usedA = new Set<number>();
-  const usedB = new Set<number>();
+  const usedA = new Set<CocoIdKey>();
+  const usedB = new Set<CocoIdKey>();
 
   datasetA.forEach((annA) => {
     let bestMatch: { annotation: COCOAnnotation; iou: number } | null = null;
 
     datasetB.forEach((annB) => {
-      if (usedB.has(annB.id)) return;
+      if (usedB.has(cocoIdKey(annB.id))) return;
 
-      const mappedCategories = categoryMapping.get(annA.category_id);
+      const mappedCategories = categoryMapping.get(cocoIdKey(annA.category_id));
       if (mappedCategories !== undefined) {
         if (!mappedCategories.includes(annB.category_id)) return;
       } else {
@@ -279,17 +307,17 @@ function findOverlappingAnnotations(
         annotationB: foundMatch.annotation,
         iou: foundMatch.iou,
       });
-      usedA.add(annA.id);
-      usedB.add(foundMatch.annotation.id);
+      usedA.add(cocoIdKey(annA.id));
+      usedB.add(cocoIdKey(foundMatch.annotation.id));
     }
   });
 
-  const unmatchedA = datasetA.filter((ann) => !usedA.has(ann.id));
-  const unmatchedB = datasetB.filter((ann) => !usedB.has(ann.id));
+  const unmatchedA = datasetA.filter((ann) => !usedA.has(cocoIdKey(ann.id)));
+  const unmatchedB = datasetB.filter((ann) => !usedB.has(cocoIdKey(ann.id)));
 
   unmatchedA.forEach((annA) => {
     unmatchedB.forEach((annB) => {
-      const mappedCategories = categoryMapping.get(annA.category_id);
+      const mappedCategories = categoryMapping.get(cocoIdKey(annA.category_id));
       if (mappedCategories !== undefined) {
         if (!mappedCategories.includes(annB.category_id)) return;
       } else {
@@ -310,66 +338,97 @@ function findOverlappingAnnotations(
   return { matches, unmatchedA, unmatchedB, belowThresholdMatches };
 }
 
+/**
+ * Check whether a category is unverified in a GT image with federated (LVIS) labels.
+ * Categories in `not_exhaustive_category_ids` are unverified, and so are categories that are
+ * neither annotated nor listed in `neg_category_ids` when the image has negative labels.
+ */
+function isUnverifiedCategory(
+  image: COCOImage | undefined,
+  categoryId: CocoId,
+  annotatedCategoryIds: Set<CocoIdKey>
+): boolean {
+  if (!image) return false;
+  if (image.not_exhaustive_category_ids?.includes(categoryId)) return true;
+  if (!image.neg_category_ids) return false;
+  return (
+    !annotatedCategoryIds.has(cocoIdKey(categoryId)) && !image.neg_category_ids.includes(categoryId)
+  );
+}
+
 /**
  * Calculate statistics from diff results
  */
 function calculateStatistics(
-  diffResults: Map<number, DiffResult>,
-  categories: { id: number; name: string }[]
+  diffResults: Map<CocoIdKey, DiffResult>,
+  categories: { id: CocoId; name: string }[]
 ): DiffStatistics {
-  const categoryStatsMap = new Map<number, { tp: number; fp: number; fn: number }>();
+  const categoryStatsMap = new Map<
+    CocoIdKey,
+    { tp: number; fp: number; fn: number; unknown: number }
+  >();
   let totalTP = 0;
   let totalFP = 0;
   let totalFN = 0;
+  let totalUnknown = 0;
 
   categories.forEach((cat) => {
-    categoryStatsMap.set(cat.id, { tp: 0, fp: 0, fn: 0 });
+    categoryStatsMap.set(cocoIdKey(cat.id), { tp: 0, fp: 0, fn: 0, unknown: 0 });
   });
 
   diffResults.forEach((result) => {
     result.truePositives.forEach((match) => {
       totalTP++;
       const catId = match.gtAnnotation.category_id;
-      const stats = categoryStatsMap.get(catId);
+      const stats = categoryStatsMap.get(cocoIdKey(catId));
       if (stats) stats.tp++;
     });
 
     result.falsePositives.forEach((ann) => {
       totalFP++;
       const catId = ann.category_id;
-      const stats = categoryStatsMap.get(catId);
+      const stats = categoryStatsMap.get(cocoIdKey(catId));
       if (stats) stats.fp++;
     });
 
     result.falseNegatives.forEach((ann) => {
       totalFN++;
       const catId = ann.category_id;
-      const stats = categoryStatsMap.get(catId);
+      const stats = categoryStatsMap.get(cocoIdKey(catId));
       if (stats) stats.fn++;
     });
+
+    result.unknownPredictions.forEach((ann) => {
+      totalUnknown++;
+      const catId = ann.category_id;
+      const stats = categoryStatsMap.get(cocoIdKey(catId));
+      if (stats) stats.unknown++;
+    });
   });
 
-  const calculateMetrics = (tp: number, fp: number, fn: number): CategoryStats => {
+  // Unknown predictions are excluded from precision
+  const calculateMetrics = (tp: number, fp: number, fn: number, unknown: number): CategoryStats => {
     const precision = tp + fp > 0 ? tp / (tp + fp) : 0;
     const recall = tp + fn > 0 ? tp / (tp + fn) : 0;
     const f1 = precision + recall > 0 ? (2 * (precision * recall)) / (precision + recall) : 0;
 
-    return { tp, fp, fn, precision, recall, f1 };
+    return { tp, fp, fn, unknown, precision, recall, f1 };
   };
 
-  const byCategory = new Map<number, CategoryStats & { categoryName: string }>();
+  const byCategory = new Map<CocoIdKey, CategoryStats & { categoryName: string }>();
   categories.forEach((cat) => {
-    const stats = categoryStatsMap.get(cat.id);
+    const key = cocoIdKey(cat.id);
+    const stats = categoryStatsMap.get(key);
     if (stats) {
-      byCategory.set(cat.id, {
-        ...calculateMetrics(stats.tp, stats.fp, stats.fn),
+      byCategory.set(key, {
+        ...calculateMetrics(stats.tp, stats.fp, stats.fn, stats.unknown),
         categoryName: cat.name,
       });
     }
   });
 
   return {
-    total: calculateMetrics(totalTP, totalFP, totalFN),
+    total: calculateMetrics(totalTP, totalFP, totalFN, totalUnknown),
     byCategory,
   };
 }
@@ -382,18 +441,36 @@ export function calculateDiff(
   dataB: COCOData,
   settings: ComparisonSettings
 ): {
-  results: Map<number, DiffResult>;
+  results: Map<CocoIdKey, DiffResult>;
   statistics: DiffStatistics;
 } {
-  const results = new Map<number, DiffResult>();
+  const results = new Map<CocoIdKey, DiffResult>();
+  const isPrimaryGT = settings.gtFileId === 'primary';
+  const gtData = isPrimaryGT ? dataA : dataB;
+  const gtImages = new Map(gtData.images.map((image) => [cocoIdKey(image.id), image]));
+
+  // Pred category ID -> GT category IDs
+  const gtCategories = new Map(gtData.categories.map((cat) => [cocoIdKey(cat.id), cat.id]));
+  const predToGtCategories = new Map<CocoIdKey, CocoId[]>();
+  settings.categoryMapping.forEach((predCategoryIds, gtCategoryKey) => {
+    const gtCategoryId = gtCategories.get(gtCategoryKey);
+    if (gtCategoryId === undefined) return;
+    predCategoryIds.forEach((predCategoryId) => {
+      const key = cocoIdKey(predCategoryId);
+      const gtCategoryIds = predToGtCategories.get(key) || [];
+      gtCategoryIds.push(gtCategoryId);
+      predToGtCategories.set(key, gtCategoryIds);
+    });
+  });
 
-  const allImageIds = new Set<number>();
-  dataA.annotations.forEach((ann) => allImageIds.add(ann.image_id));
-  dataB.annotations.forEach((ann) => allImageIds.add(ann.image_id));
+  // The two files may send the same integer image ID as a number and as a string
+  const allImageIds = new Map<CocoIdKey, CocoId>();
+  dataA.annotations.forEach((ann) => allImageIds.set(cocoIdKey(ann.image_id), ann.image_id));
+  dataB.annotations.forEach((ann) => allImageIds.set(cocoIdKey(ann.image_id), ann.image_id));
 
-  allImageIds.forEach((imageId) => {
-    const annotationsA = dataA.annotations.filter((ann) => ann.image_id === imageId);
-    const annotationsB = dataB.annotations.filter((ann) => ann.image_id === imageId);
+  allImageIds.forEach((imageId, imageKey) => {
+    const annotationsA = dataA.annotations.filter((ann) => cocoIdKey(ann.image_id) === imageKey);
+    const annotationsB = dataB.annotations.filter((ann) => cocoIdKey(ann.image_id) === imageKey);
 
     const iouMethod = settings.iouMethod || 'bbox';
     const overlappingResult =
@@ -419,8 +496,7 @@ export function calculateDiff(
     const truePositives: MatchedAnnotation[] = [];
     const falsePositives: COCOAnnotation[] = [];
     const falseNegatives: COCOAnnotation[] = [];
-
-    const isPrimaryGT = settings.gtFileId === 'primary';
+    const unknownPredictions: COCOAnnotation[] = [];
 
     if (isPrimaryGT) {
       matches.forEach((match) => {
@@ -431,7 +507,6 @@ export function calculateDiff(
         });
       });
       falseNegatives.push(...unmatchedA);
-      falsePositives.push(...unmatchedB);
     } else {
       matches.forEach((match) => {
         truePositives.push({
@@ -441,9 +516,27 @@ export function calculateDiff(
         });
       });
       falseNegatives.push(...unmatchedB);
-      falsePositives.push(...unmatchedA);
     }
 
+    // Predictions for categories not exhaustively labeled in GT cannot be judged as FP
+    const gtImage = gtImages.get(imageKey);
+    const annotatedCategoryIds = new Set(
+      (isPrimaryGT ? annotationsA : annotationsB).map((ann) => cocoIdKey(ann.category_id))
+    );
+    (isPrimaryGT ? unmatchedB : unmatchedA).forEach((ann) => {
+      const gtCategoryIds = predToGtCategories.get(cocoIdKey(ann.category_id)) || [];
+      const isUnknown =
+        gtCategoryIds.length > 0 &&
+        gtCategoryIds.every((categoryId) =>
+          isUnverifiedCategory(gtImage, categoryId, annotatedCategoryIds)
+        );
+      if (isUnknown) {
+        unknownPredictions.push(ann);
+      } else {
+        falsePositives.push(ann);
+      }
+    });
+
     const belowThresholdMatchedAnnotations: MatchedAnnotation[] = belowThresholdMatches.map(
       (match) => {
         if (isPrimaryGT) {
@@ -462,17 +555,17 @@ export function calculateDiff(
       }
     );
 
-    results.set(imageId, {
+    results.set(imageKey, {
       imageId,
       truePositives,
       falsePositives,
       falseNegatives,
+      unknownPredictions,
       belowThresholdMatches: belowThresholdMatchedAnnotations,
     });
   });
 
-  const gtDataForStats = settings.gtFileId === 'primary' ? dataA : dataB;
-  const statistics = calculateStatistics(results, gtDataForStats.categories);
+  const statistics = calculateStatistics(results, gtData.categories);
 
   return { results, statistics };
 }
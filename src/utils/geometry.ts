@@ -3,7 +3,8 @@
  * 複数のポリゴンがある場合は合計面積を返す
  */
 export function calculatePolygonArea(segmentation: number[][]): number {
-  if (!segmentation || segmentation.length === 0) {
+  // RLE（iscrowd: 1）はポリゴンではないため対象外
+  if (!Array.isArray(segmentation) || segmentation.length === 0) {
     return 0;
   }
 
@@ -29,3 +30,23 @@ export function calculatePolygonArea(segmentation: number[][]): number {
 
   return totalArea;
 }
+
+/**
+ * 回転矩形 [cx, cy, width, height, angle] の4頂点を [x1, y1, ..., x4, y4] で返す
+ * angle は度数法で、画面上の時計回りが正
+ */
+export function rotatedBoxPoints(rbox: [number, number, number, number, number]): number[] {
+  const [cx, cy, width, height, angle] = rbox;
+  const rad = (angle * Math.PI) / 180;
+  const cos = Math.cos(rad);
+  const sin = Math.sin(rad);
+  const hw = width / 2;
+  const hh = height / 2;
+
+  return [
+    [-hw, -hh],
+    [hw, -hh],
+    [hw, hh],
+    [-hw, hh],
+  ].flatMap(([dx, dy]) => [cx + dx * cos - dy * sin, cy + dx * sin + dy * cos]);
+}
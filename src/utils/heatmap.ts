@@ -1,6 +1,7 @@
-import { COCOData, COCOAnnotation } from '../types/coco';
+import { COCOData, COCOAnnotation, CocoId, CocoIdKey } from '../types/coco';
 import { HeatmapData, HeatmapType, HeatmapSettings, HeatmapBin } from '../stores/useHeatmapStore';
-import { calculatePolygonArea } from './geometry';
+import { calculateSegmentationArea } from './segmentation';
+import { cocoIdKey, compareCocoId } from './cocoId';
 
 // アノテーションから2次元の値を取得
 function getAnnotationValues(
@@ -27,13 +28,11 @@ function getAnnotationValues(
     }
 
     case 'polygonAreaAspectRatio': {
-      // ポリゴン面積を計算
-      const polygonArea = annotation.segmentation
-        ? calculatePolygonArea(annotation.segmentation)
-        : 0;
+      // ポリゴン面積（RLEは前景ピクセル数）を計算
+      const segmentationArea = calculateSegmentationArea(annotation.segmentation);
 
-      // ポリゴンがない場合はbbox面積を使用
-      const area = polygonArea > 0 ? polygonArea : bbox[2] * bbox[3];
+      // セグメンテーションがない場合はbbox面積を使用
+      const area = segmentationArea > 0 ? segmentationArea : bbox[2] * bbox[3];
 
       // アスペクト比はbboxから計算（ポリゴンの正確なアスペクト比は複雑）
       const aspectRatio = bbox[3] > 0 ? bbox[2] / bbox[3] : 0;
@@ -67,7 +66,7 @@ export function calculateHeatmap(
   cocoData: COCOData,
   type: HeatmapType,
   settings: HeatmapSettings,
-  currentImageId?: number | null
+  currentImageId?: CocoId | null
 ): HeatmapData | null {
   if (!cocoData.annotations || cocoData.annotations.length === 0) {
     return null;
@@ -84,7 +83,7 @@ export function calculateHeatmap(
   // カテゴリによるフィルタリング
   if (settings.selectedCategories.size > 0) {
     filteredAnnotations = filteredAnnotations.filter((ann) =>
-      settings.selectedCategories.has(ann.category_id)
+      settings.selectedCategories.has(cocoIdKey(ann.category_id))
     );
   }
 
@@ -163,8 +162,9 @@ export function calculateHeatmap(
     bin.annotations.push(val.annotation.id);
 
     // カテゴリ別カウント
-    const categoryCount = bin.categoryBreakdown.get(val.annotation.category_id) || 0;
-    bin.categoryBreakdown.set(val.annotation.category_id, categoryCount + 1);
+    const categoryKey = cocoIdKey(val.annotation.category_id);
+    const categoryCount = bin.categoryBreakdown.get(categoryKey) || 0;
+    bin.categoryBreakdown.set(categoryKey, categoryCount + 1);
   }
 
   const labels = getHeatmapLabels(type);
@@ -183,13 +183,13 @@ export function calculateHeatmap(
 }
 
 // ヒートマップデータをCSV形式でエクスポート
-export function exportHeatmapAsCSV(data: HeatmapData, categories: Map<number, string>): string {
+export function exportHeatmapAsCSV(data: HeatmapData, categories: Map<CocoIdKey, string>): string {
   const headers = ['X_Min', 'X_Max', 'Y_Min', 'Y_Max', 'Count'];
 
   // カテゴリヘッダーを追加
   const categoryIds = Array.from(
     new Set(data.bins.flat().flatMap((bin) => Array.from(bin.categoryBreakdown.keys())))
-  ).sort((a, b) => a - b);
+  ).sort(compareCocoId);
 
   categoryIds.forEach((id) => {
     const categoryName = categories.get(id) || `Category ${id}`;
@@ -1,4 +1,4 @@
-import { COCOAnnotation, COCOData } from '../types/coco';
+import { COCOAnnotation, COCOData, CocoId, CocoIdKey } from '../types/coco';
 import {
   HistogramBin,
   HistogramData,
@@ -6,7 +6,8 @@ import {
   HistogramType,
   HistogramSettings,
 } from '../stores/useHistogramStore';
-import { calculatePolygonArea } from './geometry';
+import { calculateSegmentationArea } from './segmentation';
+import { cocoIdKey, compareCocoId } from './cocoId';
 
 // アノテーションからサイズ値を取得
 export function getAnnotationSize(annotation: COCOAnnotation, type: HistogramType): number {
@@ -23,16 +24,11 @@ export function getAnnotationSize(annotation: COCOAnnotation, type: HistogramTyp
       return height;
     case 'area':
       return width * height;
-    case 'polygonArea':
-      if (
-        annotation.segmentation &&
-        Array.isArray(annotation.segmentation) &&
-        annotation.segmentation.length > 0
-      ) {
-        return calculatePolygonArea(annotation.segmentation);
-      }
-      // ポリゴンデータがない場合はbbox面積を返す
-      return width * height;
+    case 'polygonArea': {
+      // RLEは前景ピクセル数、セグメンテーションがない場合はbbox面積を返す
+      const segmentationArea = calculateSegmentationArea(annotation.segmentation);
+      return segmentationArea > 0 ? segmentationArea : width * height;
+    }
     case 'aspectRatio':
       return height > 0 ? width / height : 0;
     default:
@@ -137,7 +133,7 @@ export function calculateHistogram(
   cocoData: COCOData,
   type: HistogramType,
   settings: HistogramSettings,
-  currentImageId?: number | null
+  currentImageId?: CocoId | null
 ): HistogramData | null {
   if (!cocoData.annotations || cocoData.annotations.length === 0) {
     return null;
@@ -154,7 +150,7 @@ export function calculateHistogram(
   // カテゴリフィルタ
   if (settings.selectedCategories.size > 0) {
     annotations = annotations.filter(
-      (ann) => ann.category_id && settings.selectedCategories.has(ann.category_id)
+      (ann) => ann.category_id && settings.selectedCategories.has(cocoIdKey(ann.category_id))
     );
   }
 
@@ -209,8 +205,9 @@ export function calculateHistogram(
 
         // カテゴリ別カウント
         if (annotation.category_id) {
-          const currentCount = bins[i].categoryBreakdown.get(annotation.category_id) || 0;
-          bins[i].categoryBreakdown.set(annotation.category_id, currentCount + 1);
+          const categoryKey = cocoIdKey(annotation.category_id);
+          const currentCount = bins[i].categoryBreakdown.get(categoryKey) || 0;
+          bins[i].categoryBreakdown.set(categoryKey, currentCount + 1);
         }
         break;
       }
@@ -225,14 +222,17 @@ export function calculateHistogram(
 }
 
 // データをCSV形式でエクスポート
-export function exportHistogramAsCSV(data: HistogramData, categories: Map<number, string>): string {
+export function exportHistogramAsCSV(
+  data: HistogramData,
+  categories: Map<CocoIdKey, string>
+): string {
   try {
     const headers = ['Bin Range', 'Count', 'Percentage'];
 
     // カテゴリ別ヘッダーを追加
     const categoryIds = Array.from(
       new Set(data.bins.flatMap((bin) => Array.from(bin.categoryBreakdown.keys())))
-    ).sort((a, b) => a - b);
+    ).sort(compareCocoId);
 
     categoryIds.forEach((id) => {
       const categoryName = categories.get(id) || `Category ${id}`;
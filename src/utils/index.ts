@@ -5,3 +5,6 @@ export * from './heatmap';
 export * from './geometry';
 export * from './colorConverter';
 export * from './diffCalculator';
+export * from './cocoId';
+export * from './segmentation';
+export * from './dataset';
@@ -0,0 +1,157 @@
+import { RLE, Segmentation } from '../types/coco';
+import { calculatePolygonArea } from './geometry';
+
+/**
+ * セグメンテーションがRLE（`{ size, counts }`）かどうか
+ */
+export function isRle(segmentation: Segmentation | null | undefined): segmentation is RLE {
+  return (
+    !!segmentation &&
+    !Array.isArray(segmentation) &&
+    Array.isArray(segmentation.size) &&
+    (Array.isArray(segmentation.counts) || typeof segmentation.counts === 'string')
+  );
+}
+
+/**
+ * ポリゴン配列を返す（RLEや未設定の場合は空配列）
+ */
+export function getPolygons(segmentation: Segmentation | null | undefined): number[][] {
+  return Array.isArray(segmentation) ? segmentation : [];
+}
+
+/**
+ * RLEの連長配列を返す（pycocotools形式の圧縮文字列は展開する）
+ */
+export function decodeRleCounts(rle: RLE): number[] {
+  if (Array.isArray(rle.counts)) {
+    return rle.counts;
+  }
+
+  const counts: number[] = [];
+  let p = 0;
+  while (p < rle.counts.length) {
+    // 5ビットずつ下位から詰める（32ビットを超えるためビット演算は使わない）
+    let x = 0;
+    let k = 0;
+    let more = true;
+    while (more && p < rle.counts.length) {
+      const c = rle.counts.charCodeAt(p) - 48;
+      x += (c & 0x1f) * 2 ** (5 * k);
+      more = (c & 0x20) !== 0;
+      p++;
+      k++;
+      if (!more && (c & 0x10) !== 0) {
+        x -= 2 ** (5 * k);
+      }
+    }
+    if (counts.length > 2) {
+      x += counts[counts.length - 2];
+    }
+    counts.push(x);
+  }
+
+  return counts;
+}
+
+/**
+ * RLEの前景ピクセル数
+ */
+export function calculateRleArea(rle: RLE): number {
+  const counts = decodeRleCounts(rle);
+  let area = 0;
+  for (let i = 1; i < counts.length; i += 2) {
+    area += counts[i];
+  }
+  return area;
+}
+
+/**
+ * セグメンテーションの面積（ポリゴンはShoelace公式、RLEは前景ピクセル数）
+ */
+export function calculateSegmentationArea(segmentation: Segmentation | null | undefined): number {
+  if (isRle(segmentation)) {
+    return calculateRleArea(segmentation);
+  }
+  return calculatePolygonArea(getPolygons(segmentation));
+}
+
+/**
+ * RLEを行優先（row-major）の `height * width` マスク（0/1）に展開する
+ */
+export function decodeRleMask(rle: RLE): Uint8Array {
+  const [height, width] = rle.size;
+  const mask = new Uint8Array(height * width);
+  const counts = decodeRleCounts(rle);
+
+  let position = 0;
+  for (let i = 0; i < counts.length; i++) {
+    if (i % 2 === 1) {
+      const end = Math.min(position + counts[i], mask.length);
+      for (let p = position; p < end; p++) {
+        // 列優先のインデックスを行優先に変換
+        mask[(p % height) * width + Math.floor(p / height)] = 1;
+      }
+    }
+    position += counts[i];
+  }
+
+  return mask;
+}
+
+/**
+ * 座標 (x, y) のピクセルがRLEの前景に含まれるか
+ */
+export function isPointInRle(x: number, y: number, rle: RLE, mask = decodeRleMask(rle)): boolean {
+  const [height, width] = rle.size;
+  const px = Math.floor(x);
+  const py = Math.floor(y);
+  if (px < 0 || py < 0 || px >= width || py >= height) {
+    return false;
+  }
+  return mask[py * width + px] === 1;
+}
+
+const maskCanvasCache = new WeakMap<RLE, Map<string, HTMLCanvasElement>>();
+
+/**
+ * RLEの前景を指定色で塗ったキャンバスを返す（描画用、RLEと色ごとにキャッシュ）
+ */
+export function getRleMaskCanvas(rle: RLE, color: string): HTMLCanvasElement | null {
+  const [height, width] = rle.size;
+  if (!(width > 0 && height > 0)) {
+    return null;
+  }
+
+  let byColor = maskCanvasCache.get(rle);
+  if (!byColor) {
+    byColor = new Map();
+    maskCanvasCache.set(rle, byColor);
+  }
+  const cached = byColor.get(color);
+  if (cached) {
+    return cached;
+  }
+
+  const canvas = document.createElement('canvas');
+  canvas.width = width;
+  canvas.height = height;
+  const context = canvas.getContext('2d');
+  if (!context) {
+    return null;
+  }
+
+  // 前景を不透明で描いてから、source-in で前景部分だけを指定色に置き換える
+  const mask = decodeRleMask(rle);
+  const imageData = context.createImageData(width, height);
+  for (let i = 0; i < mask.length; i++) {
+    imageData.data[i * 4 + 3] = mask[i] * 255;
+  }
+  context.putImageData(imageData, 0, 0);
+  context.globalCompositeOperation = 'source-in';
+  context.fillStyle = color;
+  context.fillRect(0, 0, width, height);
+
+  byColor.set(color, canvas);
+  return canvas;
+}
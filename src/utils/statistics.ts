@@ -1,7 +1,8 @@
-import { COCOData, COCOAnnotation } from '../types/coco';
+import { COCOData, COCOAnnotation, CocoId, CocoIdKey } from '../types/coco';
+import { cocoIdKey } from './cocoId';
 
 export interface CategoryStats {
-  categoryId: number;
+  categoryId: CocoId;
   categoryName: string;
   count: number;
   totalArea: number;
@@ -33,9 +34,9 @@ export interface AnnotationStatistics {
 
 export function calculateAnnotationStatistics(
   cocoData: COCOData | null,
-  imageId: number | null,
-  visibleCategoryIds: number[],
-  selectedAnnotationIds: (number | string)[]
+  imageId: CocoId | null,
+  visibleCategoryIds: CocoId[],
+  selectedAnnotationIds: CocoId[]
 ): AnnotationStatistics | null {
   if (!cocoData) return null;
 
@@ -51,21 +52,26 @@ export function calculateAnnotationStatistics(
   );
 
   // Calculate category statistics
-  const categoryMap = new Map<number, { name: string; annotations: COCOAnnotation[] }>();
+  const categoryMap = new Map<
+    CocoIdKey,
+    { categoryId: CocoId; name: string; annotations: COCOAnnotation[] }
+  >();
 
   imageAnnotations.forEach((ann) => {
-    const category = cocoData.categories.find((cat) => cat.id === ann.category_id);
-    if (!categoryMap.has(ann.category_id)) {
-      categoryMap.set(ann.category_id, {
+    const key = cocoIdKey(ann.category_id);
+    if (!categoryMap.has(key)) {
+      const category = cocoData.categories.find((cat) => cocoIdKey(cat.id) === key);
+      categoryMap.set(key, {
+        categoryId: ann.category_id,
         name: category?.name || 'Unknown',
         annotations: [],
       });
     }
-    categoryMap.get(ann.category_id)!.annotations.push(ann);
+    categoryMap.get(key)!.annotations.push(ann);
   });
 
-  const categoryStats: CategoryStats[] = Array.from(categoryMap.entries()).map(
-    ([categoryId, { name, annotations }]) => {
+  const categoryStats: CategoryStats[] = Array.from(categoryMap.values()).map(
+    ({ categoryId, name, annotations }) => {
       const totalArea = annotations.reduce((sum, ann) => sum + ann.area, 0);
       return {
         categoryId,